@@ -0,0 +1,310 @@
+// Control API for Solana Flash Loan Arbitrage Bot
+// Starts, stops, and inspects a running bot over authenticated HTTP
+
+use std::thread::{self, JoinHandle};
+use axum::{
+    extract::{Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use log::{info, error};
+
+use crate::{BotConfig, BotError, BotStatistics, BotStatus, ThreadSafeArbitrageBot};
+use crate::profit_management::ProfitStatistics;
+
+/// Control API configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    /// Address to listen on, e.g. "127.0.0.1:8080"
+    pub bind_address: String,
+    /// Token every request must present as `Authorization: Bearer <token>`
+    pub bearer_token: String,
+}
+
+impl ApiConfig {
+    /// Validate the configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if self.bind_address.is_empty() {
+            return Err("API bind address must not be empty".to_string());
+        }
+        
+        if self.bearer_token.trim().is_empty() {
+            return Err("API bearer token must not be empty".to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+/// Error returned to API clients as `{ "error": ... }`
+struct ApiError {
+    /// HTTP status
+    status: StatusCode,
+    /// Error message
+    message: String,
+}
+
+impl ApiError {
+    /// Map a bot error to a status code
+    ///
+    /// Requests that don't fit the bot's current state (start while running,
+    /// resume while not paused) are conflicts, rejected configurations are bad
+    /// requests, and anything else is a server error.
+    fn from_bot(error: BotError) -> Self {
+        let status = match error {
+            BotError::AlreadyRunning | BotError::NotRunning | BotError::NotPaused => StatusCode::CONFLICT,
+            BotError::Config(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        
+        Self { status, message: error.to_string() }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+/// Bot status as reported by the API
+#[derive(Serialize)]
+struct StatusResponse {
+    /// Current status
+    status: BotStatus,
+}
+
+/// Bot and profit statistics as reported by the API
+#[derive(Serialize)]
+struct StatisticsResponse {
+    /// Current status
+    status: BotStatus,
+    /// Seconds since the bot was started (if running)
+    uptime_secs: Option<u64>,
+    /// Opportunities detected
+    opportunities_detected: u64,
+    /// Trades executed
+    trades_executed: u64,
+    /// Trades that failed
+    failed_trades: u64,
+    /// Total profit in lamports
+    total_profit_lamports: u64,
+    /// Total profit in USD cents
+    total_profit_usd_cents: u64,
+    /// Success rate as a percentage
+    success_rate: f64,
+    /// Average profit per trade in lamports
+    avg_profit_per_trade: u64,
+    /// Average execution time in milliseconds
+    avg_execution_time_ms: u64,
+    /// Fees and tips paid by the operational wallet in lamports
+    operational_fees_lamports: u64,
+    /// Whether trading is paused until the operational wallet is topped up
+    low_operational_balance: bool,
+    /// Profit accounting
+    profit: ProfitStatistics,
+}
+
+impl StatisticsResponse {
+    /// Combine a statistics snapshot with the profit statistics
+    fn new(stats: BotStatistics, profit: ProfitStatistics) -> Self {
+        Self {
+            status: stats.status,
+            uptime_secs: stats.start_time.map(|start| start.elapsed().as_secs()),
+            opportunities_detected: stats.opportunities_detected,
+            trades_executed: stats.trades_executed,
+            failed_trades: stats.failed_trades,
+            total_profit_lamports: stats.total_profit_lamports,
+            total_profit_usd_cents: stats.total_profit_usd_cents,
+            success_rate: stats.success_rate,
+            avg_profit_per_trade: stats.avg_profit_per_trade,
+            avg_execution_time_ms: stats.avg_execution_time_ms,
+            operational_fees_lamports: stats.operational_fees_lamports,
+            low_operational_balance: stats.low_operational_balance,
+            profit,
+        }
+    }
+}
+
+/// Public wallet information (never private keys)
+#[derive(Serialize)]
+struct WalletResponse {
+    /// Wallet public key
+    pubkey: String,
+    /// Wallet type
+    wallet_type: &'static str,
+    /// Wallet label
+    label: String,
+    /// Whether the wallet has a local keypair
+    has_keypair: bool,
+}
+
+/// State shared by the handlers
+#[derive(Clone)]
+struct ApiState {
+    /// Bot being controlled
+    bot: ThreadSafeArbitrageBot,
+    /// Expected bearer token
+    bearer_token: String,
+}
+
+/// Serve the control API from a background thread
+pub fn serve(bot: ThreadSafeArbitrageBot, config: ApiConfig) -> Result<JoinHandle<()>, String> {
+    config.validate()?;
+    
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Failed to create API runtime: {}", e))?;
+    
+    // Bind up front so a taken port fails the caller rather than the thread
+    let listener = runtime.block_on(tokio::net::TcpListener::bind(&config.bind_address))
+        .map_err(|e| format!("Failed to bind control API {}: {}", config.bind_address, e))?;
+    
+    let state = ApiState {
+        bot,
+        bearer_token: config.bearer_token,
+    };
+    
+    let app = Router::new()
+        .route("/start", post(start))
+        .route("/stop", post(stop))
+        .route("/pause", post(pause))
+        .route("/resume", post(resume))
+        .route("/status", get(status))
+        .route("/statistics", get(statistics))
+        .route("/wallets", get(wallets))
+        .route("/config", post(update_config))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state);
+    
+    info!("Serving control API on http://{}", config.bind_address);
+    
+    Ok(thread::spawn(move || {
+        if let Err(e) = runtime.block_on(axum::serve(listener, app)) {
+            error!("Control API stopped: {}", e);
+        }
+    }))
+}
+
+/// Reject requests without the configured bearer token
+async fn require_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let authorized = request.headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| constant_time_eq(token.as_bytes(), state.bearer_token.as_bytes()))
+        .unwrap_or(false);
+    
+    if !authorized {
+        return ApiError {
+            status: StatusCode::UNAUTHORIZED,
+            message: "Missing or invalid bearer token".to_string(),
+        }.into_response();
+    }
+    
+    next.run(request).await
+}
+
+/// Compare tokens without leaking how much of a guess matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Run a blocking bot call off the async runtime
+///
+/// Starting and stopping join threads and wait on RPC calls.
+async fn call_bot<T, F>(bot: ThreadSafeArbitrageBot, f: F) -> Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce(&ThreadSafeArbitrageBot) -> Result<T, BotError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(&bot))
+        .await
+        .map_err(|e| ApiError {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: format!("Bot call panicked: {}", e),
+        })?
+        .map_err(ApiError::from_bot)
+}
+
+/// POST /start
+async fn start(State(state): State<ApiState>) -> Result<Json<StatusResponse>, ApiError> {
+    call_bot(state.bot, |bot| {
+        bot.start()?;
+        Ok(Json(StatusResponse { status: bot.get_status()? }))
+    }).await
+}
+
+/// POST /stop
+async fn stop(State(state): State<ApiState>) -> Result<Json<StatusResponse>, ApiError> {
+    call_bot(state.bot, |bot| {
+        bot.stop()?;
+        Ok(Json(StatusResponse { status: bot.get_status()? }))
+    }).await
+}
+
+/// POST /pause
+async fn pause(State(state): State<ApiState>) -> Result<Json<StatusResponse>, ApiError> {
+    call_bot(state.bot, |bot| {
+        bot.pause()?;
+        Ok(Json(StatusResponse { status: bot.get_status()? }))
+    }).await
+}
+
+/// POST /resume
+async fn resume(State(state): State<ApiState>) -> Result<Json<StatusResponse>, ApiError> {
+    call_bot(state.bot, |bot| {
+        bot.resume()?;
+        Ok(Json(StatusResponse { status: bot.get_status()? }))
+    }).await
+}
+
+/// GET /status
+async fn status(State(state): State<ApiState>) -> Result<Json<StatusResponse>, ApiError> {
+    call_bot(state.bot, |bot| Ok(Json(StatusResponse { status: bot.get_status()? }))).await
+}
+
+/// GET /statistics
+async fn statistics(State(state): State<ApiState>) -> Result<Json<StatisticsResponse>, ApiError> {
+    call_bot(state.bot, |bot| {
+        let stats = bot.get_statistics()?;
+        let profit = bot.get_profit_statistics()?;
+        Ok(Json(StatisticsResponse::new(stats, profit)))
+    }).await
+}
+
+/// GET /wallets
+async fn wallets(State(state): State<ApiState>) -> Result<Json<Vec<WalletResponse>>, ApiError> {
+    call_bot(state.bot, |bot| {
+        let wallets = bot.get_all_wallets()?
+            .into_iter()
+            .map(|info| WalletResponse {
+                pubkey: info.pubkey.to_string(),
+                wallet_type: info.wallet_type.as_str(),
+                label: info.label,
+                has_keypair: info.has_keypair,
+            })
+            .collect();
+        Ok(Json(wallets))
+    }).await
+}
+
+/// POST /config
+async fn update_config(State(state): State<ApiState>, Json(config): Json<BotConfig>) -> Result<Json<StatusResponse>, ApiError> {
+    // Reject bad configurations as the client's fault before touching the bot
+    config.validate().map_err(|message| ApiError {
+        status: StatusCode::BAD_REQUEST,
+        message,
+    })?;
+    
+    call_bot(state.bot, move |bot| {
+        bot.update_config(config)?;
+        Ok(Json(StatusResponse { status: bot.get_status()? }))
+    }).await
+}
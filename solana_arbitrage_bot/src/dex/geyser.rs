@@ -0,0 +1,431 @@
+// Geyser Price Source for Solana Flash Loan Arbitrage Bot
+// Streams AMM pool vault updates from a Yellowstone gRPC endpoint
+
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::time::Duration;
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use tokio::sync::broadcast;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::prelude::{
+    subscribe_update::UpdateOneof,
+    CommitmentLevel,
+    SubscribeRequest,
+    SubscribeRequestFilterAccounts,
+    SubscribeRequestPing,
+    SubscribeUpdate,
+};
+use log::{info, warn, debug};
+
+use super::{DexError, DexType, PriceInfo};
+
+/// Offset of the `amount` field in an SPL token account
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// Pool watched over Geyser
+#[derive(Debug, Clone)]
+pub struct GeyserPoolConfig {
+    /// DEX the pool belongs to
+    pub dex: DexType,
+    /// Base token
+    pub base_token: Pubkey,
+    /// Quote token
+    pub quote_token: Pubkey,
+    /// Token account holding the pool's base reserve
+    pub base_vault: Pubkey,
+    /// Token account holding the pool's quote reserve
+    pub quote_vault: Pubkey,
+    /// Base token decimals
+    pub base_decimals: u8,
+    /// Quote token decimals
+    pub quote_decimals: u8,
+}
+
+/// Geyser connection configuration
+#[derive(Debug, Clone)]
+pub struct GeyserConfig {
+    /// Yellowstone gRPC endpoint
+    pub endpoint: String,
+    /// Optional x-token for authenticated endpoints
+    pub x_token: Option<String>,
+    /// Pools to subscribe to
+    pub pools: Vec<GeyserPoolConfig>,
+    /// Initial reconnect delay in milliseconds
+    pub reconnect_base_delay_ms: u64,
+    /// Maximum reconnect delay in milliseconds
+    pub reconnect_max_delay_ms: u64,
+}
+
+impl GeyserConfig {
+    /// Create a new Geyser configuration
+    pub fn new(endpoint: &str, x_token: Option<String>, pools: Vec<GeyserPoolConfig>) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            x_token,
+            pools,
+            reconnect_base_delay_ms: 500,
+            reconnect_max_delay_ms: 30_000,
+        }
+    }
+}
+
+/// Last observed reserves for a pool
+#[derive(Default)]
+struct PoolReserves {
+    /// Base reserve (if seen)
+    base: Option<u64>,
+    /// Quote reserve (if seen)
+    quote: Option<u64>,
+}
+
+/// Price source fed by Geyser account updates
+pub struct GeyserPriceSource {
+    /// Geyser configuration
+    config: GeyserConfig,
+    /// Vault account -> (pool index, is base vault)
+    vault_index: HashMap<Pubkey, (usize, bool)>,
+    /// Reserves by pool index
+    reserves: Vec<PoolReserves>,
+    /// Channel prices are published to
+    sender: broadcast::Sender<PriceInfo>,
+    /// Id of the last keepalive ping sent on the current stream
+    ping_id: i32,
+    /// Updates received since the last reconnect delay was taken
+    updates_received: u64,
+    /// Delay before the next reconnect while connections keep failing, in milliseconds
+    reconnect_delay_ms: u64,
+}
+
+impl GeyserPriceSource {
+    /// Create a new Geyser price source publishing into `sender`
+    pub fn new(config: GeyserConfig, sender: broadcast::Sender<PriceInfo>) -> Self {
+        let mut vault_index = HashMap::new();
+        for (i, pool) in config.pools.iter().enumerate() {
+            vault_index.insert(pool.base_vault, (i, true));
+            vault_index.insert(pool.quote_vault, (i, false));
+        }
+
+        let reserves = config.pools.iter().map(|_| PoolReserves::default()).collect();
+        let reconnect_delay_ms = config.reconnect_base_delay_ms;
+
+        Self {
+            config,
+            vault_index,
+            reserves,
+            sender,
+            ping_id: 0,
+            updates_received: 0,
+            reconnect_delay_ms,
+        }
+    }
+
+    /// Build the subscription request for all configured vault accounts
+    fn subscribe_request(&self) -> SubscribeRequest {
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            "pool_vaults".to_string(),
+            SubscribeRequestFilterAccounts {
+                account: self.vault_index.keys().map(|k| k.to_string()).collect(),
+                ..Default::default()
+            },
+        );
+
+        SubscribeRequest {
+            accounts,
+            commitment: Some(CommitmentLevel::Processed as i32),
+            ..Default::default()
+        }
+    }
+
+    /// Handle a single account update and publish a price if both reserves are known
+    pub fn handle_account_update(&mut self, pubkey: &Pubkey, data: &[u8]) -> Option<PriceInfo> {
+        let (pool_idx, is_base) = *self.vault_index.get(pubkey)?;
+
+        let amount = decode_token_amount(data)?;
+        let reserves = &mut self.reserves[pool_idx];
+        if is_base {
+            reserves.base = Some(amount);
+        } else {
+            reserves.quote = Some(amount);
+        }
+
+        let (base, quote) = match (reserves.base, reserves.quote) {
+            (Some(base), Some(quote)) if base > 0 => (base, quote),
+            _ => return None,
+        };
+
+        // Reserves are in atoms; adjust for decimals so the price compares with other DEXs
+        let pool = &self.config.pools[pool_idx];
+        let price = (quote as f64 / 10f64.powi(pool.quote_decimals as i32))
+            / (base as f64 / 10f64.powi(pool.base_decimals as i32));
+        let price_info = PriceInfo {
+            base_token: pool.base_token,
+            quote_token: pool.quote_token,
+            price,
+            liquidity: base,
+            dex: pool.dex,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            base_decimals: pool.base_decimals,
+            quote_decimals: pool.quote_decimals,
+            // Only the vaults are configured
+            pool: None,
+            fee_bps: None,
+        };
+
+        // A send error only means there are no receivers right now
+        let _ = self.sender.send(price_info.clone());
+
+        Some(price_info)
+    }
+
+    /// Build the next keepalive ping, each with a fresh id the server echoes in its pong
+    fn next_ping(&mut self) -> SubscribeRequest {
+        self.ping_id = self.ping_id.wrapping_add(1);
+
+        SubscribeRequest {
+            ping: Some(SubscribeRequestPing { id: self.ping_id }),
+            ..Default::default()
+        }
+    }
+
+    /// Connect and stream updates once, returning when the stream ends or errors
+    async fn stream_once(&mut self) -> Result<(), DexError> {
+        let mut client = GeyserGrpcClient::build_from_shared(self.config.endpoint.clone())
+            .map_err(|e| DexError::ApiError(format!("Invalid Geyser endpoint: {}", e)))?
+            .x_token(self.config.x_token.clone())
+            .map_err(|e| DexError::ApiError(format!("Invalid Geyser x-token: {}", e)))?
+            .connect()
+            .await
+            .map_err(|e| DexError::ApiError(format!("Failed to connect to Geyser: {}", e)))?;
+
+        let (mut subscribe_tx, mut stream) = client
+            .subscribe_with_request(Some(self.subscribe_request()))
+            .await
+            .map_err(|e| DexError::ApiError(format!("Failed to subscribe: {}", e)))?;
+
+        info!("Subscribed to {} pool vaults over Geyser", self.vault_index.len());
+
+        self.process_updates(&mut subscribe_tx, &mut stream).await
+    }
+
+    /// Handle a subscription's updates until it ends, answering pings on `subscribe_tx`
+    async fn process_updates<S, U, E>(&mut self, subscribe_tx: &mut S, updates: &mut U) -> Result<(), DexError>
+    where
+        S: Sink<SubscribeRequest> + Unpin,
+        U: Stream<Item = Result<SubscribeUpdate, E>> + Unpin,
+        E: std::fmt::Display,
+    {
+        while let Some(message) = updates.next().await {
+            let update = message
+                .map_err(|e| DexError::ApiError(format!("Geyser stream error: {}", e)))?;
+            self.updates_received += 1;
+
+            match update.update_oneof {
+                Some(UpdateOneof::Account(account_update)) => {
+                    if let Some(account) = account_update.account {
+                        let pubkey = match Pubkey::try_from(account.pubkey.as_slice()) {
+                            Ok(pubkey) => pubkey,
+                            Err(_) => continue,
+                        };
+                        self.handle_account_update(&pubkey, &account.data);
+                    }
+                },
+                Some(UpdateOneof::Ping(_)) => {
+                    // Keep the connection alive behind load balancers
+                    let _ = subscribe_tx.send(self.next_ping()).await;
+                },
+                Some(UpdateOneof::Pong(pong)) => {
+                    if pong.id != self.ping_id {
+                        debug!("Geyser pong id {} does not match last ping {}", pong.id, self.ping_id);
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Delay before reconnecting, doubling up to the maximum while connections fail
+    ///
+    /// A stream that delivered updates was healthy, however it ended, so the delay after it
+    /// starts over from the base.
+    fn next_reconnect_delay(&mut self) -> Duration {
+        if self.updates_received > 0 {
+            self.reconnect_delay_ms = self.config.reconnect_base_delay_ms;
+            self.updates_received = 0;
+        }
+
+        let delay_ms = self.reconnect_delay_ms;
+        self.reconnect_delay_ms = (delay_ms * 2).min(self.config.reconnect_max_delay_ms);
+        Duration::from_millis(delay_ms)
+    }
+
+    /// Run the price source forever, reconnecting with exponential backoff
+    pub async fn run(&mut self) {
+        loop {
+            let result = self.stream_once().await;
+            let delay = self.next_reconnect_delay();
+            match result {
+                Ok(()) => warn!("Geyser stream ended, reconnecting in {}ms", delay.as_millis()),
+                Err(e) => warn!("Geyser stream dropped: {}, reconnecting in {}ms", e, delay.as_millis()),
+            }
+
+            tokio::time::sleep(delay).await;
+            debug!("Next Geyser reconnect delay: {}ms", self.reconnect_delay_ms);
+        }
+    }
+}
+
+/// Decode the token amount from raw SPL token account data
+fn decode_token_amount(data: &[u8]) -> Option<u64> {
+    let bytes = data.get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc;
+    use futures::stream;
+    use yellowstone_grpc_proto::prelude::{
+        SubscribeUpdateAccount,
+        SubscribeUpdateAccountInfo,
+        SubscribeUpdatePing,
+        SubscribeUpdatePong,
+    };
+
+    /// SPL token account bytes holding `amount`
+    fn token_account(amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 165];
+        data[TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8]
+            .copy_from_slice(&amount.to_le_bytes());
+        data
+    }
+
+    fn pool() -> GeyserPoolConfig {
+        GeyserPoolConfig {
+            dex: DexType::Raydium,
+            base_token: Pubkey::new_unique(),
+            quote_token: Pubkey::new_unique(),
+            base_vault: Pubkey::new_unique(),
+            quote_vault: Pubkey::new_unique(),
+            base_decimals: 9,
+            quote_decimals: 6,
+        }
+    }
+
+    /// Subscription frame carrying `update`
+    fn frame(update: UpdateOneof) -> Result<SubscribeUpdate, String> {
+        Ok(SubscribeUpdate {
+            update_oneof: Some(update),
+            ..Default::default()
+        })
+    }
+
+    /// Account update frame for the account with address bytes `pubkey`
+    fn account_frame(pubkey: &[u8], data: Vec<u8>) -> Result<SubscribeUpdate, String> {
+        frame(UpdateOneof::Account(SubscribeUpdateAccount {
+            account: Some(SubscribeUpdateAccountInfo {
+                pubkey: pubkey.to_vec(),
+                data,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+    }
+
+    /// Source over `pool` and the receiver its prices are published to
+    fn source(pool: &GeyserPoolConfig) -> (GeyserPriceSource, broadcast::Receiver<PriceInfo>) {
+        let (sender, receiver) = broadcast::channel(16);
+        let mut config = GeyserConfig::new("http://localhost:10000", None, vec![pool.clone()]);
+        config.reconnect_base_delay_ms = 500;
+        config.reconnect_max_delay_ms = 2_000;
+        (GeyserPriceSource::new(config, sender), receiver)
+    }
+
+    #[tokio::test]
+    async fn streamed_account_frames_publish_prices() {
+        let pool = pool();
+        let (mut source, mut receiver) = source(&pool);
+        let (mut requests, _) = mpsc::unbounded::<SubscribeRequest>();
+
+        let mut updates = stream::iter(vec![
+            // Nothing is published until both vaults have been seen
+            account_frame(pool.base_vault.as_ref(), token_account(1_000 * 1_000_000_000)),
+            // Unknown accounts, malformed keys and truncated frames are ignored
+            account_frame(Pubkey::new_unique().as_ref(), token_account(1)),
+            account_frame(&[1, 2, 3], token_account(1)),
+            account_frame(pool.quote_vault.as_ref(), vec![0u8; 10]),
+            frame(UpdateOneof::Account(SubscribeUpdateAccount::default())),
+            account_frame(pool.quote_vault.as_ref(), token_account(150_000 * 1_000_000)),
+            // A later base frame republishes at the new ratio
+            account_frame(pool.base_vault.as_ref(), token_account(500 * 1_000_000_000)),
+        ]);
+        source.process_updates(&mut requests, &mut updates).await.unwrap();
+
+        let published = receiver.try_recv().expect("price once both reserves are known");
+        assert_eq!(published.base_token, pool.base_token);
+        assert_eq!(published.quote_token, pool.quote_token);
+        assert_eq!(published.dex, DexType::Raydium);
+        assert_eq!(published.liquidity, 1_000 * 1_000_000_000);
+        assert!((published.price - 150.0).abs() < 1e-9);
+
+        let updated = receiver.try_recv().expect("updated price");
+        assert!((updated.price - 300.0).abs() < 1e-9);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn pings_are_answered_on_the_subscription() {
+        let pool = pool();
+        let (mut source, _receiver) = source(&pool);
+        let (mut requests, sent) = mpsc::unbounded::<SubscribeRequest>();
+
+        let mut updates = stream::iter(vec![
+            frame(UpdateOneof::Ping(SubscribeUpdatePing::default())),
+            frame(UpdateOneof::Pong(SubscribeUpdatePong { id: 1 })),
+            frame(UpdateOneof::Ping(SubscribeUpdatePing::default())),
+        ]);
+        source.process_updates(&mut requests, &mut updates).await.unwrap();
+        drop(requests);
+
+        // Each ping is answered with a fresh id
+        let ping_ids: Vec<i32> = sent.map(|request| request.ping.expect("ping").id).collect().await;
+        assert_eq!(ping_ids, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn backoff_starts_over_once_a_stream_delivers_updates() {
+        let pool = pool();
+        let (mut source, _receiver) = source(&pool);
+        let (mut requests, _) = mpsc::unbounded::<SubscribeRequest>();
+        let delay_ms = |source: &mut GeyserPriceSource| source.next_reconnect_delay().as_millis();
+
+        // Failed connections double the delay up to the maximum
+        assert_eq!(delay_ms(&mut source), 500);
+        assert_eq!(delay_ms(&mut source), 1_000);
+        assert_eq!(delay_ms(&mut source), 2_000);
+        assert_eq!(delay_ms(&mut source), 2_000);
+
+        // A stream that delivered updates before dropping was healthy
+        let mut updates = stream::iter(vec![
+            account_frame(pool.base_vault.as_ref(), token_account(1)),
+            Err("connection reset".to_string()),
+        ]);
+        match source.process_updates(&mut requests, &mut updates).await {
+            Err(DexError::ApiError(message)) => assert!(message.contains("connection reset"), "{}", message),
+            other => panic!("expected the stream error, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(delay_ms(&mut source), 500);
+        assert_eq!(delay_ms(&mut source), 1_000);
+
+        // One that dropped straight away keeps backing off
+        let mut updates = stream::iter(vec![Err::<SubscribeUpdate, _>("connection reset".to_string())]);
+        assert!(source.process_updates(&mut requests, &mut updates).await.is_err());
+        assert_eq!(delay_ms(&mut source), 2_000);
+    }
+}
@@ -2,19 +2,59 @@
 // Handles interaction with decentralized exchanges on Solana
 
 use solana_sdk::{
+    account::Account,
     pubkey::Pubkey,
+    commitment_config::CommitmentConfig,
     instruction::{Instruction, AccountMeta},
-    transaction::Transaction,
+    transaction::{Transaction, VersionedTransaction},
     signer::Signer,
+    message::VersionedMessage,
+    address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
+    compute_budget,
 };
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_account_decoder::UiAccountEncoding;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver};
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use reqwest::Client as HttpClient;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use log::{info, warn, error, debug};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use futures::future::join_all;
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::lock_or_recover;
+use crate::rpc::{FailoverRpcClient, RetryPolicy};
+
+#[cfg(feature = "geyser")]
+pub mod geyser;
+pub mod stream;
+
+#[cfg(test)]
+mod tests;
+
+use stream::{PriceStream, PriceStreamConfig, StreamedPool};
+
+/// Buffered streamed prices per subscriber; updates for a full subscriber are dropped
+const PRICE_STREAM_CHANNEL_CAPACITY: usize = 1024;
+
+/// Trade size aggregator prices are quoted for when none is configured, in quote token atoms
+const DEFAULT_QUOTE_AMOUNT: u64 = 1_000_000_000;
+
+/// Slippage tolerance aggregator price quotes are requested with, in basis points
+const PRICE_QUOTE_SLIPPAGE_BPS: u64 = 50;
+
+/// Most accounts a single getMultipleAccounts request may ask for
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// How long a quote decoded from a batched account fetch waits to be used before it's dropped
+const PREFETCH_MAX_AGE: Duration = Duration::from_secs(2);
 
 /// Error type for DEX operations
 #[derive(Debug)]
@@ -46,7 +86,7 @@ impl std::fmt::Display for DexError {
 impl std::error::Error for DexError {}
 
 /// DEX type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DexType {
     /// Jupiter (aggregator)
     Jupiter,
@@ -54,6 +94,10 @@ pub enum DexType {
     Raydium,
     /// Orca
     Orca,
+    /// Meteora DLMM
+    Meteora,
+    /// Phoenix (order book)
+    Phoenix,
     /// Custom DEX
     Custom,
 }
@@ -65,14 +109,92 @@ pub struct PriceInfo {
     pub base_token: Pubkey,
     /// Quote token
     pub quote_token: Pubkey,
-    /// Price (quote per base)
+    /// Price in whole tokens (quote per base), comparable across DEXs
     pub price: f64,
-    /// Available liquidity in base token
+    /// Available liquidity in base token atoms
     pub liquidity: u64,
     /// DEX providing this price
     pub dex: DexType,
     /// Timestamp when price was fetched
     pub timestamp: u64,
+    /// Base token decimals
+    pub base_decimals: u8,
+    /// Quote token decimals
+    pub quote_decimals: u8,
+    /// Pool or market the price was read from (None for aggregator routes)
+    pub pool: Option<Pubkey>,
+    /// Swap fee in basis points, as reported by the pool or configured for the DEX
+    /// (None leaves it to the cost model)
+    pub fee_bps: Option<u64>,
+}
+
+impl PriceInfo {
+    /// Price in atoms: quote atoms per base atom
+    pub fn raw_price(&self) -> f64 {
+        self.price * 10f64.powi(self.quote_decimals as i32 - self.base_decimals as i32)
+    }
+    
+    /// Quote atoms worth `base_atoms` at this price
+    pub fn base_to_quote_atoms(&self, base_atoms: u64) -> u64 {
+        (base_atoms as f64 * self.raw_price()) as u64
+    }
+    
+    /// Base atoms bought with `quote_atoms` at this price
+    pub fn quote_to_base_atoms(&self, quote_atoms: u64) -> u64 {
+        let raw_price = self.raw_price();
+        if raw_price <= 0.0 {
+            return 0;
+        }
+        (quote_atoms as f64 / raw_price) as u64
+    }
+    
+    /// Available liquidity valued in quote token atoms
+    pub fn liquidity_in_quote(&self) -> u64 {
+        self.base_to_quote_atoms(self.liquidity)
+    }
+}
+
+/// Minimum output of a swap expected to return `expected_out` atoms, at `slippage` percent tolerance
+///
+/// Always rounds down: the tolerance is rounded up to whole basis points and the
+/// product is computed in integers, so the minimum never exceeds what the tolerance allows.
+/// Float error below a millionth of a basis point is ignored, so 0.3% is 30 bps, not 31.
+pub fn min_amount_out(expected_out: u64, slippage: f64) -> u64 {
+    let slippage_bps = (slippage * 100.0 - 1e-6).ceil().clamp(0.0, 10_000.0) as u128;
+    (expected_out as u128 * (10_000 - slippage_bps) / 10_000) as u64
+}
+
+/// Aggregator quote for swapping an exact input amount along its best route
+#[derive(Debug, Clone, Copy)]
+pub struct RouteQuote {
+    /// Input amount in source token atoms
+    pub in_amount: u64,
+    /// Expected output in destination token atoms, net of every pool's fee
+    pub out_amount: u64,
+    /// Output the route guarantees at the requested slippage, in destination token atoms
+    pub other_amount_threshold: u64,
+    /// Price impact of the whole route, as a fraction
+    pub price_impact_pct: f64,
+}
+
+impl RouteQuote {
+    /// Parse a Jupiter `/quote` response
+    fn from_jupiter(json: &Value) -> Result<Self, DexError> {
+        let amount = |field: &str| json[field]
+            .as_str()
+            .and_then(|amount| amount.parse::<u64>().ok())
+            .ok_or_else(|| DexError::ApiError(format!("{} not found in quote", field)));
+        
+        Ok(Self {
+            in_amount: amount("inAmount")?,
+            out_amount: amount("outAmount")?,
+            other_amount_threshold: amount("otherAmountThreshold")?,
+            price_impact_pct: json["priceImpactPct"]
+                .as_str()
+                .and_then(|impact| impact.parse::<f64>().ok())
+                .unwrap_or(0.0),
+        })
+    }
 }
 
 /// Swap parameters
@@ -93,6 +215,568 @@ pub struct SwapParams {
     pub slippage: f64,
 }
 
+/// Number of quote latency samples kept per connector
+const LATENCY_WINDOW: usize = 128;
+
+/// Quote latency statistics for a DEX
+#[derive(Debug, Clone, Default)]
+pub struct LatencyStats {
+    /// Number of samples in the window
+    pub samples: usize,
+    /// Average latency in milliseconds
+    pub average_ms: f64,
+    /// Median latency in milliseconds
+    pub p50_ms: f64,
+    /// 95th percentile latency in milliseconds
+    pub p95_ms: f64,
+    /// Maximum latency in milliseconds
+    pub max_ms: f64,
+}
+
+/// Fixed-size rolling window of quote latencies
+struct LatencyWindow {
+    /// Latency samples in microseconds
+    samples: [u64; LATENCY_WINDOW],
+    /// Number of valid samples
+    len: usize,
+    /// Index the next sample is written to
+    next: usize,
+}
+
+impl LatencyWindow {
+    /// Create an empty window
+    fn new() -> Self {
+        Self {
+            samples: [0; LATENCY_WINDOW],
+            len: 0,
+            next: 0,
+        }
+    }
+    
+    /// Record a latency sample, overwriting the oldest once full
+    fn record(&mut self, micros: u64) {
+        self.samples[self.next] = micros;
+        self.next = (self.next + 1) % LATENCY_WINDOW;
+        self.len = (self.len + 1).min(LATENCY_WINDOW);
+    }
+    
+    /// Compute statistics over the current window
+    fn stats(&self) -> LatencyStats {
+        if self.len == 0 {
+            return LatencyStats::default();
+        }
+        
+        let mut sorted = self.samples;
+        let sorted = &mut sorted[..self.len];
+        sorted.sort_unstable();
+        
+        let percentile = |p: f64| {
+            let idx = ((self.len - 1) as f64 * p).round() as usize;
+            sorted[idx] as f64 / 1000.0
+        };
+        
+        LatencyStats {
+            samples: self.len,
+            average_ms: sorted.iter().sum::<u64>() as f64 / self.len as f64 / 1000.0,
+            p50_ms: percentile(0.5),
+            p95_ms: percentile(0.95),
+            max_ms: sorted[self.len - 1] as f64 / 1000.0,
+        }
+    }
+}
+
+/// Circuit breaker state for a DEX connector
+#[derive(Debug, Default)]
+struct ConnectorHealth {
+    /// Consecutive failed requests
+    consecutive_failures: u32,
+    /// When the current run of failures started
+    first_failure: Option<Instant>,
+    /// When a disabled connector may be probed again
+    disabled_until: Option<Instant>,
+}
+
+/// Size of a Raydium AMM v4 pool account
+const RAYDIUM_AMM_ACCOUNT_LEN: u64 = 752;
+
+/// Offsets of fields in a Raydium AMM v4 pool account
+const RAYDIUM_COIN_DECIMALS_OFFSET: usize = 32;
+const RAYDIUM_PC_DECIMALS_OFFSET: usize = 40;
+const RAYDIUM_SWAP_FEE_NUMERATOR_OFFSET: usize = 176;
+const RAYDIUM_SWAP_FEE_DENOMINATOR_OFFSET: usize = 184;
+const RAYDIUM_COIN_NEED_TAKE_PNL_OFFSET: usize = 192;
+const RAYDIUM_PC_NEED_TAKE_PNL_OFFSET: usize = 200;
+const RAYDIUM_COIN_VAULT_OFFSET: usize = 336;
+const RAYDIUM_PC_VAULT_OFFSET: usize = 368;
+const RAYDIUM_COIN_MINT_OFFSET: usize = 400;
+const RAYDIUM_PC_MINT_OFFSET: usize = 432;
+
+/// Orca Whirlpools program ID
+const ORCA_WHIRLPOOL_PROGRAM_ID: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+/// Size of an Orca Whirlpool account
+const WHIRLPOOL_ACCOUNT_LEN: u64 = 653;
+
+/// Offsets of fields in an Orca Whirlpool account
+const WHIRLPOOL_FEE_RATE_OFFSET: usize = 45;
+const WHIRLPOOL_LIQUIDITY_OFFSET: usize = 49;
+const WHIRLPOOL_SQRT_PRICE_OFFSET: usize = 65;
+const WHIRLPOOL_MINT_A_OFFSET: usize = 101;
+const WHIRLPOOL_MINT_B_OFFSET: usize = 181;
+
+/// Price band around the current price counted as tradable liquidity (1%)
+const WHIRLPOOL_LIQUIDITY_BAND: f64 = 0.01;
+
+/// Meteora DLMM program ID
+const METEORA_DLMM_PROGRAM_ID: &str = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9t5y3DdXoK";
+
+/// Size of a Meteora DLMM LbPair account
+const DLMM_LB_PAIR_ACCOUNT_LEN: u64 = 904;
+
+/// Offsets of fields in a Meteora DLMM LbPair account
+const DLMM_BASE_FACTOR_OFFSET: usize = 8;
+const DLMM_ACTIVE_ID_OFFSET: usize = 76;
+const DLMM_BIN_STEP_OFFSET: usize = 80;
+const DLMM_TOKEN_X_MINT_OFFSET: usize = 88;
+const DLMM_TOKEN_Y_MINT_OFFSET: usize = 120;
+const DLMM_RESERVE_X_OFFSET: usize = 152;
+const DLMM_RESERVE_Y_OFFSET: usize = 184;
+
+/// Bins per DLMM bin array
+const DLMM_BINS_PER_ARRAY: i32 = 70;
+
+/// Anchor discriminator of the DLMM `swap` instruction
+const DLMM_SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+/// Phoenix program ID
+const PHOENIX_PROGRAM_ID: &str = "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY";
+
+/// Offsets of fields in a Phoenix market header
+const PHOENIX_BIDS_SIZE_OFFSET: usize = 16;
+const PHOENIX_ASKS_SIZE_OFFSET: usize = 24;
+const PHOENIX_BASE_DECIMALS_OFFSET: usize = 40;
+const PHOENIX_BASE_MINT_OFFSET: usize = 48;
+const PHOENIX_BASE_VAULT_OFFSET: usize = 80;
+const PHOENIX_BASE_LOT_SIZE_OFFSET: usize = 112;
+const PHOENIX_QUOTE_DECIMALS_OFFSET: usize = 120;
+const PHOENIX_QUOTE_MINT_OFFSET: usize = 128;
+const PHOENIX_QUOTE_VAULT_OFFSET: usize = 160;
+const PHOENIX_QUOTE_LOT_SIZE_OFFSET: usize = 192;
+const PHOENIX_TICK_SIZE_OFFSET: usize = 200;
+const PHOENIX_RAW_BASE_UNITS_OFFSET: usize = 312;
+
+/// Offset of the bids tree (header plus the fixed FIFO market fields)
+const PHOENIX_BIDS_TREE_OFFSET: usize = 880;
+
+/// Size of an order tree's root/allocator header
+const PHOENIX_TREE_HEADER_LEN: usize = 32;
+
+/// Size of one order tree node: 4 u32 registers, a 16-byte order ID and a 32-byte resting order
+const PHOENIX_TREE_NODE_LEN: usize = 64;
+
+/// Tag of the Phoenix `Swap` instruction
+const PHOENIX_SWAP_TAG: u8 = 0;
+
+/// Offset of the `decimals` field in an SPL mint account
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// Offset of the `amount` field in an SPL token account
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// Read a little-endian u64 from account data
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Read a little-endian u128 from account data
+fn read_u128(data: &[u8], offset: usize) -> Option<u128> {
+    let bytes = data.get(offset..offset + 16)?;
+    Some(u128::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Read a little-endian i32 from account data
+fn read_i32(data: &[u8], offset: usize) -> Option<i32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(i32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Read a little-endian u32 from account data
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Read a little-endian u16 from account data
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// A fee expressed as `numerator / denominator` in basis points, rounded up
+fn fee_bps(numerator: u64, denominator: u64) -> Option<u64> {
+    (denominator > 0).then(|| (numerator as u128 * 10_000).div_ceil(denominator as u128) as u64)
+}
+
+/// Decode a Phoenix market header, `inverted` when its base mint is our quote token
+fn phoenix_market_from_account(address: Pubkey, data: &[u8], inverted: bool) -> Result<PhoenixMarket, DexError> {
+    let decode_err = || DexError::ApiError(format!("Failed to decode Phoenix market {}", address));
+    Ok(PhoenixMarket {
+        address,
+        base_mint: read_pubkey(data, PHOENIX_BASE_MINT_OFFSET).ok_or_else(decode_err)?,
+        quote_mint: read_pubkey(data, PHOENIX_QUOTE_MINT_OFFSET).ok_or_else(decode_err)?,
+        base_vault: read_pubkey(data, PHOENIX_BASE_VAULT_OFFSET).ok_or_else(decode_err)?,
+        quote_vault: read_pubkey(data, PHOENIX_QUOTE_VAULT_OFFSET).ok_or_else(decode_err)?,
+        base_decimals: read_u32(data, PHOENIX_BASE_DECIMALS_OFFSET).ok_or_else(decode_err)?,
+        quote_decimals: read_u32(data, PHOENIX_QUOTE_DECIMALS_OFFSET).ok_or_else(decode_err)?,
+        base_lot_size: read_u64(data, PHOENIX_BASE_LOT_SIZE_OFFSET).ok_or_else(decode_err)?,
+        quote_lot_size: read_u64(data, PHOENIX_QUOTE_LOT_SIZE_OFFSET).ok_or_else(decode_err)?,
+        tick_size: read_u64(data, PHOENIX_TICK_SIZE_OFFSET).ok_or_else(decode_err)?,
+        raw_base_units_per_base_unit: read_u32(data, PHOENIX_RAW_BASE_UNITS_OFFSET).ok_or_else(decode_err)?.max(1),
+        bids_size: read_u64(data, PHOENIX_BIDS_SIZE_OFFSET).ok_or_else(decode_err)?,
+        asks_size: read_u64(data, PHOENIX_ASKS_SIZE_OFFSET).ok_or_else(decode_err)?,
+        inverted,
+    })
+}
+
+/// Decode a Raydium AMM v4 pool account oriented for a token pair, or None if it doesn't trade the pair
+fn raydium_pool_from_account(address: &Pubkey, data: &[u8], base_token: &Pubkey, quote_token: &Pubkey) -> Option<RaydiumPool> {
+    if data.len() != RAYDIUM_AMM_ACCOUNT_LEN as usize {
+        return None;
+    }
+    
+    let coin_mint = read_pubkey(data, RAYDIUM_COIN_MINT_OFFSET)?;
+    let pc_mint = read_pubkey(data, RAYDIUM_PC_MINT_OFFSET)?;
+    let inverted = if (coin_mint, pc_mint) == (*base_token, *quote_token) {
+        false
+    } else if (coin_mint, pc_mint) == (*quote_token, *base_token) {
+        true
+    } else {
+        return None;
+    };
+    
+    let coin_vault = read_pubkey(data, RAYDIUM_COIN_VAULT_OFFSET)?;
+    let pc_vault = read_pubkey(data, RAYDIUM_PC_VAULT_OFFSET)?;
+    let coin_decimals = read_u64(data, RAYDIUM_COIN_DECIMALS_OFFSET)? as u32;
+    let pc_decimals = read_u64(data, RAYDIUM_PC_DECIMALS_OFFSET)? as u32;
+    let fee_bps = fee_bps(
+        read_u64(data, RAYDIUM_SWAP_FEE_NUMERATOR_OFFSET)?,
+        read_u64(data, RAYDIUM_SWAP_FEE_DENOMINATOR_OFFSET)?,
+    );
+    
+    Some(if inverted {
+        RaydiumPool {
+            address: *address,
+            base_vault: pc_vault,
+            quote_vault: coin_vault,
+            base_decimals: pc_decimals,
+            quote_decimals: coin_decimals,
+            fee_bps,
+            inverted,
+        }
+    } else {
+        RaydiumPool {
+            address: *address,
+            base_vault: coin_vault,
+            quote_vault: pc_vault,
+            base_decimals: coin_decimals,
+            quote_decimals: pc_decimals,
+            fee_bps,
+            inverted,
+        }
+    })
+}
+
+/// Share of a `quote_amount` swap through a whirlpool lost to its fee and price impact
+///
+/// Treats the active liquidity as a constant-product pool with virtual reserves of
+/// L / sqrt(P) token A and L * sqrt(P) token B, which holds while the swap stays in the
+/// current tick range. None if the pool can't be decoded or has no active liquidity.
+fn whirlpool_swap_cost(data: &[u8], quote_is_a: bool, quote_amount: u64) -> Option<f64> {
+    let fee_rate = read_u16(data, WHIRLPOOL_FEE_RATE_OFFSET)?;
+    let sqrt_price = read_u128(data, WHIRLPOOL_SQRT_PRICE_OFFSET)? as f64 / 2f64.powi(64);
+    let liquidity = read_u128(data, WHIRLPOOL_LIQUIDITY_OFFSET)? as f64;
+    if sqrt_price == 0.0 || liquidity == 0.0 {
+        return None;
+    }
+    
+    let quote_reserve = if quote_is_a { liquidity / sqrt_price } else { liquidity * sqrt_price };
+    let amount = quote_amount as f64;
+    // Fee rate is in hundredths of a basis point
+    Some(fee_rate as f64 / 1_000_000.0 + amount / (quote_reserve + amount))
+}
+
+/// Best price (in ticks) on one side of a Phoenix book and the base lots resting at it
+///
+/// Walks the red-black tree from its root so freed nodes are never counted.
+fn phoenix_book_side(data: &[u8], tree_offset: usize, capacity: u64, best_is_max: bool) -> Option<(u64, u64)> {
+    let node_offset = |index: u32| tree_offset + PHOENIX_TREE_HEADER_LEN + (index as usize - 1) * PHOENIX_TREE_NODE_LEN;
+    
+    let root = read_u32(data, tree_offset)?;
+    let mut stack = vec![root];
+    let mut visited = 0u64;
+    let mut best: Option<(u64, u64)> = None;
+    
+    while let Some(index) = stack.pop() {
+        if index == 0 {
+            continue;
+        }
+        visited += 1;
+        if visited > capacity {
+            // A corrupt tree would otherwise loop forever
+            return None;
+        }
+        
+        let offset = node_offset(index);
+        stack.push(read_u32(data, offset)?);
+        stack.push(read_u32(data, offset + 4)?);
+        
+        let price_in_ticks = read_u64(data, offset + 16)?;
+        let num_base_lots = read_u64(data, offset + 40)?;
+        
+        best = match best {
+            Some((price, lots)) if price == price_in_ticks => Some((price, lots + num_base_lots)),
+            Some((price, _)) if (price_in_ticks > price) == best_is_max => Some((price_in_ticks, num_base_lots)),
+            Some(current) => Some(current),
+            None => Some((price_in_ticks, num_base_lots)),
+        };
+    }
+    
+    best
+}
+
+/// Read a pubkey from account data
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    let bytes = data.get(offset..offset + 32)?;
+    Some(Pubkey::new_from_array(bytes.try_into().ok()?))
+}
+
+/// Recently fetched quotes, reused until they are older than the TTL
+struct PriceCache {
+    /// How long a quote stays fresh
+    ttl: Duration,
+    /// Quotes keyed by (DEX, base token, quote token) with the time they were fetched
+    entries: HashMap<(DexType, Pubkey, Pubkey), (Instant, PriceInfo)>,
+}
+
+impl PriceCache {
+    /// Create an empty cache
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+    
+    /// Get a quote if it's still fresh
+    fn get(&self, key: &(DexType, Pubkey, Pubkey)) -> Option<PriceInfo> {
+        self.entries.get(key)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < self.ttl)
+            .map(|(_, price)| price.clone())
+    }
+    
+    /// Store a freshly fetched quote
+    fn insert(&mut self, key: (DexType, Pubkey, Pubkey), price: PriceInfo) {
+        self.entries.insert(key, (Instant::now(), price));
+    }
+    
+    /// Drop all cached quotes
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Discovered Raydium pool for a token pair
+#[derive(Debug, Clone)]
+struct RaydiumPool {
+    /// Pool (AMM) account
+    address: Pubkey,
+    /// Token account holding the base reserve
+    base_vault: Pubkey,
+    /// Token account holding the quote reserve
+    quote_vault: Pubkey,
+    /// Base token decimals
+    base_decimals: u32,
+    /// Quote token decimals
+    quote_decimals: u32,
+    /// Swap fee in basis points
+    fee_bps: Option<u64>,
+    /// Whether the pool's coin side is our quote token
+    inverted: bool,
+}
+
+/// Discovered Orca Whirlpool for a token pair
+#[derive(Debug, Clone)]
+struct OrcaPool {
+    /// Whirlpool account
+    address: Pubkey,
+    /// Decimals of the pool's token A
+    decimals_a: u32,
+    /// Decimals of the pool's token B
+    decimals_b: u32,
+    /// Whether the pool's token A is our quote token
+    inverted: bool,
+}
+
+/// Discovered Meteora DLMM pair for a token pair
+#[derive(Debug, Clone)]
+struct MeteoraPool {
+    /// LbPair account
+    address: Pubkey,
+    /// Mint of token X
+    token_x_mint: Pubkey,
+    /// Mint of token Y
+    token_y_mint: Pubkey,
+    /// Token account holding the X reserve
+    reserve_x: Pubkey,
+    /// Token account holding the Y reserve
+    reserve_y: Pubkey,
+    /// Decimals of token X
+    decimals_x: u32,
+    /// Decimals of token Y
+    decimals_y: u32,
+    /// Price step between bins in basis points
+    bin_step: u16,
+    /// Whether the pair's token X is our quote token
+    inverted: bool,
+}
+
+/// Discovered Phoenix market for a token pair
+#[derive(Debug, Clone)]
+struct PhoenixMarket {
+    /// Market account
+    address: Pubkey,
+    /// Market base mint
+    base_mint: Pubkey,
+    /// Market quote mint
+    quote_mint: Pubkey,
+    /// Market base vault
+    base_vault: Pubkey,
+    /// Market quote vault
+    quote_vault: Pubkey,
+    /// Market base decimals
+    base_decimals: u32,
+    /// Market quote decimals
+    quote_decimals: u32,
+    /// Base atoms per base lot
+    base_lot_size: u64,
+    /// Quote atoms per quote lot
+    quote_lot_size: u64,
+    /// Quote atoms per base unit per tick
+    tick_size: u64,
+    /// Whole base tokens per base unit
+    raw_base_units_per_base_unit: u32,
+    /// Capacity of the bids tree
+    bids_size: u64,
+    /// Capacity of the asks tree
+    asks_size: u64,
+    /// Whether the market's base token is our quote token
+    inverted: bool,
+}
+
+/// Top of an order book, in our base/quote orientation
+#[derive(Debug, Clone, Copy)]
+pub struct OrderBookTop {
+    /// Best bid (quote per base)
+    pub best_bid: f64,
+    /// Best ask (quote per base)
+    pub best_ask: f64,
+    /// Base token resting at the best bid, in base atoms
+    pub bid_liquidity: u64,
+    /// Base token resting at the best ask, in base atoms
+    pub ask_liquidity: u64,
+}
+
+/// Instructions for a swap and the lookup tables they reference
+#[derive(Debug, Clone, Default)]
+pub struct SwapInstructions {
+    /// Swap instructions, in order
+    pub instructions: Vec<Instruction>,
+    /// Address lookup tables needed to fit the instructions in a v0 transaction
+    pub lookup_tables: Vec<AddressLookupTableAccount>,
+}
+
+impl SwapInstructions {
+    /// Wrap a single instruction that needs no lookup tables
+    pub fn single(instruction: Instruction) -> Self {
+        Self {
+            instructions: vec![instruction],
+            lookup_tables: Vec::new(),
+        }
+    }
+    
+    /// Append another swap's instructions and lookup tables
+    pub fn extend(&mut self, other: SwapInstructions) {
+        self.instructions.extend(other.instructions);
+        for table in other.lookup_tables {
+            if !self.lookup_tables.iter().any(|t| t.key == table.key) {
+                self.lookup_tables.push(table);
+            }
+        }
+    }
+}
+
+/// Decompile a versioned message back into instructions using resolved lookup tables
+fn decompile_message(message: &VersionedMessage, lookup_tables: &[AddressLookupTableAccount]) -> Result<Vec<Instruction>, DexError> {
+    let header = message.header();
+    let static_keys = message.static_account_keys();
+    
+    let num_signers = header.num_required_signatures as usize;
+    let num_writable_signers = num_signers.saturating_sub(header.num_readonly_signed_accounts as usize);
+    let num_writable_static = static_keys.len().saturating_sub(header.num_readonly_unsigned_accounts as usize);
+    
+    // Account keys in message order: static keys, then loaded writable, then loaded readonly
+    let mut keys: Vec<(Pubkey, bool)> = static_keys.iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let writable = if i < num_signers { i < num_writable_signers } else { i < num_writable_static };
+            (*key, writable)
+        })
+        .collect();
+    
+    let lookups = message.address_table_lookups().unwrap_or_default();
+    for writable in [true, false] {
+        for lookup in lookups {
+            let table = lookup_tables.iter()
+                .find(|t| t.key == lookup.account_key)
+                .ok_or_else(|| DexError::ApiError(format!("Lookup table {} not resolved", lookup.account_key)))?;
+            
+            let indexes = if writable { &lookup.writable_indexes } else { &lookup.readonly_indexes };
+            for index in indexes {
+                let key = table.addresses.get(*index as usize)
+                    .ok_or_else(|| DexError::ApiError(format!("Lookup index {} out of range for {}", index, table.key)))?;
+                keys.push((*key, writable));
+            }
+        }
+    }
+    
+    message.instructions().iter()
+        .map(|compiled| {
+            let (program_id, _) = *keys.get(compiled.program_id_index as usize)
+                .ok_or_else(|| DexError::ApiError("Program index out of range".to_string()))?;
+            
+            let accounts = compiled.accounts.iter()
+                .map(|index| {
+                    let index = *index as usize;
+                    let (pubkey, is_writable) = *keys.get(index)
+                        .ok_or_else(|| DexError::ApiError("Account index out of range".to_string()))?;
+                    Ok(AccountMeta {
+                        pubkey,
+                        is_signer: index < num_signers,
+                        is_writable,
+                    })
+                })
+                .collect::<Result<Vec<_>, DexError>>()?;
+            
+            Ok(Instruction {
+                program_id,
+                accounts,
+                data: compiled.data.clone(),
+            })
+        })
+        .collect()
+}
+
 /// DEX configuration
 pub struct DexConfig {
     /// DEX type
@@ -105,6 +789,21 @@ pub struct DexConfig {
     pub custom_name: Option<String>,
     /// Whether this DEX is enabled
     pub enabled: bool,
+    /// Pool fee tier to restrict discovery to when a pair has several pools
+    /// (Whirlpool fee rate in hundredths of a bp, DLMM bin step in bps)
+    pub fee_tier: Option<u16>,
+    /// Pools to use per (base, quote) pair instead of discovering one (Raydium and Orca);
+    /// a pin applies to the pair in either order
+    pub pinned_pools: HashMap<(Pubkey, Pubkey), Pubkey>,
+    /// Swap fee in basis points for pools that don't report their own (None leaves it to the cost model)
+    pub fee_bps: Option<u64>,
+    /// How long a fetched quote is reused before hitting the network again (0 disables caching)
+    pub price_cache_ttl_ms: u64,
+    /// Trade size in quote token atoms that aggregator prices are quoted for (so the price includes
+    /// its impact) and that pools are compared at when a pair has several
+    pub quote_amount: u64,
+    /// Pricing and swap logic for custom DEXs
+    pub custom_adapter: Option<Box<dyn CustomDexAdapter>>,
 }
 
 impl DexConfig {
@@ -116,6 +815,12 @@ impl DexConfig {
             program_id: Pubkey::from_str("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4").unwrap_or_default(),
             custom_name: None,
             enabled: true,
+            fee_tier: None,
+            pinned_pools: HashMap::new(),
+            fee_bps: None,
+            price_cache_ttl_ms: 500,
+            quote_amount: DEFAULT_QUOTE_AMOUNT,
+            custom_adapter: None,
         }
     }
     
@@ -127,6 +832,12 @@ impl DexConfig {
             program_id: Pubkey::from_str("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8").unwrap_or_default(),
             custom_name: None,
             enabled: true,
+            fee_tier: None,
+            pinned_pools: HashMap::new(),
+            fee_bps: None,
+            price_cache_ttl_ms: 500,
+            quote_amount: DEFAULT_QUOTE_AMOUNT,
+            custom_adapter: None,
         }
     }
     
@@ -138,21 +849,205 @@ impl DexConfig {
             program_id: Pubkey::from_str("9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP").unwrap_or_default(),
             custom_name: None,
             enabled: true,
+            fee_tier: None,
+            pinned_pools: HashMap::new(),
+            fee_bps: None,
+            price_cache_ttl_ms: 500,
+            quote_amount: DEFAULT_QUOTE_AMOUNT,
+            custom_adapter: None,
+        }
+    }
+    
+    /// Create a new Meteora DLMM configuration
+    pub fn new_meteora() -> Self {
+        Self {
+            dex_type: DexType::Meteora,
+            api_url: "https://dlmm-api.meteora.ag".to_string(),
+            program_id: Pubkey::from_str(METEORA_DLMM_PROGRAM_ID).unwrap_or_default(),
+            custom_name: None,
+            enabled: true,
+            fee_tier: None,
+            pinned_pools: HashMap::new(),
+            fee_bps: None,
+            price_cache_ttl_ms: 500,
+            quote_amount: DEFAULT_QUOTE_AMOUNT,
+            custom_adapter: None,
+        }
+    }
+    
+    /// Create a new Phoenix configuration
+    pub fn new_phoenix() -> Self {
+        Self {
+            dex_type: DexType::Phoenix,
+            // Phoenix is read directly from chain; there's no HTTP API to call
+            api_url: String::new(),
+            program_id: Pubkey::from_str(PHOENIX_PROGRAM_ID).unwrap_or_default(),
+            custom_name: None,
+            enabled: true,
+            fee_tier: None,
+            pinned_pools: HashMap::new(),
+            fee_bps: None,
+            price_cache_ttl_ms: 500,
+            quote_amount: DEFAULT_QUOTE_AMOUNT,
+            custom_adapter: None,
         }
     }
     
-    /// Create a new custom DEX configuration
-    pub fn new_custom(api_url: &str, program_id: Pubkey, name: &str) -> Self {
+    /// Create a new custom DEX configuration backed by an adapter
+    pub fn new_custom(api_url: &str, program_id: Pubkey, name: &str, adapter: Box<dyn CustomDexAdapter>) -> Self {
         Self {
             dex_type: DexType::Custom,
             api_url: api_url.to_string(),
             program_id,
             custom_name: Some(name.to_string()),
             enabled: true,
+            fee_tier: None,
+            pinned_pools: HashMap::new(),
+            fee_bps: None,
+            price_cache_ttl_ms: 500,
+            quote_amount: DEFAULT_QUOTE_AMOUNT,
+            custom_adapter: Some(adapter),
+        }
+    }
+}
+
+/// Pricing and swap logic for a DEX the connector doesn't know natively
+pub trait CustomDexAdapter: Send + Sync {
+    /// Quote the price of `base_token` in `quote_token`
+    fn get_price(&self, rpc_client: &RpcClient, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError>;
+    
+    /// Build the swap instruction for the given parameters
+    fn create_swap_instruction(&self, params: &SwapParams) -> Result<Instruction, DexError>;
+}
+
+/// Adapter for a simple constant-product (x * y = k) AMM pool
+///
+/// Prices from the two vault balances and builds a swap instruction with the
+/// common `[discriminator, amount_in, min_amount_out]` layout.
+pub struct ConstantProductAdapter {
+    /// AMM program ID
+    pub program_id: Pubkey,
+    /// Pool state account
+    pub pool: Pubkey,
+    /// Pool authority (PDA that owns the vaults)
+    pub authority: Pubkey,
+    /// Token mint of the first vault
+    pub mint_a: Pubkey,
+    /// Token mint of the second vault
+    pub mint_b: Pubkey,
+    /// Token account holding the pool's `mint_a` reserve
+    pub vault_a: Pubkey,
+    /// Token account holding the pool's `mint_b` reserve
+    pub vault_b: Pubkey,
+    /// Decimals of `mint_a`
+    pub decimals_a: u32,
+    /// Decimals of `mint_b`
+    pub decimals_b: u32,
+    /// Swap instruction discriminator
+    pub swap_discriminator: u8,
+}
+
+impl ConstantProductAdapter {
+    /// Vaults and decimals ordered as (base, quote), or None if the pool doesn't trade this pair
+    fn orient(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Option<(Pubkey, Pubkey, u32, u32)> {
+        if *base_token == self.mint_a && *quote_token == self.mint_b {
+            Some((self.vault_a, self.vault_b, self.decimals_a, self.decimals_b))
+        } else if *base_token == self.mint_b && *quote_token == self.mint_a {
+            Some((self.vault_b, self.vault_a, self.decimals_b, self.decimals_a))
+        } else {
+            None
+        }
+    }
+}
+
+impl CustomDexAdapter for ConstantProductAdapter {
+    fn get_price(&self, rpc_client: &RpcClient, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
+        let (base_vault, quote_vault, base_decimals, quote_decimals) = self.orient(base_token, quote_token)
+            .ok_or_else(|| DexError::ParameterError(format!("Pool {} does not trade {}/{}", self.pool, base_token, quote_token)))?;
+        
+        let accounts = rpc_client.get_multiple_accounts(&[base_vault, quote_vault])
+            .map_err(|e| DexError::RpcError(format!("Failed to fetch pool vaults: {}", e)))?;
+        
+        let decode_err = || DexError::ApiError(format!("Failed to decode vaults of pool {}", self.pool));
+        let (base_reserve, quote_reserve) = match accounts.as_slice() {
+            [Some(base), Some(quote)] => (
+                read_u64(&base.data, TOKEN_ACCOUNT_AMOUNT_OFFSET).ok_or_else(decode_err)?,
+                read_u64(&quote.data, TOKEN_ACCOUNT_AMOUNT_OFFSET).ok_or_else(decode_err)?,
+            ),
+            _ => return Err(DexError::ApiError(format!("Vaults of pool {} not found", self.pool))),
+        };
+        
+        if base_reserve == 0 || quote_reserve == 0 {
+            return Err(DexError::ApiError(format!("Pool {} has no liquidity", self.pool)));
         }
+        
+        let price = (quote_reserve as f64 / 10f64.powi(quote_decimals as i32))
+            / (base_reserve as f64 / 10f64.powi(base_decimals as i32));
+        
+        Ok(PriceInfo {
+            base_token: *base_token,
+            quote_token: *quote_token,
+            price,
+            liquidity: base_reserve,
+            dex: DexType::Custom,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            
+            base_decimals: base_decimals as u8,
+            quote_decimals: quote_decimals as u8,
+            pool: Some(self.pool),
+            fee_bps: None,
+        })
+    }
+    
+    fn create_swap_instruction(&self, params: &SwapParams) -> Result<Instruction, DexError> {
+        // Tokens flow from the user into the source-side vault and out of the destination-side vault
+        let (pool_source, pool_destination, _, _) = self.orient(&params.source_token, &params.destination_token)
+            .ok_or_else(|| DexError::ParameterError(format!(
+                "Pool {} does not trade {}/{}", self.pool, params.source_token, params.destination_token
+            )))?;
+        
+        let accounts = vec![
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new_readonly(params.source_wallet, true),
+            AccountMeta::new(get_associated_token_address(&params.source_wallet, &params.source_token), false),
+            AccountMeta::new(get_associated_token_address(&params.destination_wallet, &params.destination_token), false),
+            AccountMeta::new(pool_source, false),
+            AccountMeta::new(pool_destination, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ];
+        
+        let mut data = vec![self.swap_discriminator];
+        data.extend_from_slice(&params.amount_in.to_le_bytes());
+        data.extend_from_slice(&params.min_amount_out.to_le_bytes());
+        
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data,
+        })
     }
 }
 
+/// Maximum number of characters of an error body included in API errors
+const ERROR_BODY_SNIPPET_LEN: usize = 200;
+
+/// Turn a non-2xx HTTP response into an API error carrying the status and body
+async fn check_response_status(response: reqwest::Response) -> Result<reqwest::Response, DexError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    
+    let body = response.text().await.unwrap_or_default();
+    let snippet: String = body.chars().take(ERROR_BODY_SNIPPET_LEN).collect();
+    
+    Err(DexError::ApiError(format!("HTTP {}: {}", status, snippet)))
+}
+
 /// DEX connector
 pub struct DexConnector {
     /// RPC client for Solana
@@ -161,179 +1056,991 @@ pub struct DexConnector {
     http_client: HttpClient,
     /// DEX configuration
     config: DexConfig,
+    /// Rolling quote latency samples
+    latency: Mutex<LatencyWindow>,
+    /// Raydium pools discovered per token pair
+    raydium_pools: Mutex<HashMap<(Pubkey, Pubkey), RaydiumPool>>,
+    /// Orca Whirlpools discovered per token pair
+    orca_pools: Mutex<HashMap<(Pubkey, Pubkey), OrcaPool>>,
+    /// Meteora DLMM pairs discovered per token pair
+    meteora_pools: Mutex<HashMap<(Pubkey, Pubkey), MeteoraPool>>,
+    /// Phoenix markets discovered per token pair
+    phoenix_markets: Mutex<HashMap<(Pubkey, Pubkey), PhoenixMarket>>,
+    /// Recently fetched quotes
+    price_cache: Mutex<PriceCache>,
+    /// Quotes decoded from a batched account fetch, each served to the next `get_price` for its pair
+    prefetched: Mutex<HashMap<(Pubkey, Pubkey), (Instant, Result<PriceInfo, DexError>)>>,
+    /// Mint decimals fetched so far
+    mint_decimals: Mutex<HashMap<Pubkey, u8>>,
+    /// Retry policy for transient HTTP failures
+    retry_policy: RetryPolicy,
 }
 
 impl DexConnector {
     /// Create a new DEX connector
     pub fn new(rpc_url: &str, config: DexConfig) -> Self {
-        let rpc_client = RpcClient::new(rpc_url.to_string());
+        Self::with_rpc_client(RpcClient::new(rpc_url.to_string()), config)
+    }
+    
+    /// Create a new DEX connector over an existing RPC client
+    pub fn with_rpc_client(rpc_client: RpcClient, config: DexConfig) -> Self {
         let http_client = HttpClient::new();
+        let price_cache = PriceCache::new(Duration::from_millis(config.price_cache_ttl_ms));
         
         Self {
             rpc_client,
             http_client,
             config,
+            price_cache: Mutex::new(price_cache),
+            prefetched: Mutex::new(HashMap::new()),
+            latency: Mutex::new(LatencyWindow::new()),
+            raydium_pools: Mutex::new(HashMap::new()),
+            orca_pools: Mutex::new(HashMap::new()),
+            meteora_pools: Mutex::new(HashMap::new()),
+            phoenix_markets: Mutex::new(HashMap::new()),
+            mint_decimals: Mutex::new(HashMap::new()),
+            retry_policy: RetryPolicy::default(),
         }
     }
     
-    /// Get price from Jupiter
-    async fn get_price_jupiter(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
-        // Jupiter Price API V2 endpoint
-        let url = format!("{}/price?inputMint={}&outputMint={}&amount=1000000&slippageBps=50",
-            self.config.api_url, base_token, quote_token);
+    /// Decimals of a token mint, fetched once and cached
+    fn token_decimals(&self, mint: &Pubkey) -> Result<u8, DexError> {
+        if let Some(decimals) = lock_or_recover(&self.mint_decimals).get(mint) {
+            return Ok(*decimals);
+        }
         
-        let response = self.http_client.get(&url)
-            .send()
-            .await
-            .map_err(|e| DexError::ApiError(format!("Failed to send request: {}", e)))?;
+        let data = self.rpc_client.get_account_data(mint)
+            .map_err(|e| DexError::RpcError(format!("Failed to fetch mint {}: {}", mint, e)))?;
+        let decimals = *data.get(MINT_DECIMALS_OFFSET)
+            .ok_or_else(|| DexError::ApiError(format!("Failed to read decimals of mint {}", mint)))?;
         
-        let json: Value = response.json()
-            .await
-            .map_err(|e| DexError::ApiError(format!("Failed to parse response: {}", e)))?;
+        lock_or_recover(&self.mint_decimals).insert(*mint, decimals);
+        
+        Ok(decimals)
+    }
+    
+    /// Set the retry policy for transient HTTP failures
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+    
+    /// Replace the RPC client pool and vault accounts are read with
+    pub fn set_rpc_client(&mut self, rpc_client: RpcClient) {
+        self.rpc_client = rpc_client;
+    }
+    
+    /// Send an HTTP request, retrying transient failures and 5xx/429 responses
+    ///
+    /// Only used for requests that are safe to repeat (quotes and unsigned swap builds).
+    async fn send_request(&self, request: reqwest::RequestBuilder, what: &str) -> Result<reqwest::Response, DexError> {
+        self.retry_policy.retry_async(what, || async {
+            let request = request.try_clone()
+                .ok_or_else(|| DexError::GeneralError(format!("{} request can't be retried", what)))?;
+            let response = request.send()
+                .await
+                .map_err(|e| DexError::ApiError(format!("Failed to send {} request: {}", what, e)))?;
+            check_response_status(response).await
+        }).await
+    }
+    
+    /// Get price from Jupiter for buying the base token with `amount_in` quote token atoms
+    ///
+    /// The price is the route's effective price at that size, so it includes price impact
+    /// rather than reflecting a tiny probe.
+    async fn get_price_jupiter(&self, base_token: &Pubkey, quote_token: &Pubkey, amount_in: u64) -> Result<PriceInfo, DexError> {
+        if amount_in == 0 {
+            return Err(DexError::ParameterError("Jupiter quote amount must be positive".to_string()));
+        }
+        
+        let quote_json = self.fetch_jupiter_quote(quote_token, base_token, amount_in, PRICE_QUOTE_SLIPPAGE_BPS).await?;
+        let route = RouteQuote::from_jupiter(&quote_json)?;
+        if route.out_amount == 0 {
+            return Err(DexError::ApiError(format!("Jupiter route for {} returns nothing", amount_in)));
+        }
         
-        // Extract price from response
-        let price = json["data"]["price"]
-            .as_f64()
-            .ok_or_else(|| DexError::ApiError("Price not found in response".to_string()))?;
+        // Jupiter quotes in atoms; prices are compared across DEXs in whole tokens
+        let base_decimals = self.token_decimals(base_token)?;
+        let quote_decimals = self.token_decimals(quote_token)?;
         
-        // Extract liquidity (simplified)
-        let liquidity = json["data"]["inAmount"]
-            .as_str()
-            .and_then(|s| s.parse::<u64>().ok())
-            .unwrap_or(0);
+        let quote_in = route.in_amount as f64 / 10f64.powi(quote_decimals as i32);
+        let base_out = route.out_amount as f64 / 10f64.powi(base_decimals as i32);
+        let price = quote_in / base_out;
+        debug!("Jupiter {}/{} at size {}: {} ({:.4}% impact)",
+               base_token, quote_token, amount_in, price, route.price_impact_pct * 100.0);
         
         Ok(PriceInfo {
             base_token: *base_token,
             quote_token: *quote_token,
             price,
-            liquidity,
+            // What the route guarantees to deliver at this size
+            liquidity: route.other_amount_threshold,
             dex: DexType::Jupiter,
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_secs(),
+            base_decimals,
+            quote_decimals,
+            pool: None,
+            fee_bps: None,
         })
     }
     
     /// Get price from Raydium
     async fn get_price_raydium(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
-        // This is a simplified implementation
-        // In a real implementation, you would need to:
-        // 1. Find the correct pool for the token pair
-        // 2. Get the pool state
-        // 3. Calculate the price from the pool state
+        let pool = self.find_raydium_pool(base_token, quote_token)?;
         
-        // For now, we'll return a placeholder price
-        Ok(PriceInfo {
-            base_token: *base_token,
-            quote_token: *quote_token,
-            price: 0.0, // Placeholder
-            liquidity: 0, // Placeholder
-            dex: DexType::Raydium,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-        })
+        // Read the pool and both vaults in one round trip
+        let accounts = self.rpc_client.get_multiple_accounts(&[pool.address, pool.base_vault, pool.quote_vault])
+            .map_err(|e| DexError::RpcError(format!("Failed to fetch Raydium pool accounts: {}", e)))?;
+        Self::raydium_price(base_token, quote_token, &pool, &accounts)
     }
     
-    /// Get price from Orca
-    async fn get_price_orca(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
-        // Similar to Raydium, this is a simplified implementation
+    /// Price a Raydium pool from its account and its base and quote vaults, in that order
+    fn raydium_price(base_token: &Pubkey, quote_token: &Pubkey, pool: &RaydiumPool, accounts: &[Option<Account>]) -> Result<PriceInfo, DexError> {
+        let (pool_account, base_vault, quote_vault) = match accounts {
+            [Some(pool_account), Some(base_vault), Some(quote_vault)] => (pool_account, base_vault, quote_vault),
+            _ => return Err(DexError::ApiError(format!("Raydium pool {} accounts not found", pool.address))),
+        };
+        
+        // Fees owed to the protocol sit in the vaults but aren't tradable
+        let (base_pnl_offset, quote_pnl_offset) = if pool.inverted {
+            (RAYDIUM_PC_NEED_TAKE_PNL_OFFSET, RAYDIUM_COIN_NEED_TAKE_PNL_OFFSET)
+        } else {
+            (RAYDIUM_COIN_NEED_TAKE_PNL_OFFSET, RAYDIUM_PC_NEED_TAKE_PNL_OFFSET)
+        };
+        
+        let decode_err = || DexError::ApiError(format!("Failed to decode Raydium pool {}", pool.address));
+        let base_reserve = read_u64(&base_vault.data, TOKEN_ACCOUNT_AMOUNT_OFFSET).ok_or_else(decode_err)?
+            .saturating_sub(read_u64(&pool_account.data, base_pnl_offset).ok_or_else(decode_err)?);
+        let quote_reserve = read_u64(&quote_vault.data, TOKEN_ACCOUNT_AMOUNT_OFFSET).ok_or_else(decode_err)?
+            .saturating_sub(read_u64(&pool_account.data, quote_pnl_offset).ok_or_else(decode_err)?);
+        
+        if base_reserve == 0 || quote_reserve == 0 {
+            return Err(DexError::ApiError(format!("Raydium pool {} has no liquidity", pool.address)));
+        }
+        
+        // Constant-product spot price, adjusted for token decimals
+        let price = (quote_reserve as f64 / 10f64.powi(pool.quote_decimals as i32))
+            / (base_reserve as f64 / 10f64.powi(pool.base_decimals as i32));
         
         Ok(PriceInfo {
             base_token: *base_token,
             quote_token: *quote_token,
-            price: 0.0, // Placeholder
-            liquidity: 0, // Placeholder
-            dex: DexType::Orca,
+            price,
+            liquidity: base_reserve,
+            dex: DexType::Raydium,
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_secs(),
+            
+            base_decimals: pool.base_decimals as u8,
+            quote_decimals: pool.quote_decimals as u8,
+            pool: Some(pool.address),
+            fee_bps: pool.fee_bps,
         })
     }
     
-    /// Get price from the configured DEX
-    pub async fn get_price(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
-        if !self.config.enabled {
-            return Err(DexError::GeneralError("DEX is disabled".to_string()));
+    /// Pool pinned for a token pair in either order, if any
+    fn pinned_pool(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Option<Pubkey> {
+        self.config.pinned_pools.get(&(*base_token, *quote_token))
+            .or_else(|| self.config.pinned_pools.get(&(*quote_token, *base_token)))
+            .copied()
+    }
+    
+    /// Find (and cache) the Raydium AMM pool for a token pair
+    fn find_raydium_pool(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<RaydiumPool, DexError> {
+        let token_pair = (*base_token, *quote_token);
+        
+        if let Some(pool) = lock_or_recover(&self.raydium_pools).get(&token_pair) {
+            return Ok(pool.clone());
         }
         
-        match self.config.dex_type {
-            DexType::Jupiter => self.get_price_jupiter(base_token, quote_token).await,
+        let pool = match self.pinned_pool(base_token, quote_token) {
+            Some(address) => {
+                let account = self.rpc_client.get_account(&address)
+                    .map_err(|e| DexError::RpcError(format!("Failed to fetch pinned Raydium pool {}: {}", address, e)))?;
+                if account.owner != self.config.program_id {
+                    return Err(DexError::ParameterError(format!("Pinned pool {} is not a Raydium AMM pool", address)));
+                }
+                raydium_pool_from_account(&address, &account.data, base_token, quote_token)
+                    .ok_or_else(|| DexError::ParameterError(format!(
+                        "Pinned Raydium pool {} does not trade {}/{}", address, base_token, quote_token
+                    )))?
+            },
+            None => self.deepest_raydium_pool(base_token, quote_token)?,
+        };
+        
+        debug!("Using Raydium pool {} for {}/{}", pool.address, base_token, quote_token);
+        lock_or_recover(&self.raydium_pools).insert(token_pair, pool.clone());
+        
+        Ok(pool)
+    }
+    
+    /// Scan for every Raydium AMM pool trading a token pair and choose the deepest
+    ///
+    /// Raydium AMM pools all charge the same fee, so the pool with the largest quote
+    /// reserve moves least for a trade of any size.
+    fn deepest_raydium_pool(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<RaydiumPool, DexError> {
+        // Pools may list the pair in either order
+        let mut candidates = Vec::new();
+        for (coin_mint, pc_mint) in [(base_token, quote_token), (quote_token, base_token)] {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(RAYDIUM_AMM_ACCOUNT_LEN),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(RAYDIUM_COIN_MINT_OFFSET, coin_mint.as_ref())),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(RAYDIUM_PC_MINT_OFFSET, pc_mint.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            
+            let accounts = self.rpc_client.get_program_accounts_with_config(&self.config.program_id, config)
+                .map_err(|e| DexError::RpcError(format!("Failed to scan Raydium pools: {}", e)))?;
+            candidates.extend(accounts.iter()
+                .filter_map(|(address, account)| raydium_pool_from_account(address, &account.data, base_token, quote_token)));
+        }
+        
+        self.deepest_by_vault(candidates, |pool| pool.quote_vault)?
+            .ok_or_else(|| DexError::ApiError(format!("No Raydium pool found for {}/{}", base_token, quote_token)))
+    }
+    
+    /// Of the pools found for a pair, the one whose `quote_vault` holds the most
+    ///
+    /// Every candidate's vault is read in one round trip. Ties go to the higher vault address,
+    /// so the choice doesn't depend on the order the RPC node listed the pools in.
+    fn deepest_by_vault<T>(&self, mut candidates: Vec<T>, quote_vault: impl Fn(&T) -> Pubkey) -> Result<Option<T>, DexError> {
+        if candidates.len() <= 1 {
+            return Ok(candidates.pop());
+        }
+        
+        let vaults = candidates.iter().map(&quote_vault).collect::<Vec<_>>();
+        let vault_accounts = self.rpc_client.get_multiple_accounts(&vaults)
+            .map_err(|e| DexError::RpcError(format!("Failed to fetch {:?} pool vaults: {}", self.config.dex_type, e)))?;
+        
+        let pool_count = candidates.len();
+        let deepest = candidates.into_iter()
+            .zip(vault_accounts)
+            .map(|(candidate, account)| {
+                let depth = account
+                    .and_then(|account| read_u64(&account.data, TOKEN_ACCOUNT_AMOUNT_OFFSET))
+                    .unwrap_or(0);
+                (depth, quote_vault(&candidate), candidate)
+            })
+            .max_by_key(|(depth, vault, _)| (*depth, *vault));
+        
+        Ok(deepest.map(|(depth, vault, candidate)| {
+            debug!("Chose the {:?} pool with quote vault {} of {} (quote reserve {})",
+                   self.config.dex_type, vault, pool_count, depth);
+            candidate
+        }))
+    }
+    
+    /// Find (and cache) the Orca Whirlpool for a token pair
+    ///
+    /// A pinned whirlpool is used as is. Otherwise, of the whirlpools in `fee_tier` (or
+    /// all of them when unset), the one losing least to fee and impact at `quote_amount` is chosen.
+    fn find_orca_pool(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<OrcaPool, DexError> {
+        let token_pair = (*base_token, *quote_token);
+        
+        if let Some(pool) = lock_or_recover(&self.orca_pools).get(&token_pair) {
+            return Ok(pool.clone());
+        }
+        
+        let program_id = Pubkey::from_str(ORCA_WHIRLPOOL_PROGRAM_ID)
+            .map_err(|e| DexError::GeneralError(format!("Invalid Whirlpool program ID: {}", e)))?;
+        
+        let (address, mint_a, mint_b, inverted) = match self.pinned_pool(base_token, quote_token) {
+            Some(address) => {
+                let account = self.rpc_client.get_account(&address)
+                    .map_err(|e| DexError::RpcError(format!("Failed to fetch pinned whirlpool {}: {}", address, e)))?;
+                if account.owner != program_id {
+                    return Err(DexError::ParameterError(format!("Pinned pool {} is not an Orca whirlpool", address)));
+                }
+                
+                let decode_err = || DexError::ApiError(format!("Failed to decode whirlpool {}", address));
+                let mint_a = read_pubkey(&account.data, WHIRLPOOL_MINT_A_OFFSET).ok_or_else(decode_err)?;
+                let mint_b = read_pubkey(&account.data, WHIRLPOOL_MINT_B_OFFSET).ok_or_else(decode_err)?;
+                let inverted = if (mint_a, mint_b) == (*base_token, *quote_token) {
+                    false
+                } else if (mint_a, mint_b) == (*quote_token, *base_token) {
+                    true
+                } else {
+                    return Err(DexError::ParameterError(format!(
+                        "Pinned whirlpool {} does not trade {}/{}", address, base_token, quote_token
+                    )));
+                };
+                (address, mint_a, mint_b, inverted)
+            },
+            None => self.cheapest_orca_pool(&program_id, base_token, quote_token)?,
+        };
+        
+        // Decimals come from the mint accounts
+        let mints = self.rpc_client.get_multiple_accounts(&[mint_a, mint_b])
+            .map_err(|e| DexError::RpcError(format!("Failed to fetch mints: {}", e)))?;
+        let decimals = |account: &Option<solana_sdk::account::Account>| {
+            account.as_ref()
+                .and_then(|account| account.data.get(MINT_DECIMALS_OFFSET).copied())
+                .map(u32::from)
+                .ok_or_else(|| DexError::ApiError("Failed to read mint decimals".to_string()))
+        };
+        
+        let pool = OrcaPool {
+            address,
+            decimals_a: decimals(&mints[0])?,
+            decimals_b: decimals(&mints[1])?,
+            inverted,
+        };
+        
+        debug!("Using Orca whirlpool {} for {}/{}", pool.address, base_token, quote_token);
+        lock_or_recover(&self.orca_pools).insert(token_pair, pool.clone());
+        
+        Ok(pool)
+    }
+    
+    /// Scan for the whirlpools trading a token pair and choose the cheapest to trade `quote_amount` through
+    ///
+    /// Returns the whirlpool's address, its token A and B mints and whether token A is our quote token.
+    fn cheapest_orca_pool(&self, program_id: &Pubkey, base_token: &Pubkey, quote_token: &Pubkey) -> Result<(Pubkey, Pubkey, Pubkey, bool), DexError> {
+        // Whirlpools order their mints, so try both orientations
+        let mut candidates = Vec::new();
+        for (mint_a, mint_b, inverted) in [(base_token, quote_token, false), (quote_token, base_token, true)] {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(WHIRLPOOL_ACCOUNT_LEN),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(WHIRLPOOL_MINT_A_OFFSET, mint_a.as_ref())),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(WHIRLPOOL_MINT_B_OFFSET, mint_b.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            
+            let accounts = self.rpc_client.get_program_accounts_with_config(program_id, config)
+                .map_err(|e| DexError::RpcError(format!("Failed to scan whirlpools: {}", e)))?;
+            
+            for (address, account) in accounts {
+                let fee_rate = read_u16(&account.data, WHIRLPOOL_FEE_RATE_OFFSET).unwrap_or_default();
+                if self.config.fee_tier.map_or(false, |fee_tier| fee_tier != fee_rate) {
+                    continue;
+                }
+                // Whirlpools without active liquidity can't fill anything
+                if let Some(cost) = whirlpool_swap_cost(&account.data, inverted, self.config.quote_amount) {
+                    candidates.push((address, fee_rate, cost, *mint_a, *mint_b, inverted));
+                }
+            }
+        }
+        
+        let (address, fee_rate, cost, mint_a, mint_b, inverted) = candidates.iter()
+            .copied()
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .ok_or_else(|| DexError::ApiError(format!("No Orca whirlpool found for {}/{}", base_token, quote_token)))?;
+        
+        if candidates.len() > 1 {
+            debug!("Chose whirlpool {} (fee rate {}) of {} for {}/{}, {:.4}% lost at size {}",
+                   address, fee_rate, candidates.len(), base_token, quote_token, cost * 100.0, self.config.quote_amount);
+        }
+        Ok((address, mint_a, mint_b, inverted))
+    }
+    
+    /// Get price from Orca
+    async fn get_price_orca(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
+        let pool = self.find_orca_pool(base_token, quote_token)?;
+        
+        let data = self.rpc_client.get_account_data(&pool.address)
+            .map_err(|e| DexError::RpcError(format!("Failed to fetch whirlpool {}: {}", pool.address, e)))?;
+        Self::orca_price(base_token, quote_token, &pool, &data)
+    }
+    
+    /// Price a whirlpool from its account data
+    fn orca_price(base_token: &Pubkey, quote_token: &Pubkey, pool: &OrcaPool, data: &[u8]) -> Result<PriceInfo, DexError> {
+        let decode_err = || DexError::ApiError(format!("Failed to decode whirlpool {}", pool.address));
+        let sqrt_price_x64 = read_u128(data, WHIRLPOOL_SQRT_PRICE_OFFSET).ok_or_else(decode_err)?;
+        let active_liquidity = read_u128(data, WHIRLPOOL_LIQUIDITY_OFFSET).ok_or_else(decode_err)?;
+        // Fee tier in hundredths of a basis point
+        let fee_bps = read_u16(data, WHIRLPOOL_FEE_RATE_OFFSET).and_then(|fee_rate| fee_bps(fee_rate as u64, 1_000_000));
+        
+        if sqrt_price_x64 == 0 || active_liquidity == 0 {
+            return Err(DexError::ApiError(format!("Whirlpool {} has no active liquidity", pool.address)));
+        }
+        
+        // sqrt_price is Q64.64 of sqrt(raw token B per raw token A)
+        let sqrt_price = sqrt_price_x64 as f64 / 2f64.powi(64);
+        let raw_price_b_per_a = sqrt_price * sqrt_price;
+        let price_b_per_a = raw_price_b_per_a * 10f64.powi(pool.decimals_a as i32 - pool.decimals_b as i32);
+        
+        // Token A tradable before the price moves out of the band: L * (1/sqrt(P) - 1/sqrt(P * (1 + band)))
+        let liquidity = active_liquidity as f64;
+        let band_sqrt = sqrt_price * (1.0 + WHIRLPOOL_LIQUIDITY_BAND).sqrt();
+        let tradable_a = liquidity * (1.0 / sqrt_price - 1.0 / band_sqrt);
+        
+        let (price, tradable_base, base_decimals, quote_decimals) = if pool.inverted {
+            // Our base is token B: invert the price and express depth in B
+            (1.0 / price_b_per_a, tradable_a * raw_price_b_per_a, pool.decimals_b, pool.decimals_a)
+        } else {
+            (price_b_per_a, tradable_a, pool.decimals_a, pool.decimals_b)
+        };
+        
+        Ok(PriceInfo {
+            base_token: *base_token,
+            quote_token: *quote_token,
+            price,
+            liquidity: tradable_base as u64,
+            dex: DexType::Orca,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            
+            base_decimals: base_decimals as u8,
+            quote_decimals: quote_decimals as u8,
+            pool: Some(pool.address),
+            fee_bps,
+        })
+    }
+    
+    /// Find (and cache) the Meteora DLMM pair for a token pair
+    ///
+    /// Of the pairs with bin step `fee_tier` (or every bin step when unset), the one
+    /// holding the most quote token is chosen.
+    fn find_meteora_pool(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<MeteoraPool, DexError> {
+        let token_pair = (*base_token, *quote_token);
+        
+        if let Some(pool) = lock_or_recover(&self.meteora_pools).get(&token_pair) {
+            return Ok(pool.clone());
+        }
+        
+        // Pairs order their mints, so try both orientations
+        let mut candidates = Vec::new();
+        for (mint_x, mint_y, inverted) in [(base_token, quote_token, false), (quote_token, base_token, true)] {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(DLMM_LB_PAIR_ACCOUNT_LEN),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(DLMM_TOKEN_X_MINT_OFFSET, mint_x.as_ref())),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(DLMM_TOKEN_Y_MINT_OFFSET, mint_y.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            
+            let accounts = self.rpc_client.get_program_accounts_with_config(&self.config.program_id, config)
+                .map_err(|e| DexError::RpcError(format!("Failed to scan DLMM pairs: {}", e)))?;
+            
+            for (address, account) in accounts {
+                let decode_err = || DexError::ApiError(format!("Failed to decode DLMM pair {}", address));
+                candidates.push((
+                    address,
+                    read_u16(&account.data, DLMM_BIN_STEP_OFFSET).ok_or_else(decode_err)?,
+                    read_pubkey(&account.data, DLMM_RESERVE_X_OFFSET).ok_or_else(decode_err)?,
+                    read_pubkey(&account.data, DLMM_RESERVE_Y_OFFSET).ok_or_else(decode_err)?,
+                    *mint_x,
+                    *mint_y,
+                    inverted,
+                ));
+            }
+        }
+        
+        if let Some(bin_step) = self.config.fee_tier {
+            candidates.retain(|(_, step, _, _, _, _, _)| *step == bin_step);
+        }
+        
+        // The quote token is X when the pair lists it first
+        let (address, bin_step, reserve_x, reserve_y, token_x_mint, token_y_mint, inverted) = self
+            .deepest_by_vault(candidates, |(_, _, reserve_x, reserve_y, _, _, inverted)| if *inverted { *reserve_x } else { *reserve_y })?
+            .ok_or_else(|| DexError::ApiError(format!("No Meteora DLMM pair found for {}/{}", base_token, quote_token)))?;
+        
+        let mints = self.rpc_client.get_multiple_accounts(&[token_x_mint, token_y_mint])
+            .map_err(|e| DexError::RpcError(format!("Failed to fetch mints: {}", e)))?;
+        let decimals = |account: &Option<solana_sdk::account::Account>| {
+            account.as_ref()
+                .and_then(|account| account.data.get(MINT_DECIMALS_OFFSET).copied())
+                .map(u32::from)
+                .ok_or_else(|| DexError::ApiError("Failed to read mint decimals".to_string()))
+        };
+        
+        let pool = MeteoraPool {
+            address,
+            token_x_mint,
+            token_y_mint,
+            reserve_x,
+            reserve_y,
+            decimals_x: decimals(&mints[0])?,
+            decimals_y: decimals(&mints[1])?,
+            bin_step,
+            inverted,
+        };
+        
+        debug!("Found Meteora DLMM pair {} for {}/{}", pool.address, base_token, quote_token);
+        lock_or_recover(&self.meteora_pools).insert(token_pair, pool.clone());
+        
+        Ok(pool)
+    }
+    
+    /// Get price from Meteora DLMM
+    async fn get_price_meteora(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
+        let pool = self.find_meteora_pool(base_token, quote_token)?;
+        
+        let accounts = self.rpc_client.get_multiple_accounts(&[pool.address, pool.reserve_x, pool.reserve_y])
+            .map_err(|e| DexError::RpcError(format!("Failed to fetch DLMM pair accounts: {}", e)))?;
+        Self::meteora_price(base_token, quote_token, &pool, &accounts)
+    }
+    
+    /// Price a DLMM pair from its account and its X and Y reserves, in that order
+    fn meteora_price(base_token: &Pubkey, quote_token: &Pubkey, pool: &MeteoraPool, accounts: &[Option<Account>]) -> Result<PriceInfo, DexError> {
+        let (pair_account, reserve_x, reserve_y) = match accounts {
+            [Some(pair_account), Some(reserve_x), Some(reserve_y)] => (pair_account, reserve_x, reserve_y),
+            _ => return Err(DexError::ApiError(format!("DLMM pair {} accounts not found", pool.address))),
+        };
+        
+        let decode_err = || DexError::ApiError(format!("Failed to decode DLMM pair {}", pool.address));
+        let active_id = read_i32(&pair_account.data, DLMM_ACTIVE_ID_OFFSET).ok_or_else(decode_err)?;
+        // Base fee of base_factor * bin_step * 10 billionths; the volatility surcharge comes on top
+        let fee_bps = read_u16(&pair_account.data, DLMM_BASE_FACTOR_OFFSET)
+            .and_then(|base_factor| fee_bps(base_factor as u64 * pool.bin_step as u64 * 10, 1_000_000_000));
+        let amount_x = read_u64(&reserve_x.data, TOKEN_ACCOUNT_AMOUNT_OFFSET).ok_or_else(decode_err)?;
+        let amount_y = read_u64(&reserve_y.data, TOKEN_ACCOUNT_AMOUNT_OFFSET).ok_or_else(decode_err)?;
+        
+        if amount_x == 0 || amount_y == 0 {
+            return Err(DexError::ApiError(format!("DLMM pair {} has no liquidity", pool.address)));
+        }
+        
+        // The active bin's price is (1 + bin_step) ^ active_id raw token Y per raw token X
+        let raw_price_y_per_x = (1.0 + pool.bin_step as f64 / 10_000.0).powi(active_id);
+        let price_y_per_x = raw_price_y_per_x * 10f64.powi(pool.decimals_x as i32 - pool.decimals_y as i32);
+        
+        // Reserves span every bin, so this is an upper bound on depth near the active price
+        let (price, liquidity, base_decimals, quote_decimals) = if pool.inverted {
+            (1.0 / price_y_per_x, amount_y, pool.decimals_y, pool.decimals_x)
+        } else {
+            (price_y_per_x, amount_x, pool.decimals_x, pool.decimals_y)
+        };
+        
+        Ok(PriceInfo {
+            base_token: *base_token,
+            quote_token: *quote_token,
+            price,
+            liquidity,
+            dex: DexType::Meteora,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            
+            base_decimals: base_decimals as u8,
+            quote_decimals: quote_decimals as u8,
+            pool: Some(pool.address),
+            fee_bps,
+        })
+    }
+    
+    /// Find (and cache) the Phoenix market for a token pair
+    ///
+    /// When several markets trade the pair, the one holding the most quote token is chosen.
+    fn find_phoenix_market(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PhoenixMarket, DexError> {
+        let token_pair = (*base_token, *quote_token);
+        
+        if let Some(market) = lock_or_recover(&self.phoenix_markets).get(&token_pair) {
+            return Ok(market.clone());
+        }
+        
+        // Market size varies with book capacity, so filter on the mints alone
+        let mut candidates = Vec::new();
+        for (market_base, market_quote, inverted) in [(base_token, quote_token, false), (quote_token, base_token, true)] {
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(PHOENIX_BASE_MINT_OFFSET, market_base.as_ref())),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(PHOENIX_QUOTE_MINT_OFFSET, market_quote.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            
+            let accounts = self.rpc_client.get_program_accounts_with_config(&self.config.program_id, config)
+                .map_err(|e| DexError::RpcError(format!("Failed to scan Phoenix markets: {}", e)))?;
+            
+            for (address, account) in accounts {
+                candidates.push(phoenix_market_from_account(address, &account.data, inverted)?);
+            }
+        }
+        
+        // The quote token sits in the market's base vault when the market is inverted
+        let market = self.deepest_by_vault(candidates, |market| if market.inverted { market.base_vault } else { market.quote_vault })?
+            .ok_or_else(|| DexError::ApiError(format!("No Phoenix market found for {}/{}", base_token, quote_token)))?;
+        
+        debug!("Found Phoenix market {} for {}/{}", market.address, base_token, quote_token);
+        lock_or_recover(&self.phoenix_markets).insert(token_pair, market.clone());
+        
+        Ok(market)
+    }
+    
+    /// Read the best bid/ask and the size resting at each from a Phoenix market
+    pub fn get_order_book_top(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<OrderBookTop, DexError> {
+        let market = self.find_phoenix_market(base_token, quote_token)?;
+        
+        let data = self.rpc_client.get_account_data(&market.address)
+            .map_err(|e| DexError::RpcError(format!("Failed to fetch Phoenix market {}: {}", market.address, e)))?;
+        Self::order_book_top(&market, &data)
+    }
+    
+    /// Top of a Phoenix market's book from its account data, oriented for our pair
+    fn order_book_top(market: &PhoenixMarket, data: &[u8]) -> Result<OrderBookTop, DexError> {
+        let asks_offset = PHOENIX_BIDS_TREE_OFFSET + PHOENIX_TREE_HEADER_LEN + market.bids_size as usize * PHOENIX_TREE_NODE_LEN;
+        let empty_err = || DexError::ApiError(format!("Phoenix market {} has an empty book side", market.address));
+        let (bid_ticks, bid_lots) = phoenix_book_side(data, PHOENIX_BIDS_TREE_OFFSET, market.bids_size, true)
+            .ok_or_else(empty_err)?;
+        let (ask_ticks, ask_lots) = phoenix_book_side(data, asks_offset, market.asks_size, false)
+            .ok_or_else(empty_err)?;
+        
+        // Ticks are quote atoms per base unit; convert to whole quote tokens per whole base token
+        let to_price = |ticks: u64| {
+            ticks as f64 * market.tick_size as f64
+                / 10f64.powi(market.quote_decimals as i32)
+                / market.raw_base_units_per_base_unit as f64
+        };
+        let bid = to_price(bid_ticks);
+        let ask = to_price(ask_ticks);
+        let bid_base_atoms = bid_lots * market.base_lot_size;
+        let ask_base_atoms = ask_lots * market.base_lot_size;
+        
+        if !market.inverted {
+            return Ok(OrderBookTop {
+                best_bid: bid,
+                best_ask: ask,
+                bid_liquidity: bid_base_atoms,
+                ask_liquidity: ask_base_atoms,
+            });
+        }
+        
+        // Our base is the market's quote: selling it means buying the market base at its ask
+        let to_quote_atoms = |base_atoms: u64, price: f64| {
+            (base_atoms as f64 * price * 10f64.powi(market.quote_decimals as i32 - market.base_decimals as i32)) as u64
+        };
+        
+        Ok(OrderBookTop {
+            best_bid: 1.0 / ask,
+            best_ask: 1.0 / bid,
+            bid_liquidity: to_quote_atoms(ask_base_atoms, ask),
+            ask_liquidity: to_quote_atoms(bid_base_atoms, bid),
+        })
+    }
+    
+    /// Get price from Phoenix
+    async fn get_price_phoenix(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
+        let market = self.find_phoenix_market(base_token, quote_token)?;
+        let top = self.get_order_book_top(base_token, quote_token)?;
+        Ok(Self::phoenix_price(base_token, quote_token, &market, &top))
+    }
+    
+    /// Price a Phoenix market from its book: mid of the best bid and ask, depth of the thinner side
+    fn phoenix_price(base_token: &Pubkey, quote_token: &Pubkey, market: &PhoenixMarket, top: &OrderBookTop) -> PriceInfo {
+        // Our base is the market's quote when the market is inverted
+        let (base_decimals, quote_decimals) = if market.inverted {
+            (market.quote_decimals, market.base_decimals)
+        } else {
+            (market.base_decimals, market.quote_decimals)
+        };
+        
+        PriceInfo {
+            base_token: *base_token,
+            quote_token: *quote_token,
+            price: (top.best_bid + top.best_ask) / 2.0,
+            liquidity: top.bid_liquidity.min(top.ask_liquidity),
+            dex: DexType::Phoenix,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            
+            base_decimals: base_decimals as u8,
+            quote_decimals: quote_decimals as u8,
+            pool: Some(market.address),
+            fee_bps: None,
+        }
+    }
+    
+    /// Get price from the configured DEX
+    pub async fn get_price(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
+        if !self.config.enabled {
+            return Err(DexError::GeneralError("DEX is disabled".to_string()));
+        }
+        
+        // Quotes decoded from the latest batched account fetch are used once
+        if let Some(result) = self.take_prefetched(base_token, quote_token) {
+            return result;
+        }
+        
+        let cache_key = (self.config.dex_type, *base_token, *quote_token);
+        if let Some(price) = lock_or_recover(&self.price_cache).get(&cache_key) {
+            return Ok(price);
+        }
+        
+        let started = Instant::now();
+        
+        let result = match self.config.dex_type {
+            DexType::Jupiter => self.get_price_jupiter(base_token, quote_token, self.config.quote_amount).await,
             DexType::Raydium => self.get_price_raydium(base_token, quote_token).await,
             DexType::Orca => self.get_price_orca(base_token, quote_token).await,
-            DexType::Custom => Err(DexError::GeneralError("Custom DEX not implemented".to_string())),
+            DexType::Meteora => self.get_price_meteora(base_token, quote_token).await,
+            DexType::Phoenix => self.get_price_phoenix(base_token, quote_token).await,
+            DexType::Custom => self.custom_adapter()?.get_price(&self.rpc_client, base_token, quote_token),
+        }.map(|price| self.with_configured_fee(price));
+        
+        lock_or_recover(&self.latency).record(started.elapsed().as_micros() as u64);
+        
+        if let Ok(price) = &result {
+            lock_or_recover(&self.price_cache).insert(cache_key, price.clone());
         }
+        
+        result
     }
     
-    /// Create swap instruction for Jupiter
-    async fn create_swap_instruction_jupiter(&self, params: &SwapParams) -> Result<Instruction, DexError> {
+    /// Cache a quote obtained elsewhere (e.g. streamed), as if it had just been fetched
+    pub fn cache_price(&self, price: PriceInfo) {
+        let price = self.with_configured_fee(price);
+        lock_or_recover(&self.price_cache).insert((price.dex, price.base_token, price.quote_token), price);
+    }
+    
+    /// Fill in the configured fee for a quote whose pool didn't report one
+    fn with_configured_fee(&self, mut price: PriceInfo) -> PriceInfo {
+        price.fee_bps = price.fee_bps.or(self.config.fee_bps);
+        price
+    }
+    
+    /// Accounts a quote for a token pair is decoded from, so many pairs can be fetched in one batch
+    ///
+    /// Empty for DEXs quoted over HTTP or through an adapter.
+    pub fn price_accounts(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<Vec<Pubkey>, DexError> {
+        if !self.config.enabled {
+            return Ok(Vec::new());
+        }
+        
+        match self.config.dex_type {
+            DexType::Raydium => {
+                let pool = self.find_raydium_pool(base_token, quote_token)?;
+                Ok(vec![pool.address, pool.base_vault, pool.quote_vault])
+            },
+            DexType::Orca => Ok(vec![self.find_orca_pool(base_token, quote_token)?.address]),
+            DexType::Meteora => {
+                let pool = self.find_meteora_pool(base_token, quote_token)?;
+                Ok(vec![pool.address, pool.reserve_x, pool.reserve_y])
+            },
+            DexType::Phoenix => Ok(vec![self.find_phoenix_market(base_token, quote_token)?.address]),
+            DexType::Jupiter | DexType::Custom => Ok(Vec::new()),
+        }
+    }
+    
+    /// Decode a quote from the accounts `price_accounts` named, fetched in the same order
+    ///
+    /// The result is what the next `get_price` for the pair returns, so a pair whose
+    /// accounts came back missing is unpriceable until the next batch.
+    pub fn prefetch_price(&self, base_token: &Pubkey, quote_token: &Pubkey, accounts: &[Option<Account>]) {
+        let result = self.price_from_accounts(base_token, quote_token, accounts)
+            .map(|price| self.with_configured_fee(price));
+        if let Ok(price) = &result {
+            self.cache_price(price.clone());
+        }
+        lock_or_recover(&self.prefetched).insert((*base_token, *quote_token), (Instant::now(), result));
+    }
+    
+    /// Decode a quote from already fetched pool accounts
+    fn price_from_accounts(&self, base_token: &Pubkey, quote_token: &Pubkey, accounts: &[Option<Account>]) -> Result<PriceInfo, DexError> {
+        let account_data = || accounts.first()
+            .and_then(|account| account.as_ref())
+            .map(|account| account.data.as_slice())
+            .ok_or_else(|| DexError::ApiError(format!(
+                "{:?} account for {}/{} missing from batch", self.config.dex_type, base_token, quote_token
+            )));
+        
+        match self.config.dex_type {
+            DexType::Raydium => {
+                let pool = self.find_raydium_pool(base_token, quote_token)?;
+                Self::raydium_price(base_token, quote_token, &pool, accounts)
+            },
+            DexType::Orca => {
+                let pool = self.find_orca_pool(base_token, quote_token)?;
+                Self::orca_price(base_token, quote_token, &pool, account_data()?)
+            },
+            DexType::Meteora => {
+                let pool = self.find_meteora_pool(base_token, quote_token)?;
+                Self::meteora_price(base_token, quote_token, &pool, accounts)
+            },
+            DexType::Phoenix => {
+                let market = self.find_phoenix_market(base_token, quote_token)?;
+                let top = Self::order_book_top(&market, account_data()?)?;
+                Ok(Self::phoenix_price(base_token, quote_token, &market, &top))
+            },
+            DexType::Jupiter | DexType::Custom => Err(DexError::ParameterError(format!(
+                "{:?} quotes aren't decoded from accounts", self.config.dex_type
+            ))),
+        }
+    }
+    
+    /// Take the batched quote for a pair, if one was decoded recently enough
+    fn take_prefetched(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Option<Result<PriceInfo, DexError>> {
+        lock_or_recover(&self.prefetched)
+            .remove(&(*base_token, *quote_token))
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < PREFETCH_MAX_AGE)
+            .map(|(_, result)| result)
+    }
+    
+    /// Reserve accounts to stream for a token pair, if this DEX prices from a constant-product pool
+    pub fn streamed_pool(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<Option<StreamedPool>, DexError> {
+        if !self.config.enabled {
+            return Ok(None);
+        }
+        
+        match self.config.dex_type {
+            DexType::Raydium => {
+                let pool = self.find_raydium_pool(base_token, quote_token)?;
+                Ok(Some(StreamedPool {
+                    dex: DexType::Raydium,
+                    pool: pool.address,
+                    base_token: *base_token,
+                    quote_token: *quote_token,
+                    base_vault: pool.base_vault,
+                    quote_vault: pool.quote_vault,
+                    base_decimals: pool.base_decimals,
+                    quote_decimals: pool.quote_decimals,
+                    fee_bps: pool.fee_bps,
+                }))
+            },
+            // Aggregator routes, concentrated liquidity and order books can't be priced from two vaults
+            _ => Ok(None),
+        }
+    }
+    
+    /// Drop cached quotes so the next request goes to the network (e.g. right before committing to a trade)
+    pub fn invalidate_cache(&self) {
+        lock_or_recover(&self.price_cache).clear();
+    }
+    
+    /// Get quote latency statistics for this DEX
+    pub fn latency_stats(&self) -> LatencyStats {
+        lock_or_recover(&self.latency).stats()
+    }
+    
+    /// Fetch Jupiter's best route for swapping exactly `amount_in` of `input_mint`
+    async fn fetch_jupiter_quote(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount_in: u64, slippage_bps: u64) -> Result<Value, DexError> {
         // Jupiter Swap API V6 endpoint for quote
         let quote_url = format!("{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
-            self.config.api_url,
-            params.source_token,
-            params.destination_token,
-            params.amount_in,
-            (params.slippage * 100.0) as u64);
+            self.config.api_url, input_mint, output_mint, amount_in, slippage_bps);
         
-        let quote_response = self.http_client.get(&quote_url)
-            .send()
-            .await
-            .map_err(|e| DexError::ApiError(format!("Failed to send quote request: {}", e)))?;
+        let quote_response = self.send_request(self.http_client.get(&quote_url), "quote").await?;
         
-        let quote_json: Value = quote_response.json()
+        quote_response.json()
             .await
-            .map_err(|e| DexError::ApiError(format!("Failed to parse quote response: {}", e)))?;
+            .map_err(|e| DexError::ApiError(format!("Failed to parse quote response: {}", e)))
+    }
+    
+    /// Quote the aggregated route for swapping exactly `amount_in` atoms, bypassing the price cache
+    ///
+    /// Only the Jupiter connector routes across venues.
+    pub async fn quote_route(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount_in: u64, slippage_bps: u64) -> Result<RouteQuote, DexError> {
+        if !self.config.enabled {
+            return Err(DexError::GeneralError("DEX is disabled".to_string()));
+        }
+        if self.config.dex_type != DexType::Jupiter {
+            return Err(DexError::ParameterError(format!("{:?} does not quote aggregated routes", self.config.dex_type)));
+        }
+        
+        let quote_json = self.fetch_jupiter_quote(input_mint, output_mint, amount_in, slippage_bps).await?;
+        RouteQuote::from_jupiter(&quote_json)
+    }
+    
+    /// Create swap instruction for Jupiter
+    async fn create_swap_instructions_jupiter(&self, params: &SwapParams) -> Result<SwapInstructions, DexError> {
+        let quote_json = self.fetch_jupiter_quote(
+            &params.source_token,
+            &params.destination_token,
+            params.amount_in,
+            (params.slippage * 100.0) as u64,
+        ).await?;
         
-        // Extract route information
-        let route_id = quote_json["routeId"]
+        // Jupiter enforces its own threshold on-chain, so it must be at least our minimum
+        let route_minimum = quote_json["otherAmountThreshold"]
             .as_str()
-            .ok_or_else(|| DexError::ApiError("Route ID not found in response".to_string()))?;
+            .and_then(|amount| amount.parse::<u64>().ok())
+            .ok_or_else(|| DexError::ApiError("otherAmountThreshold not found in quote".to_string()))?;
+        if route_minimum < params.min_amount_out {
+            return Err(DexError::ParameterError(format!(
+                "Route minimum output {} is below the required {}", route_minimum, params.min_amount_out
+            )));
+        }
         
-        // Jupiter Swap API V6 endpoint for swap
+        // Jupiter Swap API V6 endpoint for swap, which takes the quote back verbatim
         let swap_url = format!("{}/swap", self.config.api_url);
         
         let swap_request = json!({
-            "routeId": route_id,
+            "quoteResponse": quote_json,
             "userPublicKey": params.source_wallet.to_string(),
+            "wrapAndUnwrapSol": true,
         });
         
-        let swap_response = self.http_client.post(&swap_url)
-            .json(&swap_request)
-            .send()
-            .await
-            .map_err(|e| DexError::ApiError(format!("Failed to send swap request: {}", e)))?;
+        // Jupiter only builds the transaction here, so repeating the request is harmless
+        let swap_response = self.send_request(self.http_client.post(&swap_url).json(&swap_request), "swap").await?;
         
         let swap_json: Value = swap_response.json()
             .await
             .map_err(|e| DexError::ApiError(format!("Failed to parse swap response: {}", e)))?;
         
-        // Extract transaction data
-        // In a real implementation, you would parse the transaction data and create an instruction
-        // For now, we'll return a placeholder instruction
+        // Decode the full route transaction Jupiter built for us
+        let swap_transaction = swap_json["swapTransaction"]
+            .as_str()
+            .ok_or_else(|| DexError::ApiError("swapTransaction not found in response".to_string()))?;
         
-        let program_id = self.config.program_id;
+        let transaction_bytes = BASE64.decode(swap_transaction)
+            .map_err(|e| DexError::ApiError(format!("Invalid swapTransaction encoding: {}", e)))?;
+        let transaction: VersionedTransaction = bincode::deserialize(&transaction_bytes)
+            .map_err(|e| DexError::ApiError(format!("Failed to decode swapTransaction: {}", e)))?;
         
-        let accounts = vec![
-            AccountMeta::new(params.source_wallet, true),
-            AccountMeta::new(params.destination_wallet, false),
-            AccountMeta::new_readonly(params.source_token, false),
-            AccountMeta::new_readonly(params.destination_token, false),
-        ];
+        // Resolve the lookup tables the route relies on
+        let table_keys: Vec<Pubkey> = transaction.message.address_table_lookups()
+            .unwrap_or_default()
+            .iter()
+            .map(|lookup| lookup.account_key)
+            .collect();
+        let lookup_tables = self.fetch_lookup_tables(&table_keys)?;
         
-        let mut data = vec![0]; // Placeholder instruction discriminator
-        data.extend_from_slice(&params.amount_in.to_le_bytes());
-        data.extend_from_slice(&params.min_amount_out.to_le_bytes());
+        // Compute budget is set by whoever assembles the final transaction
+        let instructions = decompile_message(&transaction.message, &lookup_tables)?
+            .into_iter()
+            .filter(|instruction| instruction.program_id != compute_budget::id())
+            .collect();
         
-        Ok(Instruction {
-            program_id,
-            accounts,
-            data,
+        Ok(SwapInstructions {
+            instructions,
+            lookup_tables,
         })
     }
     
+    /// Fetch address lookup table accounts
+    fn fetch_lookup_tables(&self, keys: &[Pubkey]) -> Result<Vec<AddressLookupTableAccount>, DexError> {
+        keys.iter()
+            .map(|key| {
+                let account = self.rpc_client.get_account(key)
+                    .map_err(|e| DexError::RpcError(format!("Failed to fetch lookup table {}: {}", key, e)))?;
+                let table = AddressLookupTable::deserialize(&account.data)
+                    .map_err(|e| DexError::ApiError(format!("Invalid lookup table {}: {}", key, e)))?;
+                Ok(AddressLookupTableAccount {
+                    key: *key,
+                    addresses: table.addresses.to_vec(),
+                })
+            })
+            .collect()
+    }
+    
     /// Create swap instruction for Raydium
     async fn create_swap_instruction_raydium(&self, params: &SwapParams) -> Result<Instruction, DexError> {
         // Similar to Jupiter, but with Raydium-specific parameters
@@ -384,22 +2091,143 @@ impl DexConnector {
         })
     }
     
+    /// Create swap instruction for Meteora DLMM
+    async fn create_swap_instruction_meteora(&self, params: &SwapParams) -> Result<Instruction, DexError> {
+        let pool = self.find_meteora_pool(&params.source_token, &params.destination_token)?;
+        let program_id = self.config.program_id;
+        
+        // The active bin decides which bin arrays the swap walks through
+        let data = self.rpc_client.get_account_data(&pool.address)
+            .map_err(|e| DexError::RpcError(format!("Failed to fetch DLMM pair {}: {}", pool.address, e)))?;
+        let active_id = read_i32(&data, DLMM_ACTIVE_ID_OFFSET)
+            .ok_or_else(|| DexError::ApiError(format!("Failed to decode DLMM pair {}", pool.address)))?;
+        
+        // Selling X for Y pushes the price down through lower bins
+        let swap_for_y = params.source_token == pool.token_x_mint;
+        let active_array = active_id.div_euclid(DLMM_BINS_PER_ARRAY) as i64;
+        let next_array = if swap_for_y { active_array - 1 } else { active_array + 1 };
+        let bin_array = |index: i64| {
+            Pubkey::find_program_address(&[b"bin_array", pool.address.as_ref(), &index.to_le_bytes()], &program_id).0
+        };
+        
+        let oracle = Pubkey::find_program_address(&[b"oracle", pool.address.as_ref()], &program_id).0;
+        let event_authority = Pubkey::find_program_address(&[b"__event_authority"], &program_id).0;
+        
+        // Optional accounts are passed as the program ID
+        let accounts = vec![
+            AccountMeta::new(pool.address, false),
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new(pool.reserve_x, false),
+            AccountMeta::new(pool.reserve_y, false),
+            AccountMeta::new(get_associated_token_address(&params.source_wallet, &params.source_token), false),
+            AccountMeta::new(get_associated_token_address(&params.destination_wallet, &params.destination_token), false),
+            AccountMeta::new_readonly(pool.token_x_mint, false),
+            AccountMeta::new_readonly(pool.token_y_mint, false),
+            AccountMeta::new(oracle, false),
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new_readonly(params.source_wallet, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(event_authority, false),
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new(bin_array(active_array), false),
+            AccountMeta::new(bin_array(next_array), false),
+        ];
+        
+        let mut data = DLMM_SWAP_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&params.amount_in.to_le_bytes());
+        data.extend_from_slice(&params.min_amount_out.to_le_bytes());
+        
+        Ok(Instruction {
+            program_id,
+            accounts,
+            data,
+        })
+    }
+    
+    /// Create swap instruction for Phoenix (an immediate-or-cancel order)
+    async fn create_swap_instruction_phoenix(&self, params: &SwapParams) -> Result<Instruction, DexError> {
+        let market = self.find_phoenix_market(&params.source_token, &params.destination_token)?;
+        let program_id = self.config.program_id;
+        
+        if market.base_lot_size == 0 || market.quote_lot_size == 0 {
+            return Err(DexError::ApiError(format!("Phoenix market {} has zero lot size", market.address)));
+        }
+        
+        // Selling the market base is an ask; buying it is a bid
+        let is_ask = params.source_token == market.base_mint;
+        let (side, num_base_lots, num_quote_lots, min_base_lots, min_quote_lots) = if is_ask {
+            (1u8, params.amount_in / market.base_lot_size, 0, 0, params.min_amount_out / market.quote_lot_size)
+        } else {
+            (0u8, 0, params.amount_in / market.quote_lot_size, params.min_amount_out / market.base_lot_size, 0)
+        };
+        
+        let log_authority = Pubkey::find_program_address(&[b"log"], &program_id).0;
+        let trader = params.source_wallet;
+        
+        let accounts = vec![
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new_readonly(log_authority, false),
+            AccountMeta::new(market.address, false),
+            AccountMeta::new_readonly(trader, true),
+            AccountMeta::new(get_associated_token_address(&trader, &market.base_mint), false),
+            AccountMeta::new(get_associated_token_address(&trader, &market.quote_mint), false),
+            AccountMeta::new(market.base_vault, false),
+            AccountMeta::new(market.quote_vault, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ];
+        
+        // Borsh-encoded OrderPacket::ImmediateOrCancel
+        let mut data = vec![PHOENIX_SWAP_TAG, 2, side];
+        data.push(0); // price_in_ticks: None (take any price, bounded by the minimum fill)
+        data.extend_from_slice(&num_base_lots.to_le_bytes());
+        data.extend_from_slice(&num_quote_lots.to_le_bytes());
+        data.extend_from_slice(&min_base_lots.to_le_bytes());
+        data.extend_from_slice(&min_quote_lots.to_le_bytes());
+        data.push(0); // self_trade_behavior: Abort
+        data.push(0); // match_limit: None
+        data.extend_from_slice(&0u128.to_le_bytes()); // client_order_id
+        data.push(0); // use_only_deposited_funds: false
+        data.push(0); // last_valid_slot: None
+        data.push(0); // last_valid_unix_timestamp_in_seconds: None
+        
+        Ok(Instruction {
+            program_id,
+            accounts,
+            data,
+        })
+    }
+    
     /// Create swap instruction for the configured DEX
-    pub async fn create_swap_instruction(&self, params: &SwapParams) -> Result<Instruction, DexError> {
+    pub async fn create_swap_instructions(&self, params: &SwapParams) -> Result<SwapInstructions, DexError> {
         if !self.config.enabled {
             return Err(DexError::GeneralError("DEX is disabled".to_string()));
         }
         
+        // Without a minimum the DEX program can't revert on excess slippage
+        if params.min_amount_out == 0 {
+            return Err(DexError::ParameterError("Swap has no minimum output".to_string()));
+        }
+        
         match self.config.dex_type {
-            DexType::Jupiter => self.create_swap_instruction_jupiter(params).await,
-            DexType::Raydium => self.create_swap_instruction_raydium(params).await,
-            DexType::Orca => self.create_swap_instruction_orca(params).await,
-            DexType::Custom => Err(DexError::GeneralError("Custom DEX not implemented".to_string())),
+            DexType::Jupiter => self.create_swap_instructions_jupiter(params).await,
+            DexType::Raydium => self.create_swap_instruction_raydium(params).await.map(SwapInstructions::single),
+            DexType::Orca => self.create_swap_instruction_orca(params).await.map(SwapInstructions::single),
+            DexType::Meteora => self.create_swap_instruction_meteora(params).await.map(SwapInstructions::single),
+            DexType::Phoenix => self.create_swap_instruction_phoenix(params).await.map(SwapInstructions::single),
+            DexType::Custom => self.custom_adapter()?.create_swap_instruction(params).map(SwapInstructions::single),
         }
     }
+    
+    /// Adapter backing a custom DEX
+    fn custom_adapter(&self) -> Result<&dyn CustomDexAdapter, DexError> {
+        self.config.custom_adapter.as_deref()
+            .ok_or_else(|| DexError::ParameterError("Custom DEX has no adapter configured".to_string()))
+    }
 }
 
 /// Thread-safe wrapper for DexConnector
+#[derive(Clone)]
 pub struct ThreadSafeDexConnector {
     inner: Arc<Mutex<DexConnector>>,
 }
@@ -412,54 +2240,501 @@ impl ThreadSafeDexConnector {
         }
     }
     
+    /// Create a new thread-safe DEX connector over an existing RPC client
+    pub fn with_rpc_client(rpc_client: RpcClient, config: DexConfig) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(DexConnector::with_rpc_client(rpc_client, config))),
+        }
+    }
+    
     /// Get price from the configured DEX (thread-safe)
     pub async fn get_price(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
-        let connector = self.inner.lock()
-            .map_err(|e| DexError::GeneralError(format!("Lock error: {}", e)))?;
+        let connector = lock_or_recover(&self.inner);
         connector.get_price(base_token, quote_token).await
     }
     
-    /// Create swap instruction for the configured DEX (thread-safe)
-    pub async fn create_swap_instruction(&self, params: &SwapParams) -> Result<Instruction, DexError> {
-        let connector = self.inner.lock()
-            .map_err(|e| DexError::GeneralError(format!("Lock error: {}", e)))?;
-        connector.create_swap_instruction(params).await
+    /// Create swap instructions for the configured DEX (thread-safe)
+    pub async fn create_swap_instructions(&self, params: &SwapParams) -> Result<SwapInstructions, DexError> {
+        let connector = lock_or_recover(&self.inner);
+        connector.create_swap_instructions(params).await
+    }
+    
+    /// Quote the aggregated route for an exact input amount (thread-safe)
+    pub async fn quote_route(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount_in: u64, slippage_bps: u64) -> Result<RouteQuote, DexError> {
+        let connector = lock_or_recover(&self.inner);
+        connector.quote_route(input_mint, output_mint, amount_in, slippage_bps).await
+    }
+    
+    /// Get quote latency statistics (thread-safe)
+    pub fn latency_stats(&self) -> Result<LatencyStats, DexError> {
+        let connector = lock_or_recover(&self.inner);
+        Ok(connector.latency_stats())
+    }
+    
+    /// Drop cached quotes (thread-safe)
+    pub fn invalidate_cache(&self) -> Result<(), DexError> {
+        let connector = lock_or_recover(&self.inner);
+        connector.invalidate_cache();
+        Ok(())
+    }
+    
+    /// Set the retry policy for transient HTTP failures (thread-safe)
+    pub fn set_retry_policy(&self, retry_policy: RetryPolicy) -> Result<(), DexError> {
+        let mut connector = lock_or_recover(&self.inner);
+        connector.set_retry_policy(retry_policy);
+        Ok(())
+    }
+    
+    /// Replace the RPC client pool and vault accounts are read with (thread-safe)
+    pub fn set_rpc_client(&self, rpc_client: RpcClient) -> Result<(), DexError> {
+        let mut connector = lock_or_recover(&self.inner);
+        connector.set_rpc_client(rpc_client);
+        Ok(())
+    }
+    
+    /// Cache a quote obtained elsewhere (thread-safe)
+    pub fn cache_price(&self, price: PriceInfo) -> Result<(), DexError> {
+        let connector = lock_or_recover(&self.inner);
+        connector.cache_price(price);
+        Ok(())
+    }
+    
+    /// Reserve accounts to stream for a token pair (thread-safe)
+    pub fn streamed_pool(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<Option<StreamedPool>, DexError> {
+        let connector = lock_or_recover(&self.inner);
+        connector.streamed_pool(base_token, quote_token)
+    }
+    
+    /// Accounts a quote for a token pair is decoded from (thread-safe)
+    pub fn price_accounts(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<Vec<Pubkey>, DexError> {
+        let connector = lock_or_recover(&self.inner);
+        connector.price_accounts(base_token, quote_token)
+    }
+    
+    /// Decode a quote from batched accounts for the next `get_price` (thread-safe)
+    pub fn prefetch_price(&self, base_token: &Pubkey, quote_token: &Pubkey, accounts: &[Option<Account>]) -> Result<(), DexError> {
+        let connector = lock_or_recover(&self.inner);
+        connector.prefetch_price(base_token, quote_token, accounts);
+        Ok(())
+    }
+}
+
+/// Find the most profitable buy/sell pair among quotes for the same token pair
+///
+/// Pure detection logic with no RPC or network access, shared by live scanning
+/// and offline replay. Returns (buy, sell, profit percentage) when the best
+/// spread reaches `min_profit_percentage`.
+pub fn find_best_spread(prices: &[PriceInfo], min_profit_percentage: f64) -> Option<(PriceInfo, PriceInfo, f64)> {
+    let valid = prices.iter().filter(|p| p.price > 0.0 && p.liquidity > 0);
+    
+    let buy = valid.clone().min_by(|a, b| a.price.total_cmp(&b.price))?;
+    let sell = valid.max_by(|a, b| a.price.total_cmp(&b.price))?;
+    
+    if buy.dex == sell.dex {
+        return None;
+    }
+    
+    let profit_percentage = (sell.price - buy.price) / buy.price * 100.0;
+    if profit_percentage < min_profit_percentage {
+        return None;
+    }
+    
+    Some((buy.clone(), sell.clone(), profit_percentage))
+}
+
+impl From<DexManager> for ThreadSafeDexManager {
+    fn from(manager: DexManager) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(manager)),
+        }
+    }
+}
+
+impl ThreadSafeDexManager {
+    /// Find the cheapest venue to buy on and the richest venue to sell on (thread-safe)
+    pub async fn best_buy_and_sell(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<(PriceInfo, PriceInfo), DexError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.best_buy_and_sell(base_token, quote_token).await
+    }
+    
+    /// Create swap instructions on a specific DEX (thread-safe)
+    pub async fn create_swap_instructions_for(&self, dex_type: DexType, params: &SwapParams) -> Result<SwapInstructions, DexError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.create_swap_instructions_for(dex_type, params).await
+    }
+    
+    /// Quote Jupiter's aggregated route for an exact input amount (thread-safe)
+    pub async fn quote_aggregated_route(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount_in: u64, slippage_bps: u64) -> Result<RouteQuote, DexError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.quote_aggregated_route(input_mint, output_mint, amount_in, slippage_bps).await
+    }
+    
+    /// Get quote latency statistics per DEX (thread-safe)
+    pub fn latency_stats(&self) -> Result<HashMap<DexType, LatencyStats>, DexError> {
+        let manager = lock_or_recover(&self.inner);
+        Ok(manager.latency_stats())
+    }
+    
+    /// Set the commitment reads are made at, on every connector (thread-safe)
+    pub fn set_read_commitment(&self, read_commitment: CommitmentConfig) -> Result<(), DexError> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.set_read_commitment(read_commitment)
+    }
+    
+    /// Receive price updates for token pairs as they change (thread-safe)
+    pub fn subscribe_prices(&self, pairs: &[(Pubkey, Pubkey)]) -> Result<Receiver<PriceInfo>, DexError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.subscribe_prices(pairs)
+    }
+    
+    /// Fetch the pool accounts behind every pair's quotes in batches (thread-safe)
+    ///
+    /// Only the connectors are read under the lock; the fetches run without it.
+    pub fn prefetch_prices(&self, pairs: &[(Pubkey, Pubkey)]) -> Result<(), DexError> {
+        let prefetch = lock_or_recover(&self.inner).batch_prefetch();
+        prefetch.prefetch_prices(pairs)
+    }
+}
+
+/// Connectors and RPC client a batched prefetch runs against, copied out of the manager
+struct BatchPrefetch {
+    /// RPC client at the manager's read commitment
+    rpc_client: RpcClient,
+    /// Connectors available when the prefetch started
+    connectors: Vec<(DexType, ThreadSafeDexConnector)>,
+}
+
+impl BatchPrefetch {
+    /// Batch fetch the accounts every connector prices `pairs` from and decode their quotes
+    fn prefetch_prices(&self, pairs: &[(Pubkey, Pubkey)]) -> Result<(), DexError> {
+        // Each connector and pair decodes its own slice of the batch
+        let mut requests = Vec::new();
+        let mut keys = Vec::new();
+        for (dex_type, connector) in &self.connectors {
+            for (base_token, quote_token) in pairs {
+                match connector.price_accounts(base_token, quote_token) {
+                    Ok(accounts) if !accounts.is_empty() => {
+                        requests.push((connector, *base_token, *quote_token, keys.len()..keys.len() + accounts.len()));
+                        keys.extend(accounts);
+                    },
+                    Ok(_) => {},
+                    Err(e) => debug!("Not batching {:?} for {}/{}: {}", dex_type, base_token, quote_token, e),
+                }
+            }
+        }
+        
+        if keys.is_empty() {
+            return Ok(());
+        }
+        
+        let mut accounts = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let fetched = self.rpc_client.get_multiple_accounts(chunk)
+                .map_err(|e| DexError::RpcError(format!("Failed to batch fetch pool accounts: {}", e)))?;
+            accounts.extend(fetched);
+        }
+        
+        for (connector, base_token, quote_token, range) in requests {
+            connector.prefetch_price(&base_token, &quote_token, &accounts[range])?;
+        }
+        
+        debug!("Batched {} pool accounts into {} request(s)", keys.len(), keys.len().div_ceil(MAX_MULTIPLE_ACCOUNTS));
+        Ok(())
     }
 }
 
 /// DEX manager
 /// Manages multiple DEX connectors and provides aggregated functionality
 pub struct DexManager {
-    /// RPC URL
-    rpc_url: String,
+    /// RPC endpoints shared by every connector
+    rpc: FailoverRpcClient,
     /// DEX connectors
     connectors: HashMap<DexType, ThreadSafeDexConnector>,
+    /// Circuit breaker state per connector
+    health: Mutex<HashMap<DexType, ConnectorHealth>>,
+    /// Consecutive failures within `failure_window` that disable a connector
+    failure_threshold: u32,
+    /// Window consecutive failures must fall within
+    failure_window: Duration,
+    /// How long a disabled connector waits before being probed again
+    probe_interval: Duration,
+    /// Per-DEX deadline for a single quote request
+    quote_timeout: Duration,
+    /// Websocket endpoint and polling cadence for price subscriptions
+    stream_config: PriceStreamConfig,
+    /// Retry policy applied to every connector
+    retry_policy: RetryPolicy,
+    /// Commitment every pool, vault and mint read is made at
+    read_commitment: CommitmentConfig,
 }
 
 impl DexManager {
     /// Create a new DEX manager
     pub fn new(rpc_url: &str) -> Self {
+        Self::with_rpc(FailoverRpcClient::new(rpc_url, &[]))
+    }
+    
+    /// Create a new DEX manager whose connectors fail over across `rpc`'s endpoints
+    pub fn with_rpc(rpc: FailoverRpcClient) -> Self {
+        let ws_url = stream::websocket_url(rpc.primary_url());
+        
         Self {
-            rpc_url: rpc_url.to_string(),
+            rpc,
             connectors: HashMap::new(),
+            health: Mutex::new(HashMap::new()),
+            failure_threshold: 5,
+            failure_window: Duration::from_secs(60),
+            probe_interval: Duration::from_secs(30),
+            quote_timeout: Duration::from_millis(1500),
+            stream_config: PriceStreamConfig::new(&ws_url),
+            retry_policy: RetryPolicy::default(),
+            read_commitment: CommitmentConfig::confirmed(),
+        }
+    }
+    
+    /// Set the commitment reads are made at, on every connector
+    pub fn set_read_commitment(&mut self, read_commitment: CommitmentConfig) -> Result<(), DexError> {
+        self.read_commitment = read_commitment;
+        for connector in self.connectors.values() {
+            connector.set_rpc_client(self.rpc.rpc_client(read_commitment))?;
+        }
+        Ok(())
+    }
+    
+    /// Configure the websocket endpoint and polling cadence used by `subscribe_prices`
+    pub fn set_price_stream_config(&mut self, stream_config: PriceStreamConfig) {
+        self.stream_config = stream_config;
+    }
+    
+    /// Set how long a single DEX may take to quote before it's dropped from a scan
+    pub fn set_quote_timeout(&mut self, quote_timeout: Duration) {
+        self.quote_timeout = quote_timeout;
+    }
+    
+    /// Set the retry policy for transient HTTP failures on every connector
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) -> Result<(), DexError> {
+        self.retry_policy = retry_policy;
+        for connector in self.connectors.values() {
+            connector.set_retry_policy(retry_policy)?;
+        }
+        Ok(())
+    }
+    
+    /// Configure when failing connectors are disabled and re-probed
+    pub fn set_circuit_breaker(&mut self, failure_threshold: u32, failure_window: Duration, probe_interval: Duration) {
+        self.failure_threshold = failure_threshold.max(1);
+        self.failure_window = failure_window;
+        self.probe_interval = probe_interval;
+    }
+    
+    /// Whether a connector should be queried right now
+    ///
+    /// Disabled connectors are let through once their probe interval elapses.
+    fn is_available(&self, dex_type: DexType) -> bool {
+        match lock_or_recover(&self.health).get(&dex_type).and_then(|h| h.disabled_until) {
+            Some(until) => Instant::now() >= until,
+            None => true,
         }
     }
     
+    /// Record the outcome of a connector request
+    fn record_outcome(&self, dex_type: DexType, success: bool) {
+        let mut health = lock_or_recover(&self.health);
+        let entry = health.entry(dex_type).or_default();
+        let now = Instant::now();
+        
+        if success {
+            if entry.disabled_until.is_some() {
+                info!("{:?} connector recovered, re-enabling", dex_type);
+            }
+            *entry = ConnectorHealth::default();
+            return;
+        }
+        
+        // A probe of a disabled connector failed, keep it disabled
+        if entry.disabled_until.is_some() {
+            entry.disabled_until = Some(now + self.probe_interval);
+            debug!("{:?} connector probe failed", dex_type);
+            return;
+        }
+        
+        // Start a new run if the previous failures are outside the window
+        match entry.first_failure {
+            Some(first) if now.duration_since(first) <= self.failure_window => {
+                entry.consecutive_failures += 1;
+            },
+            _ => {
+                entry.first_failure = Some(now);
+                entry.consecutive_failures = 1;
+            },
+        }
+        
+        if entry.consecutive_failures >= self.failure_threshold {
+            warn!("{:?} connector failed {} times in a row, disabling for {:?}",
+                  dex_type, entry.consecutive_failures, self.probe_interval);
+            entry.disabled_until = Some(now + self.probe_interval);
+        }
+    }
+    
+    /// DEX types whose connectors are currently disabled
+    pub fn disabled_connectors(&self) -> Vec<DexType> {
+        let now = Instant::now();
+        lock_or_recover(&self.health).iter()
+            .filter(|(_, h)| h.disabled_until.map_or(false, |until| now < until))
+            .map(|(dex_type, _)| *dex_type)
+            .collect()
+    }
+    
     /// Add a DEX connector
     pub fn add_connector(&mut self, config: DexConfig) {
-        let connector = ThreadSafeDexConnector::new(&self.rpc_url, config);
-        self.connectors.insert(config.dex_type, connector);
+        let dex_type = config.dex_type;
+        let connector = ThreadSafeDexConnector::with_rpc_client(self.rpc.rpc_client(self.read_commitment), config);
+        if let Err(e) = connector.set_retry_policy(self.retry_policy) {
+            warn!("Failed to set retry policy for {:?}: {}", dex_type, e);
+        }
+        self.connectors.insert(dex_type, connector);
     }
     
     /// Get price from all DEXs
     pub async fn get_prices(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Vec<Result<PriceInfo, DexError>> {
-        let mut results = Vec::new();
+        // Skip circuit-broken connectors so one bad endpoint can't stall every scan
+        let requests = self.connectors.iter()
+            .filter(|(dex_type, _)| self.is_available(**dex_type))
+            .map(|(dex_type, connector)| {
+                // Connectors quote over the blocking RPC client, so each runs on its own
+                // blocking thread; otherwise one slow quote holds up the rest and the timeout
+                let connector = connector.clone();
+                let (base_token, quote_token) = (*base_token, *quote_token);
+                let quote = tokio::task::spawn_blocking(move || {
+                    tokio::runtime::Handle::current().block_on(connector.get_price(&base_token, &quote_token))
+                });
+                async move {
+                    let result = match tokio::time::timeout(self.quote_timeout, quote).await {
+                        Ok(Ok(result)) => result,
+                        Ok(Err(e)) => Err(DexError::GeneralError(format!("{:?} quote task failed: {}", dex_type, e))),
+                        Err(_) => Err(DexError::ApiError(format!(
+                            "{:?} quote timed out after {}ms", dex_type, self.quote_timeout.as_millis()
+                        ))),
+                    };
+                    (*dex_type, result)
+                }
+            });
         
-        for connector in self.connectors.values() {
-            results.push(connector.get_price(base_token, quote_token).await);
+        // Quote every DEX at once so the scan takes as long as the slowest one, not the sum
+        join_all(requests).await
+            .into_iter()
+            .map(|(dex_type, result)| {
+                self.record_outcome(dex_type, result.is_ok());
+                result
+            })
+            .collect()
+    }
+    
+    /// Fetch the accounts every available connector prices `pairs` from with as few
+    /// getMultipleAccounts requests as possible, decoding the quotes for the next `get_prices`
+    ///
+    /// A pair whose accounts come back missing is unpriceable on that DEX until the next
+    /// batch. DEXs quoted over HTTP, and pairs whose pools aren't known yet, fetch as usual.
+    pub fn prefetch_prices(&self, pairs: &[(Pubkey, Pubkey)]) -> Result<(), DexError> {
+        self.batch_prefetch().prefetch_prices(pairs)
+    }
+    
+    /// The available connectors and an RPC client to batch their accounts with
+    fn batch_prefetch(&self) -> BatchPrefetch {
+        BatchPrefetch {
+            rpc_client: self.rpc.rpc_client(self.read_commitment),
+            connectors: self.connectors.iter()
+                .filter(|(dex_type, _)| self.is_available(**dex_type))
+                .map(|(dex_type, connector)| (*dex_type, connector.clone()))
+                .collect(),
+        }
+    }
+    
+    /// Find the cheapest venue to buy on and the richest venue to sell on
+    ///
+    /// Quotes every available DEX concurrently and ignores errored or
+    /// zero-liquidity quotes. Each returned `PriceInfo` carries the DEX to
+    /// route that leg through.
+    pub async fn best_buy_and_sell(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<(PriceInfo, PriceInfo), DexError> {
+        let prices: Vec<PriceInfo> = self.get_prices(base_token, quote_token).await
+            .into_iter()
+            .filter_map(|result| match result {
+                Ok(price) if price.price > 0.0 && price.liquidity > 0 => Some(price),
+                Ok(price) => {
+                    debug!("Ignoring empty quote from {:?}", price.dex);
+                    None
+                },
+                Err(e) => {
+                    debug!("Quote failed: {}", e);
+                    None
+                },
+            })
+            .collect();
+        
+        let buy = prices.iter().min_by(|a, b| a.price.total_cmp(&b.price))
+            .ok_or_else(|| DexError::GeneralError(format!("No usable quotes for {}/{}", base_token, quote_token)))?;
+        let sell = prices.iter().max_by(|a, b| a.price.total_cmp(&b.price))
+            .ok_or_else(|| DexError::GeneralError(format!("No usable quotes for {}/{}", base_token, quote_token)))?;
+        
+        if buy.dex == sell.dex {
+            return Err(DexError::GeneralError(format!("Only one DEX quoted {}/{}", base_token, quote_token)));
+        }
+        
+        Ok((buy.clone(), sell.clone()))
+    }
+    
+    /// Receive price updates for token pairs as they change
+    ///
+    /// Pools that can be priced from their reserves are streamed over websocket
+    /// account subscriptions; every other DEX is polled. Updates stop once the
+    /// receiver is dropped.
+    pub fn subscribe_prices(&self, pairs: &[(Pubkey, Pubkey)]) -> Result<Receiver<PriceInfo>, DexError> {
+        let (sender, receiver) = mpsc::sync_channel(PRICE_STREAM_CHANNEL_CAPACITY);
+        let mut stream = PriceStream::new(self.stream_config.clone(), sender);
+        
+        for (base_token, quote_token) in pairs {
+            for (dex_type, connector) in &self.connectors {
+                match connector.streamed_pool(base_token, quote_token) {
+                    Ok(Some(pool)) => stream.add_pool(pool, connector.clone()),
+                    Ok(None) => stream.add_polled(connector.clone(), *base_token, *quote_token),
+                    Err(e) => {
+                        warn!("Polling {:?} for {}/{}: {}", dex_type, base_token, quote_token, e);
+                        stream.add_polled(connector.clone(), *base_token, *quote_token);
+                    },
+                }
+            }
         }
         
-        results
+        stream.spawn()?;
+        Ok(receiver)
+    }
+    
+    /// Get the connector for a DEX type
+    pub fn get_connector(&self, dex_type: DexType) -> Option<&ThreadSafeDexConnector> {
+        self.connectors.get(&dex_type)
+    }
+    
+    /// Create swap instructions on a specific DEX
+    pub async fn create_swap_instructions_for(&self, dex_type: DexType, params: &SwapParams) -> Result<SwapInstructions, DexError> {
+        let connector = self.get_connector(dex_type)
+            .ok_or_else(|| DexError::ParameterError(format!("No connector configured for {:?}", dex_type)))?;
+        connector.create_swap_instructions(params).await
+    }
+    
+    /// Quote Jupiter's aggregated route for swapping exactly `amount_in` atoms
+    pub async fn quote_aggregated_route(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount_in: u64, slippage_bps: u64) -> Result<RouteQuote, DexError> {
+        let connector = self.get_connector(DexType::Jupiter)
+            .ok_or_else(|| DexError::ParameterError("No Jupiter connector configured".to_string()))?;
+        connector.quote_route(input_mint, output_mint, amount_in, slippage_bps).await
+    }
+    
+    /// Get quote latency statistics per DEX
+    pub fn latency_stats(&self) -> HashMap<DexType, LatencyStats> {
+        self.connectors.iter()
+            .filter_map(|(dex_type, connector)| {
+                connector.latency_stats().ok().map(|stats| (*dex_type, stats))
+            })
+            .collect()
     }
     
     ///<response clipped><NOTE>To save on context only part of this file has been shown to you. You should retry this tool after you have searched inside the file with `grep -n` in order to find the line numbers of what you are looking for.</NOTE>
\ No newline at end of file
@@ -0,0 +1,558 @@
+// Streaming Price Source for Solana Flash Loan Arbitrage Bot
+// Pushes prices from websocket account subscriptions, polling while the socket is down
+
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_account_decoder::UiAccountEncoding;
+use std::collections::HashMap;
+use std::sync::mpsc::{SyncSender, TrySendError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use futures::stream::{self, select_all, BoxStream, StreamExt};
+use tokio::sync::{mpsc, oneshot};
+use log::{info, warn, debug};
+
+use super::{read_u64, DexError, DexType, PriceInfo, ThreadSafeDexConnector, TOKEN_ACCOUNT_AMOUNT_OFFSET};
+
+/// Price stream configuration
+#[derive(Debug, Clone)]
+pub struct PriceStreamConfig {
+    /// Websocket (pubsub) endpoint
+    pub ws_url: String,
+    /// How often sources without a subscription are polled, in milliseconds
+    pub poll_interval_ms: u64,
+    /// Initial reconnect delay in milliseconds
+    pub reconnect_base_delay_ms: u64,
+    /// Maximum reconnect delay in milliseconds
+    pub reconnect_max_delay_ms: u64,
+}
+
+impl PriceStreamConfig {
+    /// Create a new price stream configuration
+    pub fn new(ws_url: &str) -> Self {
+        Self {
+            ws_url: ws_url.to_string(),
+            poll_interval_ms: 1000,
+            reconnect_base_delay_ms: 500,
+            reconnect_max_delay_ms: 30_000,
+        }
+    }
+}
+
+/// Websocket URL for an RPC endpoint, following the usual http -> ws convention
+pub fn websocket_url(rpc_url: &str) -> String {
+    if let Some(rest) = rpc_url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = rpc_url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        rpc_url.to_string()
+    }
+}
+
+/// Vault account updates, as (vault, raw account data), until the connection drops
+type VaultUpdates = BoxStream<'static, (Pubkey, Vec<u8>)>;
+
+/// Connects to a source of vault account updates
+#[async_trait]
+trait VaultFeed: Send {
+    /// Subscribe to every vault, failing if the connection or any subscription does
+    async fn connect(&mut self, vaults: &[Pubkey]) -> Result<VaultUpdates, DexError>;
+}
+
+/// Vault updates over websocket account subscriptions
+struct WebsocketFeed {
+    /// Websocket (pubsub) endpoint
+    ws_url: String,
+}
+
+#[async_trait]
+impl VaultFeed for WebsocketFeed {
+    async fn connect(&mut self, vaults: &[Pubkey]) -> Result<VaultUpdates, DexError> {
+        let client = PubsubClient::new(&self.ws_url).await
+            .map_err(|e| DexError::ApiError(format!("Failed to connect to {}: {}", self.ws_url, e)))?;
+        
+        let account_config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(CommitmentConfig::processed()),
+            ..Default::default()
+        };
+        
+        // Subscriptions borrow the client, so a task owns both and forwards the updates
+        let vaults = vaults.to_vec();
+        let (subscribed_tx, subscribed_rx) = oneshot::channel();
+        let (update_tx, update_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut subscriptions = Vec::with_capacity(vaults.len());
+            for vault in vaults {
+                match client.account_subscribe(&vault, Some(account_config.clone())).await {
+                    Ok((updates, _unsubscribe)) => subscriptions.push(updates.map(move |response| (vault, response))),
+                    Err(e) => {
+                        let _ = subscribed_tx.send(Err(DexError::ApiError(format!("Failed to subscribe to {}: {}", vault, e))));
+                        return;
+                    },
+                }
+            }
+            let _ = subscribed_tx.send(Ok(()));
+            
+            let mut updates = select_all(subscriptions);
+            loop {
+                tokio::select! {
+                    update = updates.next() => {
+                        let Some((vault, response)) = update else { break };
+                        if let Some(data) = response.value.data.decode() {
+                            if update_tx.send((vault, data)).is_err() {
+                                break;
+                            }
+                        }
+                    },
+                    // The stream was dropped, so stop listening
+                    _ = update_tx.closed() => break,
+                }
+            }
+        });
+        
+        subscribed_rx.await
+            .map_err(|_| DexError::ApiError(format!("Websocket to {} closed while subscribing", self.ws_url)))??;
+        
+        Ok(stream::unfold(update_rx, |mut update_rx| async move {
+            update_rx.recv().await.map(|update| (update, update_rx))
+        }).boxed())
+    }
+}
+
+/// Constant-product pool whose reserves can be streamed
+///
+/// Reserves are read straight from the vaults, so protocol fees still owed
+/// aren't netted out the way polled quotes do.
+#[derive(Debug, Clone)]
+pub struct StreamedPool {
+    /// DEX the pool belongs to
+    pub dex: DexType,
+    /// Pool account
+    pub pool: Pubkey,
+    /// Base token
+    pub base_token: Pubkey,
+    /// Quote token
+    pub quote_token: Pubkey,
+    /// Token account holding the base reserve
+    pub base_vault: Pubkey,
+    /// Token account holding the quote reserve
+    pub quote_vault: Pubkey,
+    /// Base token decimals
+    pub base_decimals: u32,
+    /// Quote token decimals
+    pub quote_decimals: u32,
+    /// Swap fee in basis points, if the pool reports one
+    pub fee_bps: Option<u64>,
+}
+
+/// Last observed reserves for a pool
+#[derive(Default)]
+struct PoolReserves {
+    /// Base reserve (if seen)
+    base: Option<u64>,
+    /// Quote reserve (if seen)
+    quote: Option<u64>,
+}
+
+/// A connector polled for one token pair
+struct PolledSource {
+    /// Connector to poll
+    connector: ThreadSafeDexConnector,
+    /// Base token
+    base_token: Pubkey,
+    /// Quote token
+    quote_token: Pubkey,
+}
+
+/// Pushes price updates for a set of token pairs to a subscriber
+///
+/// Pools with streamable reserves are watched over websocket account
+/// subscriptions; every other source is polled. While the socket is down the
+/// streamed pools are polled too, until a reconnect succeeds.
+pub struct PriceStream {
+    /// Stream configuration
+    config: PriceStreamConfig,
+    /// Streamed pools
+    pools: Vec<StreamedPool>,
+    /// Vault account -> (pool index, is base vault)
+    vault_index: HashMap<Pubkey, (usize, bool)>,
+    /// Reserves by pool index
+    reserves: Vec<PoolReserves>,
+    /// Sources that are always polled
+    polled: Vec<PolledSource>,
+    /// Sources for the streamed pools, polled while the socket is down
+    fallback: Vec<PolledSource>,
+    /// Channel prices are published to
+    sender: SyncSender<PriceInfo>,
+    /// Where vault updates come from
+    feed: Box<dyn VaultFeed>,
+}
+
+impl PriceStream {
+    /// Create a new price stream publishing into `sender`
+    pub fn new(config: PriceStreamConfig, sender: SyncSender<PriceInfo>) -> Self {
+        let feed = WebsocketFeed { ws_url: config.ws_url.clone() };
+        Self::with_feed(config, sender, Box::new(feed))
+    }
+    
+    /// Create a price stream reading vault updates from `feed`
+    fn with_feed(config: PriceStreamConfig, sender: SyncSender<PriceInfo>, feed: Box<dyn VaultFeed>) -> Self {
+        Self {
+            config,
+            pools: Vec::new(),
+            vault_index: HashMap::new(),
+            reserves: Vec::new(),
+            polled: Vec::new(),
+            fallback: Vec::new(),
+            sender,
+            feed,
+        }
+    }
+    
+    /// Stream a pool's reserves, falling back to polling its connector
+    pub fn add_pool(&mut self, pool: StreamedPool, connector: ThreadSafeDexConnector) {
+        let index = self.pools.len();
+        self.vault_index.insert(pool.base_vault, (index, true));
+        self.vault_index.insert(pool.quote_vault, (index, false));
+        self.reserves.push(PoolReserves::default());
+        
+        self.fallback.push(PolledSource {
+            connector,
+            base_token: pool.base_token,
+            quote_token: pool.quote_token,
+        });
+        self.pools.push(pool);
+    }
+    
+    /// Poll a connector for a token pair
+    pub fn add_polled(&mut self, connector: ThreadSafeDexConnector, base_token: Pubkey, quote_token: Pubkey) {
+        self.polled.push(PolledSource {
+            connector,
+            base_token,
+            quote_token,
+        });
+    }
+    
+    /// Run the stream on its own thread until the subscriber hangs up
+    pub fn spawn(self) -> Result<JoinHandle<()>, DexError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| DexError::GeneralError(format!("Failed to create price stream runtime: {}", e)))?;
+        
+        Ok(std::thread::spawn(move || {
+            let mut stream = self;
+            runtime.block_on(stream.run());
+        }))
+    }
+    
+    /// Send a price to the subscriber, returning false once it has hung up
+    fn publish(&self, price: PriceInfo) -> bool {
+        match self.sender.try_send(price) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                debug!("Price subscriber is behind, dropping update");
+                true
+            },
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+    
+    /// Poll every source once (including the streamed pools' if `include_fallback`)
+    async fn poll(&self, include_fallback: bool) -> bool {
+        let fallback: &[PolledSource] = if include_fallback { &self.fallback } else { &[] };
+        
+        for source in self.polled.iter().chain(fallback) {
+            match source.connector.get_price(&source.base_token, &source.quote_token).await {
+                Ok(price) => {
+                    if !self.publish(price) {
+                        return false;
+                    }
+                },
+                Err(e) => debug!("Polling {}/{} failed: {}", source.base_token, source.quote_token, e),
+            }
+        }
+        
+        true
+    }
+    
+    /// Apply a vault update and compute the pool's price if both reserves are known
+    fn handle_account_update(&mut self, vault: &Pubkey, data: &[u8]) -> Option<PriceInfo> {
+        let (pool_idx, is_base) = *self.vault_index.get(vault)?;
+        
+        let amount = read_u64(data, TOKEN_ACCOUNT_AMOUNT_OFFSET)?;
+        let reserves = &mut self.reserves[pool_idx];
+        if is_base {
+            reserves.base = Some(amount);
+        } else {
+            reserves.quote = Some(amount);
+        }
+        
+        let (base, quote) = match (reserves.base, reserves.quote) {
+            (Some(base), Some(quote)) if base > 0 && quote > 0 => (base, quote),
+            _ => return None,
+        };
+        
+        // Constant-product spot price, adjusted for token decimals
+        let pool = &self.pools[pool_idx];
+        let price = (quote as f64 / 10f64.powi(pool.quote_decimals as i32))
+            / (base as f64 / 10f64.powi(pool.base_decimals as i32));
+        
+        let price_info = PriceInfo {
+            base_token: pool.base_token,
+            quote_token: pool.quote_token,
+            price,
+            liquidity: base,
+            dex: pool.dex,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            base_decimals: pool.base_decimals as u8,
+            quote_decimals: pool.quote_decimals as u8,
+            pool: Some(pool.pool),
+            fee_bps: pool.fee_bps,
+        };
+        
+        // Later quotes from this connector should reflect the streamed state
+        if let Err(e) = self.fallback[pool_idx].connector.cache_price(price_info.clone()) {
+            debug!("Failed to cache streamed price: {}", e);
+        }
+        
+        Some(price_info)
+    }
+    
+    /// Subscribe to every vault and stream until the socket drops
+    ///
+    /// Returns `Ok(())` once the subscriber has hung up; an error means the socket dropped.
+    async fn stream_once(&mut self) -> Result<(), DexError> {
+        let vaults: Vec<Pubkey> = self.vault_index.keys().cloned().collect();
+        let mut updates = self.feed.connect(&vaults).await?;
+        
+        info!("Streaming {} pool vaults over {}", self.vault_index.len(), self.config.ws_url);
+        
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms);
+        let mut next_poll = Instant::now();
+        
+        loop {
+            let wait = next_poll.saturating_duration_since(Instant::now());
+            match tokio::time::timeout(wait, updates.next()).await {
+                Ok(Some((vault, data))) => {
+                    if let Some(price) = self.handle_account_update(&vault, &data) {
+                        if !self.publish(price) {
+                            return Ok(());
+                        }
+                    }
+                },
+                Ok(None) => return Err(DexError::ApiError("Websocket closed".to_string())),
+                Err(_) => {
+                    // Sources without a subscription still need polling
+                    if !self.poll(false).await {
+                        return Ok(());
+                    }
+                    next_poll = Instant::now() + poll_interval;
+                },
+            }
+        }
+    }
+    
+    /// Run the stream, reconnecting with exponential backoff and polling in between
+    pub async fn run(&mut self) {
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms);
+        let mut delay_ms = self.config.reconnect_base_delay_ms;
+        
+        loop {
+            if !self.pools.is_empty() {
+                let connected_at = Instant::now();
+                match self.stream_once().await {
+                    Ok(()) => break,
+                    Err(e) => warn!("Price stream dropped: {}, polling for {}ms before reconnecting", e, delay_ms),
+                }
+                
+                // A connection that held for a while earns a fresh backoff
+                if connected_at.elapsed() >= Duration::from_millis(self.config.reconnect_max_delay_ms) {
+                    delay_ms = self.config.reconnect_base_delay_ms;
+                }
+            }
+            
+            // Poll everything until it's time to try the socket again
+            let retry_at = Instant::now() + Duration::from_millis(delay_ms);
+            loop {
+                if !self.poll(true).await {
+                    debug!("Price subscriber hung up, stopping stream");
+                    return;
+                }
+                if self.pools.is_empty() || Instant::now() < retry_at {
+                    tokio::time::sleep(poll_interval).await;
+                } else {
+                    break;
+                }
+            }
+            
+            delay_ms = (delay_ms * 2).min(self.config.reconnect_max_delay_ms);
+        }
+        
+        debug!("Price subscriber hung up, stopping stream");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use solana_sdk::instruction::Instruction;
+    use solana_client::rpc_client::RpcClient;
+    use crate::dex::{CustomDexAdapter, DexConfig, SwapParams};
+    
+    /// Price the fallback connector quotes while the socket is down
+    const POLLED_PRICE: f64 = 2.0;
+    
+    /// Custom DEX quoting a fixed price, standing in for the polled connector
+    struct FixedPrice;
+    
+    impl CustomDexAdapter for FixedPrice {
+        fn get_price(&self, _rpc_client: &RpcClient, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
+            Ok(PriceInfo {
+                base_token: *base_token,
+                quote_token: *quote_token,
+                price: POLLED_PRICE,
+                liquidity: u64::MAX,
+                dex: DexType::Custom,
+                timestamp: 0,
+                base_decimals: 6,
+                quote_decimals: 6,
+                pool: None,
+                fee_bps: None,
+            })
+        }
+        
+        fn create_swap_instruction(&self, params: &SwapParams) -> Result<Instruction, DexError> {
+            Err(DexError::GeneralError(format!("Fixed price DEX cannot swap {}", params.amount_in)))
+        }
+    }
+    
+    /// One scripted connection attempt
+    enum Connection {
+        /// The connection is refused
+        Refused,
+        /// These updates are delivered, then the socket stays open this long before dropping
+        Streams(Vec<(Pubkey, Vec<u8>)>, Duration),
+    }
+    
+    /// Feed playing back scripted connections, refusing once the script runs out
+    struct ScriptedFeed {
+        /// Connections still to play, in order
+        script: VecDeque<Connection>,
+        /// When each connection was attempted
+        attempts: Arc<Mutex<Vec<Instant>>>,
+    }
+    
+    #[async_trait]
+    impl VaultFeed for ScriptedFeed {
+        async fn connect(&mut self, _vaults: &[Pubkey]) -> Result<VaultUpdates, DexError> {
+            self.attempts.lock().unwrap().push(Instant::now());
+            match self.script.pop_front() {
+                Some(Connection::Streams(updates, hold)) => {
+                    let dropped = stream::once(tokio::time::sleep(hold)).filter_map(|_| async { None });
+                    Ok(stream::iter(updates).chain(dropped).boxed())
+                },
+                Some(Connection::Refused) | None => Err(DexError::ApiError("Connection refused".to_string())),
+            }
+        }
+    }
+    
+    /// SPL token account bytes holding `amount`
+    fn token_account(amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 165];
+        data[TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8].copy_from_slice(&amount.to_le_bytes());
+        data
+    }
+    
+    fn pool() -> StreamedPool {
+        StreamedPool {
+            dex: DexType::Raydium,
+            pool: Pubkey::new_unique(),
+            base_token: Pubkey::new_unique(),
+            quote_token: Pubkey::new_unique(),
+            base_vault: Pubkey::new_unique(),
+            quote_vault: Pubkey::new_unique(),
+            base_decimals: 9,
+            quote_decimals: 6,
+            fee_bps: Some(25),
+        }
+    }
+    
+    /// Uncached connector quoting `POLLED_PRICE`
+    fn fallback_connector() -> ThreadSafeDexConnector {
+        let mut config = DexConfig::new_custom("", Pubkey::new_unique(), "fixed", Box::new(FixedPrice));
+        config.price_cache_ttl_ms = 0;
+        ThreadSafeDexConnector::with_rpc_client(RpcClient::new_mock("succeeds".to_string()), config)
+    }
+    
+    #[test]
+    fn reconnects_with_backoff_and_polls_while_the_socket_is_down() {
+        let pool = pool();
+        let reserves = vec![
+            (pool.base_vault, token_account(1_000 * 1_000_000_000)),
+            (pool.quote_vault, token_account(150_000 * 1_000_000)),
+        ];
+        let attempts = Arc::new(Mutex::new(Vec::new()));
+        let feed = ScriptedFeed {
+            script: VecDeque::from(vec![
+                Connection::Refused,
+                Connection::Refused,
+                Connection::Refused,
+                // Held past the maximum delay, so the next reconnect starts over
+                Connection::Streams(reserves, Duration::from_millis(250)),
+            ]),
+            attempts: attempts.clone(),
+        };
+        
+        let mut config = PriceStreamConfig::new("ws://127.0.0.1:0");
+        config.poll_interval_ms = 10;
+        config.reconnect_base_delay_ms = 50;
+        config.reconnect_max_delay_ms = 200;
+        let (sender, receiver) = std::sync::mpsc::sync_channel(4096);
+        let mut stream = PriceStream::with_feed(config, sender, Box::new(feed));
+        stream.add_pool(pool.clone(), fallback_connector());
+        let handle = stream.spawn().unwrap();
+        
+        // Collect prices until the reconnect after the streamed connection
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut prices = Vec::new();
+        while attempts.lock().unwrap().len() < 5 && Instant::now() < deadline {
+            if let Ok(price) = receiver.recv_timeout(Duration::from_millis(10)) {
+                prices.push(price);
+            }
+        }
+        let attempts = attempts.lock().unwrap().clone();
+        assert_eq!(attempts.len(), 5, "stream stopped reconnecting");
+        
+        // Refused connections fall back to polling the pool's connector
+        assert_eq!(prices[0].price, POLLED_PRICE);
+        assert_eq!(prices[0].pool, None);
+        
+        // The streamed connection prices the pool from its vaults
+        let streamed = prices.iter().find(|price| price.pool == Some(pool.pool)).expect("streamed price");
+        assert_eq!(streamed.dex, DexType::Raydium);
+        assert!((streamed.price - 150.0).abs() < 1e-9);
+        
+        // Each refusal doubles the delay, up to the maximum
+        let gaps: Vec<Duration> = attempts.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        assert!(gaps[0] >= Duration::from_millis(50), "{:?}", gaps);
+        assert!(gaps[1] >= Duration::from_millis(100), "{:?}", gaps);
+        assert!(gaps[2] >= Duration::from_millis(200), "{:?}", gaps);
+        
+        // After the long-lived connection the base delay applies again, not the maximum
+        assert!(gaps[3] < Duration::from_millis(250 + 150), "{:?}", gaps);
+        
+        // Hanging up stops the stream
+        drop(receiver);
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,563 @@
+// Tests for the DEX module
+// Run against mock adapters and mock RPC clients, never the network
+
+use super::*;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use async_trait::async_trait;
+use solana_client::client_error::Result as ClientResult;
+use solana_client::rpc_client::RpcClientConfig;
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_sdk::program_pack::Pack;
+
+/// Custom DEX quoting a fixed price after an optional delay
+struct MockDex {
+    /// Price every quote returns
+    price: f64,
+    /// How long each quote takes
+    delay: Duration,
+    /// Fail every quote while set
+    failing: Arc<AtomicBool>,
+}
+
+impl CustomDexAdapter for MockDex {
+    fn get_price(&self, _rpc_client: &RpcClient, base_token: &Pubkey, quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
+        std::thread::sleep(self.delay);
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(DexError::ApiError("Mock DEX is down".to_string()));
+        }
+        Ok(PriceInfo {
+            base_token: *base_token,
+            quote_token: *quote_token,
+            price: self.price,
+            liquidity: u64::MAX,
+            dex: DexType::Custom,
+            timestamp: 0,
+            base_decimals: 6,
+            quote_decimals: 6,
+            pool: None,
+            fee_bps: None,
+        })
+    }
+    
+    fn create_swap_instruction(&self, params: &SwapParams) -> Result<Instruction, DexError> {
+        Err(DexError::GeneralError(format!("Mock DEX cannot swap {}", params.amount_in)))
+    }
+}
+
+/// Uncached custom DEX config over a mock DEX, with the switch that makes it fail
+fn mock_config(price: f64, delay: Duration) -> (DexConfig, Arc<AtomicBool>) {
+    let failing = Arc::new(AtomicBool::new(false));
+    let adapter = MockDex { price, delay, failing: failing.clone() };
+    let mut config = DexConfig::new_custom("", Pubkey::new_unique(), "mock", Box::new(adapter));
+    config.price_cache_ttl_ms = 0;
+    (config, failing)
+}
+
+/// Uncached custom connector over a mock DEX and a mock RPC client
+fn mock_connector(price: f64, delay: Duration) -> DexConnector {
+    DexConnector::with_rpc_client(RpcClient::new_mock("succeeds".to_string()), mock_config(price, delay).0)
+}
+
+/// HTTP server answering every request with the same status and body, counting requests
+///
+/// Returns the server's base URL.
+fn mock_http_server(status: &'static str, body: &'static str, requests: Arc<AtomicUsize>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            
+            // Read the request head, then answer and close
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).map_or(false, |read| read > 0) && line != "\r\n" {
+                line.clear();
+            }
+            requests.fetch_add(1, Ordering::SeqCst);
+            
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status, body.len(), body,
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    
+    url
+}
+
+/// Jupiter connector against `api_url`, retrying quickly
+fn jupiter_connector(api_url: &str) -> DexConnector {
+    let mut config = DexConfig::new_jupiter();
+    config.api_url = api_url.to_string();
+    let mut connector = DexConnector::with_rpc_client(RpcClient::new_mock("succeeds".to_string()), config);
+    connector.set_retry_policy(RetryPolicy {
+        max_attempts: 3,
+        base_delay_ms: 1,
+        max_delay_ms: 1,
+        jitter: 0.0,
+    });
+    connector
+}
+
+#[tokio::test]
+async fn service_unavailable_is_retried_then_surfaced() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let url = mock_http_server("503 Service Unavailable", "upstream overloaded", requests.clone());
+    let connector = jupiter_connector(&url);
+    
+    let result = connector.quote_route(&Pubkey::new_unique(), &Pubkey::new_unique(), 1_000_000, 50).await;
+    
+    match result {
+        Err(DexError::ApiError(message)) => {
+            assert!(message.contains("503"), "{}", message);
+            assert!(message.contains("upstream overloaded"), "{}", message);
+        },
+        other => panic!("expected an API error, got {:?}", other.map(|route| route.out_amount)),
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn bad_request_surfaces_the_json_error_without_retrying() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let body = r#"{"error":"Could not find any route","errorCode":"COULD_NOT_FIND_ANY_ROUTE"}"#;
+    let url = mock_http_server("400 Bad Request", body, requests.clone());
+    let connector = jupiter_connector(&url);
+    
+    let result = connector.quote_route(&Pubkey::new_unique(), &Pubkey::new_unique(), 1_000_000, 50).await;
+    
+    match result {
+        Err(DexError::ApiError(message)) => {
+            assert!(message.contains("400"), "{}", message);
+            assert!(message.contains("COULD_NOT_FIND_ANY_ROUTE"), "{}", message);
+        },
+        other => panic!("expected an API error, got {:?}", other.map(|route| route.out_amount)),
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn latency_window_reports_percentiles_and_rolls_over() {
+    let mut window = LatencyWindow::new();
+    assert_eq!(window.stats().samples, 0);
+    
+    // 1ms..=100ms
+    for ms in 1..=100u64 {
+        window.record(ms * 1000);
+    }
+    let stats = window.stats();
+    assert_eq!(stats.samples, 100);
+    assert_eq!(stats.average_ms, 50.5);
+    assert_eq!(stats.p50_ms, 51.0);
+    assert_eq!(stats.p95_ms, 95.0);
+    assert_eq!(stats.max_ms, 100.0);
+    
+    // A full window of 1ms samples pushes every older one out
+    for _ in 0..LATENCY_WINDOW {
+        window.record(1000);
+    }
+    let stats = window.stats();
+    assert_eq!(stats.samples, LATENCY_WINDOW);
+    assert_eq!(stats.max_ms, 1.0);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn each_connector_tracks_its_own_latency() {
+    let (base, quote) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let slow = mock_connector(1.0, Duration::from_millis(25));
+    let fast = mock_connector(1.0, Duration::ZERO);
+    
+    for _ in 0..3 {
+        slow.get_price(&base, &quote).await.unwrap();
+    }
+    fast.get_price(&base, &quote).await.unwrap();
+    
+    let slow_stats = slow.latency_stats();
+    assert_eq!(slow_stats.samples, 3);
+    assert!(slow_stats.p50_ms >= 25.0);
+    
+    let fast_stats = fast.latency_stats();
+    assert_eq!(fast_stats.samples, 1);
+    assert!(fast_stats.max_ms < 25.0);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn circuit_breaker_excludes_then_reincludes_a_failing_dex() {
+    let (base, quote) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut manager = DexManager::new("http://127.0.0.1:8899");
+    manager.set_circuit_breaker(2, Duration::from_secs(60), Duration::from_millis(50));
+    let (config, failing) = mock_config(1.0, Duration::ZERO);
+    manager.add_connector(config);
+    
+    // Two failures in a row trip the breaker
+    failing.store(true, Ordering::SeqCst);
+    assert!(manager.get_prices(&base, &quote).await[0].is_err());
+    assert!(manager.disabled_connectors().is_empty());
+    assert!(manager.get_prices(&base, &quote).await[0].is_err());
+    assert_eq!(manager.disabled_connectors(), vec![DexType::Custom]);
+    
+    // Excluded from scans until the probe interval passes, even once it's healthy again
+    failing.store(false, Ordering::SeqCst);
+    assert!(manager.get_prices(&base, &quote).await.is_empty());
+    
+    // A successful probe re-enables it
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let prices = manager.get_prices(&base, &quote).await;
+    assert_eq!(prices.len(), 1);
+    assert_eq!(prices[0].as_ref().unwrap().price, 1.0);
+    assert!(manager.disabled_connectors().is_empty());
+}
+
+#[tokio::test]
+async fn blocking_quotes_run_concurrently_and_time_out() {
+    let (base, quote) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut manager = DexManager::new("http://127.0.0.1:8899");
+    manager.set_quote_timeout(Duration::from_millis(500));
+    
+    // Mock adapters block their thread like the RPC-backed connectors do
+    for (dex_type, delay) in [(DexType::Raydium, 300), (DexType::Orca, 300), (DexType::Custom, 2_000)] {
+        let (config, _) = mock_config(1.0, Duration::from_millis(delay));
+        let connector = ThreadSafeDexConnector::with_rpc_client(RpcClient::new_mock("succeeds".to_string()), config);
+        manager.connectors.insert(dex_type, connector);
+    }
+    
+    let started = Instant::now();
+    let prices = manager.get_prices(&base, &quote).await;
+    let elapsed = started.elapsed();
+    
+    // The quick quotes overlap, and the stuck one is cut off at the timeout
+    assert!(elapsed < Duration::from_millis(1_500), "scan took {:?}", elapsed);
+    assert_eq!(prices.iter().filter(|price| price.is_ok()).count(), 2);
+    assert_eq!(prices.iter().filter(|price| matches!(price, Err(DexError::ApiError(message)) if message.contains("timed out"))).count(), 1);
+}
+
+#[test]
+fn read_commitment_reaches_every_connector() {
+    let mut manager = DexManager::new("http://127.0.0.1:8899");
+    manager.add_connector(DexConfig::new_raydium());
+    manager.set_read_commitment(CommitmentConfig::processed()).unwrap();
+    
+    // Connectors added afterwards read at the same commitment
+    manager.add_connector(DexConfig::new_orca());
+    
+    assert_eq!(manager.connectors.len(), 2);
+    for connector in manager.connectors.values() {
+        assert_eq!(lock_or_recover(&connector.inner).rpc_client.commitment(), CommitmentConfig::processed());
+    }
+}
+
+/// `getAccountInfo`-style JSON for an account owned by `owner` holding `data`
+fn account_json(owner: &Pubkey, data: &[u8]) -> Value {
+    json!({
+        "data": [BASE64.encode(data), "base64"],
+        "executable": false,
+        "lamports": 2_039_280,
+        "owner": owner.to_string(),
+        "rentEpoch": 0,
+        "space": data.len(),
+    })
+}
+
+/// `getAccountInfo`-style JSON for an SPL token account holding `amount`
+fn token_account_json(mint: &Pubkey, amount: u64) -> Value {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: *mint,
+        owner: Pubkey::new_unique(),
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }.pack_into_slice(&mut data);
+    
+    account_json(&spl_token::id(), &data)
+}
+
+/// `getAccountInfo`-style JSON for an SPL mint with `decimals`
+fn mint_json(decimals: u8) -> Value {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        decimals,
+        is_initialized: true,
+        ..Default::default()
+    }.pack_into_slice(&mut data);
+    
+    account_json(&spl_token::id(), &data)
+}
+
+#[tokio::test]
+async fn constant_product_pool_prices_from_vaults_and_builds_swaps() {
+    let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let adapter = ConstantProductAdapter {
+        program_id: Pubkey::new_unique(),
+        pool: Pubkey::new_unique(),
+        authority: Pubkey::new_unique(),
+        mint_a: sol,
+        mint_b: usdc,
+        vault_a: Pubkey::new_unique(),
+        vault_b: Pubkey::new_unique(),
+        decimals_a: 9,
+        decimals_b: 6,
+        swap_discriminator: 9,
+    };
+    let (program_id, pool, vault_a, vault_b) = (adapter.program_id, adapter.pool, adapter.vault_a, adapter.vault_b);
+    
+    // 1,000 SOL against 150,000 USDC
+    let mut mocks = HashMap::new();
+    mocks.insert(RpcRequest::GetMultipleAccounts, json!({
+        "context": { "slot": 1 },
+        "value": [token_account_json(&sol, 1_000_000_000_000), token_account_json(&usdc, 150_000_000_000)],
+    }));
+    let mut config = DexConfig::new_custom("", program_id, "cpamm", Box::new(adapter));
+    config.price_cache_ttl_ms = 0;
+    let connector = DexConnector::with_rpc_client(RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks), config);
+    
+    let price = connector.get_price(&sol, &usdc).await.unwrap();
+    assert!((price.price - 150.0).abs() < 1e-9, "{}", price.price);
+    assert_eq!(price.liquidity, 1_000_000_000_000);
+    assert_eq!((price.base_decimals, price.quote_decimals), (9, 6));
+    assert_eq!(price.pool, Some(pool));
+    
+    // Buy SOL with 150 USDC
+    let wallet = Pubkey::new_unique();
+    let swap = connector.create_swap_instructions(&SwapParams {
+        amount_in: 150_000_000,
+        min_amount_out: 990_000_000,
+        source_token: usdc,
+        destination_token: sol,
+        source_wallet: wallet,
+        destination_wallet: wallet,
+        slippage: 1.0,
+    }).await.unwrap();
+    
+    assert_eq!(swap.instructions.len(), 1);
+    let instruction = &swap.instructions[0];
+    assert_eq!(instruction.program_id, program_id);
+    assert_eq!(instruction.data[0], 9);
+    assert_eq!(instruction.data[1..9], 150_000_000u64.to_le_bytes());
+    assert_eq!(instruction.data[9..17], 990_000_000u64.to_le_bytes());
+    
+    let keys: Vec<Pubkey> = instruction.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(keys[0], pool);
+    assert_eq!(keys[3], get_associated_token_address(&wallet, &usdc));
+    assert_eq!(keys[4], get_associated_token_address(&wallet, &sol));
+    // USDC goes into the USDC vault and SOL comes out of the SOL vault
+    assert_eq!(keys[5], vault_b);
+    assert_eq!(keys[6], vault_a);
+    assert!(instruction.accounts[2].is_signer);
+}
+
+#[test]
+fn min_amount_out_always_rounds_down() {
+    // Exact results stay exact
+    assert_eq!(min_amount_out(1_000, 0.5), 995);
+    assert_eq!(min_amount_out(10_000, 0.3), 9_970);
+    
+    // 994.005 and 9,974.5 atoms round down, as does a fraction of a basis point in the tolerance
+    assert_eq!(min_amount_out(999, 0.5), 994);
+    assert_eq!(min_amount_out(10_000, 0.255), 9_974);
+    assert_eq!(min_amount_out(1, 0.5), 0);
+    
+    // Never above what the tolerance allows, across awkward amounts and tolerances
+    for expected_out in [1, 7, 999, 123_456_789, 987_654_321_012] {
+        for slippage in [0.01, 0.1, 0.3, 0.333, 0.5, 1.0, 2.7, 50.0] {
+            let minimum = min_amount_out(expected_out, slippage);
+            let allowed = expected_out as f64 * (1.0 - slippage / 100.0);
+            assert!(minimum as f64 <= allowed, "{} at {}% gave {}, above {}", expected_out, slippage, minimum, allowed);
+            assert!(allowed - (minimum as f64) < expected_out as f64 / 10_000.0 + 1.0);
+        }
+    }
+    
+    // Out-of-range tolerances are clamped, and large amounts don't overflow
+    assert_eq!(min_amount_out(u64::MAX, 0.0), u64::MAX);
+    assert_eq!(min_amount_out(1_000, -1.0), 1_000);
+    assert_eq!(min_amount_out(1_000, 150.0), 0);
+}
+
+/// RPC endpoint that holds each connection for `hold` and closes it unanswered
+///
+/// Returns the endpoint's URL and a receiver signalled as each request arrives.
+fn stalling_rpc(hold: Duration) -> (String, mpsc::Receiver<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (arrived, receiver) = mpsc::channel();
+    
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { break };
+            let _ = arrived.send(());
+            std::thread::sleep(hold);
+            drop(stream);
+        }
+    });
+    
+    (url, receiver)
+}
+
+#[test]
+fn prefetch_leaves_the_manager_usable_while_it_fetches() {
+    let (base, quote) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (rpc_url, requests) = stalling_rpc(Duration::from_secs(2));
+    let mut manager = DexManager::new(&rpc_url);
+    manager.add_connector(DexConfig::new_orca());
+    
+    // The pool is already known, so the batch fetch is the only request
+    let pool = OrcaPool { address: Pubkey::new_unique(), decimals_a: 9, decimals_b: 6, inverted: false };
+    let connector = lock_or_recover(&manager.connectors[&DexType::Orca].inner);
+    lock_or_recover(&connector.orca_pools).insert((base, quote), pool);
+    drop(connector);
+    let manager = Arc::new(ThreadSafeDexManager::from(manager));
+    
+    let prefetching = manager.clone();
+    let prefetch = std::thread::spawn(move || prefetching.prefetch_prices(&[(base, quote)]));
+    requests.recv_timeout(Duration::from_secs(5)).expect("batch fetch reached the RPC");
+    
+    // Other callers get the manager while the fetch is stuck
+    let started = Instant::now();
+    assert_eq!(manager.latency_stats().unwrap().len(), 1);
+    assert!(started.elapsed() < Duration::from_secs(1), "waited {:?}", started.elapsed());
+    
+    assert!(prefetch.join().unwrap().is_err());
+}
+
+/// RPC node holding a DEX program's accounts and the accounts they point to
+///
+/// Program scans are answered by applying the request's filters to `program_accounts`,
+/// so the node lists them in whatever order they were given.
+struct AccountStore {
+    /// Program the scanned accounts belong to
+    program_id: Pubkey,
+    /// Address and data of each program account
+    program_accounts: Vec<(Pubkey, Vec<u8>)>,
+    /// Every other account, as `getAccountInfo` JSON
+    accounts: HashMap<Pubkey, Value>,
+}
+
+#[async_trait]
+impl RpcSender for AccountStore {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        match request {
+            RpcRequest::GetVersion => Ok(json!({ "solana-core": "1.18.26", "feature-set": 0 })),
+            RpcRequest::GetProgramAccounts => {
+                let filters = params[1]["filters"].as_array().cloned().unwrap_or_default();
+                let matches = |data: &[u8]| filters.iter().all(|filter| match filter["dataSize"].as_u64() {
+                    Some(size) => data.len() as u64 == size,
+                    None => {
+                        let offset = filter["memcmp"]["offset"].as_u64().unwrap() as usize;
+                        let bytes = Pubkey::from_str(filter["memcmp"]["bytes"].as_str().unwrap()).unwrap();
+                        data.get(offset..offset + 32) == Some(bytes.as_ref())
+                    },
+                });
+                Ok(self.program_accounts.iter()
+                    .filter(|(_, data)| matches(data))
+                    .map(|(address, data)| json!({ "pubkey": address.to_string(), "account": account_json(&self.program_id, data) }))
+                    .collect())
+            },
+            RpcRequest::GetMultipleAccounts => {
+                let value: Vec<Value> = params[0].as_array().unwrap().iter()
+                    .map(|address| {
+                        let address = Pubkey::from_str(address.as_str().unwrap()).unwrap();
+                        self.accounts.get(&address).cloned().unwrap_or(Value::Null)
+                    })
+                    .collect();
+                Ok(json!({ "context": { "slot": 1 }, "value": value }))
+            },
+            other => panic!("Unexpected request {:?}", other),
+        }
+    }
+    
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+    
+    fn url(&self) -> String {
+        "account-store".to_string()
+    }
+}
+
+/// Connector for `config` over an `AccountStore`
+fn store_connector(config: DexConfig, program_accounts: Vec<(Pubkey, Vec<u8>)>, accounts: HashMap<Pubkey, Value>) -> DexConnector {
+    let store = AccountStore { program_id: config.program_id, program_accounts, accounts };
+    DexConnector::with_rpc_client(RpcClient::new_sender(store, RpcClientConfig::with_commitment(CommitmentConfig::confirmed())), config)
+}
+
+/// Phoenix market header trading `base_mint` for `quote_mint`
+fn phoenix_market_data(base_mint: &Pubkey, quote_mint: &Pubkey, base_vault: &Pubkey, quote_vault: &Pubkey) -> Vec<u8> {
+    let mut data = vec![0u8; PHOENIX_BIDS_TREE_OFFSET];
+    data[PHOENIX_BASE_MINT_OFFSET..PHOENIX_BASE_MINT_OFFSET + 32].copy_from_slice(base_mint.as_ref());
+    data[PHOENIX_BASE_VAULT_OFFSET..PHOENIX_BASE_VAULT_OFFSET + 32].copy_from_slice(base_vault.as_ref());
+    data[PHOENIX_QUOTE_MINT_OFFSET..PHOENIX_QUOTE_MINT_OFFSET + 32].copy_from_slice(quote_mint.as_ref());
+    data[PHOENIX_QUOTE_VAULT_OFFSET..PHOENIX_QUOTE_VAULT_OFFSET + 32].copy_from_slice(quote_vault.as_ref());
+    data
+}
+
+#[test]
+fn deepest_phoenix_market_is_chosen_whatever_the_listing_order() {
+    let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut program_accounts = Vec::new();
+    let mut accounts = HashMap::new();
+    
+    // Two SOL/USDC markets and a USDC/SOL one, whose USDC sits in its base vault
+    let mut market = |base_mint: &Pubkey, quote_mint: &Pubkey, usdc_deposits: u64| {
+        let (address, base_vault, quote_vault) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        program_accounts.push((address, phoenix_market_data(base_mint, quote_mint, &base_vault, &quote_vault)));
+        let usdc_vault = if *base_mint == usdc { base_vault } else { quote_vault };
+        accounts.insert(usdc_vault, token_account_json(&usdc, usdc_deposits));
+        address
+    };
+    market(&sol, &usdc, 20_000_000_000);
+    let deepest = market(&usdc, &sol, 90_000_000_000);
+    market(&sol, &usdc, 50_000_000_000);
+    
+    let connector = store_connector(DexConfig::new_phoenix(), program_accounts.clone(), accounts.clone());
+    let chosen = connector.find_phoenix_market(&sol, &usdc).unwrap();
+    assert_eq!(chosen.address, deepest);
+    assert!(chosen.inverted);
+    
+    program_accounts.reverse();
+    let connector = store_connector(DexConfig::new_phoenix(), program_accounts, accounts);
+    assert_eq!(connector.find_phoenix_market(&sol, &usdc).unwrap().address, deepest);
+}
+
+#[test]
+fn deepest_meteora_pair_is_chosen_within_the_fee_tier() {
+    let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut program_accounts = Vec::new();
+    let mut accounts = HashMap::from([(sol, mint_json(9)), (usdc, mint_json(6))]);
+    
+    // SOL/USDC pairs at two bin steps, each holding some USDC
+    let mut pair = |bin_step: u16, usdc_reserve: u64| {
+        let (address, reserve_x, reserve_y) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = vec![0u8; DLMM_LB_PAIR_ACCOUNT_LEN as usize];
+        data[DLMM_BIN_STEP_OFFSET..DLMM_BIN_STEP_OFFSET + 2].copy_from_slice(&bin_step.to_le_bytes());
+        data[DLMM_TOKEN_X_MINT_OFFSET..DLMM_TOKEN_X_MINT_OFFSET + 32].copy_from_slice(sol.as_ref());
+        data[DLMM_TOKEN_Y_MINT_OFFSET..DLMM_TOKEN_Y_MINT_OFFSET + 32].copy_from_slice(usdc.as_ref());
+        data[DLMM_RESERVE_X_OFFSET..DLMM_RESERVE_X_OFFSET + 32].copy_from_slice(reserve_x.as_ref());
+        data[DLMM_RESERVE_Y_OFFSET..DLMM_RESERVE_Y_OFFSET + 32].copy_from_slice(reserve_y.as_ref());
+        program_accounts.push((address, data));
+        accounts.insert(reserve_y, token_account_json(&usdc, usdc_reserve));
+        address
+    };
+    let shallow_tight = pair(10, 5_000_000_000);
+    let deep_tight = pair(10, 40_000_000_000);
+    let deep_wide = pair(25, 80_000_000_000);
+    
+    // Once several pairs trade it, the deepest is used rather than refusing
+    let connector = store_connector(DexConfig::new_meteora(), program_accounts.clone(), accounts.clone());
+    let chosen = connector.find_meteora_pool(&sol, &usdc).unwrap();
+    assert_eq!(chosen.address, deep_wide);
+    assert_eq!((chosen.bin_step, chosen.decimals_x, chosen.decimals_y), (25, 9, 6));
+    
+    // A fee tier narrows the choice before depth decides
+    let mut config = DexConfig::new_meteora();
+    config.fee_tier = Some(10);
+    let connector = store_connector(config, program_accounts, accounts);
+    let chosen = connector.find_meteora_pool(&sol, &usdc).unwrap();
+    assert_eq!(chosen.address, deep_tight);
+    assert_ne!(chosen.address, shallow_tight);
+}
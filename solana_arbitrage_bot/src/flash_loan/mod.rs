@@ -7,12 +7,20 @@ use solana_sdk::{
     transaction::Transaction,
     signer::Signer,
     system_program,
+    sysvar,
 };
 use solana_client::rpc_client::RpcClient;
+use spl_associated_token_account::get_associated_token_address;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use log::{info, warn, error, debug};
 
+use crate::lock_or_recover;
+use crate::dex::{min_amount_out, ThreadSafeDexManager, PriceInfo, SwapParams};
+
 /// Error type for flash loan operations
 #[derive(Debug)]
 pub enum FlashLoanError {
@@ -43,7 +51,8 @@ impl std::fmt::Display for FlashLoanError {
 impl std::error::Error for FlashLoanError {}
 
 /// Flash loan provider type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FlashLoanProvider {
     /// Solend
     Solend,
@@ -51,10 +60,150 @@ pub enum FlashLoanProvider {
     FlashProtocol,
     /// Flash Loan Mastery
     FlashLoanMastery,
+    /// Kamino Lending
+    Kamino,
     /// Custom provider
     Custom,
 }
 
+/// Role of an account in a flash loan instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlashLoanAccountRole {
+    /// Borrower (signer)
+    Borrower,
+    /// Receiver of the funds
+    Receiver,
+    /// Token mint
+    TokenMint,
+    /// Callback program
+    CallbackProgram,
+    /// System program
+    SystemProgram,
+    /// SPL token program
+    TokenProgram,
+    /// Instructions sysvar (for providers that introspect the transaction)
+    InstructionsSysvar,
+    /// Provider liquidity supply account registered for the token mint
+    LiquiditySupply,
+    /// A fixed provider account, such as a lending market or fee receiver
+    Fixed {
+        /// Account address
+        address: Pubkey,
+        /// Whether the account is writable
+        writable: bool,
+    },
+}
+
+/// Builds a custom provider's borrow instruction from
+/// (amount, token mint, borrower, receiver, callback program)
+pub type CustomFlashLoanBuilder = Box<
+    dyn Fn(u64, &Pubkey, &Pubkey, &Pubkey, &Pubkey) -> Result<Instruction, FlashLoanError> + Send + Sync
+>;
+
+/// Instruction layout for a custom flash loan provider
+#[derive(Debug, Clone)]
+pub struct CustomFlashLoanLayout {
+    /// Borrow instruction discriminator (a single tag byte, or 8 bytes for Anchor programs)
+    pub discriminator: Vec<u8>,
+    /// Repay instruction discriminator
+    pub repay_discriminator: Vec<u8>,
+    /// Whether the amount is encoded as a little-endian u64 (big-endian otherwise)
+    pub amount_little_endian: bool,
+    /// Order in which accounts are passed to the program
+    pub account_order: Vec<FlashLoanAccountRole>,
+}
+
+impl CustomFlashLoanLayout {
+    /// Create a layout matching the built-in providers' account ordering
+    ///
+    /// The repay discriminator follows the borrow one, as with the built-in providers.
+    pub fn new(discriminator: u8) -> Self {
+        Self::with_discriminators(&[discriminator], &[discriminator.wrapping_add(1)])
+    }
+    
+    /// Create a layout with explicit borrow and repay discriminators
+    pub fn with_discriminators(discriminator: &[u8], repay_discriminator: &[u8]) -> Self {
+        Self {
+            discriminator: discriminator.to_vec(),
+            repay_discriminator: repay_discriminator.to_vec(),
+            amount_little_endian: true,
+            account_order: vec![
+                FlashLoanAccountRole::Borrower,
+                FlashLoanAccountRole::Receiver,
+                FlashLoanAccountRole::TokenMint,
+                FlashLoanAccountRole::CallbackProgram,
+                FlashLoanAccountRole::SystemProgram,
+            ],
+        }
+    }
+}
+
+/// Instruction data for a custom provider: the discriminator, then the amount in the layout's byte order
+fn custom_layout_data(layout: &CustomFlashLoanLayout, discriminator: &[u8], amount: u64) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    if layout.amount_little_endian {
+        data.extend_from_slice(&amount.to_le_bytes());
+    } else {
+        data.extend_from_slice(&amount.to_be_bytes());
+    }
+    data
+}
+
+/// Kamino Lending program ID
+pub const KAMINO_LENDING_PROGRAM_ID: &str = "KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD";
+
+/// Anchor discriminator for Kamino's flashBorrowReserveLiquidity
+const KAMINO_FLASH_BORROW_DISCRIMINATOR: [u8; 8] = [135, 231, 52, 167, 7, 52, 212, 193];
+
+/// Anchor discriminator for Kamino's flashRepayReserveLiquidity
+const KAMINO_FLASH_REPAY_DISCRIMINATOR: [u8; 8] = [185, 117, 0, 203, 96, 245, 180, 186];
+
+/// Kamino reserve used for a token
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KaminoReserveConfig {
+    /// Lending market the reserve belongs to
+    #[serde(with = "crate::serde_pubkey")]
+    pub lending_market: Pubkey,
+    /// Reserve account
+    #[serde(with = "crate::serde_pubkey")]
+    pub reserve: Pubkey,
+}
+
+/// Kamino PDAs derived for a reserve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KaminoReservePdas {
+    /// Lending market authority
+    pub lending_market_authority: Pubkey,
+    /// Reserve liquidity supply vault
+    pub liquidity_supply: Pubkey,
+    /// Reserve fee receiver
+    pub fee_receiver: Pubkey,
+}
+
+impl KaminoReserveConfig {
+    /// Derive the PDAs Kamino uses for this reserve
+    pub fn derive_pdas(&self, program_id: &Pubkey) -> KaminoReservePdas {
+        let (lending_market_authority, _) = Pubkey::find_program_address(
+            &[b"lma", self.lending_market.as_ref()],
+            program_id,
+        );
+        let (liquidity_supply, _) = Pubkey::find_program_address(
+            &[b"reserve_liq_supply", self.reserve.as_ref()],
+            program_id,
+        );
+        let (fee_receiver, _) = Pubkey::find_program_address(
+            &[b"fee_receiver", self.reserve.as_ref()],
+            program_id,
+        );
+        
+        KaminoReservePdas {
+            lending_market_authority,
+            liquidity_supply,
+            fee_receiver,
+        }
+    }
+}
+
 /// Flash loan configuration
 pub struct FlashLoanConfig {
     /// Provider to use
@@ -65,6 +214,14 @@ pub struct FlashLoanConfig {
     pub fee_percentage: f64,
     /// Custom provider program ID (if using Custom provider)
     pub custom_provider_program_id: Option<Pubkey>,
+    /// Custom provider instruction layout (if using Custom provider)
+    pub custom_layout: Option<CustomFlashLoanLayout>,
+    /// Custom provider borrow instruction builder; takes precedence over `custom_layout`
+    pub custom_builder: Option<CustomFlashLoanBuilder>,
+    /// Provider liquidity supply (vault) token account by token mint
+    pub liquidity_accounts: HashMap<Pubkey, Pubkey>,
+    /// Kamino reserves by token mint (if using Kamino)
+    pub kamino_reserves: HashMap<Pubkey, KaminoReserveConfig>,
 }
 
 impl FlashLoanConfig {
@@ -75,6 +232,10 @@ impl FlashLoanConfig {
             max_loan_amount,
             fee_percentage: 0.3, // Solend charges 0.3%
             custom_provider_program_id: None,
+            custom_layout: None,
+            custom_builder: None,
+            liquidity_accounts: HashMap::new(),
+            kamino_reserves: HashMap::new(),
         }
     }
     
@@ -85,6 +246,10 @@ impl FlashLoanConfig {
             max_loan_amount,
             fee_percentage: 0.2, // Example fee
             custom_provider_program_id: None,
+            custom_layout: None,
+            custom_builder: None,
+            liquidity_accounts: HashMap::new(),
+            kamino_reserves: HashMap::new(),
         }
     }
     
@@ -95,18 +260,162 @@ impl FlashLoanConfig {
             max_loan_amount,
             fee_percentage: 0.25, // Example fee
             custom_provider_program_id: None,
+            custom_layout: None,
+            custom_builder: None,
+            liquidity_accounts: HashMap::new(),
+            kamino_reserves: HashMap::new(),
         }
     }
     
     /// Create a new flash loan configuration with a custom provider
     pub fn new_custom(max_loan_amount: u64, fee_percentage: f64, program_id: Pubkey) -> Self {
+        Self::new_custom_with_layout(max_loan_amount, fee_percentage, program_id, CustomFlashLoanLayout::new(0))
+    }
+    
+    /// Create a new flash loan configuration with a custom provider and instruction layout
+    pub fn new_custom_with_layout(
+        max_loan_amount: u64,
+        fee_percentage: f64,
+        program_id: Pubkey,
+        layout: CustomFlashLoanLayout,
+    ) -> Self {
         Self {
             provider: FlashLoanProvider::Custom,
             max_loan_amount,
             fee_percentage,
             custom_provider_program_id: Some(program_id),
+            custom_layout: Some(layout),
+            custom_builder: None,
+            liquidity_accounts: HashMap::new(),
+            kamino_reserves: HashMap::new(),
         }
     }
+    
+    /// Create a new flash loan configuration with a custom provider whose borrow instruction is built by a closure
+    ///
+    /// The default layout is kept for the repay instruction.
+    pub fn new_custom_with_builder(
+        max_loan_amount: u64,
+        fee_percentage: f64,
+        program_id: Pubkey,
+        builder: CustomFlashLoanBuilder,
+    ) -> Self {
+        let mut config = Self::new_custom(max_loan_amount, fee_percentage, program_id);
+        config.custom_builder = Some(builder);
+        config
+    }
+    
+    /// Create a new flash loan configuration with Kamino as provider
+    pub fn new_kamino(max_loan_amount: u64) -> Self {
+        Self {
+            provider: FlashLoanProvider::Kamino,
+            max_loan_amount,
+            fee_percentage: 0.001, // Kamino reserves charge 0.001%
+            custom_provider_program_id: None,
+            custom_layout: None,
+            custom_builder: None,
+            liquidity_accounts: HashMap::new(),
+            kamino_reserves: HashMap::new(),
+        }
+    }
+    
+    /// Register the provider's liquidity supply account for a token mint
+    pub fn add_liquidity_account(&mut self, token_mint: Pubkey, supply_account: Pubkey) {
+        self.liquidity_accounts.insert(token_mint, supply_account);
+    }
+    
+    /// Register the Kamino reserve to borrow a token from
+    pub fn add_kamino_reserve(&mut self, token_mint: Pubkey, reserve: KaminoReserveConfig) {
+        // The reserve's supply vault is where live liquidity is read from
+        let program_id = Pubkey::from_str(KAMINO_LENDING_PROGRAM_ID).unwrap_or_default();
+        let pdas = reserve.derive_pdas(&program_id);
+        self.liquidity_accounts.insert(token_mint, pdas.liquidity_supply);
+        self.kamino_reserves.insert(token_mint, reserve);
+    }
+}
+
+/// Provider liquidity supply account for a token, as read from the bot config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiquidityAccountConfig {
+    /// Token mint
+    #[serde(with = "crate::serde_pubkey")]
+    pub token_mint: Pubkey,
+    /// Provider supply (vault) token account for the mint
+    #[serde(with = "crate::serde_pubkey")]
+    pub supply_account: Pubkey,
+}
+
+/// Kamino reserve for a token, as read from the bot config
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KaminoReserveEntry {
+    /// Token mint
+    #[serde(with = "crate::serde_pubkey")]
+    pub token_mint: Pubkey,
+    /// Reserve to borrow the token from
+    #[serde(flatten)]
+    pub reserve: KaminoReserveConfig,
+}
+
+/// Flash loan provider as read from the bot config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlashLoanProviderConfig {
+    /// Provider to borrow from (custom providers are configured in code)
+    pub provider: FlashLoanProvider,
+    /// Supply accounts loans are checked against, per token
+    #[serde(default)]
+    pub liquidity_accounts: Vec<LiquidityAccountConfig>,
+    /// Kamino reserves per token; their supply vaults are derived, so they need no liquidity account
+    #[serde(default)]
+    pub kamino_reserves: Vec<KaminoReserveEntry>,
+}
+
+impl FlashLoanProviderConfig {
+    /// Build the provider's configuration with its liquidity registered, lending up to `max_loan_amount`
+    ///
+    /// Fails for entries that could never lend, since loans fail closed without a liquidity account.
+    pub fn to_flash_loan_config(&self, max_loan_amount: u64) -> Result<FlashLoanConfig, String> {
+        let mut config = match self.provider {
+            FlashLoanProvider::Solend => FlashLoanConfig::new_solend(max_loan_amount),
+            FlashLoanProvider::FlashProtocol => FlashLoanConfig::new_flash_protocol(max_loan_amount),
+            FlashLoanProvider::FlashLoanMastery => FlashLoanConfig::new_flash_loan_mastery(max_loan_amount),
+            FlashLoanProvider::Kamino => FlashLoanConfig::new_kamino(max_loan_amount),
+            FlashLoanProvider::Custom => {
+                return Err("Custom flash loan providers need an instruction layout and are configured in code".to_string());
+            },
+        };
+        
+        if !self.kamino_reserves.is_empty() && self.provider != FlashLoanProvider::Kamino {
+            return Err(format!("Kamino reserves configured for the {:?} flash loan provider", self.provider));
+        }
+        for account in &self.liquidity_accounts {
+            config.add_liquidity_account(account.token_mint, account.supply_account);
+        }
+        for entry in &self.kamino_reserves {
+            config.add_kamino_reserve(entry.token_mint, entry.reserve);
+        }
+        
+        if config.liquidity_accounts.is_empty() {
+            return Err(format!(
+                "{:?} flash loan provider has no liquidity accounts, so every loan would be refused",
+                self.provider
+            ));
+        }
+        
+        Ok(config)
+    }
+}
+
+/// Select the cheapest provider able to lend `amount` of `token_mint`
+///
+/// Providers whose live reserve can't cover the loan, or whose reserve can't be read, are skipped.
+pub fn select_cheapest_provider<'a>(
+    managers: &'a [FlashLoanManager],
+    token_mint: &Pubkey,
+    amount: u64,
+) -> Option<&'a FlashLoanManager> {
+    managers.iter()
+        .filter(|manager| manager.can_lend(token_mint, amount))
+        .min_by(|a, b| a.config.fee_percentage.partial_cmp(&b.config.fee_percentage).unwrap_or(std::cmp::Ordering::Equal))
 }
 
 /// Flash loan manager
@@ -121,18 +430,31 @@ pub struct FlashLoanManager {
     flash_protocol_program_id: Pubkey,
     /// Flash Loan Mastery program ID
     flash_loan_mastery_program_id: Pubkey,
+    /// Kamino Lending program ID
+    kamino_program_id: Pubkey,
 }
 
 impl FlashLoanManager {
     /// Create a new flash loan manager
     pub fn new(rpc_url: &str, config: FlashLoanConfig) -> Self {
-        let rpc_client = RpcClient::new(rpc_url.to_string());
-        
+        Self::with_rpc_client(RpcClient::new(rpc_url.to_string()), config)
+    }
+    
+    /// Create a new flash loan manager over an existing RPC client
+    pub fn with_rpc_client(rpc_client: RpcClient, config: FlashLoanConfig) -> Self {
         // Program IDs for flash loan providers
         // Note: These are placeholder values and should be replaced with actual program IDs
         let solend_program_id = Pubkey::from_str("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo").unwrap_or_default();
         let flash_protocol_program_id = Pubkey::from_str("F1ashzfw6VFQtGR3EgqmmSEnBZCR4ZvK6LaiAz5oxUg").unwrap_or_default();
         let flash_loan_mastery_program_id = Pubkey::from_str("F1ashMa5t3ryXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX").unwrap_or_default();
+        let kamino_program_id = Pubkey::from_str(KAMINO_LENDING_PROGRAM_ID).unwrap_or_default();
+        
+        if config.liquidity_accounts.is_empty() {
+            warn!(
+                "No liquidity accounts configured for {:?} flash loans; loans will be refused until one is added",
+                config.provider
+            );
+        }
         
         Self {
             rpc_client,
@@ -140,6 +462,7 @@ impl FlashLoanManager {
             solend_program_id,
             flash_protocol_program_id,
             flash_loan_mastery_program_id,
+            kamino_program_id,
         }
     }
     
@@ -149,6 +472,7 @@ impl FlashLoanManager {
             FlashLoanProvider::Solend => self.solend_program_id,
             FlashLoanProvider::FlashProtocol => self.flash_protocol_program_id,
             FlashLoanProvider::FlashLoanMastery => self.flash_loan_mastery_program_id,
+            FlashLoanProvider::Kamino => self.kamino_program_id,
             FlashLoanProvider::Custom => self.config.custom_provider_program_id.unwrap_or_default(),
         }
     }
@@ -158,6 +482,65 @@ impl FlashLoanManager {
         ((amount as f64) * (self.config.fee_percentage / 100.0)) as u64
     }
     
+    /// Total owed when repaying a flash loan: principal plus fee
+    pub fn total_repayment(&self, amount: u64) -> u64 {
+        amount + self.calculate_fee(amount)
+    }
+    
+    /// Whether this provider can lend `amount` of `token_mint` right now
+    ///
+    /// A reserve that can't be read counts as unable to lend.
+    pub fn can_lend(&self, token_mint: &Pubkey, amount: u64) -> bool {
+        if self.config.max_loan_amount < amount {
+            return false;
+        }
+        
+        match self.available_liquidity(token_mint) {
+            Ok(available) => available >= amount,
+            Err(e) => {
+                debug!("Skipping {:?} for mint {}: {}", self.config.provider, token_mint, e);
+                false
+            },
+        }
+    }
+    
+    /// Get the liquidity currently available to borrow for a token
+    pub fn available_liquidity(&self, token_mint: &Pubkey) -> Result<u64, FlashLoanError> {
+        let supply_account = self.config.liquidity_accounts.get(token_mint)
+            .ok_or_else(|| FlashLoanError::ParameterError(format!(
+                "No liquidity account configured for mint {}",
+                token_mint
+            )))?;
+        
+        let balance = self.rpc_client.get_token_account_balance(supply_account)
+            .map_err(|e| FlashLoanError::RpcError(format!("Failed to read reserve liquidity: {}", e)))?;
+        
+        balance.amount.parse::<u64>()
+            .map_err(|e| FlashLoanError::RpcError(format!("Invalid reserve balance: {}", e)))
+    }
+    
+    /// Validate a loan amount against the configured ceiling and live reserve liquidity
+    ///
+    /// Fails closed: a mint without a configured liquidity account can't be borrowed.
+    fn validate_loan_amount(&self, amount: u64, token_mint: &Pubkey) -> Result<(), FlashLoanError> {
+        if amount > self.config.max_loan_amount {
+            return Err(FlashLoanError::ParameterError(format!(
+                "Loan amount {} exceeds maximum {}",
+                amount, self.config.max_loan_amount
+            )));
+        }
+        
+        let available = self.available_liquidity(token_mint)?;
+        if amount > available {
+            return Err(FlashLoanError::ParameterError(format!(
+                "Loan amount {} exceeds available reserve liquidity {} for mint {}",
+                amount, available, token_mint
+            )));
+        }
+        
+        Ok(())
+    }
+    
     /// Create a flash loan instruction for Solend
     pub fn create_solend_flash_loan_instruction(
         &self,
@@ -168,12 +551,7 @@ impl FlashLoanManager {
         callback_program_id: &Pubkey,
     ) -> Result<Instruction, FlashLoanError> {
         // Validate parameters
-        if amount > self.config.max_loan_amount {
-            return Err(FlashLoanError::ParameterError(format!(
-                "Loan amount {} exceeds maximum {}",
-                amount, self.config.max_loan_amount
-            )));
-        }
+        self.validate_loan_amount(amount, token_mint)?;
         
         // This is a simplified example of creating a flash loan instruction for Solend
         // In a real implementation, you would need to:
@@ -215,6 +593,8 @@ impl FlashLoanManager {
         receiver: &Pubkey,
         callback_program_id: &Pubkey,
     ) -> Result<Instruction, FlashLoanError> {
+        self.validate_loan_amount(amount, token_mint)?;
+        
         // Similar to Solend, but with Flash Protocol-specific parameters
         // This is a placeholder implementation
         
@@ -247,6 +627,8 @@ impl FlashLoanManager {
         receiver: &Pubkey,
         callback_program_id: &Pubkey,
     ) -> Result<Instruction, FlashLoanError> {
+        self.validate_loan_amount(amount, token_mint)?;
+        
         // Similar to other providers, but with Flash Loan Mastery-specific parameters
         // This is a placeholder implementation
         
@@ -270,6 +652,234 @@ impl FlashLoanManager {
         })
     }
     
+    /// Create a flash loan instruction for a custom provider using its configured layout
+    pub fn create_custom_flash_loan_instruction(
+        &self,
+        amount: u64,
+        token_mint: &Pubkey,
+        borrower: &Pubkey,
+        receiver: &Pubkey,
+        callback_program_id: &Pubkey,
+    ) -> Result<Instruction, FlashLoanError> {
+        // Validate parameters
+        self.validate_loan_amount(amount, token_mint)?;
+        
+        if let Some(builder) = &self.config.custom_builder {
+            return builder(amount, token_mint, borrower, receiver, callback_program_id);
+        }
+        
+        let program_id = self.config.custom_provider_program_id
+            .ok_or_else(|| FlashLoanError::ParameterError("Custom provider program ID not set".to_string()))?;
+        
+        let layout = self.custom_layout()?;
+        
+        Ok(Instruction {
+            program_id,
+            accounts: self.custom_layout_accounts(layout, token_mint, borrower, receiver, callback_program_id)?,
+            data: custom_layout_data(layout, &layout.discriminator, amount),
+        })
+    }
+    
+    /// Instruction layout of the custom provider
+    fn custom_layout(&self) -> Result<&CustomFlashLoanLayout, FlashLoanError> {
+        self.config.custom_layout.as_ref()
+            .ok_or_else(|| FlashLoanError::ParameterError("Custom provider layout not set".to_string()))
+    }
+    
+    /// Place accounts in the order a custom provider's layout expects
+    ///
+    /// `token_account` fills the receiver role: where the loan lands, or where the repayment is paid from.
+    fn custom_layout_accounts(
+        &self,
+        layout: &CustomFlashLoanLayout,
+        token_mint: &Pubkey,
+        borrower: &Pubkey,
+        token_account: &Pubkey,
+        callback_program_id: &Pubkey,
+    ) -> Result<Vec<AccountMeta>, FlashLoanError> {
+        layout.account_order.iter()
+            .map(|role| Ok(match role {
+                FlashLoanAccountRole::Borrower => AccountMeta::new(*borrower, true),
+                FlashLoanAccountRole::Receiver => AccountMeta::new(*token_account, false),
+                FlashLoanAccountRole::TokenMint => AccountMeta::new_readonly(*token_mint, false),
+                FlashLoanAccountRole::CallbackProgram => AccountMeta::new_readonly(*callback_program_id, false),
+                FlashLoanAccountRole::SystemProgram => AccountMeta::new_readonly(system_program::id(), false),
+                FlashLoanAccountRole::TokenProgram => AccountMeta::new_readonly(spl_token::id(), false),
+                FlashLoanAccountRole::InstructionsSysvar => AccountMeta::new_readonly(sysvar::instructions::id(), false),
+                FlashLoanAccountRole::LiquiditySupply => {
+                    let supply = self.config.liquidity_accounts.get(token_mint)
+                        .ok_or_else(|| FlashLoanError::ParameterError(format!(
+                            "No liquidity account configured for mint {}",
+                            token_mint
+                        )))?;
+                    AccountMeta::new(*supply, false)
+                },
+                FlashLoanAccountRole::Fixed { address, writable: true } => AccountMeta::new(*address, false),
+                FlashLoanAccountRole::Fixed { address, writable: false } => AccountMeta::new_readonly(*address, false),
+            }))
+            .collect()
+    }
+    
+    /// Look up the Kamino reserve configured for a token
+    fn kamino_reserve(&self, token_mint: &Pubkey) -> Result<KaminoReserveConfig, FlashLoanError> {
+        self.config.kamino_reserves.get(token_mint)
+            .copied()
+            .ok_or_else(|| FlashLoanError::ParameterError(format!(
+                "No Kamino reserve configured for mint {}",
+                token_mint
+            )))
+    }
+    
+    /// Create a flash borrow instruction for Kamino (flashBorrowReserveLiquidity)
+    pub fn create_kamino_flash_loan_instruction(
+        &self,
+        amount: u64,
+        token_mint: &Pubkey,
+        borrower: &Pubkey,
+        receiver: &Pubkey,
+    ) -> Result<Instruction, FlashLoanError> {
+        self.validate_loan_amount(amount, token_mint)?;
+        
+        let program_id = self.kamino_program_id;
+        let reserve = self.kamino_reserve(token_mint)?;
+        let pdas = reserve.derive_pdas(&program_id);
+        
+        let accounts = vec![
+            AccountMeta::new_readonly(*borrower, true),                      // User transfer authority
+            AccountMeta::new_readonly(pdas.lending_market_authority, false), // Lending market authority
+            AccountMeta::new_readonly(reserve.lending_market, false),        // Lending market
+            AccountMeta::new(reserve.reserve, false),                        // Reserve
+            AccountMeta::new_readonly(*token_mint, false),                   // Reserve liquidity mint
+            AccountMeta::new(pdas.liquidity_supply, false),                  // Reserve source liquidity
+            AccountMeta::new(*receiver, false),                              // User destination liquidity
+            AccountMeta::new(pdas.fee_receiver, false),                      // Reserve liquidity fee receiver
+            AccountMeta::new_readonly(program_id, false),                    // Referrer token state (none)
+            AccountMeta::new_readonly(program_id, false),                    // Referrer account (none)
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),    // Instructions sysvar
+            AccountMeta::new_readonly(spl_token::id(), false),               // Token program
+        ];
+        
+        let mut data = KAMINO_FLASH_BORROW_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        
+        Ok(Instruction {
+            program_id,
+            accounts,
+            data,
+        })
+    }
+    
+    /// Create a flash repay instruction for Kamino (flashRepayReserveLiquidity)
+    ///
+    /// `amount` is the borrowed principal; Kamino adds the fee on-chain.
+    pub fn create_kamino_flash_repay_instruction(
+        &self,
+        amount: u64,
+        token_mint: &Pubkey,
+        borrower: &Pubkey,
+        source: &Pubkey,
+        borrow_instruction_index: u8,
+    ) -> Result<Instruction, FlashLoanError> {
+        let program_id = self.kamino_program_id;
+        let reserve = self.kamino_reserve(token_mint)?;
+        let pdas = reserve.derive_pdas(&program_id);
+        
+        let accounts = vec![
+            AccountMeta::new_readonly(*borrower, true),                      // User transfer authority
+            AccountMeta::new_readonly(pdas.lending_market_authority, false), // Lending market authority
+            AccountMeta::new_readonly(reserve.lending_market, false),        // Lending market
+            AccountMeta::new(reserve.reserve, false),                        // Reserve
+            AccountMeta::new_readonly(*token_mint, false),                   // Reserve liquidity mint
+            AccountMeta::new(pdas.liquidity_supply, false),                  // Reserve destination liquidity
+            AccountMeta::new(*source, false),                                // User source liquidity
+            AccountMeta::new(pdas.fee_receiver, false),                      // Reserve liquidity fee receiver
+            AccountMeta::new_readonly(program_id, false),                    // Referrer token state (none)
+            AccountMeta::new_readonly(program_id, false),                    // Referrer account (none)
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),    // Instructions sysvar
+            AccountMeta::new_readonly(spl_token::id(), false),               // Token program
+        ];
+        
+        let mut data = KAMINO_FLASH_REPAY_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(borrow_instruction_index);
+        
+        Ok(Instruction {
+            program_id,
+            accounts,
+            data,
+        })
+    }
+    
+    /// Create the instruction repaying a flash loan to the configured provider
+    ///
+    /// `amount` is the borrowed principal; the fee is added for providers that expect it.
+    /// `provider_program_id` must be the program the loan was borrowed from, and
+    /// `borrow_instruction_index` the borrow's position in the final transaction.
+    pub fn create_repayment_instruction(
+        &self,
+        amount: u64,
+        token_mint: &Pubkey,
+        borrower: &Pubkey,
+        provider_program_id: &Pubkey,
+        borrow_instruction_index: u8,
+    ) -> Result<Instruction, FlashLoanError> {
+        // Repaying a different program than we borrowed from would revert the whole transaction
+        let expected_program_id = self.get_provider_program_id();
+        if *provider_program_id != expected_program_id {
+            return Err(FlashLoanError::ParameterError(format!(
+                "Repayment program {} does not match provider program {}",
+                provider_program_id, expected_program_id
+            )));
+        }
+        
+        if self.config.provider == FlashLoanProvider::Kamino {
+            let source = get_associated_token_address(borrower, token_mint);
+            return self.create_kamino_flash_repay_instruction(
+                amount,
+                token_mint,
+                borrower,
+                &source,
+                borrow_instruction_index,
+            );
+        }
+        
+        let repay_amount = self.total_repayment(amount);
+        
+        // Custom providers repay through the same layout they borrow through, paying from the borrower's token account
+        if self.config.provider == FlashLoanProvider::Custom {
+            let layout = self.custom_layout()?;
+            let source = get_associated_token_address(borrower, token_mint);
+            return Ok(Instruction {
+                program_id: *provider_program_id,
+                accounts: self.custom_layout_accounts(layout, token_mint, borrower, &source, provider_program_id)?,
+                data: custom_layout_data(layout, &layout.repay_discriminator, repay_amount),
+            });
+        }
+        
+        // Repay discriminators mirror the borrow ones for each provider
+        let discriminator = match self.config.provider {
+            FlashLoanProvider::Solend => vec![13],
+            FlashLoanProvider::FlashProtocol => vec![2],
+            FlashLoanProvider::FlashLoanMastery => vec![6],
+            FlashLoanProvider::Kamino | FlashLoanProvider::Custom => unreachable!("Kamino and custom repays handled above"),
+        };
+        
+        let accounts = vec![
+            AccountMeta::new(*borrower, true),
+            AccountMeta::new_readonly(*token_mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        
+        let mut data = discriminator;
+        data.extend_from_slice(&repay_amount.to_le_bytes());
+        
+        Ok(Instruction {
+            program_id: *provider_program_id,
+            accounts,
+            data,
+        })
+    }
+    
     /// Create a flash loan instruction for the configured provider
     pub fn create_flash_loan_instruction(
         &self,
@@ -289,15 +899,18 @@ impl FlashLoanManager {
             FlashLoanProvider::FlashLoanMastery => {
                 self.create_flash_loan_mastery_instruction(amount, token_mint, borrower, receiver, callback_program_id)
             },
+            FlashLoanProvider::Kamino => {
+                self.create_kamino_flash_loan_instruction(amount, token_mint, borrower, receiver)
+            },
             FlashLoanProvider::Custom => {
-                // For custom providers, we would need more specific implementation details
-                Err(FlashLoanError::ProviderError("Custom provider not implemented".to_string()))
+                self.create_custom_flash_loan_instruction(amount, token_mint, borrower, receiver, callback_program_id)
             },
         }
     }
 }
 
 /// Thread-safe wrapper for FlashLoanManager
+#[derive(Clone)]
 pub struct ThreadSafeFlashLoanManager {
     inner: Arc<Mutex<FlashLoanManager>>,
 }
@@ -310,17 +923,33 @@ impl ThreadSafeFlashLoanManager {
         }
     }
     
+    /// Create a new thread-safe flash loan manager over an existing RPC client
+    pub fn with_rpc_client(rpc_client: RpcClient, config: FlashLoanConfig) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(FlashLoanManager::with_rpc_client(rpc_client, config))),
+        }
+    }
+    
+    /// Select the cheapest of `managers` able to lend `amount` of `token_mint` (thread-safe)
+    pub fn select_cheapest(managers: &[ThreadSafeFlashLoanManager], token_mint: &Pubkey, amount: u64) -> Option<ThreadSafeFlashLoanManager> {
+        managers.iter()
+            .filter_map(|manager| {
+                let inner = lock_or_recover(&manager.inner);
+                inner.can_lend(token_mint, amount).then(|| (inner.config.fee_percentage, manager.clone()))
+            })
+            .min_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(_, manager)| manager)
+    }
+    
     /// Get the program ID for the configured provider (thread-safe)
     pub fn get_provider_program_id(&self) -> Result<Pubkey, FlashLoanError> {
-        let manager = self.inner.lock()
-            .map_err(|e| FlashLoanError::GeneralError(format!("Lock error: {}", e)))?;
+        let manager = lock_or_recover(&self.inner);
         Ok(manager.get_provider_program_id())
     }
     
     /// Calculate the fee for a flash loan (thread-safe)
     pub fn calculate_fee(&self, amount: u64) -> Result<u64, FlashLoanError> {
-        let manager = self.inner.lock()
-            .map_err(|e| FlashLoanError::GeneralError(format!("Lock error: {}", e)))?;
+        let manager = lock_or_recover(&self.inner);
         Ok(manager.calculate_fee(amount))
     }
     
@@ -333,17 +962,38 @@ impl ThreadSafeFlashLoanManager {
         receiver: &Pubkey,
         callback_program_id: &Pubkey,
     ) -> Result<Instruction, FlashLoanError> {
-        let manager = self.inner.lock()
-            .map_err(|e| FlashLoanError::GeneralError(format!("Lock error: {}", e)))?;
+        let manager = lock_or_recover(&self.inner);
         manager.create_flash_loan_instruction(amount, token_mint, borrower, receiver, callback_program_id)
     }
+    
+    /// Total owed when repaying a flash loan (thread-safe)
+    pub fn total_repayment(&self, amount: u64) -> Result<u64, FlashLoanError> {
+        let manager = lock_or_recover(&self.inner);
+        Ok(manager.total_repayment(amount))
+    }
+    
+    /// Create the flash loan repayment instruction (thread-safe)
+    pub fn create_repayment_instruction(
+        &self,
+        amount: u64,
+        token_mint: &Pubkey,
+        borrower: &Pubkey,
+        provider_program_id: &Pubkey,
+        borrow_instruction_index: u8,
+    ) -> Result<Instruction, FlashLoanError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.create_repayment_instruction(amount, token_mint, borrower, provider_program_id, borrow_instruction_index)
+    }
 }
 
 /// Flash loan callback handler trait
 /// This trait should be implemented by components that want to handle flash loan callbacks
+///
+/// Handlers are async so they can build instructions on whatever runtime drives them.
+#[async_trait(?Send)]
 pub trait FlashLoanCallbackHandler {
     /// Handle a flash loan callback
-    fn handle_flash_loan_callback(
+    async fn handle_flash_loan_callback(
         &self,
         amount: u64,
         token_mint: &Pubkey,
@@ -352,36 +1002,123 @@ pub trait FlashLoanCallbackHandler {
     ) -> Result<Vec<Instruction>, FlashLoanError>;
 }
 
-/// Example implementation of a flash loan arbitrage callback
+/// Buy and sell legs an arbitrage callback trades along
+#[derive(Debug, Clone)]
+pub struct ArbitrageRoute {
+    /// Price on the DEX to buy the base token on
+    pub buy_price: PriceInfo,
+    /// Price on the DEX to sell the base token on
+    pub sell_price: PriceInfo,
+    /// Wallet executing the swaps
+    pub wallet: Pubkey,
+    /// Slippage tolerance (e.g., 0.5 for 0.5%)
+    pub slippage: f64,
+    /// Position of the flash borrow in the transaction the callback's instructions join
+    pub borrow_instruction_index: u8,
+}
+
+/// Flash loan arbitrage callback
+/// Buys on one DEX, sells on another and repays the loan from the proceeds
 pub struct ArbitrageCallbackHandler {
-    /// DEX connector for executing trades
-    dex_connector: Arc<Mutex<()>>, // Placeholder for actual DEX connector
+    /// DEX manager for building swap instructions
+    dex_manager: ThreadSafeDexManager,
+    /// Flash loan manager for building the repay instruction
+    flash_loan_manager: ThreadSafeFlashLoanManager,
+    /// Route to trade along
+    route: ArbitrageRoute,
 }
 
 impl ArbitrageCallbackHandler {
     /// Create a new arbitrage callback handler
-    pub fn new() -> Self {
+    pub fn new(
+        dex_manager: ThreadSafeDexManager,
+        flash_loan_manager: ThreadSafeFlashLoanManager,
+        route: ArbitrageRoute,
+    ) -> Self {
         Self {
-            dex_connector: Arc::new(Mutex::new(())),
+            dex_manager,
+            flash_loan_manager,
+            route,
         }
     }
 }
 
+#[async_trait(?Send)]
 impl FlashLoanCallbackHandler for ArbitrageCallbackHandler {
-    fn handle_flash_loan_callback(
+    async fn handle_flash_loan_callback(
         &self,
         amount: u64,
         token_mint: &Pubkey,
         fee: u64,
-        accounts: &[AccountMeta],
+        _accounts: &[AccountMeta],
     ) -> Result<Vec<Instruction>, FlashLoanError> {
-        // This would implement the arbitrage logic:
-        // 1. Buy token on DEX A
-        // 2. Sell token on DEX B
-        // 3. Repay flash loan with fee
+        let buy = &self.route.buy_price;
+        let sell = &self.route.sell_price;
+        
+        // The borrowed token is what we spend on the buy leg
+        if *token_mint != buy.quote_token {
+            return Err(FlashLoanError::ParameterError(format!(
+                "Borrowed mint {} does not match route quote token {}",
+                token_mint, buy.quote_token
+            )));
+        }
+        
+        if buy.price <= 0.0 || sell.price <= 0.0 {
+            return Err(FlashLoanError::ParameterError("Route has an invalid price".to_string()));
+        }
         
-        // For now, we'll return an empty vector as a placeholder
-        Ok(vec![])
+        // Project the output of both legs at worst-case slippage, in atoms of each token
+        let min_base_out = min_amount_out(buy.quote_to_base_atoms(amount), self.route.slippage);
+        let min_quote_out = min_amount_out(sell.base_to_quote_atoms(min_base_out), self.route.slippage);
+        
+        let repay_amount = amount + fee;
+        if min_quote_out < repay_amount {
+            return Err(FlashLoanError::ParameterError(format!(
+                "Projected output {} does not cover repayment {}",
+                min_quote_out, repay_amount
+            )));
+        }
+        
+        let buy_params = SwapParams {
+            amount_in: amount,
+            min_amount_out: min_base_out,
+            source_token: buy.quote_token,
+            destination_token: buy.base_token,
+            source_wallet: self.route.wallet,
+            destination_wallet: self.route.wallet,
+            slippage: self.route.slippage,
+        };
+        
+        // The sell leg must return at least enough to repay the loan
+        let sell_params = SwapParams {
+            amount_in: min_base_out,
+            min_amount_out: repay_amount,
+            source_token: sell.base_token,
+            destination_token: sell.quote_token,
+            source_wallet: self.route.wallet,
+            destination_wallet: self.route.wallet,
+            slippage: self.route.slippage,
+        };
+        
+        let buy_swap = self.dex_manager.create_swap_instructions_for(buy.dex, &buy_params).await
+            .map_err(|e| FlashLoanError::TransactionError(format!("Failed to build buy instructions: {}", e)))?;
+        let sell_swap = self.dex_manager.create_swap_instructions_for(sell.dex, &sell_params).await
+            .map_err(|e| FlashLoanError::TransactionError(format!("Failed to build sell instructions: {}", e)))?;
+        
+        let repay_instruction = self.flash_loan_manager.create_repayment_instruction(
+            amount,
+            token_mint,
+            &self.route.wallet,
+            &self.flash_loan_manager.get_provider_program_id()?,
+            self.route.borrow_instruction_index,
+        )?;
+        
+        // Lookup tables are dropped here, the callback only returns instructions
+        let mut instructions = buy_swap.instructions;
+        instructions.extend(sell_swap.instructions);
+        instructions.push(repay_instruction);
+        
+        Ok(instructions)
     }
 }
 
@@ -409,13 +1146,492 @@ impl FlashLoanCallbackProgram {
     }
     
     /// Process a flash loan callback
-    pub fn process_callback(
+    pub async fn process_callback(
         &self,
         amount: u64,
         token_mint: &Pubkey,
         fee: u64,
         accounts: &[AccountMeta],
     ) -> Result<Vec<Instruction>, FlashLoanError> {
-        self.callback_handler.handle_flash_loan_callback(amount, token_mint, fee, accounts)
+        self.callback_handler.handle_flash_loan_callback(amount, token_mint, fee, accounts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::{CustomDexAdapter, DexConfig, DexError, DexManager, DexType};
+    use serde_json::json;
+    use solana_client::rpc_request::RpcRequest;
+    
+    /// DEX whose swaps are instructions recording the swap parameters
+    struct MockDex {
+        program_id: Pubkey,
+    }
+    
+    impl CustomDexAdapter for MockDex {
+        fn get_price(&self, _rpc_client: &RpcClient, _base_token: &Pubkey, _quote_token: &Pubkey) -> Result<PriceInfo, DexError> {
+            Err(DexError::GeneralError("Mock DEX has no prices".to_string()))
+        }
+        
+        fn create_swap_instruction(&self, params: &SwapParams) -> Result<Instruction, DexError> {
+            let mut data = params.amount_in.to_le_bytes().to_vec();
+            data.extend_from_slice(&params.min_amount_out.to_le_bytes());
+            Ok(Instruction {
+                program_id: self.program_id,
+                accounts: vec![
+                    AccountMeta::new_readonly(params.source_token, false),
+                    AccountMeta::new_readonly(params.destination_token, false),
+                ],
+                data,
+            })
+        }
+    }
+    
+    /// Quote for the mock DEX with 6 decimals on both sides
+    fn mock_price(base_token: Pubkey, quote_token: Pubkey, price: f64) -> PriceInfo {
+        PriceInfo {
+            base_token,
+            quote_token,
+            price,
+            liquidity: u64::MAX,
+            dex: DexType::Custom,
+            timestamp: 0,
+            base_decimals: 6,
+            quote_decimals: 6,
+            pool: None,
+            fee_bps: None,
+        }
+    }
+    
+    /// Mock RPC client whose next `getTokenAccountBalance` reports `amount`
+    fn rpc_with_reserve_balance(amount: u64) -> RpcClient {
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetTokenAccountBalance,
+            json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "amount": amount.to_string(),
+                    "decimals": 6,
+                    "uiAmount": amount as f64 / 1e6,
+                    "uiAmountString": (amount as f64 / 1e6).to_string(),
+                }
+            }),
+        );
+        RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+    }
+    
+    #[test]
+    fn custom_provider_uses_layout() {
+        let program_id = Pubkey::new_unique();
+        let token_mint = Pubkey::new_unique();
+        let borrower = Pubkey::new_unique();
+        let receiver = Pubkey::new_unique();
+        let callback = Pubkey::new_unique();
+        
+        let mut config = FlashLoanConfig::new_custom_with_layout(
+            u64::MAX,
+            0.05,
+            program_id,
+            CustomFlashLoanLayout::new(42),
+        );
+        config.add_liquidity_account(token_mint, Pubkey::new_unique());
+        let manager = FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(u64::MAX), config);
+        
+        let amount: u64 = 0x0102_0304_0506_0708;
+        let instruction = manager.create_flash_loan_instruction(amount, &token_mint, &borrower, &receiver, &callback).unwrap();
+        
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(manager.get_provider_program_id(), program_id);
+        assert_eq!(instruction.data[0], 42);
+        assert_eq!(&instruction.data[1..], &[0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+        assert_eq!(instruction.accounts[0], AccountMeta::new(borrower, true));
+        assert_eq!(instruction.accounts[1], AccountMeta::new(receiver, false));
+    }
+    
+    #[test]
+    fn custom_layout_places_every_role_and_encodes_the_amount() {
+        let program_id = Pubkey::new_unique();
+        let token_mint = Pubkey::new_unique();
+        let borrower = Pubkey::new_unique();
+        let receiver = Pubkey::new_unique();
+        let callback = Pubkey::new_unique();
+        let supply = Pubkey::new_unique();
+        let market = Pubkey::new_unique();
+        let fee_receiver = Pubkey::new_unique();
+        
+        let discriminator = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mut layout = CustomFlashLoanLayout::with_discriminators(&discriminator, &[9; 8]);
+        layout.amount_little_endian = false;
+        layout.account_order = vec![
+            FlashLoanAccountRole::Fixed { address: market, writable: false },
+            FlashLoanAccountRole::LiquiditySupply,
+            FlashLoanAccountRole::Receiver,
+            FlashLoanAccountRole::Fixed { address: fee_receiver, writable: true },
+            FlashLoanAccountRole::TokenMint,
+            FlashLoanAccountRole::Borrower,
+            FlashLoanAccountRole::InstructionsSysvar,
+            FlashLoanAccountRole::TokenProgram,
+            FlashLoanAccountRole::SystemProgram,
+            FlashLoanAccountRole::CallbackProgram,
+        ];
+        let mut config = FlashLoanConfig::new_custom_with_layout(u64::MAX, 0.05, program_id, layout);
+        config.add_liquidity_account(token_mint, supply);
+        let manager = FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(u64::MAX), config);
+        
+        let amount: u64 = 0x0102_0304_0506_0708;
+        let instruction = manager.create_flash_loan_instruction(amount, &token_mint, &borrower, &receiver, &callback).unwrap();
+        
+        assert_eq!(instruction.accounts, vec![
+            AccountMeta::new_readonly(market, false),
+            AccountMeta::new(supply, false),
+            AccountMeta::new(receiver, false),
+            AccountMeta::new(fee_receiver, false),
+            AccountMeta::new_readonly(token_mint, false),
+            AccountMeta::new(borrower, true),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(callback, false),
+        ]);
+        
+        // Eight discriminator bytes, then the amount big-endian
+        assert_eq!(&instruction.data[..8], &discriminator);
+        assert_eq!(&instruction.data[8..], &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]);
+    }
+    
+    #[test]
+    fn custom_repayment_follows_the_layout() {
+        let program_id = Pubkey::new_unique();
+        let token_mint = Pubkey::new_unique();
+        let borrower = Pubkey::new_unique();
+        let supply = Pubkey::new_unique();
+        let market = Pubkey::new_unique();
+        
+        let mut layout = CustomFlashLoanLayout::with_discriminators(&[1; 8], &[9, 8, 7, 6, 5, 4, 3, 2]);
+        layout.amount_little_endian = false;
+        layout.account_order = vec![
+            FlashLoanAccountRole::Fixed { address: market, writable: false },
+            FlashLoanAccountRole::Receiver,
+            FlashLoanAccountRole::LiquiditySupply,
+            FlashLoanAccountRole::TokenMint,
+            FlashLoanAccountRole::Borrower,
+            FlashLoanAccountRole::TokenProgram,
+        ];
+        let mut config = FlashLoanConfig::new_custom_with_layout(u64::MAX, 1.0, program_id, layout);
+        config.add_liquidity_account(token_mint, supply);
+        let manager = FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(u64::MAX), config);
+        
+        let repay = manager.create_repayment_instruction(10_000, &token_mint, &borrower, &program_id, 0).unwrap();
+        
+        // Paid from the borrower's token account back into the supply, in the layout's order
+        assert_eq!(repay.program_id, program_id);
+        assert_eq!(repay.accounts, vec![
+            AccountMeta::new_readonly(market, false),
+            AccountMeta::new(get_associated_token_address(&borrower, &token_mint), false),
+            AccountMeta::new(supply, false),
+            AccountMeta::new_readonly(token_mint, false),
+            AccountMeta::new(borrower, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ]);
+        
+        // Repay discriminator, then principal plus the 1% fee big-endian
+        assert_eq!(&repay.data[..8], &[9, 8, 7, 6, 5, 4, 3, 2]);
+        assert_eq!(&repay.data[8..], &10_100u64.to_be_bytes());
+        
+        // A supply the layout needs but nobody registered fails instead of repaying the wrong account
+        let mut config = FlashLoanConfig::new_custom_with_layout(u64::MAX, 1.0, program_id, CustomFlashLoanLayout::new(0));
+        config.custom_layout.as_mut().unwrap().account_order.push(FlashLoanAccountRole::LiquiditySupply);
+        let manager = FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(u64::MAX), config);
+        assert!(manager.create_repayment_instruction(10_000, &token_mint, &borrower, &program_id, 0).is_err());
+    }
+    
+    #[test]
+    fn custom_builder_takes_precedence_over_the_layout() {
+        let program_id = Pubkey::new_unique();
+        let token_mint = Pubkey::new_unique();
+        let borrower = Pubkey::new_unique();
+        
+        let builder: CustomFlashLoanBuilder = Box::new(move |amount, mint, borrower, receiver, _callback| {
+            let mut data = vec![0xAA];
+            data.extend_from_slice(&amount.to_le_bytes());
+            Ok(Instruction {
+                program_id,
+                accounts: vec![AccountMeta::new(*receiver, false), AccountMeta::new(*borrower, true), AccountMeta::new_readonly(*mint, false)],
+                data,
+            })
+        });
+        let mut config = FlashLoanConfig::new_custom_with_builder(u64::MAX, 0.05, program_id, builder);
+        config.add_liquidity_account(token_mint, Pubkey::new_unique());
+        let manager = FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(u64::MAX), config);
+        
+        let receiver = Pubkey::new_unique();
+        let instruction = manager.create_flash_loan_instruction(500, &token_mint, &borrower, &receiver, &Pubkey::new_unique()).unwrap();
+        
+        assert_eq!(instruction.data, [&[0xAA][..], &500u64.to_le_bytes()].concat());
+        assert_eq!(instruction.accounts[0], AccountMeta::new(receiver, false));
+        assert_eq!(instruction.accounts[1], AccountMeta::new(borrower, true));
+        assert_eq!(instruction.accounts[2], AccountMeta::new_readonly(token_mint, false));
+    }
+    
+    #[test]
+    fn loan_larger_than_reserve_is_rejected() {
+        let token_mint = Pubkey::new_unique();
+        let borrower = Pubkey::new_unique();
+        
+        let mut config = FlashLoanConfig::new_solend(u64::MAX);
+        config.add_liquidity_account(token_mint, Pubkey::new_unique());
+        let manager = FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(1_000), config);
+        
+        let result = manager.create_flash_loan_instruction(2_000, &token_mint, &borrower, &borrower, &borrower);
+        match result {
+            Err(FlashLoanError::ParameterError(msg)) => assert!(msg.contains("available reserve liquidity 1000")),
+            other => panic!("expected an over-size rejection, got {:?}", other),
+        }
+        
+        let mut config = FlashLoanConfig::new_solend(u64::MAX);
+        config.add_liquidity_account(token_mint, Pubkey::new_unique());
+        let manager = FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(1_000), config);
+        assert!(manager.create_flash_loan_instruction(500, &token_mint, &borrower, &borrower, &borrower).is_ok());
+    }
+    
+    #[test]
+    fn loan_without_liquidity_account_fails_closed() {
+        let manager = FlashLoanManager::with_rpc_client(
+            rpc_with_reserve_balance(u64::MAX),
+            FlashLoanConfig::new_solend(u64::MAX),
+        );
+        let mint = Pubkey::new_unique();
+        
+        let result = manager.create_flash_loan_instruction(1, &mint, &mint, &mint, &mint);
+        assert!(matches!(result, Err(FlashLoanError::ParameterError(_))));
+    }
+    
+    #[test]
+    fn cheapest_provider_must_cover_the_loan() {
+        let token_mint = Pubkey::new_unique();
+        
+        // Cheaper provider with a thin reserve
+        let mut cheap = FlashLoanConfig::new_flash_protocol(u64::MAX);
+        cheap.add_liquidity_account(token_mint, Pubkey::new_unique());
+        // Pricier provider with a deep reserve
+        let mut deep = FlashLoanConfig::new_solend(u64::MAX);
+        deep.add_liquidity_account(token_mint, Pubkey::new_unique());
+        
+        let managers = vec![
+            FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(100), cheap),
+            FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(1_000_000), deep),
+        ];
+        
+        let selected = select_cheapest_provider(&managers, &token_mint, 10_000).expect("a provider");
+        assert_eq!(selected.config.provider, FlashLoanProvider::Solend);
+    }
+    
+    #[test]
+    fn kamino_pdas_feed_borrow_and_repay() {
+        let program_id = Pubkey::from_str(KAMINO_LENDING_PROGRAM_ID).unwrap();
+        let token_mint = Pubkey::new_unique();
+        let borrower = Pubkey::new_unique();
+        let reserve = KaminoReserveConfig {
+            lending_market: Pubkey::new_unique(),
+            reserve: Pubkey::new_unique(),
+        };
+        
+        let pdas = reserve.derive_pdas(&program_id);
+        assert_eq!(pdas, reserve.derive_pdas(&program_id));
+        assert_eq!(
+            pdas.lending_market_authority,
+            Pubkey::find_program_address(&[b"lma", reserve.lending_market.as_ref()], &program_id).0,
+        );
+        assert_eq!(
+            pdas.liquidity_supply,
+            Pubkey::find_program_address(&[b"reserve_liq_supply", reserve.reserve.as_ref()], &program_id).0,
+        );
+        assert_eq!(
+            pdas.fee_receiver,
+            Pubkey::find_program_address(&[b"fee_receiver", reserve.reserve.as_ref()], &program_id).0,
+        );
+        assert_ne!(pdas.liquidity_supply, pdas.fee_receiver);
+        
+        // Registering the reserve points the live liquidity check at its supply vault
+        let mut config = FlashLoanConfig::new_kamino(u64::MAX);
+        config.add_kamino_reserve(token_mint, reserve);
+        assert_eq!(config.liquidity_accounts.get(&token_mint), Some(&pdas.liquidity_supply));
+        
+        let manager = FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(u64::MAX), config);
+        let borrow = manager.create_flash_loan_instruction(1_000, &token_mint, &borrower, &borrower, &program_id).unwrap();
+        assert_eq!(borrow.program_id, program_id);
+        assert_eq!(borrow.accounts[1].pubkey, pdas.lending_market_authority);
+        assert_eq!(borrow.accounts[5].pubkey, pdas.liquidity_supply);
+        assert_eq!(borrow.accounts[7].pubkey, pdas.fee_receiver);
+        assert_eq!(&borrow.data[..8], &KAMINO_FLASH_BORROW_DISCRIMINATOR);
+        
+        // The repay points back at wherever the assembler put the borrow
+        let repay = manager.create_repayment_instruction(1_000, &token_mint, &borrower, &program_id, 2).unwrap();
+        assert_eq!(&repay.data[..8], &KAMINO_FLASH_REPAY_DISCRIMINATOR);
+        assert_eq!(&repay.data[8..16], &1_000u64.to_le_bytes());
+        assert_eq!(repay.data[16], 2);
+        assert_eq!(repay.accounts[5].pubkey, pdas.liquidity_supply);
+    }
+    
+    #[test]
+    fn cheapest_provider_by_fee() {
+        let token_mint = Pubkey::new_unique();
+        let reserve = KaminoReserveConfig {
+            lending_market: Pubkey::new_unique(),
+            reserve: Pubkey::new_unique(),
+        };
+        
+        let mut solend = FlashLoanConfig::new_solend(u64::MAX);
+        solend.add_liquidity_account(token_mint, Pubkey::new_unique());
+        let mut kamino = FlashLoanConfig::new_kamino(u64::MAX);
+        kamino.add_kamino_reserve(token_mint, reserve);
+        
+        let managers = vec![
+            FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(u64::MAX), solend),
+            FlashLoanManager::with_rpc_client(rpc_with_reserve_balance(u64::MAX), kamino),
+        ];
+        
+        // 0.3% against 0.001% of 1,000 tokens
+        assert_eq!(managers[0].calculate_fee(1_000_000_000), 3_000_000);
+        assert_eq!(managers[1].calculate_fee(1_000_000_000), 10_000);
+        
+        let selected = select_cheapest_provider(&managers, &token_mint, 1_000_000_000).expect("a provider");
+        assert_eq!(selected.config.provider, FlashLoanProvider::Kamino);
+        assert_eq!(selected.total_repayment(1_000_000_000), 1_000_010_000);
+    }
+    
+    #[test]
+    fn configured_providers_register_their_liquidity() {
+        let token_mint = Pubkey::new_unique();
+        let supply = Pubkey::new_unique();
+        let entry: FlashLoanProviderConfig = serde_json::from_value(json!({
+            "provider": "solend",
+            "liquidity_accounts": [{ "token_mint": token_mint.to_string(), "supply_account": supply.to_string() }],
+        })).unwrap();
+        
+        let config = entry.to_flash_loan_config(1_000).unwrap();
+        assert_eq!(config.provider, FlashLoanProvider::Solend);
+        assert_eq!(config.max_loan_amount, 1_000);
+        assert_eq!(config.liquidity_accounts.get(&token_mint), Some(&supply));
+        
+        // Kamino supply vaults are derived from the reserve
+        let reserve = KaminoReserveConfig { lending_market: Pubkey::new_unique(), reserve: Pubkey::new_unique() };
+        let entry: FlashLoanProviderConfig = serde_json::from_value(json!({
+            "provider": "kamino",
+            "kamino_reserves": [{
+                "token_mint": token_mint.to_string(),
+                "lending_market": reserve.lending_market.to_string(),
+                "reserve": reserve.reserve.to_string(),
+            }],
+        })).unwrap();
+        let config = entry.to_flash_loan_config(1_000).unwrap();
+        let program_id = Pubkey::from_str(KAMINO_LENDING_PROGRAM_ID).unwrap();
+        assert_eq!(config.liquidity_accounts.get(&token_mint), Some(&reserve.derive_pdas(&program_id).liquidity_supply));
+        
+        // Entries that could never lend are refused up front
+        let empty = FlashLoanProviderConfig { provider: FlashLoanProvider::Solend, liquidity_accounts: Vec::new(), kamino_reserves: Vec::new() };
+        assert!(empty.to_flash_loan_config(1_000).is_err());
+        let custom = FlashLoanProviderConfig { provider: FlashLoanProvider::Custom, ..entry };
+        assert!(custom.to_flash_loan_config(1_000).is_err());
+    }
+    
+    #[test]
+    fn thread_safe_selection_skips_providers_that_cannot_lend() {
+        let token_mint = Pubkey::new_unique();
+        let provider = |mut config: FlashLoanConfig, reserve: u64| {
+            config.add_liquidity_account(token_mint, Pubkey::new_unique());
+            ThreadSafeFlashLoanManager::with_rpc_client(rpc_with_reserve_balance(reserve), config)
+        };
+        
+        // The cheapest provider's reserve is too shallow for the loan
+        let managers = vec![
+            provider(FlashLoanConfig::new_solend(u64::MAX), 5_000),
+            provider(FlashLoanConfig::new_flash_protocol(u64::MAX), 500),
+            provider(FlashLoanConfig::new_flash_loan_mastery(u64::MAX), 5_000),
+        ];
+        
+        let selected = ThreadSafeFlashLoanManager::select_cheapest(&managers, &token_mint, 1_000).expect("a provider");
+        assert_eq!(selected.calculate_fee(1_000_000).unwrap(), 2_500);
+    }
+    
+    // A current-thread runtime is where the old block_in_place callback panicked
+    #[tokio::test(flavor = "current_thread")]
+    async fn callback_buys_sells_then_repays() {
+        let dex_program = Pubkey::new_unique();
+        let base = Pubkey::new_unique();
+        let quote = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        
+        let mut dex_manager = DexManager::new("http://localhost:8899");
+        dex_manager.add_connector(DexConfig::new_custom(
+            "",
+            dex_program,
+            "mock",
+            Box::new(MockDex { program_id: dex_program }),
+        ));
+        let flash_loan_manager = ThreadSafeFlashLoanManager::with_rpc_client(
+            RpcClient::new_mock("succeeds".to_string()),
+            FlashLoanConfig::new_solend(u64::MAX),
+        );
+        let solend_program_id = flash_loan_manager.get_provider_program_id().unwrap();
+        
+        let handler = ArbitrageCallbackHandler::new(
+            dex_manager.into(),
+            flash_loan_manager,
+            ArbitrageRoute {
+                buy_price: mock_price(base, quote, 1.0),
+                sell_price: mock_price(base, quote, 1.1),
+                wallet,
+                slippage: 0.5,
+                borrow_instruction_index: 0,
+            },
+        );
+        
+        let program = FlashLoanCallbackProgram::new(Pubkey::new_unique(), Box::new(handler));
+        let instructions = program.process_callback(1_000_000, &quote, 3_000, &[]).await.unwrap();
+        
+        assert_eq!(instructions.len(), 3);
+        
+        // Buy spends the borrowed quote token for the base token
+        assert_eq!(instructions[0].program_id, dex_program);
+        assert_eq!(instructions[0].accounts[0].pubkey, quote);
+        assert_eq!(instructions[0].accounts[1].pubkey, base);
+        assert_eq!(&instructions[0].data[..8], &1_000_000u64.to_le_bytes());
+        
+        // Sell returns it to the quote token, at least covering the repayment
+        assert_eq!(instructions[1].program_id, dex_program);
+        assert_eq!(instructions[1].accounts[0].pubkey, base);
+        assert_eq!(instructions[1].accounts[1].pubkey, quote);
+        assert_eq!(&instructions[1].data[8..], &1_003_000u64.to_le_bytes());
+        
+        // Repay goes back to the lender last
+        assert_eq!(instructions[2].program_id, solend_program_id);
+        assert_eq!(instructions[2].data[0], 13);
+    }
+    
+    #[tokio::test(flavor = "current_thread")]
+    async fn callback_rejects_unprofitable_route() {
+        let base = Pubkey::new_unique();
+        let quote = Pubkey::new_unique();
+        
+        let handler = ArbitrageCallbackHandler::new(
+            DexManager::new("http://localhost:8899").into(),
+            ThreadSafeFlashLoanManager::with_rpc_client(
+                RpcClient::new_mock("succeeds".to_string()),
+                FlashLoanConfig::new_solend(u64::MAX),
+            ),
+            ArbitrageRoute {
+                buy_price: mock_price(base, quote, 1.0),
+                sell_price: mock_price(base, quote, 1.0),
+                wallet: Pubkey::new_unique(),
+                slippage: 0.5,
+                borrow_instruction_index: 0,
+            },
+        );
+        
+        let result = handler.handle_flash_loan_callback(1_000_000, &quote, 3_000, &[]).await;
+        assert!(matches!(result, Err(FlashLoanError::ParameterError(_))));
     }
 }
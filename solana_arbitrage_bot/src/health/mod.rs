@@ -0,0 +1,163 @@
+// Health Check Module for Solana Flash Loan Arbitrage Bot
+// Per-component diagnostics for RPC, DEX APIs and wallets
+
+use std::fmt;
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use log::{info, warn};
+
+/// SOL the operational wallet should hold for fees (0.05 SOL, about 10,000 signatures)
+pub const MIN_OPERATIONAL_BALANCE_LAMPORTS: u64 = 50_000_000;
+
+/// When to pause trading for lack of fee funds and when to resume
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OperationalBalanceConfig {
+    /// Pause trading when the operational wallet falls below this many lamports
+    pub min_operational_balance: u64,
+    /// Resume once it's topped up to at least this many lamports
+    pub resume_operational_balance: u64,
+}
+
+impl Default for OperationalBalanceConfig {
+    fn default() -> Self {
+        Self {
+            min_operational_balance: MIN_OPERATIONAL_BALANCE_LAMPORTS,
+            // Resume with room to spare so a balance hovering at the minimum doesn't flap
+            resume_operational_balance: MIN_OPERATIONAL_BALANCE_LAMPORTS * 2,
+        }
+    }
+}
+
+impl OperationalBalanceConfig {
+    /// Check the resume threshold isn't below the pause threshold
+    pub fn validate(&self) -> Result<(), String> {
+        if self.resume_operational_balance < self.min_operational_balance {
+            return Err(format!(
+                "resume_operational_balance ({}) is below min_operational_balance ({})",
+                self.resume_operational_balance, self.min_operational_balance
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Change in whether the operational wallet can pay fees
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceTransition {
+    /// Fell below the minimum; trading should pause
+    Low,
+    /// Topped up past the resume threshold; trading can continue
+    Restored,
+}
+
+/// Tracks the operational wallet balance against the pause and resume thresholds
+///
+/// Takes balances rather than reading them, so any balance source can drive it.
+pub struct OperationalBalanceMonitor {
+    /// Thresholds
+    config: OperationalBalanceConfig,
+    /// Whether the balance is currently too low to trade
+    low: bool,
+}
+
+impl OperationalBalanceMonitor {
+    /// Create a new monitor, starting out funded
+    pub fn new(config: OperationalBalanceConfig) -> Self {
+        Self {
+            config,
+            low: false,
+        }
+    }
+    
+    /// Whether trading is paused for lack of fee funds
+    pub fn is_low(&self) -> bool {
+        self.low
+    }
+    
+    /// Record the latest balance, returning a transition if it crossed a threshold
+    pub fn record_balance(&mut self, balance: u64) -> Option<BalanceTransition> {
+        if !self.low && balance < self.config.min_operational_balance {
+            warn!("Operational balance {} below minimum {}, pausing trading", balance, self.config.min_operational_balance);
+            self.low = true;
+            Some(BalanceTransition::Low)
+        } else if self.low && balance >= self.config.resume_operational_balance {
+            info!("Operational balance topped up to {}, resuming trading", balance);
+            self.low = false;
+            Some(BalanceTransition::Restored)
+        } else {
+            None
+        }
+    }
+}
+
+/// Result of checking one component
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentHealth {
+    /// Component checked, e.g. "rpc", "dex:Jupiter" or "wallet:Trading"
+    pub component: String,
+    /// Whether the check passed
+    pub healthy: bool,
+    /// How long the check took in milliseconds
+    pub latency_ms: u64,
+    /// What was found, or why the check failed
+    pub detail: String,
+}
+
+impl ComponentHealth {
+    /// Run a check, timing it and recording its outcome
+    pub fn check<F>(component: impl Into<String>, check: F) -> Self
+    where
+        F: FnOnce() -> Result<String, String>,
+    {
+        let started = Instant::now();
+        let result = check();
+        let latency_ms = started.elapsed().as_millis() as u64;
+        
+        let (healthy, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(e) => (false, e),
+        };
+        
+        Self {
+            component: component.into(),
+            healthy,
+            latency_ms,
+            detail,
+        }
+    }
+}
+
+/// Outcome of a full health check
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HealthReport {
+    /// Every component checked, in check order
+    pub components: Vec<ComponentHealth>,
+}
+
+impl HealthReport {
+    /// Whether every component passed
+    pub fn is_healthy(&self) -> bool {
+        self.components.iter().all(|component| component.healthy)
+    }
+    
+    /// Components that failed
+    pub fn failures(&self) -> impl Iterator<Item = &ComponentHealth> {
+        self.components.iter().filter(|component| !component.healthy)
+    }
+}
+
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for component in &self.components {
+            writeln!(
+                f,
+                "{:<4} {:<24} {:>6}ms  {}",
+                if component.healthy { "ok" } else { "FAIL" },
+                component.component,
+                component.latency_ms,
+                component.detail,
+            )?;
+        }
+        Ok(())
+    }
+}
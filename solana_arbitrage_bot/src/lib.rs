@@ -1,6 +1,22 @@
 // Main module for Solana Flash Loan Arbitrage Bot
 // Coordinates all components and provides the core functionality
 
+pub mod dex;
+pub mod flash_loan;
+pub mod health;
+pub mod notify;
+pub mod oracle;
+pub mod profit_management;
+pub mod rpc;
+pub mod wallet_integration;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "http-api")]
+pub mod api;
+
+#[cfg(test)]
+mod tests;
+
 use solana_sdk::{
     pubkey::Pubkey,
     signature::Keypair,
@@ -8,20 +24,163 @@ use solana_sdk::{
     commitment_config::CommitmentConfig,
 };
 use solana_client::rpc_client::RpcClient;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::thread::JoinHandle;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use std::thread;
 use tokio::runtime::Runtime;
+use tokio::sync::broadcast;
+use serde::{Deserialize, Serialize};
 use log::{info, warn, error, debug};
 
+use crate::health::{BalanceTransition, ComponentHealth, HealthReport, OperationalBalanceConfig, OperationalBalanceMonitor};
+use crate::notify::{NotificationDispatcher, NotificationEndpoint};
 use crate::profit_management::{ThreadSafeProfitManager, ProfitDistributionConfig};
-use crate::wallet_integration::{ThreadSafeWalletManager, WalletType, WalletError};
+use crate::rpc::{FailoverRpcClient, RetryPolicy};
+use crate::rpc::blockhash::{BlockhashCache, DEFAULT_BLOCKHASH_REFRESH_INTERVAL};
+use crate::dex::DexError;
+use crate::flash_loan::{FlashLoanError, FlashLoanProviderConfig, ThreadSafeFlashLoanManager};
+use crate::wallet_integration::{ThreadSafeWalletManager, WalletType, WalletError, KdfParams, PriorityFeeConfig};
+#[cfg(feature = "jito")]
+use crate::wallet_integration::jito::JitoConfig;
+
+/// Shortest allowed monitoring interval, to stay within RPC rate limits
+const MIN_UPDATE_INTERVAL_MS: u64 = 100;
+
+/// Slippage tolerance for emergency unwinds, in percent; getting out matters more than the price
+const UNWIND_SLIPPAGE: f64 = 5.0;
+
+/// Allowed range for the gas price multiplier
+const MIN_GAS_PRICE_MULTIPLIER: f64 = 1.0;
+const MAX_GAS_PRICE_MULTIPLIER: f64 = 10.0;
+
+/// Serialize pubkeys as base58 strings in config files
+pub(crate) mod serde_pubkey {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+    
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&pubkey.to_string())
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Pubkey::from_str(&value)
+            .map_err(|e| serde::de::Error::custom(format!("Invalid pubkey {}: {}", value, e)))
+    }
+}
+
+/// Error type for bot operations
+#[derive(Debug)]
+pub enum BotError {
+    /// A lock was poisoned by a thread that panicked while holding it
+    LockPoisoned,
+    /// Error from the wallet manager
+    Wallet(WalletError),
+    /// Error from a DEX
+    Dex(DexError),
+    /// Error from a flash loan provider
+    FlashLoan(FlashLoanError),
+    /// Error from the profit manager
+    Profit(String),
+    /// Invalid configuration
+    Config(String),
+    /// The bot isn't running
+    NotRunning,
+    /// The bot is already running
+    AlreadyRunning,
+    /// The bot isn't paused
+    NotPaused,
+    /// General error
+    General(String),
+}
+
+impl std::fmt::Display for BotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotError::LockPoisoned => write!(f, "Lock poisoned"),
+            BotError::Wallet(e) => write!(f, "Wallet error: {}", e),
+            BotError::Dex(e) => write!(f, "DEX error: {}", e),
+            BotError::FlashLoan(e) => write!(f, "Flash loan error: {}", e),
+            BotError::Profit(msg) => write!(f, "Profit management error: {}", msg),
+            BotError::Config(msg) => write!(f, "Configuration error: {}", msg),
+            BotError::NotRunning => write!(f, "Bot is not running"),
+            BotError::AlreadyRunning => write!(f, "Bot is already running"),
+            BotError::NotPaused => write!(f, "Bot is not paused"),
+            BotError::General(msg) => write!(f, "Error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BotError::Wallet(e) => Some(e),
+            BotError::Dex(e) => Some(e),
+            BotError::FlashLoan(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<WalletError> for BotError {
+    fn from(e: WalletError) -> Self {
+        BotError::Wallet(e)
+    }
+}
+
+impl From<DexError> for BotError {
+    fn from(e: DexError) -> Self {
+        BotError::Dex(e)
+    }
+}
+
+impl From<FlashLoanError> for BotError {
+    fn from(e: FlashLoanError) -> Self {
+        BotError::FlashLoan(e)
+    }
+}
+
+// Components that still report failures as plain messages
+impl From<String> for BotError {
+    fn from(msg: String) -> Self {
+        BotError::General(msg)
+    }
+}
+
+impl<T> From<PoisonError<T>> for BotError {
+    fn from(_: PoisonError<T>) -> Self {
+        BotError::LockPoisoned
+    }
+}
+
+/// Lock a ThreadSafe wrapper's mutex, recovering it if a thread panicked while holding it
+///
+/// The wrapped managers stay usable after a call that panicked partway, while refusing
+/// every later call would brick the bot for good, so the guard is taken back and the
+/// poison cleared (so each recovery is logged once).
+pub(crate) fn lock_or_recover<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        warn!("Recovered {} lock poisoned by a panicked thread", std::any::type_name::<T>());
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
+}
 
 /// Bot configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BotConfig {
     /// RPC URL for Solana
     pub rpc_url: String,
+    /// RPC URLs to fail over to, in order, when `rpc_url` errors or rate limits
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    /// Retries and backoff for transient RPC and HTTP failures
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
     /// Path for wallet storage
     pub wallet_storage_path: String,
     /// Minimum profit threshold in lamports
@@ -30,6 +189,9 @@ pub struct BotConfig {
     pub max_position_size: u64,
     /// Maximum flash loan size in lamports
     pub max_flash_loan_size: u64,
+    /// Flash loan providers to borrow from; each trade uses the cheapest one able to lend
+    #[serde(default)]
+    pub flash_loan_providers: Vec<FlashLoanProviderConfig>,
     /// Token pairs to monitor
     pub token_pairs: Vec<TokenPair>,
     /// DEXs to monitor
@@ -42,19 +204,143 @@ pub struct BotConfig {
     pub max_concurrent_operations: usize,
     /// Transaction timeout in seconds
     pub transaction_timeout_sec: u64,
-    /// Gas price multiplier (1.0 = normal)
+    /// Gas price multiplier (1.0 = normal), applied to the compute unit price
     pub gas_price_multiplier: f64,
+    /// Compute unit price and limit added to every transaction
+    #[serde(default)]
+    pub priority_fee: PriorityFeeConfig,
+    /// Submit transactions as Jito bundles instead of through the public RPC (requires the `jito` feature)
+    #[cfg(feature = "jito")]
+    #[serde(default)]
+    pub jito: Option<JitoConfig>,
+    /// Number of trading wallets to keep in the rotation pool
+    pub trading_wallet_pool_size: usize,
+    /// Pay transaction fees from the operational wallet instead of the trading wallet
+    #[serde(default)]
+    pub operational_fee_payer: bool,
+    /// Operational wallet balance below which trading pauses, and above which it resumes
+    #[serde(default)]
+    pub operational_balance: OperationalBalanceConfig,
+    /// Argon2id cost for the wallet encryption key (lower on weak hardware)
+    #[serde(default)]
+    pub wallet_kdf_params: KdfParams,
+    /// Webhooks to push trade, error, circuit breaker and balance notifications to
+    #[serde(default)]
+    pub notifications: Vec<NotificationEndpoint>,
+    /// Address to serve Prometheus metrics on, e.g. "0.0.0.0:9100" (requires the `metrics` feature)
+    #[serde(default)]
+    pub metrics_bind_address: Option<String>,
+    /// Authenticated REST control API (requires the `http-api` feature)
+    #[cfg(feature = "http-api")]
+    #[serde(default)]
+    pub api: Option<api::ApiConfig>,
 }
 
 impl BotConfig {
+    /// Load configuration from a TOML or JSON file, chosen by extension
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config {}: {}", path, e))?;
+        
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+        
+        // Token pair mints are checked while parsing
+        let config: Self = match extension.as_deref() {
+            Some("toml") => toml::from_str(&contents)
+                .map_err(|e| format!("Failed to parse config {}: {}", path, e))?,
+            Some("json") => serde_json::from_str(&contents)
+                .map_err(|e| format!("Failed to parse config {}: {}", path, e))?,
+            _ => return Err(format!("Unsupported config format for {} (expected .toml or .json)", path)),
+        };
+        
+        config.validate()
+            .map_err(|e| format!("Invalid config {}: {}", path, e))?;
+        
+        Ok(config)
+    }
+    
+    /// Flash loan managers for the configured providers, reading reserves through `rpc`
+    pub fn flash_loan_managers(&self, rpc: &FailoverRpcClient) -> Result<Vec<ThreadSafeFlashLoanManager>, String> {
+        self.flash_loan_providers.iter()
+            .map(|provider| {
+                let config = provider.to_flash_loan_config(self.max_flash_loan_size)?;
+                Ok(ThreadSafeFlashLoanManager::with_rpc_client(rpc.rpc_client(CommitmentConfig::confirmed()), config))
+            })
+            .collect()
+    }
+    
+    /// Check the configuration for missing or contradictory limits
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_profit_threshold == 0 {
+            return Err("min_profit_threshold must be greater than 0".to_string());
+        }
+        
+        if self.max_position_size > self.max_flash_loan_size {
+            return Err(format!(
+                "max_position_size ({}) exceeds max_flash_loan_size ({})",
+                self.max_position_size, self.max_flash_loan_size
+            ));
+        }
+        
+        if self.update_interval_ms < MIN_UPDATE_INTERVAL_MS {
+            return Err(format!(
+                "update_interval_ms ({}) is below the minimum of {}",
+                self.update_interval_ms, MIN_UPDATE_INTERVAL_MS
+            ));
+        }
+        
+        if !(MIN_GAS_PRICE_MULTIPLIER..=MAX_GAS_PRICE_MULTIPLIER).contains(&self.gas_price_multiplier) {
+            return Err(format!(
+                "gas_price_multiplier ({}) must be between {} and {}",
+                self.gas_price_multiplier, MIN_GAS_PRICE_MULTIPLIER, MAX_GAS_PRICE_MULTIPLIER
+            ));
+        }
+        
+        if self.max_concurrent_operations == 0 {
+            return Err("max_concurrent_operations must be greater than 0".to_string());
+        }
+        
+        if self.retry_policy.max_attempts == 0 {
+            return Err("retry_policy.max_attempts must be at least 1".to_string());
+        }
+        
+        if self.trading_wallet_pool_size == 0 {
+            return Err("trading_wallet_pool_size must be greater than 0".to_string());
+        }
+        
+        self.operational_balance.validate()?;
+        
+        for provider in &self.flash_loan_providers {
+            provider.to_flash_loan_config(self.max_flash_loan_size)?;
+        }
+        
+        for endpoint in &self.notifications {
+            endpoint.validate()?;
+        }
+        
+        #[cfg(feature = "http-api")]
+        if let Some(api) = &self.api {
+            api.validate()?;
+        }
+        
+        self.profit_distribution.validate()
+            .map_err(|e| format!("Invalid profit distribution configuration: {}", e))
+    }
+    
     /// Create default configuration
     pub fn default(owner_wallet: Pubkey) -> Self {
         Self {
             rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
+            fallback_rpc_urls: Vec::new(),
+            retry_policy: RetryPolicy::default(),
             wallet_storage_path: "./wallets".to_string(),
             min_profit_threshold: 10_000_000, // 0.01 SOL in lamports
             max_position_size: 1_000_000_000, // 1 SOL in lamports
             max_flash_loan_size: 10_000_000_000, // 10 SOL in lamports
+            flash_loan_providers: Vec::new(),
             token_pairs: vec![
                 TokenPair {
                     base_token: "So11111111111111111111111111111111111111112".parse().unwrap(), // SOL
@@ -66,16 +352,22 @@ impl BotConfig {
                     name: "Jupiter".to_string(),
                     api_url: "https://quote-api.jup.ag/v6".to_string(),
                     enabled: true,
+                    pinned_pools: Vec::new(),
+                    fee_bps: None,
                 },
                 DexConfig {
                     name: "Raydium".to_string(),
                     api_url: "https://api.raydium.io".to_string(),
                     enabled: true,
+                    pinned_pools: Vec::new(),
+                    fee_bps: None,
                 },
                 DexConfig {
                     name: "Orca".to_string(),
                     api_url: "https://api.orca.so".to_string(),
                     enabled: true,
+                    pinned_pools: Vec::new(),
+                    fee_bps: None,
                 },
             ],
             update_interval_ms: 1000,
@@ -83,19 +375,45 @@ impl BotConfig {
             max_concurrent_operations: 5,
             transaction_timeout_sec: 30,
             gas_price_multiplier: 1.5,
+            priority_fee: PriorityFeeConfig::default(),
+            #[cfg(feature = "jito")]
+            jito: None,
+            trading_wallet_pool_size: 1,
+            operational_fee_payer: false,
+            operational_balance: OperationalBalanceConfig::default(),
+            wallet_kdf_params: KdfParams::default(),
+            notifications: Vec::new(),
+            metrics_bind_address: None,
+            #[cfg(feature = "http-api")]
+            api: None,
         }
     }
 }
 
 /// Token pair for monitoring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TokenPair {
     /// Base token (e.g., SOL)
+    #[serde(with = "serde_pubkey")]
     pub base_token: Pubkey,
     /// Quote token (e.g., USDC)
+    #[serde(with = "serde_pubkey")]
     pub quote_token: Pubkey,
 }
 
+/// Pool to use for a token pair instead of discovering one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedPool {
+    /// Token pair the pool is used for
+    #[serde(flatten)]
+    pub pair: TokenPair,
+    /// Pool account
+    #[serde(with = "serde_pubkey")]
+    pub pool: Pubkey,
+}
+
 /// DEX configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DexConfig {
     /// DEX name
     pub name: String,
@@ -103,10 +421,16 @@ pub struct DexConfig {
     pub api_url: String,
     /// Whether this DEX is enabled
     pub enabled: bool,
+    /// Pools to use instead of the deepest one found (Raydium and Orca)
+    #[serde(default)]
+    pub pinned_pools: Vec<PinnedPool>,
+    /// Swap fee in basis points for pools that don't report their own
+    #[serde(default)]
+    pub fee_bps: Option<u64>,
 }
 
 /// Bot status
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum BotStatus {
     /// Bot is stopped
     Stopped,
@@ -118,7 +442,133 @@ pub enum BotStatus {
     Error,
 }
 
+impl BotStatus {
+    /// Encode for sharing through an atomic
+    fn as_u8(self) -> u8 {
+        match self {
+            BotStatus::Stopped => 0,
+            BotStatus::Running => 1,
+            BotStatus::Paused => 2,
+            BotStatus::Error => 3,
+        }
+    }
+    
+    /// Decode a status shared through an atomic
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => BotStatus::Running,
+            2 => BotStatus::Paused,
+            3 => BotStatus::Error,
+            _ => BotStatus::Stopped,
+        }
+    }
+}
+
+impl DexConfig {
+    /// Convert to a DEX connector configuration, matching on the DEX name
+    ///
+    /// Custom DEXs need an adapter, so they can't be built from a name alone.
+    fn to_connector_config(&self) -> Option<dex::DexConfig> {
+        let mut config = match self.name.to_lowercase().as_str() {
+            "jupiter" => dex::DexConfig::new_jupiter(),
+            "raydium" => dex::DexConfig::new_raydium(),
+            "orca" => dex::DexConfig::new_orca(),
+            "meteora" => dex::DexConfig::new_meteora(),
+            "phoenix" => dex::DexConfig::new_phoenix(),
+            _ => return None,
+        };
+        config.api_url = self.api_url.clone();
+        config.enabled = self.enabled;
+        config.pinned_pools = self.pinned_pools.iter()
+            .map(|pinned| ((pinned.pair.base_token, pinned.pair.quote_token), pinned.pool))
+            .collect();
+        config.fee_bps = self.fee_bps;
+        Some(config)
+    }
+}
+
+/// Capacity of the bot event channel before slow subscribers start lagging
+pub(crate) const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Bot activity event for embedders
+#[derive(Debug, Clone)]
+pub enum BotEvent {
+    /// An arbitrage opportunity was detected
+    OpportunityDetected {
+        /// Base token
+        base_token: Pubkey,
+        /// Quote token
+        quote_token: Pubkey,
+        /// Expected profit percentage
+        profit_percentage: f64,
+    },
+    /// A trade landed
+    TradeExecuted {
+        /// Transaction signature (empty for paper fills)
+        signature: String,
+        /// Profit in quote token units
+        profit: u64,
+        /// Time from execution start to settlement in milliseconds
+        execution_time_ms: u64,
+    },
+    /// A trade failed
+    TradeFailed {
+        /// Failure reason
+        reason: String,
+        /// Time from execution start to failure in milliseconds
+        execution_time_ms: u64,
+    },
+    /// The bot changed status
+    StatusChanged {
+        /// Previous status
+        from: BotStatus,
+        /// New status
+        to: BotStatus,
+    },
+    /// The operational wallet can no longer cover fees, so trading paused
+    LowOperationalBalance {
+        /// Operational wallet
+        wallet: Pubkey,
+        /// Balance in lamports
+        balance: u64,
+        /// Balance trading resumes at, in lamports
+        resume_at: u64,
+    },
+    /// The operational wallet was topped up, so trading resumed
+    OperationalBalanceRestored {
+        /// Operational wallet
+        wallet: Pubkey,
+        /// Balance in lamports
+        balance: u64,
+    },
+    /// Profits were distributed
+    ProfitDistributed {
+        /// Amount reinvested
+        reinvested_amount: u64,
+        /// Amount withdrawn to owner wallet
+        withdrawn_amount: u64,
+        /// Amount kept as reserve
+        reserved_amount: u64,
+    },
+}
+
+/// Sale of one token during an emergency unwind
+#[derive(Debug, Clone)]
+pub struct UnwindResult {
+    /// Trading wallet the token was sold from
+    pub wallet: Pubkey,
+    /// Token sold
+    pub token_mint: Pubkey,
+    /// Amount sold, in the token's base units
+    pub amount: u64,
+    /// DEX the sale was sent to (if one quoted the token)
+    pub dex: Option<dex::DexType>,
+    /// Transaction signature, or why the sale failed
+    pub outcome: Result<String, String>,
+}
+
 /// Bot statistics
+#[derive(Debug, Clone)]
 pub struct BotStatistics {
     /// Current bot status
     pub status: BotStatus,
@@ -140,6 +590,78 @@ pub struct BotStatistics {
     pub avg_profit_per_trade: u64,
     /// Average execution time in milliseconds
     pub avg_execution_time_ms: u64,
+    /// Transaction fees and tips paid by the operational wallet in lamports
+    pub operational_fees_lamports: u64,
+    /// Whether trading is paused until the operational wallet is topped up
+    pub low_operational_balance: bool,
+    /// Sum of execution times, kept so the average doesn't drift from rounding
+    total_execution_time_ms: u64,
+}
+
+impl BotStatistics {
+    /// Create empty statistics
+    fn new() -> Self {
+        Self {
+            status: BotStatus::Stopped,
+            start_time: None,
+            opportunities_detected: 0,
+            trades_executed: 0,
+            failed_trades: 0,
+            total_profit_lamports: 0,
+            total_profit_usd_cents: 0,
+            success_rate: 0.0,
+            avg_profit_per_trade: 0,
+            avg_execution_time_ms: 0,
+            operational_fees_lamports: 0,
+            low_operational_balance: false,
+            total_execution_time_ms: 0,
+        }
+    }
+    
+    /// Count a detected opportunity
+    pub fn record_opportunity(&mut self) {
+        self.opportunities_detected += 1;
+    }
+    
+    /// Count an executed trade and refresh the derived rates and averages
+    pub fn record_trade(&mut self, success: bool, profit_lamports: u64, execution_time_ms: u64) {
+        self.trades_executed += 1;
+        if success {
+            self.total_profit_lamports += profit_lamports;
+        } else {
+            self.failed_trades += 1;
+        }
+        self.total_execution_time_ms += execution_time_ms;
+        
+        let successful = self.trades_executed - self.failed_trades;
+        self.success_rate = successful as f64 / self.trades_executed as f64 * 100.0;
+        self.avg_profit_per_trade = self.total_profit_lamports / self.trades_executed;
+        self.avg_execution_time_ms = self.total_execution_time_ms / self.trades_executed;
+    }
+    
+    /// Count a trade reported on the event channel; other events are ignored
+    pub fn record_event(&mut self, event: &BotEvent) {
+        match event {
+            BotEvent::TradeExecuted { profit, execution_time_ms, .. } => self.record_trade(true, *profit, *execution_time_ms),
+            BotEvent::TradeFailed { execution_time_ms, .. } => self.record_trade(false, 0, *execution_time_ms),
+            _ => {},
+        }
+    }
+}
+
+/// Count trades published on the bot's event channel until it closes
+///
+/// The engine reports trades as events, so this is what keeps the statistics current.
+fn record_bot_events(mut events: broadcast::Receiver<BotEvent>, statistics: Arc<Mutex<BotStatistics>>) -> JoinHandle<()> {
+    thread::spawn(move || {
+        loop {
+            match events.blocking_recv() {
+                Ok(event) => lock_or_recover(&statistics).record_event(&event),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => warn!("Statistics fell behind, skipped {} events", skipped),
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
 }
 
 /// Main bot implementation
@@ -154,26 +676,53 @@ pub struct ArbitrageBot {
     profit_manager: ThreadSafeProfitManager,
     /// RPC client
     rpc_client: RpcClient,
-    /// Bot statistics
-    statistics: BotStatistics,
+    /// RPC endpoints shared by every component
+    rpc: FailoverRpcClient,
+    /// Bot statistics, shared with the monitoring thread
+    statistics: Arc<Mutex<BotStatistics>>,
     /// Tokio runtime for async operations
     runtime: Runtime,
+    /// Event channel for subscribers
+    events: broadcast::Sender<BotEvent>,
+    /// Status shared with the monitoring thread
+    status_flag: Arc<AtomicU8>,
+    /// Monitoring thread handle
+    monitor_handle: Option<JoinHandle<()>>,
+    /// Prometheus metrics, once the endpoint is serving
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<metrics::BotMetrics>>,
 }
 
 impl ArbitrageBot {
     /// Create a new arbitrage bot
-    pub fn new(config: BotConfig) -> Result<Self, String> {
-        // Create RPC client
-        let rpc_client = RpcClient::new_with_commitment(
-            config.rpc_url.clone(),
-            CommitmentConfig::confirmed(),
-        );
+    pub fn new(config: BotConfig) -> Result<Self, BotError> {
+        // Validate before anything can be traded or withdrawn
+        config.validate().map_err(BotError::Config)?;
+        
+        // Create RPC client, failing over to the fallback endpoints
+        let rpc = FailoverRpcClient::new(&config.rpc_url, &config.fallback_rpc_urls);
+        let rpc_client = rpc.rpc_client(CommitmentConfig::confirmed());
         
         // Create wallet manager
-        let wallet_manager = ThreadSafeWalletManager::new(
-            &config.rpc_url,
+        let wallet_manager = ThreadSafeWalletManager::with_rpc_client(
+            rpc.rpc_client(CommitmentConfig::confirmed()),
             &config.wallet_storage_path,
         );
+        wallet_manager.set_kdf_params(config.wallet_kdf_params)?;
+        wallet_manager.set_priority_fee(config.priority_fee, config.gas_price_multiplier)?;
+        wallet_manager.set_retry_policy(config.retry_policy)?;
+        wallet_manager.set_operational_fee_payer(config.operational_fee_payer)?;
+        
+        // Refreshed off the trading path; the thread exits with the wallet manager
+        let (blockhash_cache, _) = BlockhashCache::spawn(
+            rpc.rpc_client(CommitmentConfig::confirmed()),
+            DEFAULT_BLOCKHASH_REFRESH_INTERVAL,
+        );
+        wallet_manager.set_blockhash_cache(blockhash_cache)?;
+        #[cfg(feature = "jito")]
+        if let Some(jito) = &config.jito {
+            wallet_manager.set_jito_config(jito.clone())?;
+        }
         
         // Create profit manager
         let profit_manager = ThreadSafeProfitManager::new(
@@ -182,21 +731,19 @@ impl ArbitrageBot {
         
         // Create tokio runtime
         let runtime = Runtime::new()
-            .map_err(|e| format!("Failed to create tokio runtime: {}", e))?;
+            .map_err(|e| BotError::General(format!("Failed to create tokio runtime: {}", e)))?;
         
         // Create bot statistics
-        let statistics = BotStatistics {
-            status: BotStatus::Stopped,
-            start_time: None,
-            opportunities_detected: 0,
-            trades_executed: 0,
-            failed_trades: 0,
-            total_profit_lamports: 0,
-            total_profit_usd_cents: 0,
-            success_rate: 0.0,
-            avg_profit_per_trade: 0,
-            avg_execution_time_ms: 0,
-        };
+        let statistics = Arc::new(Mutex::new(BotStatistics::new()));
+        
+        // Notifications are delivered from the event channel, off the trading threads
+        let events = broadcast::channel(EVENT_CHANNEL_CAPACITY).0;
+        record_bot_events(events.subscribe(), statistics.clone());
+        if !config.notifications.is_empty() {
+            let dispatcher = NotificationDispatcher::from_endpoints(&config.notifications)
+                .map_err(BotError::Config)?;
+            notify::forward_bot_events(events.subscribe(), dispatcher);
+        }
         
         Ok(Self {
             config,
@@ -204,18 +751,23 @@ impl ArbitrageBot {
             wallet_manager,
             profit_manager,
             rpc_client,
+            rpc,
             statistics,
             runtime,
+            events,
+            status_flag: Arc::new(AtomicU8::new(BotStatus::Stopped.as_u8())),
+            monitor_handle: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         })
     }
     
     /// Initialize the bot
-    pub fn initialize(&mut self, wallet_password: &str) -> Result<(), String> {
+    pub fn initialize(&mut self, wallet_password: &str) -> Result<(), BotError> {
         info!("Initializing arbitrage bot");
         
         // Initialize wallet encryption
-        self.wallet_manager.init_encryption(wallet_password)
-            .map_err(|e| format!("Failed to initialize wallet encryption: {}", e))?;
+        self.wallet_manager.init_encryption(wallet_password)?;
         
         // Load existing wallets
         match self.wallet_manager.load_wallets() {
@@ -233,110 +785,480 @@ impl ArbitrageBot {
         Ok(())
     }
     
+    /// Subscribe to bot events
+    ///
+    /// Subscribers that fall behind lose the oldest events rather than blocking the bot.
+    pub fn subscribe(&self) -> broadcast::Receiver<BotEvent> {
+        self.events.subscribe()
+    }
+    
+    /// Sender for the bot's event channel, so an arbitrage engine can publish on it
+    pub fn event_sender(&self) -> broadcast::Sender<BotEvent> {
+        self.events.clone()
+    }
+    
+    /// Publish an event to all subscribers
+    fn publish(&self, event: BotEvent) {
+        // A send error only means there are no subscribers right now
+        let _ = self.events.send(event);
+    }
+    
+    /// Update the bot status and notify subscribers
+    fn set_status(&mut self, status: BotStatus) {
+        let previous = self.status;
+        self.status = status;
+        lock_or_recover(&self.statistics).status = status;
+        self.status_flag.store(status.as_u8(), Ordering::SeqCst);
+        
+        if previous != status {
+            self.publish(BotEvent::StatusChanged { from: previous, to: status });
+        }
+    }
+    
     /// Ensure we have all required wallet types
-    fn ensure_required_wallets(&self) -> Result<(), String> {
+    fn ensure_required_wallets(&self) -> Result<(), BotError> {
         // Check for trading wallet
-        let trading_wallets = self.wallet_manager.get_wallets_by_type(WalletType::Trading)
-            .map_err(|e| format!("Failed to get trading wallets: {}", e))?;
+        let trading_wallets = self.wallet_manager.get_wallets_by_type(WalletType::Trading)?;
         
-        if trading_wallets.is_empty() {
-            warn!("No trading wallet found, generating one");
-            // Generate trading wallet
-            self.wallet_manager.generate_wallet(WalletType::Trading, "Main Trading Wallet")
-                .map_err(|e| format!("Failed to generate trading wallet: {}", e))?;
+        // Fill the trading wallet pool up to the configured size
+        let pool_size = self.config.trading_wallet_pool_size.max(1);
+        if trading_wallets.len() < pool_size {
+            warn!("Found {} of {} trading wallets, generating the rest", trading_wallets.len(), pool_size);
+            for i in trading_wallets.len()..pool_size {
+                let label = if i == 0 {
+                    "Main Trading Wallet".to_string()
+                } else {
+                    format!("Trading Wallet {}", i + 1)
+                };
+                self.wallet_manager.generate_wallet(WalletType::Trading, &label)?;
+            }
         }
         
         // Check for operational wallet
-        let operational_wallets = self.wallet_manager.get_wallets_by_type(WalletType::Operational)
-            .map_err(|e| format!("Failed to get operational wallets: {}", e))?;
+        let operational_wallets = self.wallet_manager.get_wallets_by_type(WalletType::Operational)?;
         
         if operational_wallets.is_empty() {
             warn!("No operational wallet found, generating one");
             // Generate operational wallet
-            self.wallet_manager.generate_wallet(WalletType::Operational, "Operational Expenses Wallet")
-                .map_err(|e| format!("Failed to generate operational wallet: {}", e))?;
+            self.wallet_manager.generate_wallet(WalletType::Operational, "Operational Expenses Wallet")?;
         }
         
         // Check for profit wallet
-        let profit_wallets = self.wallet_manager.get_wallets_by_type(WalletType::Profit)
-            .map_err(|e| format!("Failed to get profit wallets: {}", e))?;
+        let profit_wallets = self.wallet_manager.get_wallets_by_type(WalletType::Profit)?;
         
-        if profit_wallets.is_empty() {
-            warn!("No profit wallet found, generating one");
-            // Generate profit wallet
-            self.wallet_manager.generate_wallet(WalletType::Profit, "Profit Storage Wallet")
-                .map_err(|e| format!("Failed to generate profit wallet: {}", e))?;
-        }
+        let profit_wallet = match profit_wallets.first() {
+            Some(info) => info.pubkey,
+            None => {
+                warn!("No profit wallet found, generating one");
+                // Generate profit wallet
+                self.wallet_manager.generate_wallet(WalletType::Profit, "Profit Storage Wallet")?
+            }
+        };
+        
+        // Check for reserve wallet
+        let reserve_wallets = self.wallet_manager.get_wallets_by_type(WalletType::Reserve)?;
+        
+        let reserve_wallet = match reserve_wallets.first() {
+            Some(info) => info.pubkey,
+            None => {
+                warn!("No reserve wallet found, generating one");
+                self.wallet_manager.generate_wallet(WalletType::Reserve, "Reserve Wallet")?
+            }
+        };
+        
+        // Withdrawal and reserve shares are paid out of profit storage
+        self.profit_manager.set_profit_wallet(profit_wallet)
+            .map_err(BotError::Profit)?;
+        self.profit_manager.set_reserve_wallet(reserve_wallet)
+            .map_err(BotError::Profit)?;
         
         Ok(())
     }
     
     /// Start the bot
-    pub fn start(&mut self) -> Result<(), String> {
+    pub fn start(&mut self) -> Result<(), BotError> {
         if self.status == BotStatus::Running {
-            return Err("Bot is already running".to_string());
+            return Err(BotError::AlreadyRunning);
         }
         
         info!("Starting arbitrage bot");
         
         // Update status and statistics
-        self.status = BotStatus::Running;
-        self.statistics.status = BotStatus::Running;
-        self.statistics.start_time = Some(Instant::now());
+        self.set_status(BotStatus::Running);
+        lock_or_recover(&self.statistics).start_time = Some(Instant::now());
         
         // Start monitoring thread
         let config = self.config.clone();
-        let wallet_manager = self.wallet_manager.clone();
+        let events = self.events.clone();
+        let status_flag = self.status_flag.clone();
+        let runtime = self.runtime.handle().clone();
+        let statistics = self.statistics.clone();
+        
+        // Serve metrics once per process; restarts reuse the same endpoint
+        #[cfg(feature = "metrics")]
+        if self.metrics.is_none() {
+            if let Some(bind_address) = &self.config.metrics_bind_address {
+                let bot_metrics = Arc::new(metrics::BotMetrics::new().map_err(BotError::General)?);
+                metrics::serve(bot_metrics.clone(), bind_address).map_err(BotError::General)?;
+                self.metrics = Some(bot_metrics);
+            }
+        }
+        #[cfg(feature = "metrics")]
+        let bot_metrics = self.metrics.clone();
+        #[cfg(feature = "metrics")]
         let profit_manager = self.profit_manager.clone();
         
-        thread::spawn(move || {
-            // This would be the main monitoring loop
-            // In a real implementation, this would:
-            // 1. Monitor prices across DEXs
-            // 2. Identify arbitrage opportunities
-            // 3. Execute trades when profitable
-            
-            while true {
+        let dex_manager = self.build_dex_manager()?;
+        let wallet_manager = self.wallet_manager.clone();
+        let mut balance_monitor = OperationalBalanceMonitor::new(config.operational_balance);
+        
+        let handle = thread::spawn(move || {
+            loop {
+                match BotStatus::from_u8(status_flag.load(Ordering::SeqCst)) {
+                    BotStatus::Running => {
+                        // Don't burn cycles on transactions the fee payer can't pay for
+                        if Self::check_operational_balance(&config, &wallet_manager, &mut balance_monitor, &events, &statistics) {
+                            thread::sleep(Duration::from_millis(config.update_interval_ms));
+                            continue;
+                        }
+                        
+                        for pair in &config.token_pairs {
+                            if Self::scan_pair(&config, &dex_manager, &runtime, &events, pair) {
+                                lock_or_recover(&statistics).record_opportunity();
+                            }
+                        }
+                        
+                        #[cfg(feature = "metrics")]
+                        if let Some(bot_metrics) = &bot_metrics {
+                            let snapshot = lock_or_recover(&statistics).clone();
+                            bot_metrics.update_bot_statistics(&snapshot);
+                            match profit_manager.get_statistics() {
+                                Ok(profit_stats) => bot_metrics.update_profit_statistics(&profit_stats),
+                                Err(e) => debug!("Failed to read profit statistics for metrics: {}", e),
+                            }
+                            bot_metrics.update_dex_latency(&dex_manager.latency_stats());
+                        }
+                    },
+                    BotStatus::Paused => {
+                        // Keep the thread alive but don't scan
+                    },
+                    BotStatus::Stopped | BotStatus::Error => break,
+                }
+                
                 // Sleep for update interval
                 thread::sleep(Duration::from_millis(config.update_interval_ms));
-                
-                // TODO: Implement actual monitoring and trading logic
             }
+            
+            debug!("Monitoring thread exited");
         });
+        self.monitor_handle = Some(handle);
         
         info!("Bot started successfully");
         Ok(())
     }
     
+    /// Create a DEX manager with a connector for each enabled DEX
+    fn build_dex_manager(&self) -> Result<dex::DexManager, BotError> {
+        let mut dex_manager = dex::DexManager::with_rpc(self.rpc.clone());
+        for dex_config in self.config.dexes.iter().filter(|d| d.enabled) {
+            match dex_config.to_connector_config() {
+                Some(mut connector_config) => {
+                    // Quote aggregator prices at the size trades are actually made at
+                    connector_config.quote_amount = self.config.max_position_size;
+                    dex_manager.add_connector(connector_config);
+                },
+                None => warn!("Skipping DEX {}: custom DEXs must be added with an adapter", dex_config.name),
+            }
+        }
+        dex_manager.set_retry_policy(self.config.retry_policy)?;
+        
+        Ok(dex_manager)
+    }
+    
+    /// Check the RPC, each enabled DEX, the wallets and the fee balance
+    ///
+    /// Every component is checked even if an earlier one fails, so one call shows
+    /// everything that would keep the bot from trading.
+    pub fn health_check(&self) -> HealthReport {
+        let mut report = HealthReport::default();
+        
+        report.components.push(ComponentHealth::check("rpc", || {
+            self.rpc_client.get_health()
+                .map_err(|e| format!("getHealth failed: {}", e))?;
+            let version = self.rpc_client.get_version()
+                .map_err(|e| format!("getVersion failed: {}", e))?;
+            Ok(format!("solana-core {}", version.solana_core))
+        }));
+        
+        // Quote the first configured pair on each DEX
+        match self.build_dex_manager() {
+            Ok(dex_manager) => {
+                for dex_config in self.config.dexes.iter().filter(|d| d.enabled) {
+                    report.components.push(ComponentHealth::check(format!("dex:{}", dex_config.name), || {
+                        let dex_type = dex_config.to_connector_config()
+                            .ok_or_else(|| "Custom DEXs must be added with an adapter".to_string())?
+                            .dex_type;
+                        let connector = dex_manager.get_connector(dex_type)
+                            .ok_or_else(|| "No connector".to_string())?;
+                        let pair = self.config.token_pairs.first()
+                            .ok_or_else(|| "No token pair to quote".to_string())?;
+                        let price = self.runtime.block_on(connector.get_price(&pair.base_token, &pair.quote_token))
+                            .map_err(|e| format!("Quote failed: {}", e))?;
+                        Ok(format!("{}/{} at {}", pair.base_token, pair.quote_token, price.price))
+                    }));
+                }
+            },
+            Err(e) => report.components.push(ComponentHealth::check("dex", || Err(e.to_string()))),
+        }
+        
+        for wallet_type in [WalletType::Trading, WalletType::Operational, WalletType::Profit, WalletType::Reserve] {
+            report.components.push(ComponentHealth::check(format!("wallet:{:?}", wallet_type), || {
+                let wallets = self.wallet_manager.get_wallets_by_type(wallet_type)
+                    .map_err(|e| format!("Failed to get wallets: {}", e))?;
+                if wallets.is_empty() {
+                    return Err(format!("No {:?} wallet", wallet_type));
+                }
+                for info in &wallets {
+                    self.wallet_manager.verify_keypair(&info.pubkey)
+                        .map_err(|e| format!("{}: {}", info.pubkey, e))?;
+                }
+                Ok(format!("{} wallet(s), keypairs decrypt", wallets.len()))
+            }));
+        }
+        
+        report.components.push(ComponentHealth::check("fee balance", || {
+            let wallets = self.wallet_manager.get_wallets_by_type(WalletType::Operational)
+                .map_err(|e| format!("Failed to get wallets: {}", e))?;
+            let operational = wallets.first()
+                .ok_or_else(|| "No operational wallet".to_string())?;
+            let balance = self.wallet_manager.get_balance(&operational.pubkey)
+                .map_err(|e| format!("Failed to get balance: {}", e))?;
+            let minimum = self.config.operational_balance.min_operational_balance;
+            if balance < minimum {
+                return Err(format!("{} holds {} lamports, below the {} needed for fees",
+                                   operational.pubkey, balance, minimum));
+            }
+            Ok(format!("{} lamports", balance))
+        }));
+        
+        for failure in report.failures() {
+            warn!("Health check failed for {}: {}", failure.component, failure.detail);
+        }
+        report
+    }
+    
+    /// Read the operational wallet balance and pause or resume on crossing a threshold
+    ///
+    /// Returns whether trading is paused for low funds. Without an operational
+    /// wallet the trading wallets pay their own fees, so nothing is paused.
+    fn check_operational_balance(
+        config: &BotConfig,
+        wallet_manager: &ThreadSafeWalletManager,
+        balance_monitor: &mut OperationalBalanceMonitor,
+        events: &broadcast::Sender<BotEvent>,
+        statistics: &Mutex<BotStatistics>,
+    ) -> bool {
+        let wallet = match wallet_manager.get_wallets_by_type(WalletType::Operational) {
+            Ok(wallets) => match wallets.first() {
+                Some(info) => info.pubkey,
+                None => return balance_monitor.is_low(),
+            },
+            Err(e) => {
+                warn!("Failed to get operational wallet: {}", e);
+                return balance_monitor.is_low();
+            }
+        };
+        
+        // Keep the last known state if the balance can't be read
+        let balance = match wallet_manager.get_balance(&wallet) {
+            Ok(balance) => balance,
+            Err(e) => {
+                warn!("Failed to read operational balance: {}", e);
+                return balance_monitor.is_low();
+            }
+        };
+        
+        if let Some(transition) = balance_monitor.record_balance(balance) {
+            let event = match transition {
+                BalanceTransition::Low => BotEvent::LowOperationalBalance {
+                    wallet,
+                    balance,
+                    resume_at: config.operational_balance.resume_operational_balance,
+                },
+                BalanceTransition::Restored => BotEvent::OperationalBalanceRestored { wallet, balance },
+            };
+            let _ = events.send(event);
+            
+            lock_or_recover(&statistics).low_operational_balance = balance_monitor.is_low();
+        }
+        
+        balance_monitor.is_low()
+    }
+    
+    /// Stop trading and sell every token in the trading wallets for `stable_mint`
+    ///
+    /// Pauses a running bot so nothing new is scanned, then sells each non-stable
+    /// balance on the DEX quoting the best price, at the configured maximum priority
+    /// fee and a wide slippage tolerance. Only current balances are sold, so calling
+    /// it again retries whatever is left without selling anything twice.
+    pub fn emergency_unwind(&mut self, stable_mint: Pubkey) -> Result<Vec<UnwindResult>, BotError> {
+        warn!("Emergency unwind to {}", stable_mint);
+        
+        if self.status == BotStatus::Running {
+            self.set_status(BotStatus::Paused);
+        }
+        
+        let dex_manager = self.build_dex_manager()?;
+        let trading_wallets = self.wallet_manager.get_wallets_by_type(WalletType::Trading)?;
+        
+        let mut results = Vec::new();
+        for info in &trading_wallets {
+            let wallet = info.pubkey;
+            let balances = match self.wallet_manager.get_token_balances(&wallet) {
+                Ok(balances) => balances,
+                Err(e) => {
+                    error!("Failed to read token balances of {}: {}", wallet, e);
+                    continue;
+                }
+            };
+            
+            for (token_mint, amount) in balances.into_iter().filter(|(mint, _)| *mint != stable_mint) {
+                let outcome = self.unwind_token(&dex_manager, &wallet, &token_mint, &stable_mint, amount);
+                match &outcome {
+                    Ok((dex, signature)) => info!("Sold {} of {} from {} on {:?}: {}", amount, token_mint, wallet, dex, signature),
+                    Err(e) => error!("Failed to sell {} of {} from {}: {}", amount, token_mint, wallet, e),
+                }
+                results.push(UnwindResult {
+                    wallet,
+                    token_mint,
+                    amount,
+                    dex: outcome.as_ref().ok().map(|(dex, _)| *dex),
+                    outcome: outcome.map(|(_, signature)| signature),
+                });
+            }
+        }
+        
+        Ok(results)
+    }
+    
+    /// Sell a wallet's whole balance of one token on the best quoting DEX, returning the DEX and signature
+    fn unwind_token(
+        &self,
+        dex_manager: &dex::DexManager,
+        wallet: &Pubkey,
+        token_mint: &Pubkey,
+        stable_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<(dex::DexType, String), String> {
+        let best = self.runtime.block_on(dex_manager.get_prices(token_mint, stable_mint))
+            .into_iter()
+            .filter_map(|result| result.ok())
+            .filter(|price| price.price > 0.0)
+            .max_by(|a, b| a.price.total_cmp(&b.price))
+            .ok_or_else(|| format!("No DEX quotes {}/{}", token_mint, stable_mint))?;
+        
+        let min_out = dex::min_amount_out(best.base_to_quote_atoms(amount), UNWIND_SLIPPAGE);
+        let params = dex::SwapParams {
+            amount_in: amount,
+            min_amount_out: min_out,
+            source_token: *token_mint,
+            destination_token: *stable_mint,
+            source_wallet: *wallet,
+            destination_wallet: *wallet,
+            slippage: UNWIND_SLIPPAGE,
+        };
+        
+        self.wallet_manager.ensure_associated_token_account(wallet, stable_mint)
+            .map_err(|e| format!("Failed to create {} account: {}", stable_mint, e))?;
+        let swap = self.runtime.block_on(dex_manager.create_swap_instructions_for(best.dex, &params))
+            .map_err(|e| format!("Failed to build swap on {:?}: {}", best.dex, e))?;
+        
+        // Pay the most we're ever willing to, so the exit lands even when blocks are contested
+        let signature = self.wallet_manager.sign_and_send_priced(
+            swap.instructions,
+            vec![wallet],
+            swap.lookup_tables,
+            self.config.priority_fee.max_micro_lamports,
+            None,
+        ).map_err(|e| format!("Failed to send swap: {}", e))?;
+        
+        Ok((best.dex, signature))
+    }
+    
+    /// Scan one token pair across all DEXs and report an opportunity if it clears the profit threshold
+    ///
+    /// Returns whether an opportunity was reported.
+    fn scan_pair(
+        config: &BotConfig,
+        dex_manager: &dex::DexManager,
+        runtime: &tokio::runtime::Handle,
+        events: &broadcast::Sender<BotEvent>,
+        pair: &TokenPair,
+    ) -> bool {
+        let prices: Vec<dex::PriceInfo> = runtime.block_on(dex_manager.get_prices(&pair.base_token, &pair.quote_token))
+            .into_iter()
+            .filter_map(|result| match result {
+                Ok(price) => Some(price),
+                Err(e) => {
+                    debug!("Price fetch failed for {}/{}: {}", pair.base_token, pair.quote_token, e);
+                    None
+                }
+            })
+            .collect();
+        
+        let (buy, sell, profit_percentage) = match dex::find_best_spread(&prices, 0.0) {
+            Some(spread) => spread,
+            None => return false,
+        };
+        
+        let estimated_profit = (config.max_position_size as f64 * profit_percentage / 100.0) as u64;
+        if estimated_profit < config.min_profit_threshold {
+            return false;
+        }
+        
+        info!("Opportunity on {}/{}: buy on {:?} at {}, sell on {:?} at {} ({:.3}%)",
+              pair.base_token, pair.quote_token, buy.dex, buy.price, sell.dex, sell.price, profit_percentage);
+        
+        let _ = events.send(BotEvent::OpportunityDetected {
+            base_token: pair.base_token,
+            quote_token: pair.quote_token,
+            profit_percentage,
+        });
+        
+        true
+    }
+    
     /// Stop the bot
-    pub fn stop(&mut self) -> Result<(), String> {
+    pub fn stop(&mut self) -> Result<(), BotError> {
         if self.status != BotStatus::Running && self.status != BotStatus::Paused {
-            return Err("Bot is not running or paused".to_string());
+            return Err(BotError::NotRunning);
         }
         
         info!("Stopping arbitrage bot");
         
         // Update status
-        self.status = BotStatus::Stopped;
-        self.statistics.status = BotStatus::Stopped;
+        self.set_status(BotStatus::Stopped);
         
-        // TODO: Implement proper thread shutdown
+        // Wait for the monitoring thread to see the flag and exit
+        if let Some(handle) = self.monitor_handle.take() {
+            handle.join()
+                .map_err(|_| BotError::General("Monitoring thread panicked".to_string()))?;
+        }
         
         info!("Bot stopped successfully");
         Ok(())
     }
     
     /// Pause the bot
-    pub fn pause(&mut self) -> Result<(), String> {
+    pub fn pause(&mut self) -> Result<(), BotError> {
         if self.status != BotStatus::Running {
-            return Err("Bot is not running".to_string());
+            return Err(BotError::NotRunning);
         }
         
         info!("Pausing arbitrage bot");
         
         // Update status
-        self.status = BotStatus::Paused;
-        self.statistics.status = BotStatus::Paused;
+        self.set_status(BotStatus::Paused);
         
         // TODO: Implement proper thread pausing
         
@@ -345,16 +1267,15 @@ impl ArbitrageBot {
     }
     
     /// Resume the bot
-    pub fn resume(&mut self) -> Result<(), String> {
+    pub fn resume(&mut self) -> Result<(), BotError> {
         if self.status != BotStatus::Paused {
-            return Err("Bot is not paused".to_string());
+            return Err(BotError::NotPaused);
         }
         
         info!("Resuming arbitrage bot");
         
         // Update status
-        self.status = BotStatus::Running;
-        self.statistics.status = BotStatus::Running;
+        self.set_status(BotStatus::Running);
         
         // TODO: Implement proper thread resuming
         
@@ -367,71 +1288,101 @@ impl ArbitrageBot {
         self.status
     }
     
-    /// Get bot statistics
-    pub fn get_statistics(&self) -> &BotStatistics {
-        &self.statistics
+    /// Get a consistent snapshot of the bot statistics
+    pub fn get_statistics(&self) -> Result<BotStatistics, BotError> {
+        let mut statistics = lock_or_recover(&self.statistics).clone();
+        
+        // Fees are paid outside the trade accounting, so read them from the wallets
+        statistics.operational_fees_lamports = self.wallet_manager.operational_fees_paid()?;
+        
+        Ok(statistics)
+    }
+    
+    /// Report the outcome of an executed trade to subscribers
+    ///
+    /// The statistics count it from the event, the same as trades the engine publishes.
+    pub fn record_trade(&self, outcome: Result<String, String>, profit_lamports: u64, execution_time_ms: u64) -> Result<(), BotError> {
+        match outcome {
+            Ok(signature) => self.publish(BotEvent::TradeExecuted { signature, profit: profit_lamports, execution_time_ms }),
+            Err(reason) => self.publish(BotEvent::TradeFailed { reason, execution_time_ms }),
+        }
+        
+        Ok(())
     }
     
     /// Update bot configuration
-    pub fn update_config(&mut self, config: BotConfig) -> Result<(), String> {
+    pub fn update_config(&mut self, config: BotConfig) -> Result<(), BotError> {
         // Validate configuration
-        // TODO: Implement proper validation
+        config.validate().map_err(BotError::Config)?;
         
         // Update configuration
         self.config = config;
         
         // Update profit manager configuration
         self.profit_manager.update_config(self.config.profit_distribution.clone())
-            .map_err(|e| format!("Failed to update profit manager configuration: {}", e))?;
+            .map_err(BotError::Profit)?;
         
         info!("Bot configuration updated");
         Ok(())
     }
     
     /// Import wallet from keypair file
-    pub fn import_wallet_from_keypair(&self, file_path: &str, wallet_type: WalletType, label: &str) -> Result<Pubkey, String> {
+    pub fn import_wallet_from_keypair(&self, file_path: &str, wallet_type: WalletType, label: &str) -> Result<Pubkey, BotError> {
         self.wallet_manager.import_from_keypair_file(file_path, wallet_type, label)
-            .map_err(|e| format!("Failed to import wallet: {}", e))
+            .map_err(BotError::Wallet)
     }
     
     /// Import wallet from seed phrase
-    pub fn import_wallet_from_seed_phrase(&self, seed_phrase: &str, wallet_type: WalletType, label: &str) -> Result<Pubkey, String> {
+    pub fn import_wallet_from_seed_phrase(&self, seed_phrase: &str, wallet_type: WalletType, label: &str) -> Result<Pubkey, BotError> {
         self.wallet_manager.import_from_seed_phrase(seed_phrase, wallet_type, label)
-            .map_err(|e| format!("Failed to import wallet: {}", e))
+            .map_err(BotError::Wallet)
     }
     
     /// Add watch-only wallet
-    pub fn add_watch_only_wallet(&self, pubkey: Pubkey, wallet_type: WalletType, label: &str) -> Result<(), String> {
+    pub fn add_watch_only_wallet(&self, pubkey: Pubkey, wallet_type: WalletType, label: &str) -> Result<(), BotError> {
         self.wallet_manager.add_watch_only_wallet(pubkey, wallet_type, label)
-            .map_err(|e| format!("Failed to add watch-only wallet: {}", e))
+            .map_err(BotError::Wallet)
     }
     
     /// Get all wallets
-    pub fn get_all_wallets(&self) -> Result<Vec<wallet_integration::WalletInfo>, String> {
+    pub fn get_all_wallets(&self) -> Result<Vec<wallet_integration::WalletInfo>, BotError> {
         self.wallet_manager.get_all_wallets()
-            .map_err(|e| format!("Failed to get wallets: {}", e))
+            .map_err(BotError::Wallet)
     }
     
     /// Get wallet balance
-    pub fn get_wallet_balance(&self, pubkey: &Pubkey) -> Result<u64, String> {
+    pub fn get_wallet_balance(&self, pubkey: &Pubkey) -> Result<u64, BotError> {
         self.wallet_manager.get_balance(pubkey)
-            .map_err(|e| format!("Failed to get wallet balance: {}", e))
+            .map_err(BotError::Wallet)
     }
     
     /// Distribute profits
-    pub fn distribute_profits(&self) -> Result<profit_management::DistributionResult, String> {
-        // Create a temporary WalletManager instance for the profit manager
-        // In a real implementation, this would be properly integrated
-        let wallet_manager = profit_management::WalletManager;
+    pub fn distribute_profits(&self) -> Result<profit_management::DistributionResult, BotError> {
+        let result = self.profit_manager.distribute_profits(&self.wallet_manager)
+            .map_err(|e| BotError::Profit(format!("Failed to distribute profits: {}", e)))?;
         
-        self.profit_manager.distribute_profits(&wallet_manager)
-            .map_err(|e| format!("Failed to distribute profits: {}", e))
+        self.publish(BotEvent::ProfitDistributed {
+            reinvested_amount: result.reinvested_amount,
+            withdrawn_amount: result.withdrawn_amount,
+            reserved_amount: result.reserved_amount,
+        });
+        
+        Ok(result)
+    }
+    
+    /// Withdraw reserved funds of a token to the owner wallet
+    pub fn withdraw_from_reserve(&self, token_mint: Pubkey, amount: u64) -> Result<String, BotError> {
+        let signature = self.profit_manager.withdraw_from_reserve(token_mint, amount, &self.wallet_manager)
+            .map_err(|e| BotError::Profit(format!("Failed to withdraw from reserve: {}", e)))?;
+        
+        info!("Withdrew {} of {} from reserve to owner: {}", amount, token_mint, signature);
+        Ok(signature)
     }
     
     /// Get profit statistics
-    pub fn get_profit_statistics(&self) -> Result<profit_management::ProfitStatistics, String> {
+    pub fn get_profit_statistics(&self) -> Result<profit_management::ProfitStatistics, BotError> {
         self.profit_manager.get_statistics()
-            .map_err(|e| format!("Failed to get profit statistics: {}", e))
+            .map_err(BotError::Profit)
     }
 }
 
@@ -440,19 +1391,22 @@ impl Drop for ArbitrageBot {
     fn drop(&mut self) {
         // Attempt to stop the bot if it's running
         if self.status == BotStatus::Running || self.status == BotStatus::Paused {
-            let _ = self.stop();
+            if let Err(e) = self.stop() {
+                error!("Failed to stop bot cleanly: {}", e);
+            }
         }
     }
 }
 
 /// Thread-safe wrapper for ArbitrageBot
+#[derive(Clone)]
 pub struct ThreadSafeArbitrageBot {
     inner: Arc<Mutex<ArbitrageBot>>,
 }
 
 impl ThreadSafeArbitrageBot {
     /// Create a new thread-safe arbitrage bot
-    pub fn new(config: BotConfig) -> Result<Self, String> {
+    pub fn new(config: BotConfig) -> Result<Self, BotError> {
         let bot = ArbitrageBot::new(config)?;
         Ok(Self {
             inner: Arc::new(Mutex::new(bot)),
@@ -460,11 +1414,48 @@ impl ThreadSafeArbitrageBot {
     }
     
     /// Initialize the bot (thread-safe)
-    pub fn initialize(&self, wallet_password: &str) -> Result<(), String> {
-        let mut bot = self.inner.lock()
-            .map_err(|e| format!("Lock error: {}", e))?;
+    pub fn initialize(&self, wallet_password: &str) -> Result<(), BotError> {
+        let mut bot = lock_or_recover(&self.inner);
         bot.initialize(wallet_password)
     }
     
+    /// Subscribe to bot events (thread-safe)
+    pub fn subscribe(&self) -> Result<broadcast::Receiver<BotEvent>, BotError> {
+        let bot = lock_or_recover(&self.inner);
+        Ok(bot.subscribe())
+    }
+    
+    /// Withdraw reserved funds of a token to the owner wallet (thread-safe)
+    pub fn withdraw_from_reserve(&self, token_mint: Pubkey, amount: u64) -> Result<String, BotError> {
+        let bot = lock_or_recover(&self.inner);
+        bot.withdraw_from_reserve(token_mint, amount)
+    }
+    
+    /// Stop trading and sell every token in the trading wallets for a stable token (thread-safe)
+    pub fn emergency_unwind(&self, stable_mint: Pubkey) -> Result<Vec<UnwindResult>, BotError> {
+        let mut bot = lock_or_recover(&self.inner);
+        bot.emergency_unwind(stable_mint)
+    }
+    
+    /// Check the RPC, DEXs, wallets and fee balance (thread-safe)
+    pub fn health_check(&self) -> Result<HealthReport, BotError> {
+        let bot = lock_or_recover(&self.inner);
+        Ok(bot.health_check())
+    }
+    
+    /// Serve the control API if the configuration enables it
+    #[cfg(feature = "http-api")]
+    pub fn serve_api(&self) -> Result<Option<JoinHandle<()>>, BotError> {
+        let api_config = {
+            let bot = lock_or_recover(&self.inner);
+            bot.config.api.clone()
+        };
+        
+        match api_config {
+            Some(api_config) => api::serve(self.clone(), api_config).map(Some).map_err(BotError::General),
+            None => Ok(None),
+        }
+    }
+    
     /// Start the bot (thread-safe)
     pub fn start(<response clipped><NOTE>To save on context only part of this file has been shown to you. You should retry this tool after you have searched inside the file with `grep -n` in order to find the line numbers of what you are looking for.</NOTE>
\ No newline at end of file
@@ -0,0 +1,374 @@
+// Command-line interface for Solana Flash Loan Arbitrage Bot
+// Operator-facing wallet, bot, and profit commands
+
+use clap::{Args, Parser, Subcommand};
+use solana_sdk::pubkey::Pubkey;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use solana_arbitrage_bot::profit_management::ProfitStatistics;
+use solana_arbitrage_bot::wallet_integration::{WalletManager, WalletType};
+use solana_arbitrage_bot::{BotConfig, ThreadSafeArbitrageBot};
+
+/// Name of the file a running bot reports its status in
+const STATUS_FILE: &str = "bot.status";
+
+/// Name of the file used to ask a running bot to stop
+const STOP_FILE: &str = "bot.stop";
+
+/// Name of the file a running bot reports its profit statistics in
+const PROFIT_FILE: &str = "bot.profit";
+
+/// How often a running bot refreshes its profit statistics file
+const PROFIT_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Environment variable that supplies the wallet password instead of prompting
+const PASSWORD_ENV: &str = "BOT_WALLET_PASSWORD";
+
+/// Environment variable holding the control API bearer token
+#[cfg(feature = "http-api")]
+const API_TOKEN_ENV: &str = "BOT_API_TOKEN";
+
+/// Solana flash loan arbitrage bot
+#[derive(Parser)]
+#[command(name = "solana-arbitrage-bot", version, about)]
+struct Cli {
+    /// RPC URL for Solana
+    #[arg(long, global = true, default_value = "https://api.mainnet-beta.solana.com")]
+    rpc_url: String,
+
+    /// Path for wallet storage
+    #[arg(long, global = true, default_value = "./wallets")]
+    wallet_dir: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage wallets
+    #[command(subcommand)]
+    Wallet(WalletCommand),
+    /// Control the bot
+    #[command(subcommand)]
+    Bot(BotCommand),
+    /// Inspect profits
+    #[command(subcommand)]
+    Profit(ProfitCommand),
+}
+
+#[derive(Subcommand)]
+enum WalletCommand {
+    /// Generate a new wallet
+    Generate {
+        /// Wallet type (trading, operational, profit, owner, reserve)
+        #[arg(long = "type", value_parser = parse_wallet_type)]
+        wallet_type: WalletType,
+        /// Wallet label
+        #[arg(long)]
+        label: String,
+    },
+    /// Import a wallet from a keypair file
+    Import {
+        /// Path to the keypair file
+        #[arg(long)]
+        file: PathBuf,
+        /// Wallet type (trading, operational, profit, owner, reserve)
+        #[arg(long = "type", value_parser = parse_wallet_type)]
+        wallet_type: WalletType,
+        /// Wallet label
+        #[arg(long)]
+        label: String,
+    },
+    /// List all wallets
+    List,
+    /// Show the SOL balance of a wallet
+    Balance {
+        /// Wallet public key
+        pubkey: String,
+    },
+    /// Export public wallet information (never private keys) as JSON
+    Export {
+        /// Output file
+        #[arg(long)]
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum BotCommand {
+    /// Start the bot in the foreground until stopped
+    Start(BotArgs),
+    /// Ask a running bot to stop
+    Stop,
+    /// Show the status of a running bot
+    Status,
+    /// Check the RPC, DEX APIs, wallets and fee balance
+    Health(BotArgs),
+}
+
+#[derive(Subcommand)]
+enum ProfitCommand {
+    /// Print the profit report of the running (or last run) bot
+    Report,
+}
+
+#[derive(Args)]
+struct BotArgs {
+    /// Owner wallet receiving profit withdrawals
+    #[arg(long)]
+    owner: String,
+    /// Serve the control API on this address, authenticated with $BOT_API_TOKEN
+    #[cfg(feature = "http-api")]
+    #[arg(long)]
+    api_bind: Option<String>,
+}
+
+fn main() {
+    // log macros are bridged into tracing, so their lines carry the active trade span
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Wallet(command) => run_wallet_command(&cli.rpc_url, &cli.wallet_dir, command),
+        Command::Bot(command) => run_bot_command(&cli.rpc_url, &cli.wallet_dir, command),
+        Command::Profit(command) => run_profit_command(&cli.wallet_dir, command),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Parse a wallet type name
+fn parse_wallet_type(s: &str) -> Result<WalletType, String> {
+    match s.to_lowercase().as_str() {
+        "trading" => Ok(WalletType::Trading),
+        "operational" => Ok(WalletType::Operational),
+        "profit" => Ok(WalletType::Profit),
+        "owner" => Ok(WalletType::Owner),
+        "reserve" => Ok(WalletType::Reserve),
+        _ => Err(format!("Unknown wallet type: {}", s)),
+    }
+}
+
+/// Parse a base58 public key
+fn parse_pubkey(s: &str) -> Result<Pubkey, String> {
+    Pubkey::from_str(s).map_err(|e| format!("Invalid public key {}: {}", s, e))
+}
+
+/// Prompt for the wallet encryption password without echoing it
+///
+/// Taken from $BOT_WALLET_PASSWORD instead when set, for unattended runs.
+fn prompt_password() -> Result<String, String> {
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Ok(password);
+    }
+
+    rpassword::prompt_password("Wallet password: ")
+        .map_err(|e| format!("Failed to read password: {}", e))
+}
+
+/// Open the wallet store and load existing wallets
+fn open_wallet_manager(rpc_url: &str, wallet_dir: &str) -> Result<WalletManager, String> {
+    let password = prompt_password()?;
+
+    let mut wallet_manager = WalletManager::new(rpc_url, wallet_dir);
+    wallet_manager.init_encryption(&password)
+        .map_err(|e| format!("Failed to initialize wallet encryption: {}", e))?;
+    wallet_manager.load_wallets()
+        .map_err(|e| format!("Failed to load wallets: {}", e))?;
+
+    Ok(wallet_manager)
+}
+
+/// Build a bot from the command-line options
+fn build_bot(rpc_url: &str, wallet_dir: &str, args: &BotArgs) -> Result<ThreadSafeArbitrageBot, Box<dyn Error>> {
+    let owner = parse_pubkey(&args.owner)?;
+
+    let mut config = BotConfig::default(owner);
+    config.rpc_url = rpc_url.to_string();
+    config.wallet_storage_path = wallet_dir.to_string();
+    #[cfg(feature = "http-api")]
+    if let Some(bind_address) = &args.api_bind {
+        let bearer_token = std::env::var(API_TOKEN_ENV)
+            .map_err(|_| format!("{} must be set to serve the control API", API_TOKEN_ENV))?;
+        config.api = Some(solana_arbitrage_bot::api::ApiConfig {
+            bind_address: bind_address.clone(),
+            bearer_token,
+        });
+    }
+
+    let bot = ThreadSafeArbitrageBot::new(config)?;
+    bot.initialize(&prompt_password()?)?;
+
+    Ok(bot)
+}
+
+/// Run a wallet subcommand
+fn run_wallet_command(rpc_url: &str, wallet_dir: &str, command: WalletCommand) -> Result<(), Box<dyn Error>> {
+    let mut wallet_manager = open_wallet_manager(rpc_url, wallet_dir)?;
+
+    match command {
+        WalletCommand::Generate { wallet_type, label } => {
+            let pubkey = wallet_manager.generate_wallet(wallet_type, &label)
+                .map_err(|e| format!("Failed to generate wallet: {}", e))?;
+            println!("{}", pubkey);
+        },
+        WalletCommand::Import { file, wallet_type, label } => {
+            let pubkey = wallet_manager.import_from_keypair_file(&file.to_string_lossy(), wallet_type, &label)
+                .map_err(|e| format!("Failed to import wallet: {}", e))?;
+            println!("{}", pubkey);
+        },
+        WalletCommand::List => {
+            for info in wallet_manager.get_all_wallets() {
+                println!(
+                    "{}\t{:?}\t{}\t{}",
+                    info.pubkey,
+                    info.wallet_type,
+                    info.label,
+                    if info.has_keypair { "keypair" } else { "watch-only" },
+                );
+            }
+        },
+        WalletCommand::Balance { pubkey } => {
+            let pubkey = parse_pubkey(&pubkey)?;
+            let balance = wallet_manager.get_balance(&pubkey)
+                .map_err(|e| format!("Failed to get balance: {}", e))?;
+            println!("{} lamports", balance);
+        },
+        WalletCommand::Export { output } => {
+            let wallets: Vec<_> = wallet_manager.get_all_wallets()
+                .into_iter()
+                .map(|info| serde_json::json!({
+                    "pubkey": info.pubkey.to_string(),
+                    "type": format!("{:?}", info.wallet_type),
+                    "label": info.label,
+                    "has_keypair": info.has_keypair,
+                }))
+                .collect();
+
+            let json = serde_json::to_string_pretty(&wallets)
+                .map_err(|e| format!("Failed to serialize wallets: {}", e))?;
+            fs::write(&output, json)
+                .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+            println!("Exported {} wallets to {}", wallets.len(), output.display());
+        },
+    }
+
+    Ok(())
+}
+
+/// Run a bot subcommand
+fn run_bot_command(rpc_url: &str, wallet_dir: &str, command: BotCommand) -> Result<(), Box<dyn Error>> {
+    let status_path = Path::new(wallet_dir).join(STATUS_FILE);
+    let stop_path = Path::new(wallet_dir).join(STOP_FILE);
+    let profit_path = Path::new(wallet_dir).join(PROFIT_FILE);
+
+    match command {
+        BotCommand::Start(args) => {
+            let bot = build_bot(rpc_url, wallet_dir, &args)?;
+
+            // Stop on Ctrl-C as well as on a stop request from another process
+            let interrupted = Arc::new(AtomicBool::new(false));
+            let flag = interrupted.clone();
+            ctrlc::set_handler(move || flag.store(true, Ordering::SeqCst))
+                .map_err(|e| format!("Failed to install Ctrl-C handler: {}", e))?;
+
+            let _ = fs::remove_file(&stop_path);
+            #[cfg(feature = "http-api")]
+            bot.serve_api()?;
+            bot.start()?;
+            write_status(&status_path, &format!("{:?}", bot.get_status()?))?;
+            write_profit(&profit_path, &bot.get_profit_statistics()?)?;
+            println!("Bot started, press Ctrl-C or run `bot stop` to stop");
+
+            let mut last_report = std::time::Instant::now();
+            while !interrupted.load(Ordering::SeqCst) && !stop_path.exists() {
+                thread::sleep(Duration::from_millis(500));
+
+                if last_report.elapsed() >= PROFIT_REPORT_INTERVAL {
+                    write_profit(&profit_path, &bot.get_profit_statistics()?)?;
+                    last_report = std::time::Instant::now();
+                }
+            }
+
+            bot.stop()?;
+            let _ = fs::remove_file(&stop_path);
+            write_status(&status_path, &format!("{:?}", bot.get_status()?))?;
+            write_profit(&profit_path, &bot.get_profit_statistics()?)?;
+            println!("Bot stopped");
+        },
+        BotCommand::Stop => {
+            if !status_path.exists() {
+                return Err("No running bot found".into());
+            }
+            fs::write(&stop_path, b"stop")
+                .map_err(|e| format!("Failed to request stop: {}", e))?;
+            println!("Stop requested");
+        },
+        BotCommand::Health(args) => {
+            let report = build_bot(rpc_url, wallet_dir, &args)?.health_check()?;
+            print!("{}", report);
+            if !report.is_healthy() {
+                return Err(format!("{} component(s) unhealthy", report.failures().count()).into());
+            }
+        },
+        BotCommand::Status => {
+            let status = fs::read_to_string(&status_path)
+                .unwrap_or_else(|_| "Stopped".to_string());
+            println!("{}", status.trim());
+        },
+    }
+
+    Ok(())
+}
+
+/// Run a profit subcommand
+fn run_profit_command(wallet_dir: &str, command: ProfitCommand) -> Result<(), Box<dyn Error>> {
+    match command {
+        ProfitCommand::Report => {
+            // A fresh bot has no trades; report what the running bot last recorded
+            let profit_path = Path::new(wallet_dir).join(PROFIT_FILE);
+            let data = fs::read_to_string(&profit_path)
+                .map_err(|_| "No profit report found, start the bot with `bot start` first")?;
+            let stats: ProfitStatistics = serde_json::from_str(&data)
+                .map_err(|e| format!("Failed to read {}: {}", profit_path.display(), e))?;
+
+            println!("Total SOL profit:      {} lamports", stats.total_sol_profit);
+            println!("Total USD profit:      ${:.2}", stats.total_usd_profit as f64 / 100.0);
+            println!("Successful trades:     {}", stats.total_successful_trades);
+            println!("Failed trades:         {}", stats.total_failed_trades);
+            println!("Success rate:          {:.2}%", stats.overall_success_rate);
+            println!("Tokens traded:         {}", stats.token_count);
+            for reserve in &stats.reserve_balances {
+                println!("Reserve:               {} of {}", reserve.amount, reserve.token_mint);
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Record the bot status for `bot status`
+fn write_status(path: &Path, status: &str) -> Result<(), String> {
+    fs::write(path, status).map_err(|e| format!("Failed to write status file: {}", e))
+}
+
+/// Record the bot's profit statistics for `profit report`
+fn write_profit(path: &Path, stats: &ProfitStatistics) -> Result<(), String> {
+    let json = serde_json::to_string(stats)
+        .map_err(|e| format!("Failed to serialize profit statistics: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write profit file: {}", e))
+}
@@ -0,0 +1,264 @@
+// Metrics Module for Solana Flash Loan Arbitrage Bot
+// Exposes bot and profit statistics as Prometheus metrics over HTTP
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use prometheus::{Encoder, Gauge, GaugeVec, IntCounter, IntGauge, Opts, Registry, TextEncoder};
+use log::{info, warn, debug};
+
+use crate::BotStatistics;
+use crate::dex::{DexType, LatencyStats};
+use crate::profit_management::ProfitStatistics;
+
+/// Prometheus metrics for the bot
+///
+/// All metrics are atomic, so the trading thread can update them without locking.
+pub struct BotMetrics {
+    /// Registry the metrics are exported from
+    registry: Registry,
+    /// Opportunities detected
+    pub opportunities_detected: IntCounter,
+    /// Trades executed
+    pub trades_executed: IntCounter,
+    /// Trades that failed
+    pub failed_trades: IntCounter,
+    /// Total profit in lamports
+    pub total_profit_lamports: IntGauge,
+    /// Success rate as a percentage
+    pub success_rate: Gauge,
+    /// Average execution time in milliseconds
+    pub avg_execution_time_ms: Gauge,
+    /// Successful trades booked by the profit manager
+    pub booked_successful_trades: IntGauge,
+    /// Failed trades booked by the profit manager
+    pub booked_failed_trades: IntGauge,
+    /// SOL profit booked by the profit manager, in lamports
+    pub booked_sol_profit_lamports: IntGauge,
+    /// USD value of booked profit, in cents
+    pub booked_usd_profit_cents: IntGauge,
+    /// Success rate of booked trades as a percentage
+    pub booked_success_rate: Gauge,
+    /// Quote latency per DEX in milliseconds, labelled by `dex` and `stat` (average, p50, p95, max)
+    pub dex_quote_latency_ms: GaugeVec,
+}
+
+impl BotMetrics {
+    /// Create and register the bot's metrics
+    pub fn new() -> Result<Self, String> {
+        let registry = Registry::new();
+        
+        let metrics = Self {
+            opportunities_detected: IntCounter::new("opportunities_detected", "Arbitrage opportunities detected")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            trades_executed: IntCounter::new("trades_executed", "Arbitrage trades executed")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            failed_trades: IntCounter::new("failed_trades", "Arbitrage trades that failed")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            total_profit_lamports: IntGauge::new("total_profit_lamports", "Total realized profit in lamports")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            success_rate: Gauge::new("success_rate", "Trade success rate as a percentage")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            avg_execution_time_ms: Gauge::new("avg_execution_time_ms", "Average trade execution time in milliseconds")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            booked_successful_trades: IntGauge::new("booked_successful_trades", "Successful trades booked by the profit manager")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            booked_failed_trades: IntGauge::new("booked_failed_trades", "Failed trades booked by the profit manager")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            booked_sol_profit_lamports: IntGauge::new("booked_sol_profit_lamports", "SOL profit booked by the profit manager in lamports")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            booked_usd_profit_cents: IntGauge::new("booked_usd_profit_cents", "USD value of booked profit in cents")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            booked_success_rate: Gauge::new("booked_success_rate", "Success rate of booked trades as a percentage")
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            dex_quote_latency_ms: GaugeVec::new(Opts::new("dex_quote_latency_ms", "Recent DEX quote latency in milliseconds"), &["dex", "stat"])
+                .map_err(|e| format!("Failed to create metric: {}", e))?,
+            registry,
+        };
+        
+        let collectors: Vec<Box<dyn prometheus::core::Collector>> = vec![
+            Box::new(metrics.opportunities_detected.clone()),
+            Box::new(metrics.trades_executed.clone()),
+            Box::new(metrics.failed_trades.clone()),
+            Box::new(metrics.total_profit_lamports.clone()),
+            Box::new(metrics.success_rate.clone()),
+            Box::new(metrics.avg_execution_time_ms.clone()),
+            Box::new(metrics.booked_successful_trades.clone()),
+            Box::new(metrics.booked_failed_trades.clone()),
+            Box::new(metrics.booked_sol_profit_lamports.clone()),
+            Box::new(metrics.booked_usd_profit_cents.clone()),
+            Box::new(metrics.booked_success_rate.clone()),
+            Box::new(metrics.dex_quote_latency_ms.clone()),
+        ];
+        for collector in collectors {
+            metrics.registry.register(collector)
+                .map_err(|e| format!("Failed to register metric: {}", e))?;
+        }
+        
+        Ok(metrics)
+    }
+    
+    /// Bring the metrics up to date with a bot statistics snapshot
+    pub fn update_bot_statistics(&self, stats: &BotStatistics) {
+        // Counters only move forward, so add the difference
+        advance(&self.opportunities_detected, stats.opportunities_detected);
+        advance(&self.trades_executed, stats.trades_executed);
+        advance(&self.failed_trades, stats.failed_trades);
+        
+        self.total_profit_lamports.set(stats.total_profit_lamports as i64);
+        self.success_rate.set(stats.success_rate);
+        self.avg_execution_time_ms.set(stats.avg_execution_time_ms as f64);
+    }
+    
+    /// Bring the booked profit metrics up to date with a profit statistics snapshot
+    ///
+    /// The trade metrics come from the bot statistics alone, so these are kept under their own names.
+    pub fn update_profit_statistics(&self, profit: &ProfitStatistics) {
+        self.booked_successful_trades.set(profit.total_successful_trades as i64);
+        self.booked_failed_trades.set(profit.total_failed_trades as i64);
+        self.booked_sol_profit_lamports.set(profit.total_sol_profit as i64);
+        self.booked_usd_profit_cents.set(profit.total_usd_profit as i64);
+        self.booked_success_rate.set(profit.overall_success_rate);
+    }
+    
+    /// Bring the quote latency gauges up to date with each DEX's recent latency window
+    pub fn update_dex_latency(&self, latency: &HashMap<DexType, LatencyStats>) {
+        for (dex_type, stats) in latency {
+            // Connectors that haven't quoted yet have nothing to report
+            if stats.samples == 0 {
+                continue;
+            }
+            
+            let dex = format!("{:?}", dex_type).to_lowercase();
+            for (stat, value) in [("average", stats.average_ms), ("p50", stats.p50_ms), ("p95", stats.p95_ms), ("max", stats.max_ms)] {
+                self.dex_quote_latency_ms.with_label_values(&[dex.as_str(), stat]).set(value);
+            }
+        }
+    }
+    
+    /// Render all metrics in the Prometheus text format
+    pub fn encode(&self) -> Result<String, String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)
+            .map_err(|e| format!("Failed to encode metrics: {}", e))?;
+        String::from_utf8(buffer)
+            .map_err(|e| format!("Metrics are not valid UTF-8: {}", e))
+    }
+}
+
+/// Advance a counter to `value` if it's behind
+fn advance(counter: &IntCounter, value: u64) {
+    let current = counter.get();
+    if value > current {
+        counter.inc_by(value - current);
+    }
+}
+
+/// Serve metrics at `GET /metrics` on `bind_address` from a background thread
+pub fn serve(metrics: Arc<BotMetrics>, bind_address: &str) -> Result<JoinHandle<()>, String> {
+    let listener = TcpListener::bind(bind_address)
+        .map_err(|e| format!("Failed to bind metrics endpoint {}: {}", bind_address, e))?;
+    
+    info!("Serving metrics on http://{}/metrics", bind_address);
+    
+    Ok(thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(e) = handle_request(stream, &metrics) {
+                        debug!("Metrics request failed: {}", e);
+                    }
+                },
+                Err(e) => warn!("Metrics connection failed: {}", e),
+            }
+        }
+    }))
+}
+
+/// Answer a single scrape
+fn handle_request(mut stream: TcpStream, metrics: &BotMetrics) -> Result<(), String> {
+    let mut request = [0u8; 1024];
+    let read = stream.read(&mut request)
+        .map_err(|e| format!("Failed to read request: {}", e))?;
+    let request = String::from_utf8_lossy(&request[..read]);
+    
+    let response = if request.starts_with("GET /metrics") {
+        let body = metrics.encode()?;
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+    
+    stream.write_all(response.as_bytes())
+        .map_err(|e| format!("Failed to write response: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Profit statistics with the given trade counts and SOL profit
+    fn profit_statistics(successful: u64, failed: u64, sol_profit: u64) -> ProfitStatistics {
+        ProfitStatistics {
+            total_sol_profit: sol_profit,
+            total_usd_profit: 0,
+            reserve_balances: Vec::new(),
+            total_successful_trades: successful,
+            total_failed_trades: failed,
+            overall_success_rate: successful as f64 / (successful + failed).max(1) as f64 * 100.0,
+            token_count: 1,
+        }
+    }
+    
+    #[test]
+    fn bot_and_profit_statistics_never_overwrite_each_other() {
+        let metrics = BotMetrics::new().unwrap();
+        let mut stats = BotStatistics::new();
+        stats.record_trade(true, 5_000, 100);
+        stats.record_trade(false, 0, 300);
+        
+        // Alternate the two sources the way the monitor loop does, several passes over
+        for _ in 0..3 {
+            metrics.update_bot_statistics(&stats);
+            metrics.update_profit_statistics(&profit_statistics(4, 1, 7_000));
+        }
+        
+        assert_eq!(metrics.trades_executed.get(), 2);
+        assert_eq!(metrics.failed_trades.get(), 1);
+        assert_eq!(metrics.total_profit_lamports.get(), 5_000);
+        assert_eq!(metrics.success_rate.get(), 50.0);
+        
+        assert_eq!(metrics.booked_successful_trades.get(), 4);
+        assert_eq!(metrics.booked_failed_trades.get(), 1);
+        assert_eq!(metrics.booked_sol_profit_lamports.get(), 7_000);
+        assert_eq!(metrics.booked_success_rate.get(), 80.0);
+        
+        let exported = metrics.encode().unwrap();
+        assert!(exported.contains("trades_executed 2"), "{}", exported);
+        assert!(exported.contains("booked_sol_profit_lamports 7000"), "{}", exported);
+    }
+    
+    #[test]
+    fn quote_latency_is_exported_per_dex() {
+        let metrics = BotMetrics::new().unwrap();
+        let latency = HashMap::from([
+            (DexType::Orca, LatencyStats { samples: 10, average_ms: 42.5, p50_ms: 40.0, p95_ms: 90.0, max_ms: 120.0 }),
+            (DexType::Raydium, LatencyStats { samples: 3, average_ms: 15.0, p50_ms: 14.0, p95_ms: 20.0, max_ms: 21.0 }),
+            (DexType::Phoenix, LatencyStats::default()),
+        ]);
+        
+        metrics.update_dex_latency(&latency);
+        
+        assert_eq!(metrics.dex_quote_latency_ms.with_label_values(&["orca", "p95"]).get(), 90.0);
+        assert_eq!(metrics.dex_quote_latency_ms.with_label_values(&["raydium", "average"]).get(), 15.0);
+        let exported = metrics.encode().unwrap();
+        assert!(exported.contains(r#"dex_quote_latency_ms{dex="orca",stat="max"} 120"#), "{}", exported);
+        assert!(!exported.contains(r#"dex="phoenix""#), "{}", exported);
+    }
+}
@@ -0,0 +1,462 @@
+// Notification Module for Solana Flash Loan Arbitrage Bot
+// Pushes trade, error, circuit breaker and balance events to webhooks
+
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use reqwest::Client as HttpClient;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast::{self, error::RecvError};
+use log::{warn, debug};
+
+use crate::{BotEvent, BotStatus};
+
+/// Notifications buffered for delivery; further notifications are dropped while it's full
+const NOTIFICATION_QUEUE_CAPACITY: usize = 256;
+
+/// How long a webhook may take to answer
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Kind of event a notification reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// A trade landed
+    TradeExecuted,
+    /// A trade failed
+    TradeFailed,
+    /// The daily loss circuit breaker halted trading
+    CircuitBreakerTripped,
+    /// The operational wallet can't cover fees
+    LowBalance,
+    /// The bot stopped
+    BotStopped,
+}
+
+/// Message format a webhook expects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// `{"event", "message", "timestamp", "details"}`
+    Generic,
+    /// Discord webhook (`{"content"}`)
+    Discord,
+    /// Slack incoming webhook (`{"text"}`)
+    Slack,
+    /// Telegram bot API `sendMessage` URL, posting to a chat
+    Telegram {
+        /// Chat to post to
+        chat_id: String,
+    },
+}
+
+/// Where to send notifications, and which ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationEndpoint {
+    /// Webhook URL
+    pub url: String,
+    /// Message format
+    pub format: WebhookFormat,
+    /// Kinds to send (all kinds when empty)
+    #[serde(default)]
+    pub events: Vec<NotificationKind>,
+}
+
+impl NotificationEndpoint {
+    /// Validate the endpoint
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.url.starts_with("https://") && !self.url.starts_with("http://") {
+            return Err(format!("Notification URL {} must be http(s)", self.url));
+        }
+        Ok(())
+    }
+    
+    /// Whether this endpoint wants a kind of notification
+    pub fn accepts(&self, kind: NotificationKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+/// An event to notify operators of
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// What happened
+    pub kind: NotificationKind,
+    /// Human-readable summary
+    pub message: String,
+    /// Structured details for generic webhooks
+    pub details: Value,
+    /// Unix timestamp in seconds
+    pub timestamp: u64,
+}
+
+impl Notification {
+    /// Create a notification timestamped now
+    pub fn new(kind: NotificationKind, message: String, details: Value) -> Self {
+        Self {
+            kind,
+            message,
+            details,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+    
+    /// A trade landed
+    pub fn trade_executed(signature: &str, profit: u64) -> Self {
+        let link = format!("https://solscan.io/tx/{}", signature);
+        Self::new(
+            NotificationKind::TradeExecuted,
+            format!("Trade executed, profit {}: {}", profit, link),
+            json!({ "signature": signature, "profit": profit, "link": link }),
+        )
+    }
+    
+    /// A trade failed
+    pub fn trade_failed(reason: &str) -> Self {
+        Self::new(
+            NotificationKind::TradeFailed,
+            format!("Trade failed: {}", reason),
+            json!({ "reason": reason }),
+        )
+    }
+    
+    /// The circuit breaker halted trading
+    pub fn circuit_breaker_tripped(reason: &str) -> Self {
+        Self::new(
+            NotificationKind::CircuitBreakerTripped,
+            format!("Circuit breaker tripped: {}", reason),
+            json!({ "reason": reason }),
+        )
+    }
+    
+    /// Convert a bot event, if it's one operators are notified of
+    ///
+    /// This is the only path trade notifications take, so each trade is reported once.
+    pub fn from_bot_event(event: &BotEvent) -> Option<Self> {
+        match event {
+            // Paper fills and dry runs have no transaction to report
+            BotEvent::TradeExecuted { signature, .. } if signature.is_empty() => None,
+            BotEvent::TradeExecuted { signature, profit, .. } => Some(Self::trade_executed(signature, *profit)),
+            BotEvent::TradeFailed { reason, .. } => Some(Self::trade_failed(reason)),
+            BotEvent::LowOperationalBalance { wallet, balance, resume_at } => Some(Self::new(
+                NotificationKind::LowBalance,
+                format!("Operational wallet {} is down to {} lamports, trading paused until it holds {}", wallet, balance, resume_at),
+                json!({ "wallet": wallet.to_string(), "balance": balance, "resume_at": resume_at }),
+            )),
+            BotEvent::StatusChanged { to: BotStatus::Stopped, .. } => Some(Self::new(
+                NotificationKind::BotStopped,
+                "Bot stopped".to_string(),
+                json!({}),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Delivers notifications somewhere
+pub trait Notifier: Send {
+    /// Whether this notifier wants a kind of notification
+    fn accepts(&self, kind: NotificationKind) -> bool;
+    
+    /// Deliver a notification, blocking until it's sent
+    fn notify(&self, notification: &Notification) -> Result<(), String>;
+}
+
+/// Posts notifications to a webhook as JSON
+pub struct WebhookNotifier {
+    /// Endpoint to post to
+    endpoint: NotificationEndpoint,
+    /// HTTP client
+    client: HttpClient,
+    /// Runtime the requests run on
+    runtime: Runtime,
+}
+
+impl WebhookNotifier {
+    /// Create a notifier for an endpoint
+    pub fn new(endpoint: NotificationEndpoint) -> Result<Self, String> {
+        endpoint.validate()?;
+        
+        let client = HttpClient::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| format!("Failed to create notification runtime: {}", e))?;
+        
+        Ok(Self {
+            endpoint,
+            client,
+            runtime,
+        })
+    }
+    
+    /// Request body in the endpoint's format
+    fn payload(&self, notification: &Notification) -> Value {
+        match &self.endpoint.format {
+            WebhookFormat::Generic => json!({
+                "event": notification.kind,
+                "message": notification.message,
+                "timestamp": notification.timestamp,
+                "details": notification.details,
+            }),
+            WebhookFormat::Discord => json!({ "content": notification.message }),
+            WebhookFormat::Slack => json!({ "text": notification.message }),
+            WebhookFormat::Telegram { chat_id } => json!({ "chat_id": chat_id, "text": notification.message }),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn accepts(&self, kind: NotificationKind) -> bool {
+        self.endpoint.accepts(kind)
+    }
+    
+    fn notify(&self, notification: &Notification) -> Result<(), String> {
+        let request = self.client.post(&self.endpoint.url).json(&self.payload(notification));
+        
+        self.runtime.block_on(async {
+            request.send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map(|_| ())
+                .map_err(|e| format!("Webhook {} failed: {}", self.endpoint.url, e))
+        })
+    }
+}
+
+/// Queues notifications for a background thread so slow webhooks never block trading
+#[derive(Clone)]
+pub struct NotificationDispatcher {
+    /// Queue to the delivery thread
+    sender: SyncSender<Notification>,
+}
+
+impl NotificationDispatcher {
+    /// Start delivering to `notifiers` on a background thread
+    pub fn spawn(notifiers: Vec<Box<dyn Notifier>>) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<Notification>(NOTIFICATION_QUEUE_CAPACITY);
+        
+        // Exits once every dispatcher handle is dropped
+        thread::spawn(move || {
+            for notification in receiver {
+                for notifier in notifiers.iter().filter(|n| n.accepts(notification.kind)) {
+                    if let Err(e) = notifier.notify(&notification) {
+                        warn!("Failed to send {:?} notification: {}", notification.kind, e);
+                    }
+                }
+            }
+            debug!("Notification thread exited");
+        });
+        
+        Self { sender }
+    }
+    
+    /// Start delivering to a webhook per endpoint
+    pub fn from_endpoints(endpoints: &[NotificationEndpoint]) -> Result<Self, String> {
+        let notifiers = endpoints.iter()
+            .map(|endpoint| WebhookNotifier::new(endpoint.clone()).map(|n| Box::new(n) as Box<dyn Notifier>))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::spawn(notifiers))
+    }
+    
+    /// Queue a notification without waiting, dropping it if the queue is full
+    pub fn dispatch(&self, notification: Notification) {
+        match self.sender.try_send(notification) {
+            Ok(()) => {},
+            Err(TrySendError::Full(notification)) => {
+                warn!("Notification queue full, dropping {:?} notification", notification.kind);
+            },
+            Err(TrySendError::Disconnected(_)) => warn!("Notification thread is gone"),
+        }
+    }
+}
+
+/// Forward bot events to a dispatcher until the bot's event channel closes
+pub fn forward_bot_events(mut events: broadcast::Receiver<BotEvent>, dispatcher: NotificationDispatcher) -> JoinHandle<()> {
+    thread::spawn(move || {
+        loop {
+            match events.blocking_recv() {
+                Ok(event) => {
+                    if let Some(notification) = Notification::from_bot_event(&event) {
+                        dispatcher.dispatch(notification);
+                    }
+                },
+                Err(RecvError::Lagged(skipped)) => warn!("Notifications fell behind, skipped {} events", skipped),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+    use solana_sdk::pubkey::Pubkey;
+    
+    /// Notifier recording what it's sent, optionally holding each delivery until released
+    struct RecordingNotifier {
+        /// Kinds accepted (all when empty)
+        kinds: Vec<NotificationKind>,
+        /// Notifications delivered so far
+        delivered: Arc<Mutex<Vec<Notification>>>,
+        /// Held open while deliveries should block; each delivery first announces itself on `started`
+        gate: Option<(Mutex<mpsc::Receiver<()>>, SyncSender<()>)>,
+    }
+    
+    impl Notifier for RecordingNotifier {
+        fn accepts(&self, kind: NotificationKind) -> bool {
+            self.kinds.is_empty() || self.kinds.contains(&kind)
+        }
+        
+        fn notify(&self, notification: &Notification) -> Result<(), String> {
+            if let Some((gate, started)) = &self.gate {
+                let _ = started.try_send(());
+                // Returns at once once the gate's sender is dropped
+                let _ = gate.lock().unwrap().recv();
+            }
+            self.delivered.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+    }
+    
+    /// Recording notifier for `kinds`, and the list it records into
+    fn recorder(kinds: Vec<NotificationKind>) -> (Box<dyn Notifier>, Arc<Mutex<Vec<Notification>>>) {
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        (Box::new(RecordingNotifier { kinds, delivered: delivered.clone(), gate: None }), delivered)
+    }
+    
+    /// Wait until `delivered` holds `count` notifications, then return their kinds
+    fn delivered_kinds(delivered: &Mutex<Vec<Notification>>, count: usize) -> Vec<NotificationKind> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while delivered.lock().unwrap().len() < count && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        delivered.lock().unwrap().iter().map(|n| n.kind).collect()
+    }
+    
+    /// Endpoint in `format` for a URL nothing listens on
+    fn endpoint(format: WebhookFormat) -> NotificationEndpoint {
+        NotificationEndpoint {
+            url: "http://127.0.0.1:1/hook".to_string(),
+            format,
+            events: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn only_operator_facing_events_become_notifications() {
+        let kind = |event: BotEvent| Notification::from_bot_event(&event).map(|n| n.kind);
+        
+        assert_eq!(kind(BotEvent::TradeExecuted { signature: "sig".to_string(), profit: 10, execution_time_ms: 1 }), Some(NotificationKind::TradeExecuted));
+        assert_eq!(kind(BotEvent::TradeFailed { reason: "slippage".to_string(), execution_time_ms: 1 }), Some(NotificationKind::TradeFailed));
+        assert_eq!(kind(BotEvent::StatusChanged { from: BotStatus::Running, to: BotStatus::Stopped }), Some(NotificationKind::BotStopped));
+        assert_eq!(kind(BotEvent::LowOperationalBalance { wallet: Pubkey::new_unique(), balance: 1, resume_at: 2 }), Some(NotificationKind::LowBalance));
+        
+        // Paper fills, routine status changes and detections stay quiet
+        assert_eq!(kind(BotEvent::TradeExecuted { signature: String::new(), profit: 10, execution_time_ms: 1 }), None);
+        assert_eq!(kind(BotEvent::StatusChanged { from: BotStatus::Stopped, to: BotStatus::Running }), None);
+        assert_eq!(kind(BotEvent::OpportunityDetected { base_token: Pubkey::new_unique(), quote_token: Pubkey::new_unique(), profit_percentage: 1.0 }), None);
+    }
+    
+    #[test]
+    fn each_notifier_only_gets_the_kinds_it_asked_for() {
+        let (everything, all_delivered) = recorder(Vec::new());
+        let (failures, failures_delivered) = recorder(vec![NotificationKind::TradeFailed]);
+        let dispatcher = NotificationDispatcher::spawn(vec![everything, failures]);
+        
+        dispatcher.dispatch(Notification::trade_executed("sig", 10));
+        dispatcher.dispatch(Notification::trade_failed("slippage"));
+        dispatcher.dispatch(Notification::circuit_breaker_tripped("daily loss limit"));
+        
+        assert_eq!(delivered_kinds(&all_delivered, 3), vec![
+            NotificationKind::TradeExecuted,
+            NotificationKind::TradeFailed,
+            NotificationKind::CircuitBreakerTripped,
+        ]);
+        assert_eq!(delivered_kinds(&failures_delivered, 1), vec![NotificationKind::TradeFailed]);
+        
+        let mut endpoint = endpoint(WebhookFormat::Generic);
+        assert!(endpoint.accepts(NotificationKind::LowBalance));
+        endpoint.events = vec![NotificationKind::LowBalance];
+        assert!(endpoint.accepts(NotificationKind::LowBalance));
+        assert!(!endpoint.accepts(NotificationKind::TradeExecuted));
+    }
+    
+    #[test]
+    fn payloads_match_each_webhook_format() {
+        let notification = Notification::trade_executed("sig", 1_500);
+        let payload = |format: WebhookFormat| WebhookNotifier::new(endpoint(format)).unwrap().payload(&notification);
+        
+        let generic = payload(WebhookFormat::Generic);
+        assert_eq!(generic["event"], "trade_executed");
+        assert_eq!(generic["message"], notification.message);
+        assert_eq!(generic["timestamp"], notification.timestamp);
+        assert_eq!(generic["details"], json!({ "signature": "sig", "profit": 1_500, "link": "https://solscan.io/tx/sig" }));
+        
+        assert_eq!(payload(WebhookFormat::Discord), json!({ "content": notification.message }));
+        assert_eq!(payload(WebhookFormat::Slack), json!({ "text": notification.message }));
+        assert_eq!(
+            payload(WebhookFormat::Telegram { chat_id: "-100".to_string() }),
+            json!({ "chat_id": "-100", "text": notification.message }),
+        );
+    }
+    
+    #[test]
+    fn full_queue_drops_notifications_instead_of_blocking() {
+        let (release, gate) = mpsc::channel();
+        let (started_sender, started) = mpsc::sync_channel(1);
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let notifier = RecordingNotifier {
+            kinds: Vec::new(),
+            delivered: delivered.clone(),
+            gate: Some((Mutex::new(gate), started_sender)),
+        };
+        let dispatcher = NotificationDispatcher::spawn(vec![Box::new(notifier)]);
+        
+        // The first notification holds the delivery thread, then the queue fills behind it
+        dispatcher.dispatch(Notification::trade_failed("first"));
+        started.recv_timeout(Duration::from_secs(5)).unwrap();
+        let dispatched_at = Instant::now();
+        for i in 0..NOTIFICATION_QUEUE_CAPACITY + 10 {
+            dispatcher.dispatch(Notification::trade_failed(&i.to_string()));
+        }
+        assert!(dispatched_at.elapsed() < Duration::from_secs(1));
+        
+        drop(release);
+        let kinds = delivered_kinds(&delivered, NOTIFICATION_QUEUE_CAPACITY + 1);
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(kinds.len(), NOTIFICATION_QUEUE_CAPACITY + 1);
+        assert_eq!(delivered.lock().unwrap().len(), NOTIFICATION_QUEUE_CAPACITY + 1);
+        
+        // The overflow was dropped, not delivered late
+        let last = delivered.lock().unwrap().last().unwrap().message.clone();
+        assert_eq!(last, format!("Trade failed: {}", NOTIFICATION_QUEUE_CAPACITY - 1));
+    }
+    
+    #[test]
+    fn forwarded_trade_events_notify_once_and_stop_with_the_channel() {
+        let (notifier, delivered) = recorder(Vec::new());
+        let dispatcher = NotificationDispatcher::spawn(vec![notifier]);
+        let (events, receiver) = broadcast::channel(16);
+        let forwarder = forward_bot_events(receiver, dispatcher);
+        
+        events.send(BotEvent::TradeExecuted { signature: "sig".to_string(), profit: 10, execution_time_ms: 5 }).unwrap();
+        events.send(BotEvent::TradeExecuted { signature: String::new(), profit: 10, execution_time_ms: 5 }).unwrap();
+        events.send(BotEvent::TradeFailed { reason: "Stop-loss".to_string(), execution_time_ms: 5 }).unwrap();
+        drop(events);
+        forwarder.join().unwrap();
+        
+        let kinds = delivered_kinds(&delivered, 2);
+        assert_eq!(kinds, vec![NotificationKind::TradeExecuted, NotificationKind::TradeFailed]);
+    }
+}
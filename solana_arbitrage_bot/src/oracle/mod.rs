@@ -0,0 +1,356 @@
+// Oracle Module for Solana Flash Loan Arbitrage Bot
+// Reads reference prices from on-chain oracles to sanity-check DEX quotes
+
+use solana_sdk::pubkey::Pubkey;
+use solana_client::rpc_client::RpcClient;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use reqwest::Client as HttpClient;
+use serde_json::Value;
+use tokio::runtime::Handle;
+use log::{warn, debug};
+
+use crate::lock_or_recover;
+
+/// Default Jupiter price API endpoint (prices are quoted in USD)
+pub const JUPITER_PRICE_API_URL: &str = "https://api.jup.ag/price/v2";
+
+/// How long a fetched USD price is reused by default
+const DEFAULT_PRICE_TTL: Duration = Duration::from_secs(30);
+
+/// Magic number at the start of every Pyth v2 account
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+
+/// Offset of the price exponent in a Pyth v2 price account
+const PYTH_EXPO_OFFSET: usize = 20;
+
+/// Offset of the aggregate price in a Pyth v2 price account
+const PYTH_AGG_PRICE_OFFSET: usize = 208;
+
+/// Offset of the aggregate status in a Pyth v2 price account
+const PYTH_AGG_STATUS_OFFSET: usize = 224;
+
+/// Pyth aggregate status meaning the price is currently valid
+const PYTH_STATUS_TRADING: u32 = 1;
+
+/// Error type for oracle operations
+#[derive(Debug)]
+pub enum OracleError {
+    /// Error with RPC connection
+    RpcError(String),
+    /// Error decoding oracle data
+    DecodeError(String),
+    /// No price feed configured for the token
+    MissingFeed(String),
+    /// Quote deviates too far from the oracle price
+    DeviationError(String),
+}
+
+impl std::fmt::Display for OracleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OracleError::RpcError(msg) => write!(f, "RPC error: {}", msg),
+            OracleError::DecodeError(msg) => write!(f, "Decode error: {}", msg),
+            OracleError::MissingFeed(msg) => write!(f, "Missing feed: {}", msg),
+            OracleError::DeviationError(msg) => write!(f, "Deviation error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for OracleError {}
+
+/// Source of reference USD prices for token mints
+pub trait OracleSource {
+    /// Get the USD price of a token
+    fn get_price(&self, token_mint: &Pubkey) -> Result<f64, OracleError>;
+    
+    /// Whether this source has a feed for the token
+    fn has_feed(&self, token_mint: &Pubkey) -> bool;
+}
+
+/// Pyth oracle reading v2 price accounts over RPC
+pub struct PythOracle {
+    /// RPC client for Solana
+    rpc_client: RpcClient,
+    /// Pyth price account by token mint
+    price_accounts: HashMap<Pubkey, Pubkey>,
+}
+
+impl PythOracle {
+    /// Create a new Pyth oracle
+    pub fn new(rpc_url: &str) -> Self {
+        Self::with_rpc_client(RpcClient::new(rpc_url.to_string()))
+    }
+    
+    /// Create a new Pyth oracle over an existing RPC client
+    pub fn with_rpc_client(rpc_client: RpcClient) -> Self {
+        Self {
+            rpc_client,
+            price_accounts: HashMap::new(),
+        }
+    }
+    
+    /// Register the Pyth price account for a token mint
+    pub fn add_price_account(&mut self, token_mint: Pubkey, price_account: Pubkey) {
+        self.price_accounts.insert(token_mint, price_account);
+    }
+}
+
+impl OracleSource for PythOracle {
+    fn get_price(&self, token_mint: &Pubkey) -> Result<f64, OracleError> {
+        let price_account = self.price_accounts.get(token_mint)
+            .ok_or_else(|| OracleError::MissingFeed(format!("No Pyth feed for mint {}", token_mint)))?;
+        
+        let data = self.rpc_client.get_account_data(price_account)
+            .map_err(|e| OracleError::RpcError(format!("Failed to fetch Pyth account: {}", e)))?;
+        
+        decode_pyth_price(&data)
+    }
+    
+    fn has_feed(&self, token_mint: &Pubkey) -> bool {
+        self.price_accounts.contains_key(token_mint)
+    }
+}
+
+/// USD valuation of token mints, used to report profits in USD
+pub trait PriceOracle: Send + Sync {
+    /// Get the USD price of one whole token
+    fn get_usd_price(&self, token_mint: &Pubkey) -> Result<f64, OracleError>;
+}
+
+/// USD prices from the Jupiter price API, cached for a short TTL
+///
+/// Blocks on the given runtime, so call it from blocking threads only.
+pub struct JupiterPriceOracle {
+    /// Price API endpoint
+    api_url: String,
+    /// HTTP client for the price API
+    http_client: HttpClient,
+    /// Runtime requests are driven on
+    runtime: Handle,
+    /// How long a fetched price is reused
+    ttl: Duration,
+    /// Fetched prices and when they were fetched
+    cache: Mutex<HashMap<Pubkey, (f64, Instant)>>,
+}
+
+impl JupiterPriceOracle {
+    /// Create a new Jupiter price oracle against the public API
+    pub fn new(runtime: Handle) -> Self {
+        Self::with_api_url(JUPITER_PRICE_API_URL, DEFAULT_PRICE_TTL, runtime)
+    }
+    
+    /// Create a new Jupiter price oracle against a custom endpoint and cache TTL
+    pub fn with_api_url(api_url: &str, ttl: Duration, runtime: Handle) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            http_client: HttpClient::new(),
+            runtime,
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    /// Fetch a price from the API
+    async fn fetch_price(&self, token_mint: &Pubkey) -> Result<f64, OracleError> {
+        let url = format!("{}?ids={}", self.api_url, token_mint);
+        
+        let json: Value = self.http_client.get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| OracleError::RpcError(format!("Price request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| OracleError::DecodeError(format!("Failed to parse price response: {}", e)))?;
+        
+        // Prices come back as decimal strings keyed by mint
+        let price = &json["data"][token_mint.to_string()]["price"];
+        let price = price.as_str()
+            .and_then(|price| price.parse::<f64>().ok())
+            .or_else(|| price.as_f64())
+            .ok_or_else(|| OracleError::MissingFeed(format!("No Jupiter price for mint {}", token_mint)))?;
+        
+        if price <= 0.0 {
+            return Err(OracleError::DecodeError(format!("Invalid Jupiter price {} for {}", price, token_mint)));
+        }
+        
+        Ok(price)
+    }
+}
+
+impl PriceOracle for JupiterPriceOracle {
+    fn get_usd_price(&self, token_mint: &Pubkey) -> Result<f64, OracleError> {
+        if let Some((price, fetched_at)) = lock_or_recover(&self.cache).get(token_mint) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(*price);
+            }
+        }
+        
+        let price = self.runtime.block_on(self.fetch_price(token_mint))?;
+        debug!("Jupiter USD price for {}: {}", token_mint, price);
+        
+        lock_or_recover(&self.cache).insert(*token_mint, (price, Instant::now()));
+        
+        Ok(price)
+    }
+}
+
+impl OracleSource for JupiterPriceOracle {
+    fn get_price(&self, token_mint: &Pubkey) -> Result<f64, OracleError> {
+        self.get_usd_price(token_mint)
+    }
+    
+    fn has_feed(&self, _token_mint: &Pubkey) -> bool {
+        true
+    }
+}
+
+/// Decode the aggregate price from a Pyth v2 price account
+pub fn decode_pyth_price(data: &[u8]) -> Result<f64, OracleError> {
+    let read = |offset: usize, len: usize| {
+        data.get(offset..offset + len)
+            .ok_or_else(|| OracleError::DecodeError("Pyth account data too short".to_string()))
+    };
+    
+    let magic = u32::from_le_bytes(read(0, 4)?.try_into().unwrap_or_default());
+    if magic != PYTH_MAGIC {
+        return Err(OracleError::DecodeError("Not a Pyth account".to_string()));
+    }
+    
+    let status = u32::from_le_bytes(read(PYTH_AGG_STATUS_OFFSET, 4)?.try_into().unwrap_or_default());
+    if status != PYTH_STATUS_TRADING {
+        return Err(OracleError::DecodeError(format!("Pyth price not trading (status {})", status)));
+    }
+    
+    let expo = i32::from_le_bytes(read(PYTH_EXPO_OFFSET, 4)?.try_into().unwrap_or_default());
+    let price = i64::from_le_bytes(read(PYTH_AGG_PRICE_OFFSET, 8)?.try_into().unwrap_or_default());
+    
+    if price <= 0 {
+        return Err(OracleError::DecodeError(format!("Invalid Pyth price {}", price)));
+    }
+    
+    Ok(price as f64 * 10f64.powi(expo))
+}
+
+/// Rejects DEX quotes that stray too far from the oracle price
+pub struct OracleGuard {
+    /// Oracle price source
+    source: Box<dyn OracleSource + Send + Sync>,
+    /// Maximum allowed deviation from the oracle price, in percent
+    max_oracle_deviation_pct: f64,
+}
+
+impl OracleGuard {
+    /// Create a new oracle guard
+    pub fn new(source: Box<dyn OracleSource + Send + Sync>, max_oracle_deviation_pct: f64) -> Self {
+        Self {
+            source,
+            max_oracle_deviation_pct,
+        }
+    }
+    
+    /// Get the oracle price of `base_token` denominated in `quote_token`
+    ///
+    /// Quote tokens without a feed are assumed to be USD stablecoins.
+    pub fn reference_price(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<f64, OracleError> {
+        let base_usd = self.source.get_price(base_token)?;
+        
+        let quote_usd = if self.source.has_feed(quote_token) {
+            self.source.get_price(quote_token)?
+        } else {
+            1.0
+        };
+        
+        Ok(base_usd / quote_usd)
+    }
+    
+    /// Check that every quoted price is within the allowed deviation from the oracle
+    pub fn check_prices(&self, base_token: &Pubkey, quote_token: &Pubkey, prices: &[f64]) -> Result<(), OracleError> {
+        let reference = self.reference_price(base_token, quote_token)?;
+        
+        for price in prices {
+            let deviation = ((price - reference) / reference).abs() * 100.0;
+            debug!("Quote {} vs oracle {} for {}/{}: {:.2}% deviation", price, reference, base_token, quote_token, deviation);
+            
+            if deviation > self.max_oracle_deviation_pct {
+                warn!("Rejecting quote {} for {}/{}: {:.2}% from oracle price {}", price, base_token, quote_token, deviation, reference);
+                return Err(OracleError::DeviationError(format!(
+                    "Quote {} deviates {:.2}% from oracle price {} (max {}%)",
+                    price, deviation, reference, self.max_oracle_deviation_pct
+                )));
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Oracle source with fixed USD prices
+    struct MockOracle {
+        prices: HashMap<Pubkey, f64>,
+    }
+    
+    impl OracleSource for MockOracle {
+        fn get_price(&self, token_mint: &Pubkey) -> Result<f64, OracleError> {
+            self.prices.get(token_mint).copied()
+                .ok_or_else(|| OracleError::MissingFeed(token_mint.to_string()))
+        }
+        
+        fn has_feed(&self, token_mint: &Pubkey) -> bool {
+            self.prices.contains_key(token_mint)
+        }
+    }
+    
+    /// Guard allowing 5% deviation over a mock oracle pricing `base` at $100
+    fn guard(base: Pubkey) -> OracleGuard {
+        let prices = HashMap::from([(base, 100.0)]);
+        OracleGuard::new(Box::new(MockOracle { prices }), 5.0)
+    }
+    
+    /// Pyth v2 price account with the given aggregate price, exponent and status
+    fn pyth_account(price: i64, expo: i32, status: u32) -> Vec<u8> {
+        let mut data = vec![0u8; PYTH_AGG_STATUS_OFFSET + 4];
+        data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[PYTH_EXPO_OFFSET..PYTH_EXPO_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+        data[PYTH_AGG_PRICE_OFFSET..PYTH_AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[PYTH_AGG_STATUS_OFFSET..PYTH_AGG_STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+        data
+    }
+    
+    #[test]
+    fn quotes_near_the_oracle_pass() {
+        let (base, quote) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let guard = guard(base);
+        
+        // The quote token has no feed, so it's treated as $1
+        assert_eq!(guard.reference_price(&base, &quote).unwrap(), 100.0);
+        assert!(guard.check_prices(&base, &quote, &[100.0, 97.0, 104.5]).is_ok());
+    }
+    
+    #[test]
+    fn quote_20_percent_off_is_rejected() {
+        let (base, quote) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let guard = guard(base);
+        
+        assert!(matches!(guard.check_prices(&base, &quote, &[100.0, 120.0]), Err(OracleError::DeviationError(_))));
+        assert!(matches!(guard.check_prices(&base, &quote, &[80.0]), Err(OracleError::DeviationError(_))));
+        
+        // No feed for the base token means no reference, not a pass
+        assert!(matches!(guard.check_prices(&quote, &base, &[0.01]), Err(OracleError::MissingFeed(_))));
+    }
+    
+    #[test]
+    fn pyth_price_is_scaled_by_its_exponent() {
+        let price = decode_pyth_price(&pyth_account(12_345_000_000, -8, PYTH_STATUS_TRADING)).unwrap();
+        assert!((price - 123.45).abs() < 1e-9);
+        assert!(decode_pyth_price(&pyth_account(12_345_000_000, -8, 0)).is_err());
+        assert!(decode_pyth_price(&pyth_account(-1, -8, PYTH_STATUS_TRADING)).is_err());
+        assert!(decode_pyth_price(&[0u8; 16]).is_err());
+    }
+}
@@ -5,22 +5,102 @@ use solana_sdk::{
     pubkey::Pubkey,
     signature::Keypair,
     transaction::Transaction,
+    instruction::Instruction,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::lock_or_recover;
+
+/// Seconds in a UTC day, for the daily P&L breakdown
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Basis points in a whole withdrawal
+pub const TOTAL_WITHDRAWAL_BPS: u32 = 10_000;
+
+/// Serializes withdrawal recipients as a list of `{ wallet, bps }` with base58 wallets
+mod serde_recipients {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+    
+    #[derive(Serialize, Deserialize)]
+    struct Recipient {
+        wallet: String,
+        bps: u16,
+    }
+    
+    pub fn serialize<S: Serializer>(recipients: &[(Pubkey, u16)], serializer: S) -> Result<S::Ok, S::Error> {
+        recipients.iter()
+            .map(|(wallet, bps)| Recipient { wallet: wallet.to_string(), bps: *bps })
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(Pubkey, u16)>, D::Error> {
+        Vec::<Recipient>::deserialize(deserializer)?
+            .into_iter()
+            .map(|recipient| {
+                Pubkey::from_str(&recipient.wallet)
+                    .map(|wallet| (wallet, recipient.bps))
+                    .map_err(|e| serde::de::Error::custom(format!("Invalid pubkey {}: {}", recipient.wallet, e)))
+            })
+            .collect()
+    }
+}
+
+/// Capability to sign and send transactions with locally held keys
+///
+/// Kept narrow so profit management doesn't depend on the wallet module.
+pub trait TransactionSender {
+    /// Sign `instructions` with the keypairs of `signers` and send them, returning the signature
+    fn sign_and_send(&self, instructions: Vec<Instruction>, signers: Vec<&Pubkey>) -> Result<String, String>;
+    
+    /// Create the owner's associated token account for `mint` if needed, returning its address
+    fn ensure_token_account(&self, owner: &Pubkey, mint: &Pubkey) -> Result<Pubkey, String>;
+}
 
 /// Configuration for profit distribution
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProfitDistributionConfig {
     /// Percentage of profits to reinvest (0-100)
     pub reinvestment_percentage: u8,
-    /// Percentage of profits to withdraw to owner wallet (0-100)
+    /// Percentage of profits to withdraw to the recipients (0-100)
     pub withdrawal_percentage: u8,
     /// Percentage of profits to keep as reserve (0-100)
     pub reserve_percentage: u8,
-    /// Owner wallet address for profit withdrawals
+    /// Owner wallet address, which receives reserve withdrawals and, with no recipients listed, all profit withdrawals
+    #[serde(with = "crate::serde_pubkey")]
     pub owner_wallet: Pubkey,
+    /// Wallets splitting the withdrawal share, by weight in basis points summing to 10000
+    #[serde(default, with = "serde_recipients")]
+    pub withdrawal_recipients: Vec<(Pubkey, u16)>,
     /// Minimum profit amount required before distribution (in lamports)
     pub min_distribution_amount: u64,
+    /// When distribution runs automatically (disabled by default)
+    #[serde(default)]
+    pub schedule: DistributionSchedule,
+}
+
+/// Triggers for automatic profit distribution
+///
+/// Distribution runs when either trigger fires; with neither set it only runs on demand.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct DistributionSchedule {
+    /// Distribute every this many hours
+    pub interval_hours: Option<u64>,
+    /// Distribute once any token's undistributed profit reaches this amount
+    pub threshold: Option<u64>,
+}
+
+impl DistributionSchedule {
+    /// Whether either trigger is set
+    pub fn is_enabled(&self) -> bool {
+        self.interval_hours.is_some() || self.threshold.is_some()
+    }
 }
 
 impl ProfitDistributionConfig {
@@ -32,28 +112,113 @@ impl ProfitDistributionConfig {
         owner_wallet: Pubkey,
         min_distribution_amount: u64,
     ) -> Result<Self, String> {
-        // Validate that percentages add up to 100
-        if reinvestment_percentage + withdrawal_percentage + reserve_percentage != 100 {
-            return Err("Profit distribution percentages must add up to 100".to_string());
-        }
-        
-        Ok(Self {
+        Self::with_recipients(
             reinvestment_percentage,
             withdrawal_percentage,
             reserve_percentage,
             owner_wallet,
+            vec![(owner_wallet, TOTAL_WITHDRAWAL_BPS as u16)],
             min_distribution_amount,
-        })
+        )
+    }
+    
+    /// Create a configuration that splits withdrawals among several wallets by basis points
+    pub fn with_recipients(
+        reinvestment_percentage: u8,
+        withdrawal_percentage: u8,
+        reserve_percentage: u8,
+        owner_wallet: Pubkey,
+        withdrawal_recipients: Vec<(Pubkey, u16)>,
+        min_distribution_amount: u64,
+    ) -> Result<Self, String> {
+        let config = Self {
+            reinvestment_percentage,
+            withdrawal_percentage,
+            reserve_percentage,
+            owner_wallet,
+            withdrawal_recipients,
+            min_distribution_amount,
+            schedule: DistributionSchedule::default(),
+        };
+        
+        config.validate()?;
+        
+        Ok(config)
+    }
+    
+    /// Withdrawal recipients, falling back to the owner alone when none are listed
+    pub fn recipients(&self) -> Vec<(Pubkey, u16)> {
+        if self.withdrawal_recipients.is_empty() {
+            vec![(self.owner_wallet, TOTAL_WITHDRAWAL_BPS as u16)]
+        } else {
+            self.withdrawal_recipients.clone()
+        }
+    }
+    
+    /// Split a withdrawal among the recipients by weight
+    ///
+    /// Rounding dust goes to the last recipient so the shares add up to `amount`.
+    pub fn split_withdrawal(&self, amount: u64) -> Vec<(Pubkey, u64)> {
+        let recipients = self.recipients();
+        let mut remaining = amount;
+        
+        recipients.iter()
+            .enumerate()
+            .map(|(index, (wallet, bps))| {
+                let share = if index + 1 == recipients.len() {
+                    remaining
+                } else {
+                    (amount as u128 * *bps as u128 / TOTAL_WITHDRAWAL_BPS as u128) as u64
+                };
+                remaining -= share;
+                (*wallet, share)
+            })
+            .collect()
+    }
+    
+    /// Validate the configuration
+    pub fn validate(&self) -> Result<(), String> {
+        // Validate that percentages add up to 100
+        if self.reinvestment_percentage as u32 + self.withdrawal_percentage as u32 + self.reserve_percentage as u32 != 100 {
+            return Err("Profit distribution percentages must add up to 100".to_string());
+        }
+        
+        // The all-zeros key is unspendable, withdrawals to it would burn the profits
+        if self.owner_wallet == Pubkey::default() {
+            return Err("Owner wallet must not be the default (all-zeros) public key".to_string());
+        }
+        
+        if !self.withdrawal_recipients.is_empty() {
+            let total_bps: u32 = self.withdrawal_recipients.iter().map(|(_, bps)| *bps as u32).sum();
+            if total_bps != TOTAL_WITHDRAWAL_BPS {
+                return Err(format!("Withdrawal recipient weights must add up to {} bps, got {}", TOTAL_WITHDRAWAL_BPS, total_bps));
+            }
+            
+            if self.withdrawal_recipients.iter().any(|(wallet, _)| *wallet == Pubkey::default()) {
+                return Err("Withdrawal recipients must not include the default (all-zeros) public key".to_string());
+            }
+            
+            let unique: HashSet<&Pubkey> = self.withdrawal_recipients.iter().map(|(wallet, _)| wallet).collect();
+            if unique.len() != self.withdrawal_recipients.len() {
+                return Err("Withdrawal recipients must be listed once each".to_string());
+            }
+        }
+        
+        Ok(())
     }
     
     /// Create a default profit distribution configuration (70% reinvest, 30% withdraw)
+    ///
+    /// The owner wallet is not checked here, call `validate` before use.
     pub fn default(owner_wallet: Pubkey) -> Self {
         Self {
             reinvestment_percentage: 70,
             withdrawal_percentage: 30,
             reserve_percentage: 0,
             owner_wallet,
+            withdrawal_recipients: vec![(owner_wallet, TOTAL_WITHDRAWAL_BPS as u16)],
             min_distribution_amount: 1_000_000, // 0.001 SOL in lamports
+            schedule: DistributionSchedule::default(),
         }
     }
 }
@@ -124,16 +289,150 @@ impl TokenProfit {
     }
 }
 
+/// A profitable trade as recorded for reporting
+#[derive(Debug, Clone)]
+struct ProfitRecord {
+    /// Unix timestamp in seconds
+    timestamp: u64,
+    /// Token the profit was made in
+    token_mint: Pubkey,
+    /// Profit in the token's smallest unit
+    amount: u64,
+    /// USD value of the profit when it was booked (in cents)
+    usd_value: u64,
+}
+
+/// Profit held since a trade, with the USD value it was booked at
+#[derive(Debug, Clone)]
+struct TaxLot {
+    /// Amount still held, in the token's smallest unit
+    amount: u64,
+    /// Cost basis of the remaining amount (in cents)
+    cost_basis_usd: u64,
+}
+
+/// Amount taken out of lots when profit is withdrawn
+#[derive(Debug, Clone)]
+struct LotDisposal {
+    /// Unix timestamp in seconds
+    timestamp: u64,
+    /// Token withdrawn
+    token_mint: Pubkey,
+    /// Amount withdrawn, in the token's smallest unit
+    amount: u64,
+    /// Cost basis of the lots consumed (in cents)
+    cost_basis_usd: u64,
+}
+
+/// Transfer planned by a distribution
+#[derive(Debug, Clone)]
+struct PlannedTransfer {
+    /// Destination wallet
+    to: Pubkey,
+    /// Amount in the token's smallest unit
+    amount: u64,
+    /// Whether the transfer has been sent
+    completed: bool,
+}
+
+/// Distribution of one token that has started moving funds
+///
+/// Kept until every transfer has gone through, so a retry after a failed
+/// transfer resumes the same plan instead of paying completed shares again.
+#[derive(Debug, Clone)]
+struct PendingDistribution {
+    /// Undistributed profit the plan was built from
+    amount: u64,
+    /// Share reinvested
+    reinvest_amount: u64,
+    /// Share withdrawn to the recipients
+    withdraw_amount: u64,
+    /// Share kept as reserve
+    reserve_amount: u64,
+    /// Withdrawal and reserve transfers, in the order they're sent
+    transfers: Vec<PlannedTransfer>,
+}
+
+/// Realized profit for one token over a reporting period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPnl {
+    /// Token mint address
+    #[serde(with = "crate::serde_pubkey")]
+    pub token_mint: Pubkey,
+    /// Profitable trades in the period
+    pub trade_count: u64,
+    /// Realized profit in the token's smallest unit
+    pub realized_amount: u64,
+    /// Realized profit in USD, valued when each trade was booked
+    pub realized_usd: f64,
+    /// Amount withdrawn in the period, in the token's smallest unit
+    pub withdrawn_amount: u64,
+    /// Cost basis of the withdrawn lots in USD
+    pub withdrawn_cost_basis_usd: f64,
+    /// Amount still held in open lots, in the token's smallest unit
+    pub open_amount: u64,
+    /// Cost basis of the open lots in USD
+    pub open_cost_basis_usd: f64,
+}
+
+/// Realized profit for one UTC day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyPnl {
+    /// Unix timestamp of the day's start (00:00 UTC)
+    pub day_start: u64,
+    /// Profitable trades that day
+    pub trade_count: u64,
+    /// Realized profit in USD, valued when each trade was booked
+    pub realized_usd: f64,
+}
+
+/// Realized profit report for a time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlReport {
+    /// Start of the range (Unix seconds, inclusive)
+    pub from_ts: u64,
+    /// End of the range (Unix seconds, inclusive)
+    pub to_ts: u64,
+    /// Realized profit across every token in USD
+    pub total_realized_usd: f64,
+    /// Breakdown by token
+    pub tokens: Vec<TokenPnl>,
+    /// Breakdown by UTC day, oldest first
+    pub daily: Vec<DailyPnl>,
+}
+
+/// Current Unix time in seconds
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 /// Profit management system
 pub struct ProfitManager {
     /// Configuration for profit distribution
     config: ProfitDistributionConfig,
-    /// Profit tracking by token
-    token_profits: HashMap<Pubkey, TokenProfit>,
+    /// Profit tracking by token, ordered by mint so distributions run in a fixed order
+    token_profits: BTreeMap<Pubkey, TokenProfit>,
     /// Total SOL profit in lamports
     total_sol_profit: u64,
     /// Total USD value of profit (in cents)
     total_usd_profit: u64,
+    /// Every recorded profit, oldest first
+    trade_log: Vec<ProfitRecord>,
+    /// Open lots per token, oldest first (consumed FIFO on withdrawal)
+    lots: HashMap<Pubkey, VecDeque<TaxLot>>,
+    /// Withdrawals and the cost basis they consumed
+    disposals: Vec<LotDisposal>,
+    /// Profit storage wallet that withdrawal and reserve shares are paid from
+    profit_wallet: Option<Pubkey>,
+    /// Reserve wallet the reserve share is moved to (if configured)
+    reserve_wallet: Option<Pubkey>,
+    /// Distributions interrupted by a failed transfer, by token
+    pending_distributions: HashMap<Pubkey, PendingDistribution>,
+    /// Amount held in reserve per token
+    reserve_balances: HashMap<Pubkey, u64>,
 }
 
 impl ProfitManager {
@@ -141,12 +440,29 @@ impl ProfitManager {
     pub fn new(config: ProfitDistributionConfig) -> Self {
         Self {
             config,
-            token_profits: HashMap::new(),
+            token_profits: BTreeMap::new(),
             total_sol_profit: 0,
             total_usd_profit: 0,
+            trade_log: Vec::new(),
+            lots: HashMap::new(),
+            disposals: Vec::new(),
+            profit_wallet: None,
+            reserve_wallet: None,
+            pending_distributions: HashMap::new(),
+            reserve_balances: HashMap::new(),
         }
     }
     
+    /// Pay withdrawal and reserve shares out of `profit_wallet`
+    pub fn set_profit_wallet(&mut self, profit_wallet: Pubkey) {
+        self.profit_wallet = Some(profit_wallet);
+    }
+    
+    /// Move the reserve share of each distribution to `reserve_wallet`
+    pub fn set_reserve_wallet(&mut self, reserve_wallet: Pubkey) {
+        self.reserve_wallet = Some(reserve_wallet);
+    }
+    
     /// Record profit for a specific token
     pub fn record_profit(&mut self, token_mint: Pubkey, amount: u64, sol_value: u64, usd_value: u64) {
         // Update token-specific profit
@@ -156,6 +472,18 @@ impl ProfitManager {
         
         token_profit.record_profit(amount);
         
+        // Profit stays in the bot as a lot until it's withdrawn
+        self.trade_log.push(ProfitRecord {
+            timestamp: unix_now(),
+            token_mint,
+            amount,
+            usd_value,
+        });
+        self.lots.entry(token_mint).or_default().push_back(TaxLot {
+            amount,
+            cost_basis_usd: usd_value,
+        });
+        
         // Update total profits
         self.total_sol_profit += sol_value;
         self.total_usd_profit += usd_value;
@@ -171,36 +499,84 @@ impl ProfitManager {
     }
     
     /// Distribute profits according to configuration
-    pub fn distribute_profits(&mut self, wallet_manager: &WalletManager) -> Result<DistributionResult, String> {
+    ///
+    /// Tokens are distributed in ascending mint order, so identical state always produces
+    /// the same result and the same sequence of transfers.
+    pub fn distribute_profits(&mut self, wallet_manager: &dyn TransactionSender) -> Result<DistributionResult, String> {
+        // Never build withdrawals to an unspendable owner wallet
+        self.config.validate()?;
+        
         let mut result = DistributionResult {
             reinvested_amount: 0,
             withdrawn_amount: 0,
             reserved_amount: 0,
         };
         
-        // Iterate through all tokens with undistributed profits
+        // Iterate through all tokens with undistributed profits, in mint order
         for (token_mint, token_profit) in &mut self.token_profits {
-            if token_profit.undistributed_profit < self.config.min_distribution_amount {
-                continue; // Skip if below minimum distribution amount
-            }
+            // A distribution interrupted by a failed transfer resumes its original plan
+            let mut pending = match self.pending_distributions.remove(token_mint) {
+                Some(pending) => pending,
+                None => {
+                    if token_profit.undistributed_profit < self.config.min_distribution_amount {
+                        continue; // Skip if below minimum distribution amount
+                    }
+                    
+                    let amount = token_profit.undistributed_profit;
+                    
+                    // Calculate amounts based on percentages
+                    let reinvest_amount = (amount * self.config.reinvestment_percentage as u64) / 100;
+                    let withdraw_amount = (amount * self.config.withdrawal_percentage as u64) / 100;
+                    let reserve_amount = amount - reinvest_amount - withdraw_amount;
+                    
+                    // Fan the withdrawal out to each recipient, then segregate the reserve share
+                    let mut transfers: Vec<PlannedTransfer> = self.config.split_withdrawal(withdraw_amount)
+                        .into_iter()
+                        .filter(|(_, share)| *share > 0)
+                        .map(|(to, amount)| PlannedTransfer { to, amount, completed: false })
+                        .collect();
+                    if let Some(reserve_wallet) = self.reserve_wallet.filter(|_| reserve_amount > 0) {
+                        transfers.push(PlannedTransfer { to: reserve_wallet, amount: reserve_amount, completed: false });
+                    }
+                    
+                    PendingDistribution { amount, reinvest_amount, withdraw_amount, reserve_amount, transfers }
+                }
+            };
             
-            let amount_to_distribute = token_profit.undistributed_profit;
+            // Nothing is marked distributed until every share has been paid
+            if !pending.transfers.is_empty() {
+                let profit_wallet = self.profit_wallet
+                    .ok_or_else(|| "No profit wallet configured to distribute from".to_string())?;
+                
+                if let Err(e) = send_planned_transfers(wallet_manager, token_mint, &profit_wallet, &mut pending) {
+                    self.pending_distributions.insert(*token_mint, pending);
+                    return Err(e);
+                }
+            }
             
-            // Calculate amounts based on percentages
-            let reinvest_amount = (amount_to_distribute * self.config.reinvestment_percentage as u64) / 100;
-            let withdraw_amount = (amount_to_distribute * self.config.withdrawal_percentage as u64) / 100;
-            let reserve_amount = amount_to_distribute - reinvest_amount - withdraw_amount;
+            let PendingDistribution { amount: amount_to_distribute, reinvest_amount, withdraw_amount, reserve_amount, .. } = pending;
+            if reserve_amount > 0 {
+                *self.reserve_balances.entry(*token_mint).or_insert(0) += reserve_amount;
+            }
             
             // Update token profit tracking
             token_profit.distribute_profit(amount_to_distribute)?;
             
+            // Withdrawn profit leaves the bot; reinvested and reserved profit keeps its lots
+            if withdraw_amount > 0 {
+                let cost_basis_usd = consume_lots(self.lots.entry(*token_mint).or_default(), withdraw_amount);
+                self.disposals.push(LotDisposal {
+                    timestamp: unix_now(),
+                    token_mint: *token_mint,
+                    amount: withdraw_amount,
+                    cost_basis_usd,
+                });
+            }
+            
             // Update result
             result.reinvested_amount += reinvest_amount;
             result.withdrawn_amount += withdraw_amount;
             result.reserved_amount += reserve_amount;
-            
-            // TODO: Implement actual token transfers using wallet_manager
-            // This would involve creating and sending transactions
         }
         
         Ok(result)
@@ -222,9 +598,16 @@ impl ProfitManager {
             (total_successful_trades as f64 / (total_successful_trades + total_failed_trades) as f64) * 100.0
         };
         
+        // Each token's reserve in its own units; they can't be added together
+        let mut reserve_balances: Vec<ReserveBalance> = self.reserve_balances.iter()
+            .map(|(token_mint, amount)| ReserveBalance { token_mint: *token_mint, amount: *amount })
+            .collect();
+        reserve_balances.sort_by_key(|reserve| reserve.token_mint);
+        
         ProfitStatistics {
             total_sol_profit: self.total_sol_profit,
             total_usd_profit: self.total_usd_profit,
+            reserve_balances,
             total_successful_trades,
             total_failed_trades,
             overall_success_rate,
@@ -236,9 +619,176 @@ impl ProfitManager {
     pub fn update_config(&mut self, config: ProfitDistributionConfig) {
         self.config = config;
     }
+    
+    /// Whether the schedule calls for a distribution, `since_last` after the previous one
+    pub fn distribution_due(&self, since_last: Duration) -> bool {
+        let schedule = &self.config.schedule;
+        
+        let interval_elapsed = schedule.interval_hours
+            .map_or(false, |hours| since_last >= Duration::from_secs(hours * 3600));
+        let threshold_crossed = schedule.threshold.map_or(false, |threshold| {
+            self.token_profits.values().any(|token_profit| token_profit.undistributed_profit >= threshold)
+        });
+        
+        interval_elapsed || threshold_crossed
+    }
+    
+    /// Distribution schedule from the configuration
+    pub fn schedule(&self) -> DistributionSchedule {
+        self.config.schedule
+    }
+    
+    /// Amount of a token held in reserve
+    pub fn reserve_balance(&self, token_mint: &Pubkey) -> u64 {
+        self.reserve_balances.get(token_mint).copied().unwrap_or(0)
+    }
+    
+    /// Move reserved funds to the owner wallet
+    ///
+    /// The owner wallet is the only destination, so the reserve can't be
+    /// drained into trading capital or anywhere else.
+    pub fn withdraw_from_reserve(&mut self, token_mint: Pubkey, amount: u64, wallet_manager: &dyn TransactionSender) -> Result<String, String> {
+        self.config.validate()?;
+        
+        let reserve_wallet = self.reserve_wallet
+            .ok_or_else(|| "No reserve wallet configured".to_string())?;
+        
+        let available = self.reserve_balance(&token_mint);
+        if amount == 0 || amount > available {
+            return Err(format!("Cannot withdraw {} from reserve - only {} available", amount, available));
+        }
+        
+        let signature = transfer_token(wallet_manager, &token_mint, &reserve_wallet, &self.config.owner_wallet, amount)?;
+        self.reserve_balances.insert(token_mint, available - amount);
+        
+        Ok(signature)
+    }
+    
+    /// Realized profit between two Unix timestamps (inclusive)
+    ///
+    /// Each trade is valued at the USD value it was booked at, the same basis
+    /// its lot carries, so reports don't move with later prices.
+    pub fn realized_pnl_report(&self, from_ts: u64, to_ts: u64) -> PnlReport {
+        let mut tokens: BTreeMap<Pubkey, TokenPnl> = BTreeMap::new();
+        let mut daily: BTreeMap<u64, DailyPnl> = BTreeMap::new();
+        
+        let empty_token = |token_mint: Pubkey| TokenPnl {
+            token_mint,
+            trade_count: 0,
+            realized_amount: 0,
+            realized_usd: 0.0,
+            withdrawn_amount: 0,
+            withdrawn_cost_basis_usd: 0.0,
+            open_amount: 0,
+            open_cost_basis_usd: 0.0,
+        };
+        
+        for record in self.trade_log.iter().filter(|r| r.timestamp >= from_ts && r.timestamp <= to_ts) {
+            let usd = record.usd_value as f64 / 100.0;
+            
+            let token = tokens.entry(record.token_mint).or_insert_with(|| empty_token(record.token_mint));
+            token.trade_count += 1;
+            token.realized_amount += record.amount;
+            token.realized_usd += usd;
+            
+            let day_start = record.timestamp - record.timestamp % SECONDS_PER_DAY;
+            let day = daily.entry(day_start).or_insert(DailyPnl {
+                day_start,
+                trade_count: 0,
+                realized_usd: 0.0,
+            });
+            day.trade_count += 1;
+            day.realized_usd += usd;
+        }
+        
+        for disposal in self.disposals.iter().filter(|d| d.timestamp >= from_ts && d.timestamp <= to_ts) {
+            let token = tokens.entry(disposal.token_mint).or_insert_with(|| empty_token(disposal.token_mint));
+            token.withdrawn_amount += disposal.amount;
+            token.withdrawn_cost_basis_usd += disposal.cost_basis_usd as f64 / 100.0;
+        }
+        
+        // Open lots are reported as of now, for every token in the report
+        for (token_mint, token) in tokens.iter_mut() {
+            if let Some(lots) = self.lots.get(token_mint) {
+                token.open_amount = lots.iter().map(|lot| lot.amount).sum();
+                token.open_cost_basis_usd = lots.iter().map(|lot| lot.cost_basis_usd).sum::<u64>() as f64 / 100.0;
+            }
+        }
+        
+        PnlReport {
+            from_ts,
+            to_ts,
+            total_realized_usd: tokens.values().map(|token| token.realized_usd).sum(),
+            tokens: tokens.into_values().collect(),
+            daily: daily.into_values().collect(),
+        }
+    }
+}
+
+/// Transfer `amount` of a token between two wallets' associated token accounts, signed by `from`
+fn transfer_token(
+    wallet_manager: &dyn TransactionSender,
+    token_mint: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    amount: u64,
+) -> Result<String, String> {
+    let source = get_associated_token_address(from, token_mint);
+    let destination = wallet_manager.ensure_token_account(to, token_mint)?;
+    
+    let instruction = spl_token::instruction::transfer(&spl_token::id(), &source, &destination, from, &[], amount)
+        .map_err(|e| format!("Failed to build token transfer: {}", e))?;
+    
+    wallet_manager.sign_and_send(vec![instruction], vec![from])
+}
+
+/// Send a distribution's outstanding transfers from `from` in order, marking each one sent
+///
+/// Stops at the first failure, leaving it and the rest outstanding.
+fn send_planned_transfers(
+    wallet_manager: &dyn TransactionSender,
+    token_mint: &Pubkey,
+    from: &Pubkey,
+    pending: &mut PendingDistribution,
+) -> Result<(), String> {
+    for transfer in pending.transfers.iter_mut().filter(|transfer| !transfer.completed) {
+        transfer_token(wallet_manager, token_mint, from, &transfer.to, transfer.amount)?;
+        transfer.completed = true;
+    }
+    
+    Ok(())
+}
+
+/// Take `amount` out of lots oldest-first, returning the cost basis consumed (in cents)
+///
+/// Partially consumed lots keep the proportional share of their cost basis.
+fn consume_lots(lots: &mut VecDeque<TaxLot>, mut amount: u64) -> u64 {
+    let mut cost_basis_usd = 0;
+    
+    while amount > 0 {
+        let lot = match lots.front_mut() {
+            Some(lot) => lot,
+            None => break,
+        };
+        
+        if lot.amount <= amount {
+            amount -= lot.amount;
+            cost_basis_usd += lot.cost_basis_usd;
+            lots.pop_front();
+        } else {
+            let share = (lot.cost_basis_usd as u128 * amount as u128 / lot.amount as u128) as u64;
+            lot.amount -= amount;
+            lot.cost_basis_usd -= share;
+            cost_basis_usd += share;
+            amount = 0;
+        }
+    }
+    
+    cost_basis_usd
 }
 
 /// Result of profit distribution
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DistributionResult {
     /// Amount reinvested
     pub reinvested_amount: u64,
@@ -248,12 +798,26 @@ pub struct DistributionResult {
     pub reserved_amount: u64,
 }
 
+/// Amount of one token held in the reserve wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReserveBalance {
+    /// Token mint
+    #[serde(with = "crate::serde_pubkey")]
+    pub token_mint: Pubkey,
+    /// Amount held, in the token's smallest unit
+    pub amount: u64,
+}
+
 /// Profit statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProfitStatistics {
     /// Total SOL profit in lamports
     pub total_sol_profit: u64,
     /// Total USD value of profit (in cents)
     pub total_usd_profit: u64,
+    /// Amounts held in the reserve wallet, per token in ascending mint order
+    #[serde(default)]
+    pub reserve_balances: Vec<ReserveBalance>,
     /// Total number of successful trades
     pub total_successful_trades: u64,
     /// Total number of failed trades
@@ -264,10 +828,8 @@ pub struct ProfitStatistics {
     pub token_count: u64,
 }
 
-// This is a placeholder for the WalletManager that will be implemented in the wallet_integration module
-pub struct WalletManager;
-
 /// Thread-safe wrapper for ProfitManager
+#[derive(Clone)]
 pub struct ThreadSafeProfitManager {
     inner: Arc<Mutex<ProfitManager>>,
 }
@@ -282,34 +844,321 @@ impl ThreadSafeProfitManager {
     
     /// Record profit (thread-safe)
     pub fn record_profit(&self, token_mint: Pubkey, amount: u64, sol_value: u64, usd_value: u64) -> Result<(), String> {
-        let mut manager = self.inner.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let mut manager = lock_or_recover(&self.inner);
         manager.record_profit(token_mint, amount, sol_value, usd_value);
         Ok(())
     }
     
     /// Record failed trade (thread-safe)
     pub fn record_failed_trade(&self, token_mint: Pubkey) -> Result<(), String> {
-        let mut manager = self.inner.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let mut manager = lock_or_recover(&self.inner);
         manager.record_failed_trade(token_mint);
         Ok(())
     }
     
     /// Distribute profits (thread-safe)
-    pub fn distribute_profits(&self, wallet_manager: &WalletManager) -> Result<DistributionResult, String> {
-        let mut manager = self.inner.lock().map_err(|e| format!("Lock error: {}", e))?;
+    pub fn distribute_profits(&self, wallet_manager: &dyn TransactionSender) -> Result<DistributionResult, String> {
+        let mut manager = lock_or_recover(&self.inner);
         manager.distribute_profits(wallet_manager)
     }
     
     /// Get profit statistics (thread-safe)
     pub fn get_statistics(&self) -> Result<ProfitStatistics, String> {
-        let manager = self.inner.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let manager = lock_or_recover(&self.inner);
         Ok(manager.get_statistics())
     }
     
+    /// Whether the schedule calls for a distribution (thread-safe)
+    pub fn distribution_due(&self, since_last: Duration) -> Result<bool, String> {
+        let manager = lock_or_recover(&self.inner);
+        Ok(manager.distribution_due(since_last))
+    }
+    
+    /// Distribution schedule from the configuration (thread-safe)
+    pub fn schedule(&self) -> Result<DistributionSchedule, String> {
+        let manager = lock_or_recover(&self.inner);
+        Ok(manager.schedule())
+    }
+    
+    /// Set the profit wallet that withdrawal and reserve shares are paid from (thread-safe)
+    pub fn set_profit_wallet(&self, profit_wallet: Pubkey) -> Result<(), String> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.set_profit_wallet(profit_wallet);
+        Ok(())
+    }
+    
+    /// Set the reserve wallet the reserve share is moved to (thread-safe)
+    pub fn set_reserve_wallet(&self, reserve_wallet: Pubkey) -> Result<(), String> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.set_reserve_wallet(reserve_wallet);
+        Ok(())
+    }
+    
+    /// Move reserved funds to the owner wallet (thread-safe)
+    pub fn withdraw_from_reserve(&self, token_mint: Pubkey, amount: u64, wallet_manager: &dyn TransactionSender) -> Result<String, String> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.withdraw_from_reserve(token_mint, amount, wallet_manager)
+    }
+    
+    /// Realized profit report for a time range (thread-safe)
+    pub fn realized_pnl_report(&self, from_ts: u64, to_ts: u64) -> Result<PnlReport, String> {
+        let manager = lock_or_recover(&self.inner);
+        Ok(manager.realized_pnl_report(from_ts, to_ts))
+    }
+    
     /// Update distribution configuration (thread-safe)
     pub fn update_config(&self, config: ProfitDistributionConfig) -> Result<(), String> {
-        let mut manager = self.inner.lock().map_err(|e| format!("Lock error: {}", e))?;
+        config.validate()?;
+        let mut manager = lock_or_recover(&self.inner);
         manager.update_config(config);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    
+    /// Transaction sender that records every transfer instead of sending it
+    #[derive(Default)]
+    struct RecordingSender {
+        /// Instructions sent, with their signers
+        sent: RefCell<Vec<(Vec<Instruction>, Vec<Pubkey>)>>,
+        /// Fail the next send once this many more have gone through
+        fail_after: Cell<Option<usize>>,
+    }
+    
+    impl TransactionSender for RecordingSender {
+        fn sign_and_send(&self, instructions: Vec<Instruction>, signers: Vec<&Pubkey>) -> Result<String, String> {
+            match self.fail_after.get() {
+                Some(0) => {
+                    self.fail_after.set(None);
+                    return Err("Failed to send transaction: blockhash expired".to_string());
+                },
+                Some(remaining) => self.fail_after.set(Some(remaining - 1)),
+                None => {},
+            }
+            
+            let mut sent = self.sent.borrow_mut();
+            sent.push((instructions, signers.into_iter().copied().collect()));
+            Ok(format!("signature-{}", sent.len()))
+        }
+        
+        fn ensure_token_account(&self, owner: &Pubkey, mint: &Pubkey) -> Result<Pubkey, String> {
+            Ok(get_associated_token_address(owner, mint))
+        }
+    }
+    
+    #[test]
+    fn default_owner_wallet_fails_construction_and_distribution() {
+        let config = ProfitDistributionConfig::default(Pubkey::default());
+        assert!(config.validate().is_err());
+        assert!(ProfitDistributionConfig::new(70, 30, 0, Pubkey::default(), 1).is_err());
+        assert!(ProfitDistributionConfig::with_recipients(70, 30, 0, Pubkey::new_unique(), vec![(Pubkey::default(), 10_000)], 1).is_err());
+        
+        // A manager built around the check still refuses to move anything
+        let mut manager = ProfitManager::new(config);
+        let mint = Pubkey::new_unique();
+        manager.record_profit(mint, 10_000_000, 0, 0);
+        
+        let sender = RecordingSender::default();
+        assert!(manager.distribute_profits(&sender).is_err());
+        assert!(sender.sent.borrow().is_empty());
+        
+        let statistics = manager.get_statistics();
+        assert_eq!(statistics.total_successful_trades, 1);
+        assert_eq!(manager.token_profits[&mint].undistributed_profit, 10_000_000);
+        
+        // The thread-safe wrapper won't take the bad config either
+        let owner = Pubkey::new_unique();
+        let shared = ThreadSafeProfitManager::new(ProfitDistributionConfig::default(owner));
+        assert!(shared.update_config(ProfitDistributionConfig::default(Pubkey::default())).is_err());
+    }
+    
+    /// Destination token account and amount of each transfer sent, in order
+    fn sent_transfers(sender: &RecordingSender) -> Vec<(Pubkey, u64)> {
+        sender.sent.borrow().iter()
+            .map(|(instructions, _)| {
+                let amount = match spl_token::instruction::TokenInstruction::unpack(&instructions[0].data).unwrap() {
+                    spl_token::instruction::TokenInstruction::Transfer { amount } => amount,
+                    other => panic!("Unexpected instruction {:?}", other),
+                };
+                (instructions[0].accounts[1].pubkey, amount)
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn retried_distribution_pays_each_share_once() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let config = ProfitDistributionConfig::with_recipients(50, 40, 10, alice, vec![(alice, 5_000), (bob, 5_000)], 1).unwrap();
+        let mut manager = ProfitManager::new(config);
+        let (profit_wallet, reserve_wallet) = (Pubkey::new_unique(), Pubkey::new_unique());
+        manager.set_profit_wallet(profit_wallet);
+        manager.set_reserve_wallet(reserve_wallet);
+        
+        let mint = Pubkey::new_unique();
+        manager.record_profit(mint, 1_000, 0, 0);
+        
+        // Alice is paid, then Bob's transfer fails and nothing is marked distributed
+        let sender = RecordingSender::default();
+        sender.fail_after.set(Some(1));
+        assert!(manager.distribute_profits(&sender).is_err());
+        assert_eq!(manager.token_profits[&mint].undistributed_profit, 1_000);
+        assert_eq!(manager.reserve_balance(&mint), 0);
+        
+        // Profit booked in between waits for the next distribution
+        manager.record_profit(mint, 500, 0, 0);
+        
+        // The retry picks up at Bob
+        let result = manager.distribute_profits(&sender).unwrap();
+        assert_eq!(result.withdrawn_amount, 400);
+        assert_eq!(result.reserved_amount, 100);
+        
+        let ata = |owner: &Pubkey| get_associated_token_address(owner, &mint);
+        assert_eq!(sent_transfers(&sender), vec![(ata(&alice), 200), (ata(&bob), 200), (ata(&reserve_wallet), 100)]);
+        assert!(sender.sent.borrow().iter().all(|(_, signers)| signers == &vec![profit_wallet]));
+        assert_eq!(manager.token_profits[&mint].undistributed_profit, 500);
+        assert_eq!(manager.reserve_balance(&mint), 100);
+    }
+    
+    #[test]
+    fn reserves_are_reported_per_token() {
+        let config = ProfitDistributionConfig::new(0, 50, 50, Pubkey::new_unique(), 1).unwrap();
+        let mut manager = ProfitManager::new(config);
+        manager.set_profit_wallet(Pubkey::new_unique());
+        manager.set_reserve_wallet(Pubkey::new_unique());
+        
+        // A 9-decimal and a 6-decimal token, whose amounts mean nothing summed
+        let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+        manager.record_profit(sol, 2_000_000_000, 0, 0);
+        manager.record_profit(usdc, 3_000_000, 0, 0);
+        manager.distribute_profits(&RecordingSender::default()).unwrap();
+        
+        let mut expected = vec![
+            ReserveBalance { token_mint: sol, amount: 1_000_000_000 },
+            ReserveBalance { token_mint: usdc, amount: 1_500_000 },
+        ];
+        expected.sort_by_key(|reserve| reserve.token_mint);
+        assert_eq!(manager.get_statistics().reserve_balances, expected);
+    }
+    
+    #[test]
+    fn withdrawals_are_paid_without_a_reserve_wallet() {
+        let owner = Pubkey::new_unique();
+        let mut manager = ProfitManager::new(ProfitDistributionConfig::new(0, 100, 0, owner, 1).unwrap());
+        let mint = Pubkey::new_unique();
+        manager.record_profit(mint, 1_000, 0, 0);
+        
+        // Without a wallet to pay from nothing moves
+        let sender = RecordingSender::default();
+        assert!(manager.distribute_profits(&sender).is_err());
+        assert!(sender.sent.borrow().is_empty());
+        
+        let profit_wallet = Pubkey::new_unique();
+        manager.set_profit_wallet(profit_wallet);
+        manager.distribute_profits(&sender).unwrap();
+        assert_eq!(sent_transfers(&sender), vec![(get_associated_token_address(&owner, &mint), 1_000)]);
+        assert_eq!(manager.token_profits[&mint].undistributed_profit, 0);
+    }
+    
+    #[test]
+    fn identical_state_distributes_identically() {
+        let owner = Pubkey::new_unique();
+        let profit_wallet = Pubkey::new_unique();
+        let mints: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let profit = |mint: &Pubkey| 1_000 * (mints.iter().position(|m| m == mint).unwrap() as u64 + 1);
+        
+        // Same profits booked in a different order each run
+        let run = |booking_order: &[Pubkey]| {
+            let mut manager = ProfitManager::new(ProfitDistributionConfig::new(50, 50, 0, owner, 1).unwrap());
+            manager.set_profit_wallet(profit_wallet);
+            for mint in booking_order {
+                manager.record_profit(*mint, profit(mint), 0, 0);
+            }
+            
+            let sender = RecordingSender::default();
+            let result = manager.distribute_profits(&sender).unwrap();
+            (result.reinvested_amount, result.withdrawn_amount, result.reserved_amount, sent_transfers(&sender))
+        };
+        
+        let first = run(&mints);
+        let mut reversed = mints.clone();
+        reversed.reverse();
+        let second = run(&reversed);
+        assert_eq!(first.0 + first.1, 10_000);
+        assert_eq!((first.0, first.1, first.2), (second.0, second.1, second.2));
+        assert_eq!(first.3.len(), 4);
+        assert_eq!(first.3, second.3);
+        
+        // Transfers go out in ascending mint order
+        let mut sorted = mints.clone();
+        sorted.sort();
+        let destinations: Vec<Pubkey> = first.3.iter().map(|(to, _)| *to).collect();
+        let expected: Vec<Pubkey> = sorted.iter().map(|mint| get_associated_token_address(&owner, mint)).collect();
+        assert_eq!(destinations, expected);
+        
+        // When sends run out partway, the same tokens are the ones distributed
+        let distributed_before_failure = |booking_order: &[Pubkey]| {
+            let mut manager = ProfitManager::new(ProfitDistributionConfig::new(0, 100, 0, owner, 1).unwrap());
+            manager.set_profit_wallet(profit_wallet);
+            for mint in booking_order {
+                manager.record_profit(*mint, 1_000, 0, 0);
+            }
+            
+            let sender = RecordingSender::default();
+            sender.fail_after.set(Some(2));
+            assert!(manager.distribute_profits(&sender).is_err());
+            booking_order.iter()
+                .filter(|mint| manager.token_profits[*mint].undistributed_profit == 0)
+                .copied()
+                .collect::<std::collections::BTreeSet<Pubkey>>()
+        };
+        let distributed = distributed_before_failure(&reversed);
+        assert_eq!(distributed, distributed_before_failure(&mints));
+        assert_eq!(distributed, sorted[..2].iter().copied().collect());
+    }
+    
+    #[test]
+    fn manager_keeps_working_after_a_holder_panics() {
+        let shared = ThreadSafeProfitManager::new(ProfitDistributionConfig::default(Pubkey::new_unique()));
+        let mint = Pubkey::new_unique();
+        shared.record_profit(mint, 1_000, 0, 0).unwrap();
+        
+        let inner = shared.inner.clone();
+        let panicked = std::thread::spawn(move || {
+            let _manager = inner.lock().unwrap();
+            panic!("panicked holding the profit manager");
+        }).join();
+        assert!(panicked.is_err());
+        assert!(shared.inner.is_poisoned());
+        
+        shared.record_profit(mint, 500, 0, 0).unwrap();
+        let statistics = shared.get_statistics().unwrap();
+        assert_eq!(statistics.total_successful_trades, 2);
+        assert!(!shared.inner.is_poisoned());
+    }
+    
+    #[test]
+    fn pnl_report_values_trades_at_their_booked_cost_basis() {
+        let mut manager = ProfitManager::new(ProfitDistributionConfig::default(Pubkey::new_unique()));
+        let mint = Pubkey::new_unique();
+        
+        // The same amount booked at two different prices
+        manager.record_profit(mint, 1_000_000, 0, 15_000);
+        manager.record_profit(mint, 1_000_000, 0, 20_000);
+        
+        let report = manager.realized_pnl_report(0, u64::MAX);
+        assert_eq!(report.tokens.len(), 1);
+        assert_eq!(report.tokens[0].trade_count, 2);
+        assert_eq!(report.tokens[0].realized_amount, 2_000_000);
+        assert_eq!(report.tokens[0].realized_usd, 350.0);
+        assert_eq!(report.total_realized_usd, 350.0);
+        assert_eq!(report.daily.iter().map(|day| day.realized_usd).sum::<f64>(), 350.0);
+        
+        // Realized and open figures agree on the same basis
+        assert_eq!(report.tokens[0].open_amount, 2_000_000);
+        assert_eq!(report.tokens[0].open_cost_basis_usd, 350.0);
+    }
+}
@@ -0,0 +1,208 @@
+// Blockhash Cache for Solana Flash Loan Arbitrage Bot
+// Keeps a recent blockhash on hand so sending a transaction doesn't wait on a fetch
+
+use solana_sdk::hash::Hash;
+use solana_client::client_error::Result as ClientResult;
+use solana_client::rpc_client::RpcClient;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use log::{warn, debug};
+
+use crate::lock_or_recover;
+
+/// How often the background ticker fetches a new blockhash
+pub const DEFAULT_BLOCKHASH_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Blocks a blockhash stays valid for after it's produced
+const BLOCKHASH_VALIDITY_BLOCKS: u32 = 150;
+
+/// Blocks before expiry a cached blockhash stops being served, leaving a transaction time to land
+const BLOCKHASH_EXPIRY_MARGIN_BLOCKS: u32 = 60;
+
+/// Typical block time, for estimating how many blocks a cached blockhash has left
+const BLOCK_DURATION: Duration = Duration::from_millis(400);
+
+/// A blockhash and when it was fetched
+#[derive(Debug, Clone, Copy)]
+struct CachedBlockhash {
+    /// The blockhash
+    hash: Hash,
+    /// When it was fetched
+    fetched_at: Instant,
+}
+
+/// Recent blockhash refreshed in the background and served to transaction builders
+///
+/// A cached hash is served until it nears the end of its 150-block lifetime. Past
+/// that, or before the first refresh, `get` fetches one synchronously instead.
+pub struct BlockhashCache {
+    /// RPC client blockhashes are fetched with
+    rpc_client: RpcClient,
+    /// Latest fetched blockhash
+    cached: Mutex<Option<CachedBlockhash>>,
+    /// How long a blockhash is served after it's fetched
+    max_age: Duration,
+}
+
+impl BlockhashCache {
+    /// Create an empty cache; nothing is fetched until `refresh` or `get` is called
+    pub fn new(rpc_client: RpcClient) -> Self {
+        Self {
+            rpc_client,
+            cached: Mutex::new(None),
+            max_age: BLOCK_DURATION * (BLOCKHASH_VALIDITY_BLOCKS - BLOCKHASH_EXPIRY_MARGIN_BLOCKS),
+        }
+    }
+    
+    /// Create a cache refreshed every `interval` on a background thread
+    ///
+    /// The thread exits once the last handle to the cache is dropped.
+    pub fn spawn(rpc_client: RpcClient, interval: Duration) -> (Arc<Self>, JoinHandle<()>) {
+        let cache = Arc::new(Self::new(rpc_client));
+        let weak = Arc::downgrade(&cache);
+        
+        let handle = thread::spawn(move || {
+            while let Some(cache) = weak.upgrade() {
+                if let Err(e) = cache.refresh() {
+                    warn!("Failed to refresh blockhash: {}", e);
+                }
+                drop(cache);
+                thread::sleep(interval);
+            }
+            debug!("Blockhash refresh thread exited");
+        });
+        
+        (cache, handle)
+    }
+    
+    /// Fetch a new blockhash into the cache
+    pub fn refresh(&self) -> ClientResult<Hash> {
+        let hash = self.rpc_client.get_latest_blockhash()?;
+        *lock_or_recover(&self.cached) = Some(CachedBlockhash { hash, fetched_at: Instant::now() });
+        Ok(hash)
+    }
+    
+    /// The cached blockhash while it has blocks to spare, otherwise a freshly fetched one
+    pub fn get(&self) -> ClientResult<Hash> {
+        let cached = *lock_or_recover(&self.cached);
+        match cached {
+            Some(cached) if cached.fetched_at.elapsed() < self.max_age => Ok(cached.hash),
+            _ => {
+                debug!("No fresh cached blockhash, fetching one");
+                self.refresh()
+            }
+        }
+    }
+    
+    /// Drop the cached blockhash so the next `get` fetches a new one (e.g. after "Blockhash not found")
+    pub fn invalidate(&self) {
+        *lock_or_recover(&self.cached) = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+    use solana_client::rpc_client::RpcClientConfig;
+    use solana_client::rpc_request::RpcRequest;
+    use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+    use solana_sdk::commitment_config::CommitmentConfig;
+    use std::sync::atomic::{AtomicU8, Ordering};
+    
+    /// RPC sender answering each blockhash request with a new hash, counting the fetches
+    struct CountingBlockhashes {
+        /// Blockhash requests answered so far
+        fetches: Arc<AtomicU8>,
+    }
+    
+    #[async_trait]
+    impl RpcSender for CountingBlockhashes {
+        async fn send(&self, request: RpcRequest, _params: Value) -> ClientResult<Value> {
+            assert_eq!(request, RpcRequest::GetLatestBlockhash);
+            let fetch = self.fetches.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(json!({
+                "context": {"slot": fetch},
+                "value": {"blockhash": nth_hash(fetch).to_string(), "lastValidBlockHeight": 150 + fetch as u64},
+            }))
+        }
+        
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+        
+        fn url(&self) -> String {
+            "counting-blockhashes".to_string()
+        }
+    }
+    
+    /// Blockhash served by the `n`th fetch
+    fn nth_hash(n: u8) -> Hash {
+        Hash::new_from_array([n; 32])
+    }
+    
+    /// RPC client over a `CountingBlockhashes` sender, and its fetch count
+    fn counting_client() -> (RpcClient, Arc<AtomicU8>) {
+        let fetches = Arc::new(AtomicU8::new(0));
+        let sender = CountingBlockhashes { fetches: fetches.clone() };
+        (RpcClient::new_sender(sender, RpcClientConfig::with_commitment(CommitmentConfig::confirmed())), fetches)
+    }
+    
+    #[test]
+    fn refresh_replaces_the_hash_get_serves() {
+        let (rpc_client, fetches) = counting_client();
+        let cache = BlockhashCache::new(rpc_client);
+        
+        // Nothing cached yet, so the first get fetches
+        assert_eq!(cache.get().unwrap(), nth_hash(1));
+        assert_eq!(cache.get().unwrap(), nth_hash(1));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        
+        assert_eq!(cache.refresh().unwrap(), nth_hash(2));
+        assert_eq!(cache.get().unwrap(), nth_hash(2));
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        
+        cache.invalidate();
+        assert_eq!(cache.get().unwrap(), nth_hash(3));
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+    }
+    
+    #[test]
+    fn stale_hash_is_refetched() {
+        let (rpc_client, fetches) = counting_client();
+        let mut cache = BlockhashCache::new(rpc_client);
+        cache.max_age = Duration::from_millis(50);
+        
+        cache.refresh().unwrap();
+        assert_eq!(cache.get().unwrap(), nth_hash(1));
+        
+        // Past its serving window the cached hash is fetched again rather than served
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get().unwrap(), nth_hash(2));
+        assert_eq!(cache.get().unwrap(), nth_hash(2));
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+    
+    #[test]
+    fn background_ticker_keeps_the_hash_current() {
+        let (rpc_client, fetches) = counting_client();
+        let (cache, handle) = BlockhashCache::spawn(rpc_client, Duration::from_millis(10));
+        
+        let started = Instant::now();
+        while fetches.load(Ordering::SeqCst) < 3 {
+            assert!(started.elapsed() < Duration::from_secs(5), "ticker stopped refreshing");
+            thread::sleep(Duration::from_millis(5));
+        }
+        
+        // Served straight from the cache, which has moved past the first hash
+        let served = cache.get().unwrap();
+        assert_ne!(served, nth_hash(1));
+        assert!(served.to_bytes()[0] <= fetches.load(Ordering::SeqCst));
+        
+        // Dropping the cache stops the ticker
+        drop(cache);
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,547 @@
+// RPC Utilities Module for Solana Flash Loan Arbitrage Bot
+// Shared helpers for dealing with RPC endpoint limits and failures
+
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_client::client_error::{ClientError, ClientErrorKind, Result as ClientResult};
+use solana_client::http_sender::HttpSender;
+use solana_client::rpc_client::{RpcClient, RpcClientConfig};
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::future::Future;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use log::{info, warn, debug};
+
+use crate::lock_or_recover;
+
+pub mod blockhash;
+
+/// How long a failed endpoint is skipped before it's tried again
+const DEFAULT_ENDPOINT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Check whether an error message indicates the endpoint is rate limiting us
+pub fn is_rate_limit_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("429")
+        || message.contains("too many requests")
+        || message.contains("rate limit")
+}
+
+/// Check whether an error message indicates the transaction's blockhash expired
+pub fn is_blockhash_not_found_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("blockhash not found")
+        || message.contains("blockhashnotfound")
+}
+
+/// How an RPC or HTTP failure should be handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Transport blip, timeout or rate limit; the same request may succeed if retried
+    Transient,
+    /// The transaction's blockhash expired; retryable only after re-signing with a fresh one
+    StaleBlockhash,
+    /// Retrying can't help (insufficient funds, program errors, bad requests)
+    Permanent,
+}
+
+/// Classify an error by its message
+///
+/// Anything not recognised as transient is treated as permanent, so unknown
+/// failures surface instead of being hammered.
+pub fn classify_error(message: &str) -> ErrorClass {
+    let lower = message.to_lowercase();
+    
+    const PERMANENT: [&str; 5] = [
+        "insufficient funds",
+        "insufficient lamports",
+        "custom program error",
+        "already been processed",
+        "alreadyprocessed",
+    ];
+    const TRANSIENT: [&str; 9] = [
+        "timed out",
+        "timeout",
+        "connection reset",
+        "connection refused",
+        "error sending request",
+        "http 502",
+        "http 503",
+        "http 504",
+        "temporarily unavailable",
+    ];
+    
+    if PERMANENT.iter().any(|marker| lower.contains(marker)) {
+        ErrorClass::Permanent
+    } else if is_blockhash_not_found_error(&lower) {
+        ErrorClass::StaleBlockhash
+    } else if is_rate_limit_error(&lower) || TRANSIENT.iter().any(|marker| lower.contains(marker)) {
+        ErrorClass::Transient
+    } else {
+        ErrorClass::Permanent
+    }
+}
+
+/// Retry policy for RPC and HTTP calls
+///
+/// Only transient errors are retried. Callers must only wrap requests that
+/// are safe to repeat: reads, or resubmitting an already-signed transaction,
+/// which can land at most once.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry in milliseconds
+    pub base_delay_ms: u64,
+    /// Maximum delay between attempts in milliseconds
+    pub max_delay_ms: u64,
+    /// Fraction of each delay randomised, from 0.0 (none) to 1.0
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 100,
+            max_delay_ms: 2_000,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+    
+    /// Delay before retry number `retry` (starting at 1)
+    pub fn delay(&self, retry: u32) -> Duration {
+        let exponential = self.base_delay_ms.saturating_mul(1u64 << retry.saturating_sub(1).min(20));
+        let capped = exponential.min(self.max_delay_ms) as f64;
+        
+        // Spread retries out so clients that failed together don't retry together
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let noise = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos() as f64 / 1e9;
+        let factor = 1.0 - jitter + 2.0 * jitter * noise;
+        
+        Duration::from_millis((capped * factor) as u64)
+    }
+    
+    /// Whether a failed attempt should be retried
+    fn should_retry<E: std::fmt::Display>(&self, attempt: u32, error: &E, operation: &str) -> bool {
+        if attempt >= self.max_attempts || classify_error(&error.to_string()) != ErrorClass::Transient {
+            return false;
+        }
+        
+        debug!("{} failed (attempt {}/{}), retrying: {}", operation, attempt, self.max_attempts, error);
+        true
+    }
+    
+    /// Run a blocking call, retrying transient failures with backoff
+    pub fn retry<T, E, F>(&self, operation: &str, mut call: F) -> Result<T, E>
+    where
+        E: std::fmt::Display,
+        F: FnMut() -> Result<T, E>,
+    {
+        let mut attempt = 1;
+        loop {
+            match call() {
+                Err(e) if self.should_retry(attempt, &e, operation) => {
+                    std::thread::sleep(self.delay(attempt));
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+    
+    /// Run an async call, retrying transient failures with backoff
+    pub async fn retry_async<T, E, F, Fut>(&self, operation: &str, mut call: F) -> Result<T, E>
+    where
+        E: std::fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Err(e) if self.should_retry(attempt, &e, operation) => {
+                    tokio::time::sleep(self.delay(attempt)).await;
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Adaptive throttle for polling loops
+///
+/// Backs off multiplicatively when the endpoint rate limits us and decays
+/// back toward the configured interval after a run of successful calls.
+pub struct AdaptiveThrottle {
+    /// Configured interval in milliseconds
+    base_interval_ms: u64,
+    /// Current effective interval in milliseconds
+    current_interval_ms: u64,
+    /// Upper bound for the effective interval in milliseconds
+    max_interval_ms: u64,
+    /// Multiplier applied on each rate-limit response
+    backoff_factor: f64,
+    /// Multiplier applied when decaying back toward the base interval
+    decay_factor: f64,
+    /// Consecutive successes required before each decay step
+    successes_before_decay: u32,
+    /// Consecutive successes since the last rate limit or decay step
+    success_streak: u32,
+}
+
+impl AdaptiveThrottle {
+    /// Create a new throttle around the configured interval
+    pub fn new(base_interval_ms: u64) -> Self {
+        Self {
+            base_interval_ms,
+            current_interval_ms: base_interval_ms,
+            max_interval_ms: base_interval_ms.saturating_mul(32).max(base_interval_ms),
+            backoff_factor: 2.0,
+            decay_factor: 0.8,
+            successes_before_decay: 5,
+            success_streak: 0,
+        }
+    }
+    
+    /// Get the current effective interval in milliseconds
+    pub fn current_interval_ms(&self) -> u64 {
+        self.current_interval_ms
+    }
+    
+    /// Record a rate-limit response and back off
+    pub fn record_rate_limit(&mut self) {
+        self.success_streak = 0;
+        
+        let next = ((self.current_interval_ms.max(1) as f64) * self.backoff_factor) as u64;
+        let next = next.min(self.max_interval_ms);
+        
+        if next != self.current_interval_ms {
+            warn!("RPC rate limited, increasing interval from {}ms to {}ms", self.current_interval_ms, next);
+            self.current_interval_ms = next;
+        }
+    }
+    
+    /// Record a successful call and decay back toward the base interval
+    pub fn record_success(&mut self) {
+        if self.current_interval_ms <= self.base_interval_ms {
+            return;
+        }
+        
+        self.success_streak += 1;
+        if self.success_streak < self.successes_before_decay {
+            return;
+        }
+        
+        self.success_streak = 0;
+        let next = ((self.current_interval_ms as f64) * self.decay_factor) as u64;
+        self.current_interval_ms = next.max(self.base_interval_ms);
+        
+        if self.current_interval_ms == self.base_interval_ms {
+            info!("RPC rate limiting subsided, interval back to {}ms", self.base_interval_ms);
+        }
+    }
+    
+    /// Record the outcome of a call, classifying errors by their message
+    pub fn record_result<T, E: std::fmt::Display>(&mut self, result: &Result<T, E>) {
+        match result {
+            Ok(_) => self.record_success(),
+            Err(e) if is_rate_limit_error(&e.to_string()) => self.record_rate_limit(),
+            Err(_) => {},
+        }
+    }
+}
+
+/// Check whether an RPC error means the endpoint itself is unusable, rather than the request
+///
+/// Transport failures and rate limiting fail over; errors the node returned
+/// for the request (e.g. a failed simulation) would fail the same way anywhere.
+pub fn is_endpoint_error(error: &ClientError) -> bool {
+    matches!(error.kind(), ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_))
+        || is_rate_limit_error(&error.to_string())
+}
+
+/// Health of one RPC endpoint
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    /// Consecutive failed requests
+    consecutive_failures: u32,
+    /// When a failed endpoint may be tried again
+    cooling_until: Option<Instant>,
+}
+
+/// One RPC endpoint in the failover set
+struct Endpoint {
+    /// Endpoint URL
+    url: String,
+    /// HTTP transport for the endpoint
+    sender: HttpSender,
+    /// Failure tracking
+    health: Mutex<EndpointHealth>,
+}
+
+impl Endpoint {
+    /// Whether the endpoint is outside its cooldown
+    fn is_available(&self) -> bool {
+        lock_or_recover(&self.health).cooling_until.map_or(true, |until| Instant::now() >= until)
+    }
+    
+    /// Record a successful request
+    fn record_success(&self) {
+        let mut health = lock_or_recover(&self.health);
+        if health.cooling_until.is_some() {
+            info!("RPC endpoint {} recovered", self.url);
+        }
+        *health = EndpointHealth::default();
+    }
+    
+    /// Record a failed request and start its cooldown
+    fn record_failure(&self, cooldown: Duration, error: &ClientError) {
+        let mut health = lock_or_recover(&self.health);
+        health.consecutive_failures += 1;
+        health.cooling_until = Some(Instant::now() + cooldown);
+        warn!("RPC endpoint {} failed ({} in a row), failing over: {}", self.url, health.consecutive_failures, error);
+    }
+}
+
+/// Endpoints shared by every client built from a `FailoverRpcClient`
+struct FailoverState {
+    /// Endpoints in priority order
+    endpoints: Vec<Endpoint>,
+    /// Index of the endpoint requests go to first
+    current: AtomicUsize,
+    /// How long a failed endpoint is skipped
+    cooldown: Duration,
+}
+
+/// RPC transport that rotates through several endpoints
+///
+/// Requests go to the current endpoint; on a transport error or rate limit it
+/// is put on cooldown and the next endpoint is tried. Clones share endpoint
+/// health, so every manager built from one instance fails over together.
+#[derive(Clone)]
+pub struct FailoverRpcClient {
+    /// Shared endpoint state
+    state: Arc<FailoverState>,
+}
+
+impl FailoverRpcClient {
+    /// Create a failover client that prefers `primary_url` and falls back through `fallback_urls` in order
+    pub fn new(primary_url: &str, fallback_urls: &[String]) -> Self {
+        Self::with_cooldown(primary_url, fallback_urls, DEFAULT_ENDPOINT_COOLDOWN)
+    }
+    
+    /// Create a failover client with a custom cooldown for failed endpoints
+    pub fn with_cooldown(primary_url: &str, fallback_urls: &[String], cooldown: Duration) -> Self {
+        let endpoints = std::iter::once(primary_url)
+            .chain(fallback_urls.iter().map(String::as_str))
+            .map(|url| Endpoint {
+                url: url.to_string(),
+                sender: HttpSender::new(url.to_string()),
+                health: Mutex::new(EndpointHealth::default()),
+            })
+            .collect();
+        
+        Self {
+            state: Arc::new(FailoverState {
+                endpoints,
+                current: AtomicUsize::new(0),
+                cooldown,
+            }),
+        }
+    }
+    
+    /// URL of the highest-priority endpoint
+    pub fn primary_url(&self) -> &str {
+        &self.state.endpoints[0].url
+    }
+    
+    /// URL of the endpoint requests currently go to
+    pub fn current_url(&self) -> &str {
+        let index = self.state.current.load(Ordering::Relaxed) % self.state.endpoints.len();
+        &self.state.endpoints[index].url
+    }
+    
+    /// Build an `RpcClient` that sends through this failover set
+    pub fn rpc_client(&self, commitment: CommitmentConfig) -> RpcClient {
+        RpcClient::new_sender(self.clone(), RpcClientConfig::with_commitment(commitment))
+    }
+}
+
+#[async_trait]
+impl RpcSender for FailoverRpcClient {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let endpoints = &self.state.endpoints;
+        let start = self.state.current.load(Ordering::Relaxed);
+        let mut last_error = None;
+        
+        // Healthy endpoints first, then the cooling ones as a last resort
+        let order: Vec<usize> = (0..endpoints.len()).map(|offset| (start + offset) % endpoints.len()).collect();
+        let (available, cooling): (Vec<usize>, Vec<usize>) = order.into_iter()
+            .partition(|index| endpoints[*index].is_available());
+        
+        for index in available.into_iter().chain(cooling) {
+            let endpoint = &endpoints[index];
+            match endpoint.sender.send(request, params.clone()).await {
+                Ok(value) => {
+                    endpoint.record_success();
+                    if index != start {
+                        info!("RPC requests now going to {}", endpoint.url);
+                        self.state.current.store(index, Ordering::Relaxed);
+                    }
+                    return Ok(value);
+                },
+                Err(e) if is_endpoint_error(&e) => {
+                    endpoint.record_failure(self.state.cooldown, &e);
+                    last_error = Some(e);
+                },
+                Err(e) => return Err(e),
+            }
+        }
+        
+        Err(last_error.unwrap_or_else(|| ClientErrorKind::Custom("No RPC endpoints configured".to_string()).into()))
+    }
+    
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        let mut stats = RpcTransportStats::default();
+        for endpoint in &self.state.endpoints {
+            let endpoint_stats = endpoint.sender.get_transport_stats();
+            stats.request_count += endpoint_stats.request_count;
+            stats.elapsed_time += endpoint_stats.elapsed_time;
+            stats.rate_limited_time += endpoint_stats.rate_limited_time;
+        }
+        stats
+    }
+    
+    fn url(&self) -> String {
+        self.current_url().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    
+    /// Endpoint nothing listens on, so connecting fails at once
+    const DEAD_ENDPOINT: &str = "http://127.0.0.1:1";
+    
+    /// Serve `result` to every JSON-RPC request on a local port, returning its URL
+    fn serve_json_rpc(result: Value) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let body = serde_json::json!({"jsonrpc": "2.0", "result": result, "id": 1}).to_string();
+        
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                
+                // Read the whole request before answering
+                let mut content_length = 0;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                    if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap_or(0);
+                    }
+                    line.clear();
+                }
+                let mut request = vec![0; content_length];
+                let _ = reader.read_exact(&mut request);
+                
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body,
+                );
+            }
+        });
+        
+        url
+    }
+    
+    #[test]
+    fn failover_moves_to_the_next_endpoint_when_the_first_is_down() {
+        let fallback = serve_json_rpc(Value::from(42));
+        let failover = FailoverRpcClient::new(DEAD_ENDPOINT, &[fallback.clone()]);
+        let client = failover.rpc_client(CommitmentConfig::confirmed());
+        
+        // The dead endpoint fails and the request is answered by the fallback
+        assert_eq!(client.get_slot().unwrap(), 42);
+        assert_eq!(failover.current_url(), fallback);
+        assert_eq!(failover.primary_url(), DEAD_ENDPOINT);
+        
+        // The dead endpoint cools down, so later requests go straight to the fallback
+        assert!(!failover.state.endpoints[0].is_available());
+        assert!(failover.state.endpoints[1].is_available());
+        assert_eq!(client.get_slot().unwrap(), 42);
+        assert_eq!(failover.current_url(), fallback);
+    }
+    
+    #[test]
+    fn failover_reports_the_last_error_when_every_endpoint_is_down() {
+        let failover = FailoverRpcClient::new(DEAD_ENDPOINT, &["http://127.0.0.1:2".to_string()]);
+        let client = failover.rpc_client(CommitmentConfig::confirmed());
+        
+        let error = client.get_slot().unwrap_err();
+        assert!(is_endpoint_error(&error));
+        assert!(failover.state.endpoints.iter().all(|endpoint| !endpoint.is_available()));
+    }
+    
+    #[test]
+    fn throttle_backs_off_on_429_and_recovers() {
+        let mut throttle = AdaptiveThrottle::new(1_000);
+        let rate_limited: Result<(), String> = Err("HTTP status client error (429 Too Many Requests)".to_string());
+        let ok: Result<(), String> = Ok(());
+        
+        // Each 429 doubles the interval
+        throttle.record_result(&rate_limited);
+        assert_eq!(throttle.current_interval_ms(), 2_000);
+        throttle.record_result(&rate_limited);
+        throttle.record_result(&rate_limited);
+        assert_eq!(throttle.current_interval_ms(), 8_000);
+        
+        // Other errors leave it alone
+        throttle.record_result(&Err::<(), _>("connection refused".to_string()));
+        assert_eq!(throttle.current_interval_ms(), 8_000);
+        
+        // Decays one step per five successes, never below the configured interval
+        for _ in 0..4 {
+            throttle.record_result(&ok);
+        }
+        assert_eq!(throttle.current_interval_ms(), 8_000);
+        throttle.record_result(&ok);
+        assert_eq!(throttle.current_interval_ms(), 6_400);
+        
+        for _ in 0..100 {
+            throttle.record_result(&ok);
+        }
+        assert_eq!(throttle.current_interval_ms(), 1_000);
+    }
+    
+    #[test]
+    fn throttle_caps_the_backoff() {
+        let mut throttle = AdaptiveThrottle::new(100);
+        for _ in 0..20 {
+            throttle.record_rate_limit();
+        }
+        assert_eq!(throttle.current_interval_ms(), 3_200);
+    }
+}
@@ -0,0 +1,259 @@
+// Tests for the bot coordinator
+// Build bots against an unreachable RPC endpoint and throwaway wallet stores
+
+use super::*;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use solana_client::client_error::{ClientErrorKind, Result as ClientResult};
+use solana_client::mock_sender::MockSender;
+use solana_client::rpc_client::RpcClientConfig;
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use std::collections::VecDeque;
+use tokio::sync::broadcast::error::TryRecvError;
+
+/// Fresh wallet store directory under the system temp dir
+fn temp_storage(name: &str) -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("sab-bot-{}-{}-{}", name, std::process::id(), nanos));
+    path.to_string_lossy().to_string()
+}
+
+/// Default configuration against a local endpoint nothing listens on
+fn test_config(name: &str) -> BotConfig {
+    let mut config = BotConfig::default(Pubkey::new_unique());
+    config.rpc_url = "http://127.0.0.1:1".to_string();
+    config.wallet_storage_path = temp_storage(name);
+    config.wallet_kdf_params = KdfParams {
+        memory_kib: 8,
+        iterations: 1,
+        parallelism: 1,
+    };
+    config
+}
+
+/// Expect the next event to be a status change from `from` to `to`
+fn expect_status_change(events: &mut broadcast::Receiver<BotEvent>, from: BotStatus, to: BotStatus) {
+    match events.try_recv() {
+        Ok(BotEvent::StatusChanged { from: seen_from, to: seen_to }) => {
+            assert_eq!((seen_from, seen_to), (from, to));
+        },
+        other => panic!("expected {:?} -> {:?}, got {:?}", from, to, other),
+    }
+}
+
+#[test]
+fn status_changes_reach_every_subscriber() {
+    let mut bot = ArbitrageBot::new(test_config("status-events")).unwrap();
+    let mut first = bot.subscribe();
+    let mut second = bot.subscribe();
+    
+    bot.set_status(BotStatus::Running);
+    expect_status_change(&mut first, BotStatus::Stopped, BotStatus::Running);
+    expect_status_change(&mut second, BotStatus::Stopped, BotStatus::Running);
+    
+    // Setting the same status again isn't a change
+    bot.set_status(BotStatus::Running);
+    assert!(matches!(first.try_recv(), Err(TryRecvError::Empty)));
+    
+    bot.pause().unwrap();
+    bot.resume().unwrap();
+    expect_status_change(&mut first, BotStatus::Running, BotStatus::Paused);
+    expect_status_change(&mut first, BotStatus::Paused, BotStatus::Running);
+    assert_eq!(bot.get_status(), BotStatus::Running);
+}
+
+#[test]
+fn late_subscribers_only_see_later_events() {
+    let mut bot = ArbitrageBot::new(test_config("late-subscriber")).unwrap();
+    bot.set_status(BotStatus::Running);
+    
+    let mut events = bot.subscribe();
+    assert!(matches!(events.try_recv(), Err(TryRecvError::Empty)));
+    
+    bot.pause().unwrap();
+    expect_status_change(&mut events, BotStatus::Running, BotStatus::Paused);
+}
+
+#[test]
+fn default_owner_wallet_fails_construction() {
+    let mut config = test_config("no-owner");
+    config.profit_distribution = ProfitDistributionConfig::default(Pubkey::default());
+    
+    assert!(config.validate().is_err());
+    assert!(matches!(ArbitrageBot::new(config), Err(BotError::Config(_))));
+}
+
+#[test]
+fn cloned_config_is_independent_of_the_default() {
+    let original = BotConfig::default(Pubkey::new_unique());
+    let before = format!("{:?}", original);
+    
+    let mut copy = original.clone();
+    let pair = copy.token_pairs[0];
+    copy.token_pairs.push(TokenPair {
+        base_token: pair.quote_token,
+        quote_token: pair.base_token,
+    });
+    copy.dexes[0].enabled = false;
+    copy.dexes[1].api_url = "http://127.0.0.1:1".to_string();
+    copy.profit_distribution.reinvestment_percentage = 10;
+    copy.fallback_rpc_urls.push("http://127.0.0.1:2".to_string());
+    copy.update_interval_ms *= 2;
+    
+    // Nested vectors and configs were copied, not shared
+    assert_eq!(format!("{:?}", original), before);
+    assert_eq!(original.token_pairs, vec![pair]);
+    assert!(original.dexes[0].enabled);
+    assert_ne!(original.dexes[1].api_url, copy.dexes[1].api_url);
+    assert_ne!(original.profit_distribution.reinvestment_percentage, 10);
+    assert!(original.fallback_rpc_urls.is_empty());
+    assert_eq!(copy.token_pairs.len(), 2);
+}
+
+/// Statistics once the recorder thread has counted `trades` trades
+fn statistics_after_trades(bot: &ArbitrageBot, trades: u64) -> BotStatistics {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let statistics = bot.get_statistics().unwrap();
+        if statistics.trades_executed >= trades || Instant::now() > deadline {
+            return statistics;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn statistics_follow_recorded_trades() {
+    let bot = ArbitrageBot::new(test_config("statistics")).unwrap();
+    let mut events = bot.subscribe();
+    
+    // Two wins and a failure, as the trade path reports them
+    bot.statistics.lock().unwrap().record_opportunity();
+    bot.statistics.lock().unwrap().record_opportunity();
+    bot.record_trade(Ok("sig-1".to_string()), 3_000, 100).unwrap();
+    bot.record_trade(Err("slippage".to_string()), 0, 400).unwrap();
+    bot.record_trade(Ok("sig-2".to_string()), 6_000, 250).unwrap();
+    
+    let statistics = statistics_after_trades(&bot, 3);
+    assert_eq!(statistics.opportunities_detected, 2);
+    assert_eq!(statistics.trades_executed, 3);
+    assert_eq!(statistics.failed_trades, 1);
+    assert_eq!(statistics.total_profit_lamports, 9_000);
+    assert!((statistics.success_rate - 200.0 / 3.0).abs() < 1e-9, "{}", statistics.success_rate);
+    assert_eq!(statistics.avg_profit_per_trade, 3_000);
+    assert_eq!(statistics.avg_execution_time_ms, 250);
+    
+    // Each trade was announced to subscribers
+    assert!(matches!(events.try_recv(), Ok(BotEvent::TradeExecuted { profit: 3_000, .. })));
+    assert!(matches!(events.try_recv(), Ok(BotEvent::TradeFailed { reason }) if reason == "slippage"));
+    assert!(matches!(events.try_recv(), Ok(BotEvent::TradeExecuted { profit: 6_000, .. })));
+}
+
+#[test]
+fn trades_published_by_the_engine_reach_the_statistics() {
+    let bot = ArbitrageBot::new(test_config("engine-statistics")).unwrap();
+    
+    // The engine publishes its results on the bot's sender rather than calling into the bot
+    let engine_events = bot.event_sender();
+    engine_events.send(BotEvent::TradeExecuted { signature: "sig-1".to_string(), profit: 4_000, execution_time_ms: 300 }).unwrap();
+    engine_events.send(BotEvent::OpportunityDetected {
+        base_token: Pubkey::new_unique(),
+        quote_token: Pubkey::new_unique(),
+        profit_percentage: 1.0,
+    }).unwrap();
+    engine_events.send(BotEvent::TradeFailed { reason: "Stop-loss".to_string(), execution_time_ms: 500 }).unwrap();
+    
+    let statistics = statistics_after_trades(&bot, 2);
+    assert_eq!(statistics.trades_executed, 2);
+    assert_eq!(statistics.failed_trades, 1);
+    assert_eq!(statistics.total_profit_lamports, 4_000);
+    assert_eq!(statistics.success_rate, 50.0);
+    assert_eq!(statistics.avg_execution_time_ms, 400);
+}
+
+/// RPC sender answering balance requests from a script, and everything else like the mock client
+struct ScriptedBalances {
+    /// Mock answering everything but balances
+    inner: MockSender,
+    /// Balances, or errors, for the next balance requests in order
+    balances: Arc<Mutex<VecDeque<Result<u64, String>>>>,
+}
+
+#[async_trait]
+impl RpcSender for ScriptedBalances {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        if request != RpcRequest::GetBalance {
+            return self.inner.send(request, params).await;
+        }
+        
+        match self.balances.lock().unwrap().pop_front().expect("a scripted balance") {
+            Ok(balance) => Ok(json!({"context": {"slot": 1}, "value": balance})),
+            Err(e) => Err(ClientErrorKind::Custom(e).into()),
+        }
+    }
+    
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+    
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+#[test]
+fn low_operational_balance_pauses_and_a_top_up_resumes() {
+    let config = test_config("operational-balance");
+    let balances = Arc::new(Mutex::new(VecDeque::new()));
+    let sender = ScriptedBalances {
+        inner: MockSender::new("succeeds".to_string()),
+        balances: balances.clone(),
+    };
+    let wallet_manager = ThreadSafeWalletManager::with_rpc_client(
+        RpcClient::new_sender(sender, RpcClientConfig::with_commitment(CommitmentConfig::confirmed())),
+        &config.wallet_storage_path,
+    );
+    wallet_manager.set_kdf_params(config.wallet_kdf_params).unwrap();
+    wallet_manager.set_retry_policy(RetryPolicy::none()).unwrap();
+    wallet_manager.init_encryption("test password").unwrap();
+    let operational = wallet_manager.generate_wallet(WalletType::Operational, "fees").unwrap();
+    
+    let (events, mut received) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+    let statistics = Mutex::new(BotStatistics::new());
+    let mut monitor = OperationalBalanceMonitor::new(config.operational_balance);
+    let (minimum, resume_at) = (config.operational_balance.min_operational_balance, config.operational_balance.resume_operational_balance);
+    let mut check = |balance: Result<u64, String>| {
+        balances.lock().unwrap().push_back(balance);
+        ArbitrageBot::check_operational_balance(&config, &wallet_manager, &mut monitor, &events, &statistics)
+    };
+    
+    // Funded, then drained below the minimum
+    assert!(!check(Ok(resume_at)));
+    assert!(matches!(received.try_recv(), Err(TryRecvError::Empty)));
+    assert!(check(Ok(minimum - 1)));
+    assert!(matches!(
+        received.try_recv(),
+        Ok(BotEvent::LowOperationalBalance { wallet, balance, resume_at: seen }) if wallet == operational && balance == minimum - 1 && seen == resume_at
+    ));
+    assert!(statistics.lock().unwrap().low_operational_balance);
+    
+    // Back above the minimum but short of the resume threshold, or unreadable, it stays paused
+    assert!(check(Ok(minimum)));
+    assert!(check(Err("connection reset".to_string())));
+    assert!(matches!(received.try_recv(), Err(TryRecvError::Empty)));
+    
+    // Topped up, trading resumes
+    assert!(!check(Ok(resume_at)));
+    assert!(matches!(
+        received.try_recv(),
+        Ok(BotEvent::OperationalBalanceRestored { wallet, balance }) if wallet == operational && balance == resume_at
+    ));
+    assert!(!statistics.lock().unwrap().low_operational_balance);
+    assert!(balances.lock().unwrap().is_empty());
+    
+    let _ = std::fs::remove_dir_all(&config.wallet_storage_path);
+}
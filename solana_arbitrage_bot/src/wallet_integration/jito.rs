@@ -0,0 +1,99 @@
+// Jito Bundle Submission for Solana Flash Loan Arbitrage Bot
+// Sends signed transactions to a Jito block engine as tipped bundles, keeping them out of the public mempool
+
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Signature,
+    system_instruction,
+    transaction::VersionedTransaction,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use log::debug;
+
+use super::WalletError;
+
+/// Jito block engine configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JitoConfig {
+    /// Block engine URL, e.g. "https://mainnet.block-engine.jito.wtf"
+    pub block_engine_url: String,
+    /// Tip account the bundle pays
+    #[serde(with = "crate::serde_pubkey")]
+    pub tip_account: Pubkey,
+    /// Tip paid per bundle in lamports
+    pub tip_lamports: u64,
+    /// Send through the regular RPC when the block engine rejects a bundle
+    #[serde(default)]
+    pub fallback_to_rpc: bool,
+    /// UUID for authenticated block engine access
+    #[serde(default)]
+    pub auth_uuid: Option<String>,
+}
+
+/// Client for a Jito block engine's bundle API
+pub struct JitoClient {
+    /// Block engine configuration
+    config: JitoConfig,
+    /// JSON-RPC client for the bundles endpoint
+    block_engine: RpcClient,
+}
+
+impl JitoClient {
+    /// Create a new Jito client
+    pub fn new(config: JitoConfig) -> Result<Self, WalletError> {
+        if config.tip_lamports == 0 {
+            return Err(WalletError::GeneralError("Jito bundles need a nonzero tip".to_string()));
+        }
+        
+        let mut url = format!("{}/api/v1/bundles", config.block_engine_url.trim_end_matches('/'));
+        if let Some(uuid) = &config.auth_uuid {
+            url = format!("{}?uuid={}", url, uuid);
+        }
+        
+        Ok(Self {
+            block_engine: RpcClient::new(url),
+            config,
+        })
+    }
+    
+    /// Whether rejected bundles should be sent through the regular RPC instead
+    pub fn fallback_to_rpc(&self) -> bool {
+        self.config.fallback_to_rpc
+    }
+    
+    /// Tip paid per bundle in lamports
+    pub fn tip_lamports(&self) -> u64 {
+        self.config.tip_lamports
+    }
+    
+    /// Tip transfer to append to a bundled transaction, paid by `payer`
+    pub fn tip_instruction(&self, payer: &Pubkey) -> Instruction {
+        system_instruction::transfer(payer, &self.config.tip_account, self.config.tip_lamports)
+    }
+    
+    /// Submit a signed transaction as a single-transaction bundle
+    ///
+    /// Returns the transaction's signature, which confirms like any other once the bundle lands.
+    pub fn send_bundle(&self, transaction: &VersionedTransaction) -> Result<Signature, WalletError> {
+        let signature = *transaction.signatures.first()
+            .ok_or_else(|| WalletError::TransactionError("Bundled transaction is unsigned".to_string()))?;
+        
+        let bytes = bincode::serialize(transaction)
+            .map_err(|e| WalletError::TransactionError(format!("Failed to serialize transaction: {}", e)))?;
+        
+        let bundle_id: String = self.block_engine
+            .send(
+                RpcRequest::Custom { method: "sendBundle" },
+                json!([[BASE64.encode(bytes)], { "encoding": "base64" }]),
+            )
+            .map_err(|e| WalletError::TransactionError(format!("Jito bundle rejected: {}", e)))?;
+        
+        debug!("Submitted Jito bundle {} for {}", bundle_id, signature);
+        Ok(signature)
+    }
+}
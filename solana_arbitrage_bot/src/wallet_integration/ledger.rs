@@ -0,0 +1,74 @@
+// Ledger Hardware Wallet Backend for Solana Flash Loan Arbitrage Bot
+// Signs transactions on a Ledger device through the Solana remote wallet stack
+
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+use solana_remote_wallet::locator::{Locator, Manufacturer};
+use solana_remote_wallet::remote_keypair::{generate_remote_keypair, RemoteKeypair};
+use solana_remote_wallet::remote_wallet::maybe_wallet_manager;
+
+use super::{WalletError, WalletSigner};
+
+/// Wallet whose key lives on a Ledger device
+///
+/// The device connection isn't thread-safe, so it's opened for each signature
+/// rather than held open.
+pub struct LedgerSigner {
+    /// Public key at the derivation path
+    pubkey: Pubkey,
+    /// Derivation path on the device
+    derivation_path: DerivationPath,
+}
+
+impl LedgerSigner {
+    /// Connect to the Ledger and read the key at `derivation_path` (e.g. "m/44'/501'/0'/0'")
+    pub fn connect(derivation_path: &str) -> Result<Self, WalletError> {
+        let derivation_path = DerivationPath::from_absolute_path_str(derivation_path)
+            .map_err(|e| WalletError::KeyError(format!("Invalid derivation path {}: {}", derivation_path, e)))?;
+        
+        let keypair = Self::open(&derivation_path)?;
+        
+        Ok(Self {
+            pubkey: keypair.pubkey(),
+            derivation_path,
+        })
+    }
+    
+    /// Open the device and select the key at a derivation path
+    fn open(derivation_path: &DerivationPath) -> Result<RemoteKeypair, WalletError> {
+        let wallet_manager = maybe_wallet_manager()
+            .map_err(|e| WalletError::KeyError(format!("Failed to access hardware wallets: {}", e)))?
+            .ok_or_else(|| WalletError::KeyError("No hardware wallet connected".to_string()))?;
+        
+        let locator = Locator {
+            manufacturer: Manufacturer::Ledger,
+            pubkey: None,
+        };
+        
+        generate_remote_keypair(locator, derivation_path.clone(), &wallet_manager, false, "ledger")
+            .map_err(|e| WalletError::KeyError(format!("Failed to open Ledger: {}", e)))
+    }
+}
+
+impl WalletSigner for LedgerSigner {
+    fn wallet_pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+    
+    fn sign_bytes(&self, message: &[u8]) -> Result<Signature, WalletError> {
+        let keypair = Self::open(&self.derivation_path)?;
+        
+        // A different device may have been plugged in since we connected
+        if keypair.pubkey() != self.pubkey {
+            return Err(WalletError::KeyError(format!(
+                "Connected Ledger holds {}, expected {}", keypair.pubkey(), self.pubkey
+            )));
+        }
+        
+        keypair.try_sign_message(message)
+            .map_err(|e| WalletError::TransactionError(format!("Ledger signing failed: {}", e)))
+    }
+}
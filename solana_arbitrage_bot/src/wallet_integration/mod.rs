@@ -3,21 +3,77 @@
 
 use solana_sdk::{
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::{Keypair, Signature, Signer},
     transaction::Transaction,
     commitment_config::CommitmentConfig,
     hash::Hash,
     instruction::Instruction,
+    address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
+    message::{v0, Message, VersionedMessage},
+    transaction::VersionedTransaction,
+    compute_budget::{self, ComputeBudgetInstruction},
 };
 use solana_client::rpc_client::RpcClient;
-use std::collections::HashMap;
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_account_decoder::UiAccountData;
+use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::Path;
+use std::str::FromStr;
 use ring::aead::{Aead, LessSafeKey, UnboundKey, AES_256_GCM};
 use ring::rand::{SecureRandom, SystemRandom};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use argon2::{Algorithm, Argon2, Params, Version};
+use serde::{Deserialize, Serialize};
+use log::{info, warn, debug};
+
+#[cfg(feature = "ledger")]
+pub mod ledger;
+#[cfg(feature = "jito")]
+pub mod jito;
+#[cfg(test)]
+mod tests;
+
+use crate::lock_or_recover;
+use crate::rpc::{is_blockhash_not_found_error, RetryPolicy};
+use crate::rpc::blockhash::BlockhashCache;
+use crate::profit_management::TransactionSender;
+
+/// Times a send is retried with a fresh blockhash after "Blockhash not found"
+const MAX_BLOCKHASH_RETRIES: usize = 1;
+
+/// Base fee charged per transaction signature
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Compute budget instructions prepended to every transaction, ahead of the caller's own
+///
+/// Instructions that reference others by position, such as flash loan repayments, must allow for them.
+pub const COMPUTE_BUDGET_INSTRUCTIONS: usize = 2;
+
+/// Name of the per-install key derivation salt file in the storage directory
+const KDF_SALT_FILE: &str = "kdf_salt";
+
+/// Length of the key derivation salt in bytes
+const KDF_SALT_LEN: usize = 16;
+
+/// Magic bytes at the start of versioned keypair files
+///
+/// Files without it predate the Argon2id key and are migrated on load.
+const KEYPAIR_FILE_MAGIC: &[u8] = b"SAB";
+
+/// Current keypair file format: magic, version byte, 12-byte nonce, ciphertext and tag
+const KEYPAIR_FORMAT_VERSION: u8 = 2;
+
+/// Length of the AES-256-GCM nonce in bytes
+const NONCE_LEN: usize = 12;
+
+/// Most accounts getRecentPrioritizationFees accepts in one request
+const MAX_PRIORITIZATION_FEE_ACCOUNTS: usize = 128;
 
 /// Error type for wallet operations
 #[derive(Debug)]
@@ -62,6 +118,54 @@ pub enum WalletType {
     Profit,
     /// Owner withdrawal wallet
     Owner,
+    /// Segregated reserve, only withdrawable to the owner
+    Reserve,
+}
+
+impl WalletType {
+    /// Name used in wallet info files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WalletType::Trading => "Trading",
+            WalletType::Operational => "Operational",
+            WalletType::Profit => "Profit",
+            WalletType::Owner => "Owner",
+            WalletType::Reserve => "Reserve",
+        }
+    }
+}
+
+impl FromStr for WalletType {
+    type Err = WalletError;
+    
+    /// Parse a wallet type name, rejecting anything unrecognized rather than guessing
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Trading" => Ok(WalletType::Trading),
+            "Operational" => Ok(WalletType::Operational),
+            "Profit" => Ok(WalletType::Profit),
+            "Owner" => Ok(WalletType::Owner),
+            "Reserve" => Ok(WalletType::Reserve),
+            _ => Err(WalletError::GeneralError(format!("Unknown wallet type: {}", s))),
+        }
+    }
+}
+
+/// On-disk form of `WalletInfo`, stored as `<pubkey>_info.json`
+#[derive(Debug, Serialize, Deserialize)]
+struct WalletInfoRecord {
+    /// Wallet public key
+    pubkey: String,
+    /// Wallet type name
+    #[serde(rename = "type")]
+    wallet_type: String,
+    /// Wallet label/name
+    label: String,
+    /// Whether this wallet has a local keypair
+    has_keypair: bool,
+    /// Ledger derivation path, for hardware-backed wallets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    derivation_path: Option<String>,
 }
 
 /// Wallet information
@@ -74,6 +178,307 @@ pub struct WalletInfo {
     pub label: String,
     /// Whether this wallet has a local keypair
     pub has_keypair: bool,
+    /// Ledger derivation path, for hardware-backed wallets
+    pub derivation_path: Option<String>,
+}
+
+/// Signs transactions on behalf of one wallet, whether its key is in memory or on a device
+pub trait WalletSigner: Send + Sync {
+    /// Public key of the wallet
+    fn wallet_pubkey(&self) -> Pubkey;
+    
+    /// Sign a serialized transaction message
+    fn sign_bytes(&self, message: &[u8]) -> Result<Signature, WalletError>;
+    
+    /// Add this wallet's signature to a legacy transaction
+    fn sign_transaction(&self, transaction: &mut Transaction) -> Result<(), WalletError> {
+        let index = signer_index(
+            &transaction.message.account_keys,
+            transaction.message.header.num_required_signatures,
+            &self.wallet_pubkey(),
+        )?;
+        transaction.signatures[index] = self.sign_bytes(&transaction.message_data())?;
+        Ok(())
+    }
+    
+    /// Add this wallet's signature to a versioned transaction
+    fn sign_versioned_transaction(&self, transaction: &mut VersionedTransaction) -> Result<(), WalletError> {
+        let index = signer_index(
+            transaction.message.static_account_keys(),
+            transaction.message.header().num_required_signatures,
+            &self.wallet_pubkey(),
+        )?;
+        transaction.signatures[index] = self.sign_bytes(&transaction.message.serialize())?;
+        Ok(())
+    }
+}
+
+/// Software keypairs held in memory
+impl WalletSigner for Keypair {
+    fn wallet_pubkey(&self) -> Pubkey {
+        self.pubkey()
+    }
+    
+    fn sign_bytes(&self, message: &[u8]) -> Result<Signature, WalletError> {
+        self.try_sign_message(message)
+            .map_err(|e| WalletError::TransactionError(format!("Signing failed: {}", e)))
+    }
+}
+
+/// Position of a signer among a message's required signatures
+fn signer_index(account_keys: &[Pubkey], num_required_signatures: u8, signer: &Pubkey) -> Result<usize, WalletError> {
+    account_keys.iter()
+        .take(num_required_signatures as usize)
+        .position(|key| key == signer)
+        .ok_or_else(|| WalletError::TransactionError(format!("{} is not a required signer", signer)))
+}
+
+/// Compile a v0 message when lookup tables are given, a legacy one otherwise
+fn compile_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedMessage, WalletError> {
+    if lookup_tables.is_empty() {
+        return Ok(VersionedMessage::Legacy(Message::new_with_blockhash(instructions, Some(payer), &blockhash)));
+    }
+    
+    let message = v0::Message::try_compile(payer, instructions, lookup_tables, blockhash)
+        .map_err(|e| WalletError::TransactionError(format!("Failed to compile v0 message: {}", e)))?;
+    Ok(VersionedMessage::V0(message))
+}
+
+impl ThreadSafeWalletManager {
+    /// Create a new thread-safe wallet manager over an existing RPC client
+    pub fn with_rpc_client(rpc_client: RpcClient, storage_path: &str) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(WalletManager::with_rpc_client(rpc_client, storage_path))),
+        }
+    }
+    
+    /// Get the next trading wallet from the pool (thread-safe)
+    pub fn get_next_trading_wallet(&self) -> Result<Pubkey, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.get_next_trading_wallet()
+    }
+    
+    /// Add a Ledger-backed wallet (thread-safe)
+    #[cfg(feature = "ledger")]
+    pub fn add_ledger_wallet(&self, derivation_path: &str, wallet_type: WalletType, label: &str) -> Result<Pubkey, WalletError> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.add_ledger_wallet(derivation_path, wallet_type, label)
+    }
+    
+    /// Get an owner's balance of an SPL token (thread-safe)
+    pub fn get_token_balance(&self, owner: &Pubkey, mint: &Pubkey) -> Result<u64, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.get_token_balance(owner, mint)
+    }
+    
+    /// Every SPL token an owner holds a nonzero balance of (thread-safe)
+    pub fn get_token_balances(&self, owner: &Pubkey) -> Result<Vec<(Pubkey, u64)>, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.get_token_balances(owner)
+    }
+    
+    /// Create an owner's associated token account if it doesn't exist yet (thread-safe)
+    pub fn ensure_associated_token_account(&self, owner: &Pubkey, mint: &Pubkey) -> Result<Pubkey, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.ensure_associated_token_account(owner, mint)
+    }
+    
+    /// Sign and send a v0 transaction using lookup tables (thread-safe)
+    pub fn sign_and_send_versioned(
+        &self,
+        instructions: Vec<Instruction>,
+        signers: Vec<&Pubkey>,
+        lookup_tables: Vec<AddressLookupTableAccount>,
+        fee_payer: Option<&Pubkey>,
+    ) -> Result<String, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.sign_and_send_versioned(instructions, signers, lookup_tables, fee_payer)
+    }
+    
+    /// Sign and send a transaction at a given compute unit price (thread-safe)
+    pub fn sign_and_send_priced(
+        &self,
+        instructions: Vec<Instruction>,
+        signers: Vec<&Pubkey>,
+        lookup_tables: Vec<AddressLookupTableAccount>,
+        priority_fee: u64,
+        fee_payer: Option<&Pubkey>,
+    ) -> Result<String, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.sign_and_send_priced(instructions, signers, lookup_tables, priority_fee, fee_payer)
+    }
+    
+    /// Check a wallet's stored keypair decrypts and belongs to it (thread-safe)
+    pub fn verify_keypair(&self, pubkey: &Pubkey) -> Result<(), WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.verify_keypair(pubkey)
+    }
+    
+    /// Have the operational wallet pay fees when no payer is given (thread-safe)
+    pub fn set_operational_fee_payer(&self, enabled: bool) -> Result<(), WalletError> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.set_operational_fee_payer(enabled);
+        Ok(())
+    }
+    
+    /// Lamports paid in fees by the operational wallets so far (thread-safe)
+    pub fn operational_fees_paid(&self) -> Result<u64, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        Ok(manager.operational_fees_paid())
+    }
+    
+    /// Prepend the compute budget a sent transaction carries, at the current priority fee (thread-safe)
+    ///
+    /// Lets simulations run the same instructions, at the same positions, as the transaction sent.
+    pub fn with_compute_budget(&self, instructions: Vec<Instruction>) -> Result<Vec<Instruction>, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        let priority_fee = manager.priority_fee(&instructions);
+        Ok(manager.with_compute_budget(instructions, priority_fee))
+    }
+    
+    /// Compute unit price for a transaction, in micro-lamports (thread-safe)
+    pub fn priority_fee(&self, instructions: &[Instruction]) -> Result<u64, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        Ok(manager.priority_fee(instructions))
+    }
+    
+    /// Estimate a competitive compute unit price from recent prioritization fees (thread-safe)
+    pub fn estimate_priority_fee(&self, accounts: &[Pubkey]) -> Result<u64, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.estimate_priority_fee(accounts)
+    }
+    
+    /// Send transactions as Jito bundles through a block engine (thread-safe)
+    #[cfg(feature = "jito")]
+    pub fn set_jito_config(&self, config: jito::JitoConfig) -> Result<(), WalletError> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.set_jito_config(config)
+    }
+    
+    /// Set the compute budget added to sent transactions (thread-safe)
+    pub fn set_priority_fee(&self, priority_fee: PriorityFeeConfig, gas_price_multiplier: f64) -> Result<(), WalletError> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.set_priority_fee(priority_fee, gas_price_multiplier);
+        Ok(())
+    }
+    
+    /// Set the retry policy for RPC calls (thread-safe)
+    pub fn set_retry_policy(&self, retry_policy: RetryPolicy) -> Result<(), WalletError> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.set_retry_policy(retry_policy);
+        Ok(())
+    }
+    
+    /// Take recent blockhashes from a cache (thread-safe)
+    pub fn set_blockhash_cache(&self, blockhash_cache: Arc<BlockhashCache>) -> Result<(), WalletError> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.set_blockhash_cache(blockhash_cache);
+        Ok(())
+    }
+    
+    /// Set the Argon2id cost parameters (thread-safe)
+    pub fn set_kdf_params(&self, kdf_params: KdfParams) -> Result<(), WalletError> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.set_kdf_params(kdf_params);
+        Ok(())
+    }
+    
+    /// Set the commitment levels used for reads and confirmations (thread-safe)
+    pub fn set_commitment_policy(&self, commitment_policy: CommitmentPolicy) -> Result<(), WalletError> {
+        let mut manager = lock_or_recover(&self.inner);
+        manager.set_commitment_policy(commitment_policy);
+        Ok(())
+    }
+    
+    /// Check whether a transaction has reached the confirmation commitment (thread-safe)
+    pub fn confirm_transaction(&self, signature: &str) -> Result<bool, WalletError> {
+        let manager = lock_or_recover(&self.inner);
+        manager.confirm_transaction(signature)
+    }
+}
+
+impl TransactionSender for ThreadSafeWalletManager {
+    fn sign_and_send(&self, instructions: Vec<Instruction>, signers: Vec<&Pubkey>) -> Result<String, String> {
+        let manager = lock_or_recover(&self.inner);
+        manager.sign_and_send_transaction(instructions, signers, None)
+            .map_err(|e| e.to_string())
+    }
+    
+    fn ensure_token_account(&self, owner: &Pubkey, mint: &Pubkey) -> Result<Pubkey, String> {
+        self.ensure_associated_token_account(owner, mint)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Commitment levels used for different kinds of RPC operations
+#[derive(Debug, Clone, Copy)]
+pub struct CommitmentPolicy {
+    /// Commitment for reads such as balances (e.g. processed for speed)
+    pub read: CommitmentConfig,
+    /// Commitment a sent transaction must reach to count as landed
+    pub confirm: CommitmentConfig,
+}
+
+impl Default for CommitmentPolicy {
+    fn default() -> Self {
+        Self {
+            read: CommitmentConfig::confirmed(),
+            confirm: CommitmentConfig::confirmed(),
+        }
+    }
+}
+
+/// Compute budget prepended to every transaction so it can compete during congestion
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PriorityFeeConfig {
+    /// Compute unit price in micro-lamports before the gas price multiplier
+    pub base_micro_lamports: u64,
+    /// Highest compute unit price we'll pay, in micro-lamports
+    pub max_micro_lamports: u64,
+    /// Compute unit limit requested per transaction
+    pub compute_unit_limit: u32,
+    /// Whether to raise the price to match recent prioritization fees on the accounts we write
+    pub use_recent_fees: bool,
+    /// Percentile of recent nonzero fees to match (0-100)
+    pub recent_fee_percentile: u8,
+}
+
+impl Default for PriorityFeeConfig {
+    fn default() -> Self {
+        Self {
+            base_micro_lamports: 10_000,
+            max_micro_lamports: 5_000_000,
+            compute_unit_limit: 400_000,
+            use_recent_fees: true,
+            recent_fee_percentile: 75,
+        }
+    }
+}
+
+/// Argon2id cost parameters for deriving the wallet encryption key
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of iterations
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024, // 64 MiB
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
 }
 
 /// Secure wallet storage
@@ -82,12 +487,41 @@ pub struct WalletManager {
     rpc_client: RpcClient,
     /// Map of wallet public keys to keypairs (if available)
     keypairs: HashMap<Pubkey, Keypair>,
+    /// Signers for wallets whose keys live on a hardware device
+    hardware_signers: HashMap<Pubkey, Box<dyn WalletSigner>>,
     /// Map of wallet public keys to wallet info
     wallet_info: HashMap<Pubkey, WalletInfo>,
     /// Encryption key for secure storage
     encryption_key: Option<[u8; 32]>,
+    /// Key derived with the old insecure scheme, used only to migrate old keypair files
+    legacy_encryption_key: Option<[u8; 32]>,
+    /// Argon2id cost parameters
+    kdf_params: KdfParams,
     /// Path to wallet storage directory
     storage_path: String,
+    /// Cache of fetched address lookup tables
+    lookup_table_cache: Mutex<HashMap<Pubkey, AddressLookupTableAccount>>,
+    /// Round-robin cursor over the trading wallet pool
+    trading_wallet_cursor: AtomicUsize,
+    /// Associated token accounts already known to exist
+    known_token_accounts: Mutex<HashSet<Pubkey>>,
+    /// Commitment levels for reads and confirmations
+    commitment_policy: CommitmentPolicy,
+    /// Compute unit price and limit added to sent transactions
+    priority_fee: PriorityFeeConfig,
+    /// Multiplier applied to the compute unit price
+    gas_price_multiplier: f64,
+    /// Retry policy for transient RPC failures
+    retry_policy: RetryPolicy,
+    /// Background-refreshed blockhash, so sends skip the fetch (fetched per send when unset)
+    blockhash_cache: Option<Arc<BlockhashCache>>,
+    /// Block engine that sent transactions are bundled through, if configured
+    #[cfg(feature = "jito")]
+    jito: Option<jito::JitoClient>,
+    /// Whether the operational wallet pays fees by default instead of the trading wallet
+    operational_fee_payer: bool,
+    /// Lamports paid in fees (and tips) by operational wallets
+    operational_fees_paid: AtomicU64,
 }
 
 impl WalletManager {
@@ -98,6 +532,11 @@ impl WalletManager {
             CommitmentConfig::confirmed(),
         );
         
+        Self::with_rpc_client(rpc_client, storage_path)
+    }
+    
+    /// Create a new wallet manager over an existing RPC client (e.g. one with failover)
+    pub fn with_rpc_client(rpc_client: RpcClient, storage_path: &str) -> Self {
         // Create storage directory if it doesn't exist
         if !Path::new(storage_path).exists() {
             fs::create_dir_all(storage_path).unwrap_or_else(|_| {
@@ -108,28 +547,184 @@ impl WalletManager {
         Self {
             rpc_client,
             keypairs: HashMap::new(),
+            hardware_signers: HashMap::new(),
             wallet_info: HashMap::new(),
             encryption_key: None,
+            legacy_encryption_key: None,
+            kdf_params: KdfParams::default(),
             storage_path: storage_path.to_string(),
+            lookup_table_cache: Mutex::new(HashMap::new()),
+            trading_wallet_cursor: AtomicUsize::new(0),
+            known_token_accounts: Mutex::new(HashSet::new()),
+            commitment_policy: CommitmentPolicy::default(),
+            priority_fee: PriorityFeeConfig::default(),
+            gas_price_multiplier: 1.0,
+            retry_policy: RetryPolicy::default(),
+            blockhash_cache: None,
+            #[cfg(feature = "jito")]
+            jito: None,
+            operational_fee_payer: false,
+            operational_fees_paid: AtomicU64::new(0),
         }
     }
     
+    /// Set the commitment levels used for reads and confirmations
+    pub fn set_commitment_policy(&mut self, commitment_policy: CommitmentPolicy) {
+        self.commitment_policy = commitment_policy;
+    }
+    
+    /// Have the operational wallet pay fees when no payer is given, instead of the signing trading wallet
+    pub fn set_operational_fee_payer(&mut self, enabled: bool) {
+        self.operational_fee_payer = enabled;
+    }
+    
+    /// Set the compute budget added to sent transactions and the multiplier applied to its price
+    pub fn set_priority_fee(&mut self, priority_fee: PriorityFeeConfig, gas_price_multiplier: f64) {
+        self.priority_fee = priority_fee;
+        self.gas_price_multiplier = gas_price_multiplier;
+    }
+    
+    /// Set the retry policy for transient RPC failures
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+    
+    /// Take recent blockhashes from a cache instead of fetching one per transaction
+    pub fn set_blockhash_cache(&mut self, blockhash_cache: Arc<BlockhashCache>) {
+        self.blockhash_cache = Some(blockhash_cache);
+    }
+    
+    /// Send transactions as Jito bundles through a block engine
+    #[cfg(feature = "jito")]
+    pub fn set_jito_config(&mut self, config: jito::JitoConfig) -> Result<(), WalletError> {
+        info!("Routing transactions through Jito block engine {}", config.block_engine_url);
+        self.jito = Some(jito::JitoClient::new(config)?);
+        Ok(())
+    }
+    
+    /// Estimate a competitive compute unit price from recent prioritization fees on `accounts`
+    ///
+    /// Returns the configured percentile of recent nonzero fees, in micro-lamports
+    /// (0 if no recent slot paid a fee for these accounts).
+    pub fn estimate_priority_fee(&self, accounts: &[Pubkey]) -> Result<u64, WalletError> {
+        let accounts = &accounts[..accounts.len().min(MAX_PRIORITIZATION_FEE_ACCOUNTS)];
+        let recent = self.rpc_client.get_recent_prioritization_fees(accounts)
+            .map_err(|e| WalletError::RpcError(format!("Failed to get recent prioritization fees: {}", e)))?;
+        
+        let mut fees: Vec<u64> = recent.iter()
+            .map(|fee| fee.prioritization_fee)
+            .filter(|fee| *fee > 0)
+            .collect();
+        if fees.is_empty() {
+            return Ok(0);
+        }
+        fees.sort_unstable();
+        
+        let percentile = self.priority_fee.recent_fee_percentile.min(100) as usize;
+        let index = (fees.len() - 1) * percentile / 100;
+        Ok(fees[index])
+    }
+    
+    /// Compute unit price for a transaction, in micro-lamports
+    ///
+    /// The larger of the configured base and recent fees on the accounts the
+    /// instructions write, scaled by the gas price multiplier and capped.
+    pub fn priority_fee(&self, instructions: &[Instruction]) -> u64 {
+        let mut price = self.priority_fee.base_micro_lamports;
+        
+        if self.priority_fee.use_recent_fees {
+            let mut writable: Vec<Pubkey> = instructions.iter()
+                .flat_map(|instruction| instruction.accounts.iter())
+                .filter(|account| account.is_writable)
+                .map(|account| account.pubkey)
+                .collect();
+            writable.sort();
+            writable.dedup();
+            
+            match self.estimate_priority_fee(&writable) {
+                Ok(recent) => price = price.max(recent),
+                Err(e) => warn!("Using base priority fee: {}", e),
+            }
+        }
+        
+        ((price as f64 * self.gas_price_multiplier) as u64).min(self.priority_fee.max_micro_lamports)
+    }
+    
+    /// Prepend compute budget instructions at a compute unit price, replacing any already present
+    fn with_compute_budget(&self, instructions: Vec<Instruction>, priority_fee: u64) -> Vec<Instruction> {
+        let budget: [Instruction; COMPUTE_BUDGET_INSTRUCTIONS] = [
+            ComputeBudgetInstruction::set_compute_unit_limit(self.priority_fee.compute_unit_limit),
+            ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+        ];
+        let mut budgeted = budget.to_vec();
+        budgeted.extend(instructions.into_iter().filter(|instruction| instruction.program_id != compute_budget::id()));
+        budgeted
+    }
+    
+    /// Set the Argon2id cost parameters (must be called before `init_encryption`)
+    ///
+    /// Changing the parameters changes the derived key, so existing wallets must
+    /// keep using the parameters they were encrypted with.
+    pub fn set_kdf_params(&mut self, kdf_params: KdfParams) {
+        self.kdf_params = kdf_params;
+    }
+    
     /// Initialize encryption key
     pub fn init_encryption(&mut self, password: &str) -> Result<(), WalletError> {
-        // Derive encryption key from password
-        // In a production system, use a proper key derivation function like PBKDF2
+        if password.is_empty() {
+            return Err(WalletError::CryptoError("Password must not be empty".to_string()));
+        }
+        
+        // Derive encryption key from password with Argon2id and the per-install salt
+        let salt = self.load_or_create_salt()?;
+        
+        let params = Params::new(
+            self.kdf_params.memory_kib,
+            self.kdf_params.iterations,
+            self.kdf_params.parallelism,
+            Some(32),
+        ).map_err(|e| WalletError::CryptoError(format!("Invalid key derivation parameters: {}", e)))?;
+        
         let mut key = [0u8; 32];
-        let password_bytes = password.as_bytes();
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .map_err(|e| WalletError::CryptoError(format!("Failed to derive encryption key: {}", e)))?;
         
-        // Simple key derivation (not secure for production)
+        // Old keypair files were encrypted with the password bytes repeated to 32 bytes
+        let mut legacy_key = [0u8; 32];
+        let password_bytes = password.as_bytes();
         for i in 0..32 {
-            key[i] = password_bytes[i % password_bytes.len()];
+            legacy_key[i] = password_bytes[i % password_bytes.len()];
         }
         
         self.encryption_key = Some(key);
+        self.legacy_encryption_key = Some(legacy_key);
         Ok(())
     }
     
+    /// Read the key derivation salt, generating it on first use
+    fn load_or_create_salt(&self) -> Result<Vec<u8>, WalletError> {
+        let salt_path = format!("{}/{}", self.storage_path, KDF_SALT_FILE);
+        
+        if Path::new(&salt_path).exists() {
+            let salt = fs::read(&salt_path)
+                .map_err(|e| WalletError::FileError(format!("Failed to read salt file: {}", e)))?;
+            if salt.len() != KDF_SALT_LEN {
+                return Err(WalletError::CryptoError("Corrupt salt file".to_string()));
+            }
+            return Ok(salt);
+        }
+        
+        let mut salt = vec![0u8; KDF_SALT_LEN];
+        SystemRandom::new().fill(&mut salt)
+            .map_err(|_| WalletError::CryptoError("Failed to generate salt".to_string()))?;
+        
+        fs::write(&salt_path, &salt)
+            .map_err(|e| WalletError::FileError(format!("Failed to write salt file: {}", e)))?;
+        
+        Ok(salt)
+    }
+    
     /// Generate a new wallet
     pub fn generate_wallet(&mut self, wallet_type: WalletType, label: &str) -> Result<Pubkey, WalletError> {
         // Generate new keypair
@@ -142,6 +737,7 @@ impl WalletManager {
             wallet_type,
             label: label.to_string(),
             has_keypair: true,
+            derivation_path: None,
         };
         
         self.wallet_info.insert(pubkey, wallet_info);
@@ -170,6 +766,7 @@ impl WalletManager {
             wallet_type,
             label: label.to_string(),
             has_keypair: true,
+            derivation_path: None,
         };
         
         self.wallet_info.insert(pubkey, wallet_info);
@@ -200,13 +797,98 @@ impl WalletManager {
             wallet_type,
             label: label.to_string(),
             has_keypair: false,
+            derivation_path: None,
+        };
+        
+        self.wallet_info.insert(pubkey, wallet_info);
+        
+        // Save to storage
+        self.save_wallet_info(&pubkey)?;
+        
+        Ok(())
+    }
+    
+    /// Add a wallet whose key stays on a Ledger, at a derivation path such as "m/44'/501'/0'/0'"
+    #[cfg(feature = "ledger")]
+    pub fn add_ledger_wallet(&mut self, derivation_path: &str, wallet_type: WalletType, label: &str) -> Result<Pubkey, WalletError> {
+        let signer = ledger::LedgerSigner::connect(derivation_path)?;
+        let pubkey = signer.wallet_pubkey();
+        
+        // Store wallet info
+        let wallet_info = WalletInfo {
+            pubkey,
+            wallet_type,
+            label: label.to_string(),
+            has_keypair: false,
+            derivation_path: Some(derivation_path.to_string()),
         };
         
         self.wallet_info.insert(pubkey, wallet_info);
+        self.hardware_signers.insert(pubkey, Box::new(signer));
         
         // Save to storage
         self.save_wallet_info(&pubkey)?;
         
+        Ok(pubkey)
+    }
+    
+    /// Reconnect a Ledger-backed wallet found in storage
+    #[cfg(feature = "ledger")]
+    fn restore_hardware_signer(&mut self, pubkey: &Pubkey, derivation_path: &str) {
+        match ledger::LedgerSigner::connect(derivation_path) {
+            Ok(signer) if signer.wallet_pubkey() == *pubkey => {
+                self.hardware_signers.insert(*pubkey, Box::new(signer));
+            },
+            Ok(signer) => warn!("Ledger key at {} is {}, expected {}; wallet can't sign",
+                                derivation_path, signer.wallet_pubkey(), pubkey),
+            Err(e) => warn!("Ledger wallet {} unavailable: {}", pubkey, e),
+        }
+    }
+    
+    /// Reconnect a Ledger-backed wallet found in storage
+    #[cfg(not(feature = "ledger"))]
+    fn restore_hardware_signer(&mut self, pubkey: &Pubkey, _derivation_path: &str) {
+        warn!("Wallet {} is Ledger-backed but the ledger feature is disabled; it can't sign", pubkey);
+    }
+    
+    /// Whether we can sign for a wallet, in software or on a device
+    fn can_sign(&self, pubkey: &Pubkey) -> bool {
+        self.keypairs.contains_key(pubkey) || self.hardware_signers.contains_key(pubkey)
+    }
+    
+    /// Check a wallet's stored keypair decrypts with the current password and belongs to it
+    ///
+    /// Ledger-backed wallets pass if their device was reconnected.
+    pub fn verify_keypair(&self, pubkey: &Pubkey) -> Result<(), WalletError> {
+        let info = self.wallet_info.get(pubkey)
+            .ok_or_else(|| WalletError::KeyError(format!("Wallet {} not found", pubkey)))?;
+        
+        if info.derivation_path.is_some() {
+            return if self.hardware_signers.contains_key(pubkey) {
+                Ok(())
+            } else {
+                Err(WalletError::KeyError(format!("Ledger for {} is not connected", pubkey)))
+            };
+        }
+        if !info.has_keypair {
+            return Err(WalletError::KeyError(format!("Wallet {} is watch-only", pubkey)));
+        }
+        
+        let encryption_key = self.encryption_key
+            .ok_or_else(|| WalletError::CryptoError("Encryption key not initialized".to_string()))?;
+        let keypair_path = format!("{}/{}_keypair.enc", self.storage_path, pubkey);
+        let encrypted = fs::read(&keypair_path)
+            .map_err(|e| WalletError::FileError(format!("Failed to read keypair file: {}", e)))?;
+        if !encrypted.starts_with(KEYPAIR_FILE_MAGIC) {
+            return Err(WalletError::CryptoError(format!("Keypair for {} is in the old format, load wallets to migrate it", pubkey)));
+        }
+        
+        let keypair = Keypair::from_bytes(&self.open_keypair(&encrypted, &encryption_key)?)
+            .map_err(|e| WalletError::KeyError(format!("Invalid keypair data: {}", e)))?;
+        if keypair.pubkey() != *pubkey {
+            return Err(WalletError::KeyError(format!("Keypair file for {} holds {}", pubkey, keypair.pubkey())));
+        }
+        
         Ok(())
     }
     
@@ -224,7 +906,7 @@ impl WalletManager {
         
         // Encrypt and save keypair
         let keypair_bytes = keypair.to_bytes();
-        let encrypted = self.encrypt_data(&keypair_bytes, &encryption_key)?;
+        let encrypted = self.seal_keypair(&keypair_bytes, &encryption_key)?;
         
         let keypair_path = format!("{}/{}_keypair.enc", self.storage_path, pubkey);
         fs::write(&keypair_path, encrypted)
@@ -238,14 +920,15 @@ impl WalletManager {
         let wallet_info = self.wallet_info.get(pubkey)
             .ok_or_else(|| WalletError::GeneralError("Wallet info not found".to_string()))?;
         
-        // Create a simple JSON representation
-        let json = format!(
-            "{{\"pubkey\":\"{}\",\"type\":\"{:?}\",\"label\":\"{}\",\"has_keypair\":{}}}",
-            pubkey.to_string(),
-            wallet_info.wallet_type,
-            wallet_info.label,
-            wallet_info.has_keypair
-        );
+        let record = WalletInfoRecord {
+            pubkey: pubkey.to_string(),
+            wallet_type: wallet_info.wallet_type.as_str().to_string(),
+            label: wallet_info.label.clone(),
+            has_keypair: wallet_info.has_keypair,
+            derivation_path: wallet_info.derivation_path.clone(),
+        };
+        let json = serde_json::to_string(&record)
+            .map_err(|e| WalletError::GeneralError(format!("Failed to serialize wallet info: {}", e)))?;
         
         let info_path = format!("{}/{}_info.json", self.storage_path, pubkey);
         fs::write(&info_path, json)
@@ -259,6 +942,9 @@ impl WalletManager {
         let encryption_key = self.encryption_key
             .ok_or_else(|| WalletError::CryptoError("Encryption key not initialized".to_string()))?;
         
+        // Keypair files still in the old format, re-encrypted once loading finishes
+        let mut migrated = Vec::new();
+        
         // Read directory for wallet files
         let entries = fs::read_dir(&self.storage_path)
             .map_err(|e| WalletError::FileError(format!("Failed to read wallet directory: {}", e)))?;
@@ -279,46 +965,32 @@ impl WalletManager {
                     let info_content = fs::read_to_string(&path)
                         .map_err(|e| WalletError::FileError(format!("Failed to read info file: {}", e)))?;
                     
-                    // Parse JSON (simplified for this example)
-                    // In a real implementation, use a proper JSON parser
-                    let pubkey_str = pubkey_str.to_string();
-                    let pubkey = Pubkey::try_from(pubkey_str.as_str())
+                    let pubkey = Pubkey::try_from(pubkey_str)
                         .map_err(|e| WalletError::KeyError(format!("Invalid pubkey: {}", e)))?;
                     
-                    // Extract wallet type and label from JSON
-                    // This is a simplified parser - use a proper JSON parser in production
-                    let wallet_type_str = if info_content.contains("\"type\":\"Trading\"") {
-                        WalletType::Trading
-                    } else if info_content.contains("\"type\":\"Operational\"") {
-                        WalletType::Operational
-                    } else if info_content.contains("\"type\":\"Profit\"") {
-                        WalletType::Profit
-                    } else {
-                        WalletType::Owner
-                    };
-                    
-                    // Extract label (simplified)
-                    let label_start = info_content.find("\"label\":\"").map(|i| i + 9).unwrap_or(0);
-                    let label_end = info_content[label_start..].find("\"").map(|i| i + label_start).unwrap_or(0);
-                    let label = if label_start > 0 && label_end > label_start {
-                        info_content[label_start..label_end].to_string()
-                    } else {
-                        "Unknown".to_string()
-                    };
+                    let record: WalletInfoRecord = serde_json::from_str(&info_content)
+                        .map_err(|e| WalletError::FileError(format!("Invalid info file {}: {}", file_name, e)))?;
                     
-                    // Extract has_keypair
-                    let has_keypair = info_content.contains("\"has_keypair\":true");
+                    // A misread type could route withdrawals to the wrong wallet, so don't guess
+                    let wallet_type: WalletType = record.wallet_type.parse()
+                        .map_err(|e| WalletError::GeneralError(format!("Invalid info file {}: {}", file_name, e)))?;
+                    let has_keypair = record.has_keypair;
                     
                     // Store wallet info
                     let wallet_info = WalletInfo {
                         pubkey,
-                        wallet_type: wallet_type_str,
-                        label,
+                        wallet_type,
+                        label: record.label,
                         has_keypair,
+                        derivation_path: record.derivation_path.clone(),
                     };
                     
                     self.wallet_info.insert(pubkey, wallet_info);
                     
+                    if let Some(derivation_path) = &record.derivation_path {
+                        self.restore_hardware_signer(&pubkey, derivation_path);
+                    }
+                    
                     // If wallet has keypair, try to load it
                     if has_keypair {
                         let keypair_path = format!("{}/{}_keypair.enc", self.storage_path, pubkey);
@@ -326,7 +998,14 @@ impl WalletManager {
                             let encrypted = fs::read(&keypair_path)
                                 .map_err(|e| WalletError::FileError(format!("Failed to read keypair file: {}", e)))?;
                             
-                            let keypair_bytes = self.decrypt_data(&encrypted, &encryption_key)?;
+                            let keypair_bytes = if encrypted.starts_with(KEYPAIR_FILE_MAGIC) {
+                                self.open_keypair(&encrypted, &encryption_key)?
+                            } else {
+                                let legacy_key = self.legacy_encryption_key
+                                    .ok_or_else(|| WalletError::CryptoError("Encryption key not initialized".to_string()))?;
+                                migrated.push(pubkey);
+                                self.decrypt_data(&encrypted, &legacy_key)?
+                            };
                             let keypair = Keypair::from_bytes(&keypair_bytes)
                                 .map_err(|e| WalletError::KeyError(format!("Invalid keypair data: {}", e)))?;
                             
@@ -337,6 +1016,12 @@ impl WalletManager {
             }
         }
         
+        // Re-encrypt old-format keypairs with the Argon2id-derived key
+        for pubkey in &migrated {
+            self.save_wallet(pubkey)?;
+            warn!("Migrated keypair for {} to the new encryption format", pubkey);
+        }
+        
         Ok(())
     }
     
@@ -352,37 +1037,506 @@ impl WalletManager {
             .collect()
     }
     
+    /// Get the next trading wallet from the pool in round-robin order
+    ///
+    /// Concurrent operations each get a different fee payer so they don't
+    /// contend on the same account write lock.
+    pub fn get_next_trading_wallet(&self) -> Result<Pubkey, WalletError> {
+        // Only wallets we can sign with are usable, sorted so the rotation is stable
+        let mut pool: Vec<Pubkey> = self.wallet_info.values()
+            .filter(|info| info.wallet_type == WalletType::Trading && self.can_sign(&info.pubkey))
+            .map(|info| info.pubkey)
+            .collect();
+        pool.sort();
+        
+        if pool.is_empty() {
+            return Err(WalletError::KeyError("No trading wallet found".to_string()));
+        }
+        
+        let index = self.trading_wallet_cursor.fetch_add(1, Ordering::Relaxed) % pool.len();
+        Ok(pool[index])
+    }
+    
     /// Get wallet balance
     pub fn get_balance(&self, pubkey: &Pubkey) -> Result<u64, WalletError> {
-        self.rpc_client.get_balance(pubkey)
+        self.retry_policy.retry("get_balance", || self.rpc_client.get_balance_with_commitment(pubkey, self.commitment_policy.read))
+            .map(|response| response.value)
             .map_err(|e| WalletError::RpcError(format!("Failed to get balance: {}", e)))
     }
     
-    /// Sign and send transaction
-    pub fn sign_and_send_transaction(&self, instructions: Vec<Instruction>, signers: Vec<&Pubkey>) -> Result<String, WalletError> {
-        // Ensure we have keypairs for all signers
+    /// Get an owner's balance of an SPL token in the token's base units
+    ///
+    /// Sums the associated token account and any other accounts the owner
+    /// holds for the same mint. Returns 0 when the owner has none.
+    pub fn get_token_balance(&self, owner: &Pubkey, mint: &Pubkey) -> Result<u64, WalletError> {
+        let ata = get_associated_token_address(owner, mint);
+        
+        let mut total = match self.rpc_client.get_token_account_balance_with_commitment(&ata, self.commitment_policy.read) {
+            Ok(response) => response.value.amount.parse::<u64>()
+                .map_err(|e| WalletError::RpcError(format!("Invalid token amount for {}: {}", ata, e)))?,
+            Err(e) if e.to_string().contains("could not find account") => 0,
+            Err(e) => return Err(WalletError::RpcError(format!("Failed to get token balance of {}: {}", ata, e))),
+        };
+        
+        // Tokens can also sit in accounts other than the associated one
+        let accounts = self.rpc_client.get_token_accounts_by_owner_with_commitment(
+            owner,
+            TokenAccountsFilter::Mint(*mint),
+            self.commitment_policy.read,
+        ).map_err(|e| WalletError::RpcError(format!("Failed to get token accounts of {}: {}", owner, e)))?;
+        
+        let ata = ata.to_string();
+        for keyed in accounts.value.iter().filter(|keyed| keyed.pubkey != ata) {
+            let amount = match &keyed.account.data {
+                UiAccountData::Json(parsed) => parsed.parsed["info"]["tokenAmount"]["amount"]
+                    .as_str()
+                    .and_then(|amount| amount.parse::<u64>().ok()),
+                _ => None,
+            }.ok_or_else(|| WalletError::RpcError(format!("Unparsed token account {}", keyed.pubkey)))?;
+            
+            total = total.checked_add(amount)
+                .ok_or_else(|| WalletError::GeneralError(format!("Token balance overflow for {}", mint)))?;
+        }
+        
+        Ok(total)
+    }
+    
+    /// Every SPL token an owner holds a nonzero balance of, as (mint, amount in base units) by mint
+    ///
+    /// Balances in several accounts of the same mint are summed.
+    pub fn get_token_balances(&self, owner: &Pubkey) -> Result<Vec<(Pubkey, u64)>, WalletError> {
+        let accounts = self.rpc_client.get_token_accounts_by_owner_with_commitment(
+            owner,
+            TokenAccountsFilter::ProgramId(spl_token::id()),
+            self.commitment_policy.read,
+        ).map_err(|e| WalletError::RpcError(format!("Failed to get token accounts of {}: {}", owner, e)))?;
+        
+        let mut balances: BTreeMap<Pubkey, u64> = BTreeMap::new();
+        for keyed in &accounts.value {
+            let (mint, amount) = match &keyed.account.data {
+                UiAccountData::Json(parsed) => {
+                    let info = &parsed.parsed["info"];
+                    let mint = info["mint"].as_str().and_then(|mint| Pubkey::from_str(mint).ok());
+                    let amount = info["tokenAmount"]["amount"].as_str().and_then(|amount| amount.parse::<u64>().ok());
+                    mint.zip(amount)
+                },
+                _ => None,
+            }.ok_or_else(|| WalletError::RpcError(format!("Unparsed token account {}", keyed.pubkey)))?;
+            
+            let balance = balances.entry(mint).or_insert(0);
+            *balance = balance.checked_add(amount)
+                .ok_or_else(|| WalletError::GeneralError(format!("Token balance overflow for {}", mint)))?;
+        }
+        
+        Ok(balances.into_iter().filter(|(_, amount)| *amount > 0).collect())
+    }
+    
+    /// Make sure an owner's associated token account for `mint` exists, returning its address
+    ///
+    /// A missing account is created in a transaction paid for by the operational
+    /// wallet, so the first transfer of a new token doesn't fail.
+    pub fn ensure_associated_token_account(&self, owner: &Pubkey, mint: &Pubkey) -> Result<Pubkey, WalletError> {
+        let ata = get_associated_token_address(owner, mint);
+        
+        let mut known = lock_or_recover(&self.known_token_accounts);
+        if known.contains(&ata) {
+            return Ok(ata);
+        }
+        
+        let exists = self.rpc_client.get_account_with_commitment(&ata, self.commitment_policy.read)
+            .map_err(|e| WalletError::RpcError(format!("Failed to fetch token account {}: {}", ata, e)))?
+            .value
+            .is_some();
+        
+        if !exists {
+            let payer = self.operational_wallet()
+                .ok_or_else(|| WalletError::KeyError("No operational wallet to pay for the token account".to_string()))?;
+            
+            // Idempotent, so a concurrent creation doesn't fail the transaction
+            let instruction = create_associated_token_account_idempotent(&payer, owner, mint, &spl_token::id());
+            let signature = self.sign_and_send_transaction(vec![instruction], vec![&payer], Some(&payer))?;
+            
+            let signature = Signature::from_str(&signature)
+                .map_err(|e| WalletError::TransactionError(format!("Invalid signature {}: {}", signature, e)))?;
+            self.rpc_client.poll_for_signature_with_commitment(&signature, self.commitment_policy.confirm)
+                .map_err(|e| WalletError::TransactionError(format!("Token account {} was not created: {}", ata, e)))?;
+            
+            info!("Created token account {} for {} (mint {})", ata, owner, mint);
+        }
+        
+        known.insert(ata);
+        Ok(ata)
+    }
+    
+    /// Check whether a transaction has reached the confirmation commitment
+    pub fn confirm_transaction(&self, signature: &str) -> Result<bool, WalletError> {
+        let signature = Signature::from_str(signature)
+            .map_err(|e| WalletError::TransactionError(format!("Invalid signature {}: {}", signature, e)))?;
+        
+        self.rpc_client.confirm_transaction_with_commitment(&signature, self.commitment_policy.confirm)
+            .map(|response| response.value)
+            .map_err(|e| WalletError::RpcError(format!("Failed to confirm transaction: {}", e)))
+    }
+    
+    /// Fetch address lookup tables, serving cached entries where possible
+    pub fn get_lookup_tables(&self, addresses: &[Pubkey]) -> Result<Vec<AddressLookupTableAccount>, WalletError> {
+        // Take cached tables and release the lock before any RPC call
+        let mut cached: HashMap<Pubkey, AddressLookupTableAccount> = {
+            let cache = lock_or_recover(&self.lookup_table_cache);
+            addresses.iter()
+                .filter_map(|address| cache.get(address).map(|table| (*address, table.clone())))
+                .collect()
+        };
+        
+        let mut fetched = Vec::new();
+        for address in addresses {
+            if cached.contains_key(address) {
+                continue;
+            }
+            
+            let account = self.rpc_client.get_account(address)
+                .map_err(|e| WalletError::RpcError(format!("Failed to fetch lookup table {}: {}", address, e)))?;
+            
+            let lookup_table = AddressLookupTable::deserialize(&account.data)
+                .map_err(|e| WalletError::GeneralError(format!("Invalid lookup table {}: {}", address, e)))?;
+            
+            let table = AddressLookupTableAccount {
+                key: *address,
+                addresses: lookup_table.addresses.to_vec(),
+            };
+            
+            cached.insert(*address, table.clone());
+            fetched.push(table);
+        }
+        
+        if !fetched.is_empty() {
+            let mut cache = lock_or_recover(&self.lookup_table_cache);
+            for table in fetched {
+                cache.insert(table.key, table);
+            }
+        }
+        
+        Ok(addresses.iter().filter_map(|address| cached.get(address).cloned()).collect())
+    }
+    
+    /// Sign and send a legacy transaction at the current priority fee
+    ///
+    /// Fees are paid by `fee_payer`, or by the signing trading wallet when none is given.
+    pub fn sign_and_send_transaction(
+        &self,
+        instructions: Vec<Instruction>,
+        signers: Vec<&Pubkey>,
+        fee_payer: Option<&Pubkey>,
+    ) -> Result<String, WalletError> {
+        let priority_fee = self.priority_fee(&instructions);
+        self.sign_and_send_priced(instructions, signers, Vec::new(), priority_fee, fee_payer)
+    }
+    
+    /// Sign and send a transaction at a given compute unit price (in micro-lamports)
+    ///
+    /// Sent as a v0 transaction when lookup tables are given, legacy otherwise.
+    /// The fee payer (defaulting to the signing trading wallet, or the operational
+    /// wallet when that's enabled) signs and pays fees and tips.
+    pub fn sign_and_send_priced(
+        &self,
+        instructions: Vec<Instruction>,
+        signers: Vec<&Pubkey>,
+        lookup_tables: Vec<AddressLookupTableAccount>,
+        priority_fee: u64,
+        fee_payer: Option<&Pubkey>,
+    ) -> Result<String, WalletError> {
+        let instructions = self.with_compute_budget(instructions, priority_fee);
+        
+        // The payer signs first, which makes it the fee payer of the message
+        let payer = self.resolve_fee_payer(fee_payer, &signers)?;
+        let mut ordered_signers = vec![&payer];
+        ordered_signers.extend(signers.into_iter().filter(|signer| **signer != payer));
+        let fee = self.transaction_fee(ordered_signers.len(), priority_fee);
+        
+        // Route through the Jito block engine when configured, so the trade never sits in the public mempool
+        #[cfg(feature = "jito")]
+        if let Some(jito) = &self.jito {
+            let mut bundled = instructions.clone();
+            bundled.push(jito.tip_instruction(&payer));
+            
+            match self.send_signed(&bundled, &ordered_signers, &lookup_tables, |transaction| jito.send_bundle(transaction)) {
+                Ok(signature) => {
+                    self.record_fee(&payer, fee + jito.tip_lamports());
+                    return Ok(signature);
+                },
+                Err(e) if jito.fallback_to_rpc() => warn!("Jito bundle not accepted, sending normally: {}", e),
+                Err(e) => return Err(e),
+            }
+        }
+        
+        let signature = self.send_signed(&instructions, &ordered_signers, &lookup_tables, |transaction| {
+            self.rpc_client.send_transaction(transaction)
+                .map_err(|e| WalletError::TransactionError(format!("Failed to send transaction: {}", e)))
+        })?;
+        self.record_fee(&payer, fee);
+        
+        Ok(signature)
+    }
+    
+    /// Sign and send a v0 transaction that compresses its accounts through lookup tables
+    ///
+    /// Needed for Jupiter routes and multi-DEX arbitrage that exceed the legacy account limit.
+    pub fn sign_and_send_versioned(
+        &self,
+        instructions: Vec<Instruction>,
+        signers: Vec<&Pubkey>,
+        lookup_tables: Vec<AddressLookupTableAccount>,
+        fee_payer: Option<&Pubkey>,
+    ) -> Result<String, WalletError> {
+        let priority_fee = self.priority_fee(&instructions);
+        self.sign_and_send_priced(instructions, signers, lookup_tables, priority_fee, fee_payer)
+    }
+    
+    /// The operational wallet that pays fees when enabled (lowest key if there are several)
+    fn operational_wallet(&self) -> Option<Pubkey> {
+        self.wallet_info.values()
+            .filter(|info| info.wallet_type == WalletType::Operational && self.can_sign(&info.pubkey))
+            .map(|info| info.pubkey)
+            .min()
+    }
+    
+    /// Fee payer for a transaction: the one given, else the operational wallet if enabled,
+    /// else the signing trading wallet, else the first signer
+    fn resolve_fee_payer(&self, fee_payer: Option<&Pubkey>, signers: &[&Pubkey]) -> Result<Pubkey, WalletError> {
+        if let Some(fee_payer) = fee_payer {
+            return Ok(*fee_payer);
+        }
+        
+        if self.operational_fee_payer {
+            match self.operational_wallet() {
+                Some(operational) => return Ok(operational),
+                None => debug!("No operational wallet, the signers pay the fees"),
+            }
+        }
+        
+        let trading_signer = signers.iter()
+            .find(|signer| self.wallet_info.get(**signer).map_or(false, |info| info.wallet_type == WalletType::Trading));
+        
+        trading_signer.or_else(|| signers.first())
+            .map(|signer| **signer)
+            .ok_or_else(|| WalletError::KeyError("At least one signer is required".to_string()))
+    }
+    
+    /// Fee in lamports for a transaction with `signature_count` signatures at a compute unit price
+    ///
+    /// The priority fee is charged on the requested compute unit limit, not the units used.
+    fn transaction_fee(&self, signature_count: usize, priority_fee: u64) -> u64 {
+        let priority_lamports = (priority_fee as u128 * self.priority_fee.compute_unit_limit as u128 + 999_999) / 1_000_000;
+        LAMPORTS_PER_SIGNATURE * signature_count as u64 + priority_lamports as u64
+    }
+    
+    /// Count a sent transaction's fee if an operational wallet paid it
+    fn record_fee(&self, payer: &Pubkey, fee: u64) {
+        let is_operational = self.wallet_info.get(payer)
+            .map_or(false, |info| info.wallet_type == WalletType::Operational);
+        if is_operational {
+            let total = self.operational_fees_paid.fetch_add(fee, Ordering::Relaxed) + fee;
+            debug!("Operational wallet {} paid {} lamports in fees ({} total)", payer, fee, total);
+        }
+    }
+    
+    /// Lamports paid in fees and tips by the operational wallets since startup
+    pub fn operational_fees_paid(&self) -> u64 {
+        self.operational_fees_paid.load(Ordering::Relaxed)
+    }
+    
+    /// Sign a transaction with every signer and hand it to `submit`, retrying on a stale blockhash
+    ///
+    /// Built as a v0 transaction when lookup tables are given, legacy otherwise.
+    fn send_signed<F>(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Pubkey],
+        lookup_tables: &[AddressLookupTableAccount],
+        submit: F,
+    ) -> Result<String, WalletError>
+    where
+        F: Fn(&VersionedTransaction) -> Result<Signature, WalletError>,
+    {
+        let wallet_signers = self.resolve_signers(signers)?;
+        let payer = wallet_signers[0].wallet_pubkey();
+        
+        self.send_with_blockhash_retry(|blockhash| {
+            let message = compile_message(&payer, instructions, lookup_tables, blockhash)?;
+            
+            // Sign transaction, on a device for hardware-backed wallets
+            let mut transaction = VersionedTransaction {
+                signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+                message,
+            };
+            for signer in &wallet_signers {
+                signer.sign_versioned_transaction(&mut transaction)?;
+            }
+            
+            // Resending the same signed transaction is safe, it can only land once
+            self.retry_policy.retry("send_transaction", || submit(&transaction))
+        })
+    }
+    
+    /// Resolve the software or hardware signer for each of a list of signers (the first is the fee payer)
+    fn resolve_signers(&self, signers: &[&Pubkey]) -> Result<Vec<&dyn WalletSigner>, WalletError> {
+        if signers.is_empty() {
+            return Err(WalletError::KeyError("At least one signer is required".to_string()));
+        }
+        
+        signers.iter()
+            .map(|signer_pubkey| {
+                self.keypairs.get(*signer_pubkey)
+                    .map(|keypair| keypair as &dyn WalletSigner)
+                    .or_else(|| self.hardware_signers.get(*signer_pubkey).map(|signer| signer.as_ref()))
+                    .ok_or_else(|| WalletError::KeyError(format!("Keypair not found for {}", signer_pubkey)))
+            })
+            .collect()
+    }
+    
+    /// Recent blockhash from the cache if one is set, otherwise fetched now
+    fn recent_blockhash(&self) -> Result<Hash, WalletError> {
+        match &self.blockhash_cache {
+            Some(blockhash_cache) => blockhash_cache.get(),
+            None => self.retry_policy.retry("get_latest_blockhash", || self.rpc_client.get_latest_blockhash()),
+        }.map_err(|e| WalletError::RpcError(format!("Failed to get recent blockhash: {}", e)))
+    }
+    
+    /// Build, sign and send with a fresh blockhash, retrying on "Blockhash not found"
+    fn send_with_blockhash_retry<F>(&self, build_and_send: F) -> Result<String, WalletError>
+    where
+        F: Fn(Hash) -> Result<Signature, WalletError>,
+    {
+        let mut attempt = 0;
+        loop {
+            let blockhash = self.recent_blockhash()?;
+            
+            match build_and_send(blockhash) {
+                Ok(signature) => return Ok(signature.to_string()),
+                // A stale blockhash is transient, re-sign with a fresh one
+                Err(e) if attempt < MAX_BLOCKHASH_RETRIES && is_blockhash_not_found_error(&e.to_string()) => {
+                    attempt += 1;
+                    if let Some(blockhash_cache) = &self.blockhash_cache {
+                        blockhash_cache.invalidate();
+                    }
+                    warn!("Blockhash not found, retrying with a fresh blockhash ({}/{})", attempt, MAX_BLOCKHASH_RETRIES);
+                },
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    
+    /// Build a transaction with a designated fee payer and sign it with the locally held keypairs
+    ///
+    /// Signers whose keypairs are not held locally (including an external fee payer)
+    /// are left unsigned so the caller can complete the transaction.
+    pub fn build_and_partial_sign(
+        &self,
+        instructions: Vec<Instruction>,
+        fee_payer: &Pubkey,
+        local_signers: &[&Pubkey],
+    ) -> Result<Transaction, WalletError> {
+        // Resolve the local keypairs we were asked to sign with
         let mut keypair_signers = Vec::new();
-        for signer_pubkey in signers {
-            let keypair = self.keypairs.get(signer_pubkey)
+        for signer_pubkey in local_signers {
+            let keypair = self.keypairs.get(*signer_pubkey)
                 .ok_or_else(|| WalletError::KeyError(format!("Keypair not found for {}", signer_pubkey)))?;
             keypair_signers.push(keypair);
         }
         
         // Get recent blockhash
-        let blockhash = self.rpc_client.get_latest_blockhash()
-            .map_err(|e| WalletError::RpcError(format!("Failed to get recent blockhash: {}", e)))?;
+        let blockhash = self.recent_blockhash()?;
+        
+        // Create transaction with the designated fee payer
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(fee_payer));
+        
+        // Sign with whatever we hold locally; the remaining signatures stay empty
+        transaction.try_partial_sign(&keypair_signers, blockhash)
+            .map_err(|e| WalletError::TransactionError(format!("Failed to partially sign transaction: {}", e)))?;
+        
+        Ok(transaction)
+    }
+    
+    /// Encrypt keypair bytes into the versioned on-disk format
+    fn seal_keypair(&self, data: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, WalletError> {
+        // Generate random nonce
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new().fill(&mut nonce)
+            .map_err(|_| WalletError::CryptoError("Failed to generate nonce".to_string()))?;
+        
+        let unbound_key = UnboundKey::new(&AES_256_GCM, key)
+            .map_err(|_| WalletError::CryptoError("Failed to create encryption key".to_string()))?;
+        let less_safe_key = LessSafeKey::new(unbound_key);
+        
+        let mut in_out = data.to_vec();
+        less_safe_key.seal_in_place_append_tag(
+            ring::aead::Nonce::assume_unique_for_key(nonce),
+            ring::aead::Aad::empty(),
+            &mut in_out,
+        ).map_err(|_| WalletError::CryptoError("Encryption failed".to_string()))?;
+        
+        // Header and nonce are stored in the clear so the file can be decrypted later
+        let mut sealed = Vec::with_capacity(KEYPAIR_FILE_MAGIC.len() + 1 + NONCE_LEN + in_out.len());
+        sealed.extend_from_slice(KEYPAIR_FILE_MAGIC);
+        sealed.push(KEYPAIR_FORMAT_VERSION);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&in_out);
+        
+        Ok(sealed)
+    }
+    
+    /// Decrypt keypair bytes from the versioned on-disk format
+    fn open_keypair(&self, sealed: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, WalletError> {
+        let header_len = KEYPAIR_FILE_MAGIC.len() + 1;
+        if sealed.len() < header_len + NONCE_LEN || !sealed.starts_with(KEYPAIR_FILE_MAGIC) {
+            return Err(WalletError::CryptoError("Keypair file is truncated or not in a known format".to_string()));
+        }
+        
+        let version = sealed[KEYPAIR_FILE_MAGIC.len()];
+        if version != KEYPAIR_FORMAT_VERSION {
+            return Err(WalletError::CryptoError(format!("Unsupported keypair file version {}", version)));
+        }
+        
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&sealed[header_len..header_len + NONCE_LEN]);
+        
+        let unbound_key = UnboundKey::new(&AES_256_GCM, key)
+            .map_err(|_| WalletError::CryptoError("Failed to create encryption key".to_string()))?;
+        let less_safe_key = LessSafeKey::new(unbound_key);
+        
+        let mut in_out = sealed[header_len + NONCE_LEN..].to_vec();
+        let plaintext = less_safe_key.open_in_place(
+            ring::aead::Nonce::assume_unique_for_key(nonce),
+            ring::aead::Aad::empty(),
+            &mut in_out,
+        ).map_err(|_| WalletError::CryptoError("Decryption failed (wrong password or corrupt file)".to_string()))?;
         
-        // Create transaction
-        let mut transaction = Transaction::new_with_payer(&instructions, Some(&keypair_signers[0].pubkey()));
+        Ok(plaintext.to_vec())
+    }
+    
+    /// Decrypt keypair bytes from the old unversioned format: the nonce followed by the ciphertext
+    ///
+    /// Only used to migrate files written before the versioned format existed.
+    fn decrypt_data(&self, encrypted: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, WalletError> {
+        if encrypted.len() < NONCE_LEN {
+            return Err(WalletError::CryptoError("Keypair file is truncated or not in a known format".to_string()));
+        }
         
-        // Sign transaction
-        transaction.sign(&keypair_signers, blockhash);
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&encrypted[..NONCE_LEN]);
         
-        // Send transaction
-        let signature = self.rpc_client.send_transaction(&transaction)
-            .map_err(|e| WalletError::TransactionError(format!("Failed to send transaction: {}", e)))?;
+        let unbound_key = UnboundKey::new(&AES_256_GCM, key)
+            .map_err(|_| WalletError::CryptoError("Failed to create encryption key".to_string()))?;
+        let less_safe_key = LessSafeKey::new(unbound_key);
+        
+        let mut in_out = encrypted[NONCE_LEN..].to_vec();
+        let plaintext = less_safe_key.open_in_place(
+            ring::aead::Nonce::assume_unique_for_key(nonce),
+            ring::aead::Aad::empty(),
+            &mut in_out,
+        ).map_err(|_| WalletError::CryptoError("Decryption failed (wrong password or corrupt file)".to_string()))?;
         
-        Ok(signature.to_string())
+        Ok(plaintext.to_vec())
     }
     
     /// Encrypt data
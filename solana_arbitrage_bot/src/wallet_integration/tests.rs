@@ -0,0 +1,480 @@
+// Tests for the wallet integration module
+// Run against mock RPC clients and throwaway storage directories
+
+use super::*;
+use async_trait::async_trait;
+use serde_json::json;
+use solana_client::client_error::{ClientError, ClientErrorKind, Result as ClientResult};
+use solana_client::mock_sender::MockSender;
+use solana_client::rpc_client::RpcClientConfig;
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_sdk::address_lookup_table::state::LookupTableMeta;
+use solana_sdk::instruction::AccountMeta;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+/// Fresh storage directory under the system temp dir
+fn temp_storage(name: &str) -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("sab-{}-{}-{}", name, std::process::id(), nanos));
+    path.to_string_lossy().to_string()
+}
+
+/// Cheap key derivation so tests don't spend 64 MiB per manager
+fn fast_kdf() -> KdfParams {
+    KdfParams {
+        memory_kib: 8,
+        iterations: 1,
+        parallelism: 1,
+    }
+}
+
+/// Manager over `rpc_client` and a fresh storage directory, with encryption initialized
+fn manager_with_client(name: &str, rpc_client: RpcClient) -> WalletManager {
+    let mut manager = WalletManager::with_rpc_client(rpc_client, &temp_storage(name));
+    manager.set_kdf_params(fast_kdf());
+    manager.init_encryption("test password").unwrap();
+    manager
+}
+
+/// Manager over a mock RPC client and a fresh storage directory, with encryption initialized
+fn test_manager(name: &str) -> WalletManager {
+    manager_with_client(name, RpcClient::new_mock("succeeds".to_string()))
+}
+
+/// Fresh manager over the wallets stored at `storage_path`, loaded as on restart
+fn reload_manager(storage_path: &str) -> WalletManager {
+    let mut manager = WalletManager::with_rpc_client(RpcClient::new_mock("succeeds".to_string()), storage_path);
+    manager.set_kdf_params(fast_kdf());
+    manager.init_encryption("test password").unwrap();
+    manager.load_wallets().unwrap();
+    manager
+}
+
+/// RPC sender answering like the mock client while recording every request
+struct RecordingSender {
+    /// Mock answering the requests
+    inner: MockSender,
+    /// Requests seen so far, with their params
+    requests: Arc<Mutex<Vec<(RpcRequest, serde_json::Value)>>>,
+    /// Errors returned instead of the mock's answer, in order, for the next requests of each kind
+    failures: Mutex<VecDeque<(RpcRequest, String)>>,
+}
+
+impl RecordingSender {
+    /// Sender that fails the given requests, in order, before answering like the mock
+    fn failing(failures: Vec<(RpcRequest, &str)>) -> (Self, Arc<Mutex<Vec<(RpcRequest, serde_json::Value)>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let sender = Self {
+            inner: MockSender::new("succeeds".to_string()),
+            requests: requests.clone(),
+            failures: Mutex::new(failures.into_iter().map(|(request, error)| (request, error.to_string())).collect()),
+        };
+        (sender, requests)
+    }
+}
+
+#[async_trait]
+impl RpcSender for RecordingSender {
+    async fn send(&self, request: RpcRequest, params: serde_json::Value) -> ClientResult<serde_json::Value> {
+        self.requests.lock().unwrap().push((request, params.clone()));
+        
+        let failure = {
+            let mut failures = self.failures.lock().unwrap();
+            match failures.front() {
+                Some((failing, _)) if *failing == request => failures.pop_front().map(|(_, error)| error),
+                _ => None,
+            }
+        };
+        if let Some(error) = failure {
+            return Err(ClientError::from(ClientErrorKind::Custom(error)));
+        }
+        
+        self.inner.send(request, params).await
+    }
+    
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+    
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+/// Serialized lookup table account holding `addresses`
+fn lookup_table_data(addresses: &[Pubkey]) -> Vec<u8> {
+    AddressLookupTable {
+        meta: LookupTableMeta::default(),
+        addresses: Cow::Borrowed(addresses),
+    }
+    .serialize_for_tests()
+    .expect("serialize lookup table")
+}
+
+/// `getAccountInfo` response wrapping `data`
+fn account_info_response(data: &[u8], owner: &Pubkey) -> serde_json::Value {
+    json!({
+        "context": { "slot": 1 },
+        "value": {
+            "data": [BASE64.encode(data), "base64"],
+            "executable": false,
+            "lamports": 1_000_000,
+            "owner": owner.to_string(),
+            "rentEpoch": 0,
+            "space": data.len(),
+        }
+    })
+}
+
+#[test]
+fn v0_message_loads_table_accounts() {
+    let payer = Pubkey::new_unique();
+    let program = Pubkey::new_unique();
+    let static_account = Pubkey::new_unique();
+    let table_accounts = [Pubkey::new_unique(), Pubkey::new_unique()];
+    
+    let instruction = Instruction::new_with_bytes(
+        program,
+        &[1, 2, 3],
+        vec![
+            AccountMeta::new(static_account, false),
+            AccountMeta::new(table_accounts[0], false),
+            AccountMeta::new_readonly(table_accounts[1], false),
+        ],
+    );
+    let table = AddressLookupTableAccount {
+        key: Pubkey::new_unique(),
+        addresses: table_accounts.to_vec(),
+    };
+    
+    let legacy = compile_message(&payer, &[instruction.clone()], &[], Hash::new_unique()).unwrap();
+    assert!(matches!(legacy, VersionedMessage::Legacy(_)));
+    assert_eq!(legacy.static_account_keys().len(), 5);
+    
+    let message = compile_message(&payer, &[instruction], &[table], Hash::new_unique()).unwrap();
+    let VersionedMessage::V0(v0_message) = &message else {
+        panic!("expected a v0 message");
+    };
+    
+    // Payer, program and the account missing from the table stay static
+    assert_eq!(message.static_account_keys().len(), 3);
+    assert!(message.static_account_keys().contains(&payer));
+    assert!(message.static_account_keys().contains(&program));
+    assert!(message.static_account_keys().contains(&static_account));
+    
+    assert_eq!(v0_message.address_table_lookups.len(), 1);
+    let lookup = &v0_message.address_table_lookups[0];
+    assert_eq!(lookup.writable_indexes, vec![0]);
+    assert_eq!(lookup.readonly_indexes, vec![1]);
+}
+
+#[test]
+fn lookup_tables_are_fetched_once_then_cached() {
+    let table_key = Pubkey::new_unique();
+    let addresses = vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+    
+    let mut mocks = HashMap::new();
+    mocks.insert(
+        RpcRequest::GetAccountInfo,
+        account_info_response(&lookup_table_data(&addresses), &solana_sdk::address_lookup_table::program::id()),
+    );
+    let manager = WalletManager::with_rpc_client(
+        RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks),
+        &temp_storage("lookup"),
+    );
+    
+    let tables = manager.get_lookup_tables(&[table_key]).unwrap();
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].key, table_key);
+    assert_eq!(tables[0].addresses, addresses);
+    assert!(manager.lookup_table_cache.lock().unwrap().contains_key(&table_key));
+    
+    // Served from the cache (mocks are consumed, so a refetch would fail), in request order
+    let again = manager.get_lookup_tables(&[table_key, table_key]).unwrap();
+    assert_eq!(again.len(), 2);
+    assert_eq!(again[1].addresses, addresses);
+}
+
+#[test]
+fn partial_sign_with_two_local_keypairs_and_external_fee_payer() {
+    let mut manager = test_manager("partial-sign");
+    let first = manager.generate_wallet(WalletType::Trading, "first").unwrap();
+    let second = manager.generate_wallet(WalletType::Trading, "second").unwrap();
+    let fee_payer = Pubkey::new_unique();
+    
+    let instruction = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[7],
+        vec![
+            AccountMeta::new(first, true),
+            AccountMeta::new_readonly(second, true),
+        ],
+    );
+    
+    let transaction = manager.build_and_partial_sign(vec![instruction], &fee_payer, &[&first, &second]).unwrap();
+    
+    // Fee payer comes first, then the two local signers
+    let keys = &transaction.message.account_keys;
+    assert_eq!(transaction.message.header.num_required_signatures, 3);
+    assert_eq!(keys[0], fee_payer);
+    assert_eq!(transaction.signatures.len(), 3);
+    
+    // The external fee payer's slot is left for the caller to fill
+    assert_eq!(transaction.signatures[0], Signature::default());
+    assert!(!transaction.is_signed());
+    
+    let message_bytes = transaction.message_data();
+    for signer in [first, second] {
+        let index = keys.iter().position(|key| *key == signer).unwrap();
+        assert!(index < 3);
+        assert!(transaction.signatures[index].verify(signer.as_ref(), &message_bytes));
+    }
+}
+
+#[test]
+fn partial_sign_rejects_unknown_local_signer() {
+    let manager = test_manager("partial-sign-unknown");
+    let stranger = Pubkey::new_unique();
+    
+    let result = manager.build_and_partial_sign(Vec::new(), &Pubkey::new_unique(), &[&stranger]);
+    assert!(matches!(result, Err(WalletError::KeyError(_))));
+}
+
+#[test]
+fn trading_wallets_rotate_round_robin() {
+    let mut manager = test_manager("round-robin");
+    assert!(matches!(manager.get_next_trading_wallet(), Err(WalletError::KeyError(_))));
+    
+    let mut trading = vec![
+        manager.generate_wallet(WalletType::Trading, "a").unwrap(),
+        manager.generate_wallet(WalletType::Trading, "b").unwrap(),
+        manager.generate_wallet(WalletType::Trading, "c").unwrap(),
+    ];
+    manager.generate_wallet(WalletType::Profit, "vault").unwrap();
+    trading.sort();
+    
+    // Each trading wallet once per cycle, in a stable order, never the profit wallet
+    let picks: Vec<Pubkey> = (0..6).map(|_| manager.get_next_trading_wallet().unwrap()).collect();
+    let first_cycle = &picks[..3];
+    let mut seen = first_cycle.to_vec();
+    seen.sort();
+    assert_eq!(seen, trading);
+    assert_eq!(&picks[3..], first_cycle);
+}
+
+#[test]
+fn commitment_policy_reaches_every_read() {
+    let (sender, requests) = RecordingSender::failing(Vec::new());
+    // The client default differs from the policy, so a call that ignores the policy shows up
+    let rpc_client = RpcClient::new_sender(sender, RpcClientConfig::with_commitment(CommitmentConfig::finalized()));
+    let mut manager = WalletManager::with_rpc_client(rpc_client, &temp_storage("commitment"));
+    manager.set_commitment_policy(CommitmentPolicy {
+        read: CommitmentConfig::processed(),
+        confirm: CommitmentConfig::finalized(),
+    });
+    
+    let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    manager.get_balance(&owner).unwrap();
+    let _ = manager.get_token_balance(&owner, &mint);
+    let _ = manager.get_token_balances(&owner);
+    manager.ensure_associated_token_account(&owner, &mint).unwrap();
+    
+    let recorded = requests.lock().unwrap();
+    for method in [
+        RpcRequest::GetBalance,
+        RpcRequest::GetTokenAccountBalance,
+        RpcRequest::GetTokenAccountsByOwner,
+        RpcRequest::GetAccountInfo,
+    ] {
+        let params = recorded.iter()
+            .find(|(request, _)| *request == method)
+            .map(|(_, params)| params)
+            .unwrap_or_else(|| panic!("{} was never called", method));
+        let commitment = params.as_array()
+            .and_then(|params| params.last())
+            .and_then(|config| config["commitment"].as_str());
+        assert_eq!(commitment, Some("processed"), "{} params: {}", method, params);
+    }
+}
+
+/// Count the recorded requests of one kind
+fn count(requests: &Mutex<Vec<(RpcRequest, serde_json::Value)>>, method: RpcRequest) -> usize {
+    requests.lock().unwrap().iter().filter(|(request, _)| *request == method).count()
+}
+
+#[test]
+fn blockhash_not_found_is_retried_with_a_fresh_blockhash() {
+    let (sender, requests) = RecordingSender::failing(vec![
+        (RpcRequest::SendTransaction, "Transaction simulation failed: Blockhash not found"),
+    ]);
+    let rpc_client = RpcClient::new_sender(sender, RpcClientConfig::with_commitment(CommitmentConfig::confirmed()));
+    let mut manager = manager_with_client("blockhash-retry", rpc_client);
+    let wallet = manager.generate_wallet(WalletType::Trading, "trader").unwrap();
+    
+    let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![AccountMeta::new(wallet, true)]);
+    manager.sign_and_send_transaction(vec![instruction], vec![&wallet], None).unwrap();
+    
+    // Re-signed over a newly fetched blockhash and sent again
+    assert_eq!(count(&requests, RpcRequest::SendTransaction), 2);
+    assert_eq!(count(&requests, RpcRequest::GetLatestBlockhash), 2);
+}
+
+#[test]
+fn blockhash_not_found_is_retried_only_once() {
+    let (sender, requests) = RecordingSender::failing(vec![
+        (RpcRequest::SendTransaction, "Blockhash not found"),
+        (RpcRequest::SendTransaction, "Blockhash not found"),
+    ]);
+    let rpc_client = RpcClient::new_sender(sender, RpcClientConfig::with_commitment(CommitmentConfig::confirmed()));
+    let mut manager = manager_with_client("blockhash-give-up", rpc_client);
+    let wallet = manager.generate_wallet(WalletType::Trading, "trader").unwrap();
+    
+    let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![AccountMeta::new(wallet, true)]);
+    let result = manager.sign_and_send_transaction(vec![instruction], vec![&wallet], None);
+    
+    assert!(matches!(result, Err(WalletError::TransactionError(_))));
+    assert_eq!(count(&requests, RpcRequest::SendTransaction), 2);
+}
+
+#[test]
+fn old_format_keypair_is_loaded_and_migrated() {
+    let mut manager = test_manager("legacy-migration");
+    let pubkey = manager.generate_wallet(WalletType::Trading, "old").unwrap();
+    let keypair_bytes = manager.keypairs[&pubkey].to_bytes();
+    let storage_path = manager.storage_path.clone();
+    drop(manager);
+    
+    // Rewrite the keypair the old way: nonce then ciphertext, under the repeated-password key
+    let password = b"test password";
+    let mut legacy_key = [0u8; 32];
+    for (i, byte) in legacy_key.iter_mut().enumerate() {
+        *byte = password[i % password.len()];
+    }
+    let nonce = [7u8; NONCE_LEN];
+    let mut ciphertext = keypair_bytes.to_vec();
+    LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &legacy_key).unwrap())
+        .seal_in_place_append_tag(ring::aead::Nonce::assume_unique_for_key(nonce), ring::aead::Aad::empty(), &mut ciphertext)
+        .unwrap();
+    let mut legacy_file = nonce.to_vec();
+    legacy_file.extend_from_slice(&ciphertext);
+    let keypair_path = format!("{}/{}_keypair.enc", storage_path, pubkey);
+    fs::write(&keypair_path, &legacy_file).unwrap();
+    
+    let reloaded = reload_manager(&storage_path);
+    
+    assert_eq!(reloaded.keypairs[&pubkey].to_bytes(), keypair_bytes);
+    
+    // The file was rewritten in the versioned format
+    let migrated = fs::read(&keypair_path).unwrap();
+    assert!(migrated.starts_with(KEYPAIR_FILE_MAGIC));
+    assert_eq!(reloaded.open_keypair(&migrated, &reloaded.encryption_key.unwrap()).unwrap(), keypair_bytes.to_vec());
+    
+    let _ = fs::remove_dir_all(&storage_path);
+}
+
+#[test]
+fn generated_wallet_survives_a_reload_and_signs() {
+    let mut manager = test_manager("reload-sign");
+    let pubkey = manager.generate_wallet(WalletType::Trading, "survivor").unwrap();
+    let storage_path = manager.storage_path.clone();
+    drop(manager);
+    
+    let reloaded = reload_manager(&storage_path);
+    reloaded.verify_keypair(&pubkey).unwrap();
+    
+    let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![AccountMeta::new(pubkey, true)]);
+    let transaction = reloaded.build_and_partial_sign(vec![instruction], &pubkey, &[&pubkey]).unwrap();
+    
+    assert!(transaction.is_signed());
+    assert!(transaction.signatures[0].verify(pubkey.as_ref(), &transaction.message_data()));
+    
+    let _ = fs::remove_dir_all(&storage_path);
+}
+
+#[test]
+fn label_with_quotes_and_braces_survives_a_reload() {
+    let mut manager = test_manager("label-escaping");
+    let label = r#"desk "alpha" {main}, \ backup"#;
+    let pubkey = manager.generate_wallet(WalletType::Profit, label).unwrap();
+    let storage_path = manager.storage_path.clone();
+    drop(manager);
+    
+    let reloaded = reload_manager(&storage_path);
+    let info = &reloaded.wallet_info[&pubkey];
+    assert_eq!(info.label, label);
+    assert_eq!(info.wallet_type, WalletType::Profit);
+    
+    let _ = fs::remove_dir_all(&storage_path);
+}
+
+#[test]
+fn every_wallet_type_survives_a_reload() {
+    let mut manager = test_manager("wallet-types");
+    let types = [
+        WalletType::Trading,
+        WalletType::Operational,
+        WalletType::Profit,
+        WalletType::Owner,
+        WalletType::Reserve,
+    ];
+    let wallets: Vec<(Pubkey, WalletType)> = types.iter()
+        .map(|wallet_type| (manager.generate_wallet(*wallet_type, wallet_type.as_str()).unwrap(), *wallet_type))
+        .collect();
+    let storage_path = manager.storage_path.clone();
+    drop(manager);
+    
+    let reloaded = reload_manager(&storage_path);
+    assert_eq!(reloaded.wallet_info.len(), types.len());
+    for (pubkey, wallet_type) in wallets {
+        assert_eq!(reloaded.wallet_info[&pubkey].wallet_type, wallet_type);
+        assert_eq!(reloaded.get_wallets_by_type(wallet_type).len(), 1);
+    }
+    
+    let _ = fs::remove_dir_all(&storage_path);
+}
+
+#[test]
+fn unknown_wallet_type_fails_the_reload() {
+    let mut manager = test_manager("unknown-type");
+    let pubkey = manager.generate_wallet(WalletType::Reserve, "vault").unwrap();
+    let storage_path = manager.storage_path.clone();
+    drop(manager);
+    
+    let info_path = format!("{}/{}_info.json", storage_path, pubkey);
+    let info = fs::read_to_string(&info_path).unwrap().replace("\"Reserve\"", "\"Savings\"");
+    fs::write(&info_path, info).unwrap();
+    
+    let mut reloaded = WalletManager::with_rpc_client(RpcClient::new_mock("succeeds".to_string()), &storage_path);
+    reloaded.set_kdf_params(fast_kdf());
+    reloaded.init_encryption("test password").unwrap();
+    assert!(matches!(reloaded.load_wallets(), Err(WalletError::GeneralError(_))));
+    
+    let _ = fs::remove_dir_all(&storage_path);
+}
+
+#[test]
+fn trading_wallet_pays_fees_unless_the_operational_payer_is_enabled() {
+    let mut manager = test_manager("fee-payer");
+    let profit = manager.generate_wallet(WalletType::Profit, "vault").unwrap();
+    let trading = manager.generate_wallet(WalletType::Trading, "alpha").unwrap();
+    let operational = manager.generate_wallet(WalletType::Operational, "fees").unwrap();
+    let external = Pubkey::new_unique();
+    
+    // The signing trading wallet pays by default, even when it isn't the first signer
+    assert_eq!(manager.resolve_fee_payer(None, &[&profit, &trading]).unwrap(), trading);
+    assert_eq!(manager.resolve_fee_payer(None, &[&profit]).unwrap(), profit);
+    assert_eq!(manager.resolve_fee_payer(Some(&external), &[&trading]).unwrap(), external);
+    assert!(manager.resolve_fee_payer(None, &[]).is_err());
+    
+    // Opting in moves fees to the operational wallet, but an explicit payer still wins
+    manager.set_operational_fee_payer(true);
+    assert_eq!(manager.resolve_fee_payer(None, &[&trading]).unwrap(), operational);
+    assert_eq!(manager.resolve_fee_payer(Some(&external), &[&trading]).unwrap(), external);
+    
+    let _ = fs::remove_dir_all(&manager.storage_path);
+}
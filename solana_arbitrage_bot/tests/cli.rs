@@ -0,0 +1,78 @@
+// Command-line interface tests for Solana Flash Loan Arbitrage Bot
+// Run the built binary against throwaway wallet stores
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Fresh wallet store directory under the system temp dir
+fn temp_store(name: &str) -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("sab-cli-{}-{}-{}", name, std::process::id(), nanos));
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+/// Run the CLI against `store` with the password taken from the environment
+fn run(store: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_solana_arbitrage_bot"))
+        .args(args)
+        .arg("--wallet-dir")
+        .arg(store)
+        .env("BOT_WALLET_PASSWORD", "test password")
+        .output()
+        .expect("run the CLI")
+}
+
+/// Stdout of a successful run
+fn stdout(output: Output) -> String {
+    assert!(output.status.success(), "CLI failed: {}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn wallet_list_shows_generated_wallets() {
+    let store = temp_store("wallet-list");
+
+    let trading = stdout(run(&store, &["wallet", "generate", "--type", "trading", "--label", "alpha"]));
+    let profit = stdout(run(&store, &["wallet", "generate", "--type", "profit", "--label", "vault"]));
+
+    let listing = stdout(run(&store, &["wallet", "list"]));
+    let lines: Vec<&str> = listing.lines().collect();
+    assert_eq!(lines.len(), 2);
+
+    let trading_line = lines.iter().find(|line| line.starts_with(trading.trim())).expect("trading wallet listed");
+    assert!(trading_line.contains("Trading"));
+    assert!(trading_line.contains("alpha"));
+    assert!(trading_line.contains("keypair"));
+
+    let profit_line = lines.iter().find(|line| line.starts_with(profit.trim())).expect("profit wallet listed");
+    assert!(profit_line.contains("Profit"));
+    assert!(profit_line.contains("vault"));
+
+    let _ = std::fs::remove_dir_all(&store);
+}
+
+#[test]
+fn profit_report_reads_the_running_bot_statistics() {
+    let store = temp_store("profit-report");
+
+    // Without a running bot there is nothing to report
+    assert!(!run(&store, &["profit", "report"]).status.success());
+
+    std::fs::write(
+        store.join("bot.profit"),
+        r#"{"total_sol_profit":1500,"total_usd_profit":1234,"reserve_balances":[{"token_mint":"EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v","amount":2500000}],"total_successful_trades":7,"total_failed_trades":1,"overall_success_rate":87.5,"token_count":2}"#,
+    ).unwrap();
+
+    let report = stdout(run(&store, &["profit", "report"]));
+    assert!(report.contains("1500 lamports"));
+    assert!(report.contains("$12.34"));
+    assert!(report.contains("Successful trades:     7"));
+    assert!(report.contains("87.50%"));
+    assert!(report.contains("Reserve:               2500000 of EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"));
+
+    let _ = std::fs::remove_dir_all(&store);
+}
@@ -0,0 +1,185 @@
+// Net Profit Model for Solana Flash Loan Arbitrage Bot
+// Nets flash loan, DEX, priority fee and slippage costs out of a spread's gross profit
+
+use std::collections::HashMap;
+use std::fmt;
+use log::debug;
+
+use crate::dex::{DexType, PriceInfo};
+use crate::oracle::PriceOracle;
+use crate::wallet_integration::PriorityFeeConfig;
+use super::ArbitrageOpportunity;
+
+/// Base fee charged per transaction signature, in lamports
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Lamports in one SOL
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Typical taker fee per swap in basis points, used when no override is configured
+pub fn typical_dex_fee_bps(dex: DexType) -> u64 {
+    match dex {
+        DexType::Jupiter => 0, // quoted out-amounts already net the underlying pools' fees
+        DexType::Raydium => 25,
+        DexType::Orca => 30,
+        DexType::Meteora => 25,
+        DexType::Phoenix => 5,
+        DexType::Custom => 30,
+    }
+}
+
+/// Where an opportunity's estimated profit goes, in quote token atoms
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CostBreakdown {
+    /// Profit from the spread alone
+    pub gross_profit: u64,
+    /// Flash loan fee
+    pub flash_loan_fee: u64,
+    /// Swap fees on both legs
+    pub dex_fees: u64,
+    /// Fee rate of the buy leg in basis points
+    pub buy_fee_bps: u64,
+    /// Fee rate of the sell leg in basis points
+    pub sell_fee_bps: u64,
+    /// Base and priority transaction fees, converted from lamports
+    pub transaction_fee: u64,
+    /// Expected loss to slippage on both legs
+    pub slippage: u64,
+    /// Gross profit less every cost (negative for a losing trade)
+    pub net_profit: i64,
+}
+
+impl CostBreakdown {
+    /// Sum of every cost
+    pub fn total_costs(&self) -> u64 {
+        self.flash_loan_fee + self.dex_fees + self.transaction_fee + self.slippage
+    }
+}
+
+impl fmt::Display for CostBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "net={} (gross={}, flash loan={}, dex fees={} ({}+{} bps), tx fee={}, slippage={})",
+            self.net_profit, self.gross_profit, self.flash_loan_fee, self.dex_fees, self.buy_fee_bps, self.sell_fee_bps,
+            self.transaction_fee, self.slippage
+        )
+    }
+}
+
+/// Estimates what an opportunity would actually make after costs
+pub struct NetProfitEstimator {
+    /// Fee per swap in basis points by DEX, overriding the typical fees
+    dex_fee_bps: HashMap<DexType, u64>,
+    /// Expected slippage per leg in basis points of the trade
+    slippage_bps: u64,
+    /// Compute unit price and limit transactions are sent with
+    priority_fee: PriorityFeeConfig,
+    /// Multiplier applied to the compute unit price
+    gas_price_multiplier: f64,
+    /// Signatures on an arbitrage transaction (fee payer and trading wallet)
+    signature_count: u64,
+}
+
+impl NetProfitEstimator {
+    /// Create an estimator that prices transactions the way the wallet sends them
+    pub fn new(priority_fee: PriorityFeeConfig, gas_price_multiplier: f64) -> Self {
+        Self {
+            dex_fee_bps: HashMap::new(),
+            slippage_bps: 10, // 0.1% per leg
+            priority_fee,
+            gas_price_multiplier,
+            signature_count: 2,
+        }
+    }
+    
+    /// Override the fee charged per swap on a DEX
+    pub fn set_dex_fee_bps(&mut self, dex: DexType, fee_bps: u64) {
+        self.dex_fee_bps.insert(dex, fee_bps);
+    }
+    
+    /// Set the expected slippage per leg in basis points
+    pub fn set_slippage_bps(&mut self, slippage_bps: u64) {
+        self.slippage_bps = slippage_bps;
+    }
+    
+    /// Fee per swap on a DEX in basis points
+    pub fn dex_fee_bps(&self, dex: DexType) -> u64 {
+        self.dex_fee_bps.get(&dex).copied().unwrap_or_else(|| typical_dex_fee_bps(dex))
+    }
+    
+    /// Fee for a swap at a quote, preferring the fee its pool reported over the DEX's
+    pub fn leg_fee_bps(&self, price: &PriceInfo) -> u64 {
+        price.fee_bps.unwrap_or_else(|| self.dex_fee_bps(price.dex))
+    }
+    
+    /// Estimated transaction fee in lamports at the configured compute unit price
+    ///
+    /// Recent fees on the pools may push the real price higher, up to the configured maximum.
+    pub fn transaction_fee_lamports(&self) -> u64 {
+        let price = ((self.priority_fee.base_micro_lamports as f64 * self.gas_price_multiplier) as u64)
+            .min(self.priority_fee.max_micro_lamports);
+        let priority_lamports = (price as u128 * self.priority_fee.compute_unit_limit as u128 + 999_999) / 1_000_000;
+        LAMPORTS_PER_SIGNATURE * self.signature_count + priority_lamports as u64
+    }
+    
+    /// Value a lamport amount in the opportunity's quote token atoms
+    ///
+    /// SOL pairs are converted at the quoted price; anything else goes through the oracle.
+    fn lamports_in_quote(&self, opportunity: &ArbitrageOpportunity, lamports: u64, price_oracle: &dyn PriceOracle) -> Result<u64, String> {
+        let native_mint = spl_token::native_mint::id();
+        
+        if opportunity.quote_token == native_mint {
+            return Ok(lamports);
+        }
+        if opportunity.base_token == native_mint {
+            return Ok(opportunity.sell_price.base_to_quote_atoms(lamports));
+        }
+        
+        let sol_usd = price_oracle.get_usd_price(&native_mint)
+            .map_err(|e| format!("Failed to price SOL: {}", e))?;
+        let quote_usd = price_oracle.get_usd_price(&opportunity.quote_token)
+            .map_err(|e| format!("Failed to price {}: {}", opportunity.quote_token, e))?;
+        if quote_usd <= 0.0 {
+            return Err(format!("Oracle price for {} is not positive", opportunity.quote_token));
+        }
+        
+        let quote_tokens = lamports as f64 / LAMPORTS_PER_SOL * sol_usd / quote_usd;
+        Ok((quote_tokens * 10f64.powi(opportunity.sell_price.quote_decimals as i32)) as u64)
+    }
+    
+    /// Net an opportunity's costs out of its gross profit
+    ///
+    /// `flash_loan_fee` is zero when the trade is funded from the wallet.
+    pub fn estimate(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        flash_loan_fee: u64,
+        price_oracle: &dyn PriceOracle,
+    ) -> Result<CostBreakdown, String> {
+        let size = opportunity.max_trade_size as u128;
+        let bps = |fee_bps: u64| (size * fee_bps as u128 / 10_000) as u64;
+        
+        let gross_profit = opportunity.estimated_profit;
+        let buy_fee_bps = self.leg_fee_bps(&opportunity.buy_price);
+        let sell_fee_bps = self.leg_fee_bps(&opportunity.sell_price);
+        let dex_fees = bps(buy_fee_bps) + bps(sell_fee_bps);
+        let slippage = bps(self.slippage_bps) * 2;
+        let transaction_fee = self.lamports_in_quote(opportunity, self.transaction_fee_lamports(), price_oracle)?;
+        
+        let mut breakdown = CostBreakdown {
+            gross_profit,
+            flash_loan_fee,
+            dex_fees,
+            buy_fee_bps,
+            sell_fee_bps,
+            transaction_fee,
+            slippage,
+            net_profit: 0,
+        };
+        breakdown.net_profit = gross_profit as i64 - breakdown.total_costs() as i64;
+        
+        debug!("Costs for {}/{} at size {}: {}", opportunity.base_token, opportunity.quote_token, opportunity.max_trade_size, breakdown);
+        Ok(breakdown)
+    }
+}
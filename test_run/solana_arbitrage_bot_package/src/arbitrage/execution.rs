@@ -0,0 +1,170 @@
+// Execution Backends for Solana Flash Loan Arbitrage Bot
+// Sends trades on-chain or fills them against a virtual balance for paper trading
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use log::{info, debug};
+use tokio::runtime::Handle;
+
+use crate::lock_or_recover;
+use super::{ArbitrageExecutor, ArbitrageOpportunity, ArbitrageResult};
+
+/// How the engine executes the opportunities it detects
+#[derive(Debug, Clone)]
+pub enum ExecutionMode {
+    /// Sign and send real transactions
+    Live,
+    /// Simulate fills against a virtual balance without touching the chain
+    Paper(PaperTradingConfig),
+}
+
+/// Paper trading configuration
+#[derive(Debug, Clone)]
+pub struct PaperTradingConfig {
+    /// Starting virtual balance in quote token
+    pub initial_balance: u64,
+    /// Slippage applied to each leg in basis points
+    pub slippage_bps: u64,
+    /// Total fees per round trip in basis points (flash loan + DEX fees)
+    pub fee_bps: u64,
+}
+
+impl PaperTradingConfig {
+    /// Create default configuration
+    pub fn default() -> Self {
+        Self {
+            initial_balance: 1_000_000_000, // 1000 USDC (in smallest units)
+            slippage_bps: 10, // 0.1% per leg
+            fee_bps: 39, // 0.09% flash loan + 0.15% per swap
+        }
+    }
+}
+
+/// Executes sized arbitrage opportunities
+///
+/// Called from a blocking task, so implementations may block on I/O.
+pub trait ExecutionBackend: Send + Sync {
+    /// Execute an opportunity and report the outcome
+    fn execute(&self, opportunity: &ArbitrageOpportunity) -> ArbitrageResult;
+    
+    /// Virtual balance in quote token, for backends that don't trade real funds
+    fn virtual_balance(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Sends real transactions through the trading wallets
+pub struct LiveBackend {
+    /// Transaction executor
+    executor: Arc<ArbitrageExecutor>,
+    /// Runtime the executor's async calls run on
+    runtime: Handle,
+}
+
+impl LiveBackend {
+    /// Create a new live backend
+    pub(super) fn new(executor: Arc<ArbitrageExecutor>, runtime: Handle) -> Self {
+        Self {
+            executor,
+            runtime,
+        }
+    }
+}
+
+impl ExecutionBackend for LiveBackend {
+    fn execute(&self, opportunity: &ArbitrageOpportunity) -> ArbitrageResult {
+        let start_time = Instant::now();
+        
+        match self.runtime.block_on(self.executor.execute_arbitrage(opportunity)) {
+            Ok(result) => result,
+            Err(e) => ArbitrageResult {
+                success: false,
+                actual_profit: 0,
+                realized_pnl: 0,
+                error_message: Some(e),
+                transaction_signature: None,
+                priority_fee_micro_lamports: 0,
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+                opportunity: opportunity.clone(),
+                simulation: None,
+            },
+        }
+    }
+}
+
+/// Fills trades at the quoted prices less modeled slippage and fees
+///
+/// Nothing is signed or sent, so no wallet or funds are at risk.
+pub struct PaperBackend {
+    /// Paper trading configuration
+    config: PaperTradingConfig,
+    /// Virtual balance in quote token
+    balance: Mutex<u64>,
+}
+
+impl PaperBackend {
+    /// Create a new paper backend starting from the configured balance
+    pub fn new(config: PaperTradingConfig) -> Self {
+        Self {
+            balance: Mutex::new(config.initial_balance),
+            config,
+        }
+    }
+    
+    /// Quote token out of a round trip of `amount_in`, in atoms
+    fn simulated_output(&self, opportunity: &ArbitrageOpportunity, amount_in: u64) -> u64 {
+        let slippage_factor = 1.0 - self.config.slippage_bps as f64 / 10_000.0;
+        let base_out = opportunity.buy_price.quote_to_base_atoms(amount_in) as f64 * slippage_factor;
+        let quote_out = opportunity.sell_price.base_to_quote_atoms(base_out as u64) as f64 * slippage_factor;
+        let fees = amount_in as f64 * self.config.fee_bps as f64 / 10_000.0;
+        (quote_out - fees).max(0.0) as u64
+    }
+}
+
+impl ExecutionBackend for PaperBackend {
+    fn execute(&self, opportunity: &ArbitrageOpportunity) -> ArbitrageResult {
+        let start_time = Instant::now();
+        
+        let mut balance = lock_or_recover(&self.balance);
+        
+        // Can't trade more than the virtual balance holds
+        let amount_in = opportunity.max_trade_size.min(*balance);
+        if amount_in == 0 {
+            return ArbitrageResult {
+                success: false,
+                actual_profit: 0,
+                realized_pnl: 0,
+                error_message: Some("Paper balance exhausted".to_string()),
+                transaction_signature: None,
+                priority_fee_micro_lamports: 0,
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+                opportunity: opportunity.clone(),
+                simulation: None,
+            };
+        }
+        let opportunity = opportunity.with_trade_size(amount_in);
+        
+        let amount_out = self.simulated_output(&opportunity, amount_in);
+        let realized_pnl = amount_out as i64 - amount_in as i64;
+        *balance = *balance - amount_in + amount_out;
+        
+        debug!("Paper fill for {}/{}: in={}, out={}", opportunity.base_token, opportunity.quote_token, amount_in, amount_out);
+        info!("Paper trade: pnl={}, virtual balance={}", realized_pnl, *balance);
+        
+        ArbitrageResult {
+            success: true,
+            actual_profit: realized_pnl.max(0) as u64,
+            realized_pnl,
+            error_message: None,
+            transaction_signature: None,
+            priority_fee_micro_lamports: 0,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            opportunity,
+            simulation: None,
+        }
+    }
+    
+    fn virtual_balance(&self) -> Option<u64> {
+        Some(*lock_or_recover(&self.balance))
+    }
+}
@@ -0,0 +1,134 @@
+// In-Flight Opportunity Tracking for Solana Flash Loan Arbitrage Bot
+// Keeps the same spread from being executed twice while its first trade is still pending
+
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use log::debug;
+
+use crate::dex::DexType;
+use crate::lock_or_recover;
+use super::ArbitrageOpportunity;
+
+/// Identifies an opportunity across scans: pair, venues and prices rounded to a bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OpportunityKey {
+    /// Base token
+    pub base_token: Pubkey,
+    /// Quote token
+    pub quote_token: Pubkey,
+    /// DEX bought on
+    pub buy_venue: DexType,
+    /// DEX sold on
+    pub sell_venue: DexType,
+    /// Buy price bucket
+    pub buy_price_bucket: i64,
+    /// Sell price bucket
+    pub sell_price_bucket: i64,
+}
+
+impl OpportunityKey {
+    /// Key an opportunity, treating prices within `precision_bps` of each other as the same
+    ///
+    /// Buckets are logarithmic so the precision is relative to the price, whatever its scale.
+    pub fn new(opportunity: &ArbitrageOpportunity, precision_bps: u64) -> Self {
+        let step = (1.0 + precision_bps.max(1) as f64 / 10_000.0).ln();
+        let bucket = |price: f64| (price.max(f64::MIN_POSITIVE).ln() / step).round() as i64;
+        
+        Self {
+            base_token: opportunity.base_token,
+            quote_token: opportunity.quote_token,
+            buy_venue: opportunity.buy_price.dex,
+            sell_venue: opportunity.sell_price.dex,
+            buy_price_bucket: bucket(opportunity.buy_price.price),
+            sell_price_bucket: bucket(opportunity.sell_price.price),
+        }
+    }
+}
+
+/// Opportunities currently being executed
+///
+/// An opportunity is claimed before execution and released when its result
+/// returns. Claims also expire after a TTL, so a trade whose task never
+/// reports back can't block its spread forever.
+pub struct InFlightGuard {
+    /// Key -> when it was claimed
+    claims: Mutex<HashMap<OpportunityKey, Instant>>,
+    /// How long a claim lasts without a result
+    ttl: Duration,
+    /// Price rounding for keys, in basis points
+    precision_bps: u64,
+}
+
+impl InFlightGuard {
+    /// Create a new guard
+    pub fn new(ttl: Duration, precision_bps: u64) -> Self {
+        Self {
+            claims: Mutex::new(HashMap::new()),
+            ttl,
+            precision_bps,
+        }
+    }
+    
+    /// Claim an opportunity for execution
+    ///
+    /// Returns `None` while the same opportunity is already in flight. The claim
+    /// is released when the returned ticket is dropped.
+    pub fn try_claim(self: &Arc<Self>, opportunity: &ArbitrageOpportunity) -> Option<InFlightTicket> {
+        let key = OpportunityKey::new(opportunity, self.precision_bps);
+        let now = Instant::now();
+        
+        let mut claims = lock_or_recover(&self.claims);
+        
+        // Drop claims whose results never came back
+        claims.retain(|_, claimed_at| now.duration_since(*claimed_at) < self.ttl);
+        
+        if claims.contains_key(&key) {
+            debug!("Opportunity for {}/{} ({:?} -> {:?}) already in flight, skipping",
+                   key.base_token, key.quote_token, key.buy_venue, key.sell_venue);
+            return None;
+        }
+        claims.insert(key, now);
+        
+        Some(InFlightTicket {
+            guard: self.clone(),
+            key,
+            claimed_at: now,
+        })
+    }
+    
+    /// Number of opportunities in flight
+    pub fn len(&self) -> usize {
+        lock_or_recover(&self.claims).len()
+    }
+    
+    /// Whether nothing is in flight
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Release a claim, unless it expired and was claimed again since
+    fn release(&self, key: &OpportunityKey, claimed_at: Instant) {
+        let mut claims = lock_or_recover(&self.claims);
+        if claims.get(key) == Some(&claimed_at) {
+            claims.remove(key);
+        }
+    }
+}
+
+/// A claimed opportunity, released when dropped
+pub struct InFlightTicket {
+    /// Guard the claim belongs to
+    guard: Arc<InFlightGuard>,
+    /// Claimed key
+    key: OpportunityKey,
+    /// When the claim was made
+    claimed_at: Instant,
+}
+
+impl Drop for InFlightTicket {
+    fn drop(&mut self) {
+        self.guard.release(&self.key, self.claimed_at);
+    }
+}
@@ -0,0 +1,276 @@
+// Trade Journal for Solana Flash Loan Arbitrage Bot
+// Persists every executed trade to an append-only log for P&L analysis and export
+
+use solana_sdk::pubkey::Pubkey;
+use std::fs::{self, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use log::{info, warn, error, debug};
+
+use crate::lock_or_recover;
+use super::ArbitrageResult;
+
+/// One executed (or attempted) trade as stored in the journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRecord {
+    /// Unix timestamp in seconds when the result was recorded
+    pub timestamp: u64,
+    /// Trade id from the logs (absent in records written before ids existed)
+    #[serde(default)]
+    pub trade_id: Option<Uuid>,
+    /// Base token
+    #[serde(with = "crate::serde_pubkey")]
+    pub base_token: Pubkey,
+    /// Quote token
+    #[serde(with = "crate::serde_pubkey")]
+    pub quote_token: Pubkey,
+    /// DEX bought on
+    pub buy_venue: String,
+    /// DEX sold on
+    pub sell_venue: String,
+    /// Trade size in quote token
+    pub size: u64,
+    /// Realized change in quote token (negative for a loss)
+    pub realized_pnl: i64,
+    /// Compute unit price paid, in micro-lamports
+    pub priority_fee_micro_lamports: u64,
+    /// Estimated profit minus realized P&L, in quote token (positive means worse than quoted)
+    pub slippage: i64,
+    /// Transaction signature (if sent)
+    pub transaction_signature: Option<String>,
+    /// Whether the trade succeeded
+    pub success: bool,
+    /// Quoted buy price in whole tokens (0 in records written before quotes were kept)
+    #[serde(default)]
+    pub buy_price: f64,
+    /// Quoted sell price in whole tokens
+    #[serde(default)]
+    pub sell_price: f64,
+    /// Buy pool liquidity in base token atoms
+    #[serde(default)]
+    pub buy_liquidity: u64,
+    /// Sell pool liquidity in base token atoms
+    #[serde(default)]
+    pub sell_liquidity: u64,
+    /// Base token decimals
+    #[serde(default)]
+    pub base_decimals: u8,
+    /// Quote token decimals
+    #[serde(default)]
+    pub quote_decimals: u8,
+}
+
+impl TradeRecord {
+    /// Build a record from an arbitrage result
+    pub fn from_result(result: &ArbitrageResult) -> Self {
+        let opportunity = &result.opportunity;
+        
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            trade_id: Some(opportunity.trade_id),
+            base_token: opportunity.base_token,
+            quote_token: opportunity.quote_token,
+            buy_venue: format!("{:?}", opportunity.buy_price.dex),
+            sell_venue: format!("{:?}", opportunity.sell_price.dex),
+            size: opportunity.max_trade_size,
+            realized_pnl: result.realized_pnl,
+            priority_fee_micro_lamports: result.priority_fee_micro_lamports,
+            slippage: if result.success { opportunity.estimated_profit as i64 - result.realized_pnl } else { 0 },
+            transaction_signature: result.transaction_signature.clone(),
+            success: result.success,
+            buy_price: opportunity.buy_price.price,
+            sell_price: opportunity.sell_price.price,
+            buy_liquidity: opportunity.buy_price.liquidity,
+            sell_liquidity: opportunity.sell_price.liquidity,
+            base_decimals: opportunity.buy_price.base_decimals,
+            quote_decimals: opportunity.buy_price.quote_decimals,
+        }
+    }
+}
+
+/// Records held in memory and those not yet written
+struct JournalState {
+    /// Every record, oldest first
+    records: Vec<TradeRecord>,
+    /// Records waiting to be appended to the file
+    pending: Vec<TradeRecord>,
+}
+
+/// Journal file and the records buffered for it, shared with the flush thread
+struct JournalFile {
+    /// Journal file
+    path: PathBuf,
+    /// In-memory records and write buffer
+    state: Mutex<JournalState>,
+}
+
+impl JournalFile {
+    /// Append pending records to the file
+    fn write_pending(&self) -> Result<(), String> {
+        let mut state = lock_or_recover(&self.state);
+        if state.pending.is_empty() {
+            return Ok(());
+        }
+        
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open trade journal {}: {}", self.path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+        
+        for record in &state.pending {
+            let line = serde_json::to_string(record)
+                .map_err(|e| format!("Failed to serialize trade record: {}", e))?;
+            writeln!(writer, "{}", line)
+                .map_err(|e| format!("Failed to write trade journal: {}", e))?;
+        }
+        writer.flush()
+            .map_err(|e| format!("Failed to write trade journal: {}", e))?;
+        
+        state.pending.clear();
+        Ok(())
+    }
+}
+
+/// Append-only journal of trade results
+///
+/// Stored as one JSON record per line. Records are buffered and written by a
+/// background thread every flush interval, on `flush`, and when the journal is
+/// dropped, so recording never waits on the disk.
+pub struct TradeJournal {
+    /// Journal file and write buffer
+    file: Arc<JournalFile>,
+}
+
+impl TradeJournal {
+    /// Open the journal at `path`, loading any records a previous run wrote
+    ///
+    /// Buffered records are written every `flush_interval` until the journal is dropped.
+    pub fn open<P: AsRef<Path>>(path: P, flush_interval: Duration) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        
+        let mut records = Vec::new();
+        if path.exists() {
+            let data = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read trade journal {}: {}", path.display(), e))?;
+            
+            for (line_no, line) in data.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                // A crash mid-write can leave a torn last line; keep everything before it
+                match serde_json::from_str::<TradeRecord>(line) {
+                    Ok(record) => records.push(record),
+                    Err(e) => warn!("Skipping unreadable trade journal line {}: {}", line_no + 1, e),
+                }
+            }
+            
+            info!("Loaded {} trades from {}", records.len(), path.display());
+        } else if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create trade journal directory: {}", e))?;
+        }
+        
+        let file = Arc::new(JournalFile {
+            path,
+            state: Mutex::new(JournalState {
+                records,
+                pending: Vec::new(),
+            }),
+        });
+        
+        // The thread exits once the journal is dropped
+        let weak = Arc::downgrade(&file);
+        thread::spawn(move || {
+            loop {
+                thread::sleep(flush_interval);
+                let Some(file) = weak.upgrade() else { break };
+                if let Err(e) = file.write_pending() {
+                    error!("Failed to flush trade journal: {}", e);
+                }
+            }
+            debug!("Trade journal flush thread exited");
+        });
+        
+        Ok(Self { file })
+    }
+    
+    /// Record a trade result, to be written on the next flush
+    pub fn record(&self, result: &ArbitrageResult) -> Result<(), String> {
+        let mut state = lock_or_recover(&self.file.state);
+        
+        let record = TradeRecord::from_result(result);
+        state.records.push(record.clone());
+        state.pending.push(record);
+        
+        Ok(())
+    }
+    
+    /// Write every buffered record to the journal file
+    pub fn flush(&self) -> Result<(), String> {
+        self.file.write_pending()
+    }
+    
+    /// Trades recorded between two Unix timestamps (inclusive)
+    pub fn query_by_range(&self, from: u64, to: u64) -> Result<Vec<TradeRecord>, String> {
+        let state = lock_or_recover(&self.file.state);
+        
+        Ok(state.records.iter()
+            .filter(|record| record.timestamp >= from && record.timestamp <= to)
+            .cloned()
+            .collect())
+    }
+    
+    /// Export every recorded trade as CSV with a header row
+    pub fn export_csv<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let state = lock_or_recover(&self.file.state);
+        
+        let path = path.as_ref();
+        let file = fs::File::create(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+        
+        let write_err = |e: std::io::Error| format!("Failed to write {}: {}", path.display(), e);
+        
+        writeln!(writer, "timestamp,base_token,quote_token,buy_venue,sell_venue,size,realized_pnl,priority_fee_micro_lamports,slippage,transaction_signature,success")
+            .map_err(write_err)?;
+        for record in &state.records {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                record.timestamp,
+                record.base_token,
+                record.quote_token,
+                record.buy_venue,
+                record.sell_venue,
+                record.size,
+                record.realized_pnl,
+                record.priority_fee_micro_lamports,
+                record.slippage,
+                record.transaction_signature.as_deref().unwrap_or(""),
+                record.success,
+            ).map_err(write_err)?;
+        }
+        writer.flush().map_err(write_err)?;
+        
+        info!("Exported {} trades to {}", state.records.len(), path.display());
+        Ok(())
+    }
+}
+
+impl Drop for TradeJournal {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            error!("Failed to flush trade journal on shutdown: {}", e);
+        }
+    }
+}
@@ -7,19 +7,65 @@ use solana_sdk::{
     transaction::Transaction,
     signer::Signer,
     commitment_config::CommitmentConfig,
+    hash::Hash,
+    message::{v0, VersionedMessage},
+    signature::Signature,
+    transaction::VersionedTransaction,
 };
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig, RpcTransactionConfig};
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_account_decoder::UiAccountEncoding;
+use solana_sdk::account::Account;
+use solana_sdk::program_pack::Pack;
+use spl_associated_token_account::get_associated_token_address;
+use solana_transaction_status::{UiTransactionEncoding, UiTransactionTokenBalance};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 use log::{info, warn, error, debug};
 use tokio::runtime::Runtime;
+use tokio::sync::{broadcast, Semaphore};
+use uuid::Uuid;
 
-use crate::dex::{ThreadSafeDexManager, DexConfig, DexType, PriceInfo, DexError};
+use crate::dex::{min_amount_out, ThreadSafeDexManager, DexConfig, DexType, PriceInfo, DexError, SwapParams, SwapInstructions};
 use crate::flash_loan::{ThreadSafeFlashLoanManager, FlashLoanConfig, FlashLoanProvider, FlashLoanError};
-use crate::wallet_integration::{ThreadSafeWalletManager, WalletType, WalletError};
-use crate::profit_management::{ThreadSafeProfitManager};
+use crate::wallet_integration::{
+    ThreadSafeWalletManager, WalletType, WalletError, CommitmentPolicy, PriorityFeeConfig, COMPUTE_BUDGET_INSTRUCTIONS,
+};
+use crate::profit_management::{DistributionResult, ThreadSafeProfitManager};
+use crate::risk_management::{
+    MarketCondition, MarketConditionAnalyzer, PositionScalingConfig, PositionScalingManager, RiskGuard, RiskLevel,
+    RiskManagementConfig, RiskManager, TokenFilter, TokenPolicy, VolatilityTracker,
+};
+use crate::rpc::{AdaptiveThrottle, FailoverRpcClient};
+use crate::oracle::{JupiterPriceOracle, OracleGuard, PriceOracle};
+use crate::notify::{Notification, NotificationDispatcher};
+use crate::{lock_or_recover, BotEvent, EVENT_CHANNEL_CAPACITY};
+
+pub mod backtest;
+pub mod cost_model;
+pub mod execution;
+pub mod in_flight;
+pub mod journal;
+pub mod pair_state;
+pub mod profit_tuner;
+#[cfg(test)]
+mod tests;
+
+use cost_model::{CostBreakdown, NetProfitEstimator};
+use execution::{ExecutionBackend, ExecutionMode, LiveBackend, PaperBackend};
+use in_flight::InFlightGuard;
+use journal::TradeJournal;
+use pair_state::{PairState, PairStateTracker};
+use profit_tuner::{MinProfitTuner, ProfitTunerConfig};
 
 /// Arbitrage opportunity
+#[derive(Debug, Clone)]
 pub struct ArbitrageOpportunity {
     /// Base token
     pub base_token: Pubkey,
@@ -37,22 +83,324 @@ pub struct ArbitrageOpportunity {
     pub max_trade_size: u64,
     /// Timestamp when opportunity was detected
     pub timestamp: u64,
+    /// Costs netted out of the estimated profit, once estimated at the final trade size
+    pub costs: Option<CostBreakdown>,
+    /// Correlates every log line from detection through execution
+    pub trade_id: Uuid,
+}
+
+impl ArbitrageOpportunity {
+    /// Build an opportunity from a buy/sell quote pair
+    ///
+    /// The trade is sized to at most `config.price_impact_cap` of the shallower pool.
+    /// Returns `None` when either quote is invalid, either pool holds less than
+    /// `config.min_liquidity`, or the tradable size falls below `config.min_trade_size`,
+    /// so such spreads are never executed.
+    pub fn from_prices(
+        config: &ArbitrageConfig,
+        buy_price: PriceInfo,
+        sell_price: PriceInfo,
+        profit_percentage: f64,
+    ) -> Option<Self> {
+        if buy_price.price <= 0.0 || sell_price.price <= 0.0 {
+            debug!("Skipping opportunity for {}/{}: invalid quote price (buy={}, sell={})",
+                   buy_price.base_token, buy_price.quote_token, buy_price.price, sell_price.price);
+            return None;
+        }
+        
+        // Quotes for the same pair must agree on decimals, or their prices aren't comparable
+        if buy_price.base_decimals != sell_price.base_decimals || buy_price.quote_decimals != sell_price.quote_decimals {
+            debug!("Skipping opportunity for {}/{}: quotes disagree on decimals ({}/{} vs {}/{})",
+                   buy_price.base_token, buy_price.quote_token,
+                   buy_price.base_decimals, buy_price.quote_decimals,
+                   sell_price.base_decimals, sell_price.quote_decimals);
+            return None;
+        }
+        
+        // Liquidity is quoted in base atoms, trade sizes in quote atoms
+        let max_liquidity = buy_price.liquidity_in_quote().min(sell_price.liquidity_in_quote());
+        
+        // Quotes from micro-liquidity pools are meaningless once slippage is paid
+        if max_liquidity < config.min_liquidity {
+            debug!("Skipping opportunity for {}/{}: liquidity {} below minimum {}",
+                   buy_price.base_token, buy_price.quote_token, max_liquidity, config.min_liquidity);
+            return None;
+        }
+        
+        // A trade taking a large share of a pool moves its price past the quoted spread
+        let impact_limit = (max_liquidity as f64 * config.price_impact_cap) as u64;
+        let max_trade_size = impact_limit.min(config.max_position_size);
+        if max_trade_size < config.max_position_size {
+            debug!("Clamping trade for {}/{} to {} ({:.1}% of liquidity {})",
+                   buy_price.base_token, buy_price.quote_token, max_trade_size,
+                   config.price_impact_cap * 100.0, max_liquidity);
+        }
+        
+        if max_trade_size < config.min_trade_size {
+            debug!("Skipping opportunity for {}/{}: trade size {} below minimum {}",
+                   buy_price.base_token, buy_price.quote_token, max_trade_size, config.min_trade_size);
+            return None;
+        }
+        
+        let estimated_profit = ((max_trade_size as f64) * (profit_percentage / 100.0)) as u64;
+        
+        Some(Self {
+            base_token: buy_price.base_token,
+            quote_token: buy_price.quote_token,
+            buy_price,
+            sell_price,
+            profit_percentage,
+            estimated_profit,
+            max_trade_size,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            costs: None,
+            trade_id: Uuid::new_v4(),
+        })
+    }
+    
+    /// Span that tags every log line for this opportunity with its trade id
+    pub fn span(&self) -> tracing::Span {
+        tracing::info_span!(
+            "trade",
+            trade_id = %self.trade_id,
+            pair = %format!("{}/{}", self.base_token, self.quote_token),
+        )
+    }
+    
+    /// Estimated profit after costs, or the gross estimate if costs haven't been estimated
+    pub fn net_estimated_profit(&self) -> i64 {
+        self.costs
+            .map(|costs| costs.net_profit)
+            .unwrap_or(self.estimated_profit as i64)
+    }
+    
+    /// Minimum base token out of the buy leg and quote token out of the sell leg at a slippage tolerance, in atoms
+    ///
+    /// The sell leg is projected from the buy leg's minimum, so both hold at worst-case slippage.
+    pub fn min_amounts_out(&self, slippage: f64) -> (u64, u64) {
+        let min_base_out = min_amount_out(self.buy_price.quote_to_base_atoms(self.max_trade_size), slippage);
+        let min_quote_out = min_amount_out(self.sell_price.base_to_quote_atoms(min_base_out), slippage);
+        (min_base_out, min_quote_out)
+    }
+    
+    /// Copy of this opportunity resized to `trade_size`, with the estimated profit scaled to match
+    ///
+    /// Costs depend on the size, so any estimate is dropped.
+    pub fn with_trade_size(&self, trade_size: u64) -> Self {
+        Self {
+            max_trade_size: trade_size,
+            estimated_profit: ((trade_size as f64) * (self.profit_percentage / 100.0)) as u64,
+            costs: None,
+            ..self.clone()
+        }
+    }
+    
+    /// Resize to the adaptive position size for the pair's market conditions
+    ///
+    /// Shared by the engine and backtests so both size trades the same way. Returns
+    /// `None` when the scaled size falls below `config.min_trade_size`.
+    pub fn scaled(
+        &self,
+        config: &ArbitrageConfig,
+        position_scaling: &mut PositionScalingManager,
+        condition: &MarketCondition,
+    ) -> Option<Self> {
+        let size = position_scaling.adjust_for_market_conditions(&self.base_token, &self.quote_token, condition)
+            .min(self.max_trade_size);
+        
+        if size < config.min_trade_size {
+            debug!("Skipping opportunity for {}/{}: scaled size {} below minimum {}",
+                   self.base_token, self.quote_token, size, config.min_trade_size);
+            return None;
+        }
+        
+        Some(self.with_trade_size(size))
+    }
+    
+    /// Copy of this opportunity traded on other venues, with the spread and estimated profit recomputed
+    ///
+    /// Costs depend on the venues, so any estimate is dropped.
+    pub fn with_venues(&self, buy_price: PriceInfo, sell_price: PriceInfo) -> Self {
+        let profit_percentage = (sell_price.price - buy_price.price) / buy_price.price * 100.0;
+        Self {
+            buy_price,
+            sell_price,
+            profit_percentage,
+            estimated_profit: ((self.max_trade_size as f64) * (profit_percentage / 100.0)) as u64,
+            costs: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// Amount left after a swap fee in basis points
+fn net_of_fee(amount: u64, fee_bps: u64) -> u64 {
+    (amount as u128 * (10_000 - fee_bps.min(10_000)) as u128 / 10_000) as u64
+}
+
+/// Amount before a swap fee in basis points that nets to `amount`
+fn gross_of_fee(amount: u64, fee_bps: u64) -> u64 {
+    let kept = (10_000 - fee_bps.min(9_999)) as u128;
+    (amount as u128 * 10_000 / kept).min(u64::MAX as u128) as u64
+}
+
+/// Quote for a leg filled along Jupiter's aggregated route, priced at swapping `base_atoms` for `quote_atoms`
+fn aggregated_price(direct: &PriceInfo, base_atoms: u64, quote_atoms: u64) -> PriceInfo {
+    let base = base_atoms as f64 / 10f64.powi(direct.base_decimals as i32);
+    let quote = quote_atoms as f64 / 10f64.powi(direct.quote_decimals as i32);
+    
+    PriceInfo {
+        price: quote / base,
+        // The route is only known to fill this size
+        liquidity: base_atoms,
+        dex: DexType::Jupiter,
+        pool: None,
+        // Netted back out at the cost model's Jupiter fee
+        fee_bps: None,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        ..direct.clone()
+    }
+}
+
+/// Program log fragments and error codes that indicate a swap failed on slippage
+const SLIPPAGE_ERROR_MARKERS: &[&str] = &[
+    "slippage",
+    "SlippageToleranceExceeded",
+    "AmountOutBelowMinimum",
+    "custom program error: 0x1771", // Jupiter SlippageToleranceExceeded
+    "custom program error: 0x1e",   // Raydium ExceededSlippage
+    "custom program error: 0x1794", // Orca AmountOutBelowMinimum
+];
+
+/// Check whether a failure message looks like a slippage-class swap failure
+fn is_slippage_error(message: &str) -> bool {
+    let lowered = message.to_lowercase();
+    SLIPPAGE_ERROR_MARKERS.iter().any(|marker| lowered.contains(&marker.to_lowercase()))
+}
+
+/// Run `attempt` at the configured slippage, loosening it a step at a time on slippage failures
+///
+/// The outer error of an attempt aborts; the inner one is a simulation failure, returned once it
+/// isn't slippage-class or the retries or risk limit are used up. A success carries the slippage it used.
+async fn retry_on_slippage<T, F, Fut>(config: &ArbitrageConfig, mut attempt: F) -> Result<Result<(T, f64), String>, String>
+where
+    F: FnMut(f64) -> Fut,
+    Fut: std::future::Future<Output = Result<Result<T, String>, String>>,
+{
+    // Never loosen slippage beyond the risk limit
+    let max_slippage = config.risk_management.max_slippage;
+    let mut slippage = config.slippage_tolerance.min(max_slippage);
+    let mut attempts = 0;
+    
+    loop {
+        match attempt(slippage).await? {
+            Ok(value) => return Ok(Ok((value, slippage))),
+            Err(e) if is_slippage_error(&e)
+                && attempts < config.max_slippage_retries
+                && slippage < max_slippage => {
+                attempts += 1;
+                let next = (slippage + config.slippage_retry_step).min(max_slippage);
+                warn!("Simulation failed on slippage at {}%, retrying at {}% (attempt {}/{})",
+                      slippage, next, attempts, config.max_slippage_retries);
+                slippage = next;
+            },
+            Err(e) => return Ok(Err(e)),
+        }
+    }
 }
 
 /// Arbitrage execution result
+#[derive(Debug, Clone)]
 pub struct ArbitrageResult {
     /// Whether the arbitrage was successful
     pub success: bool,
     /// Actual profit in quote token
     pub actual_profit: u64,
+    /// Realized change in quote token (negative for a loss)
+    pub realized_pnl: i64,
     /// Error message (if any)
     pub error_message: Option<String>,
     /// Transaction signature (if successful)
     pub transaction_signature: Option<String>,
+    /// Compute unit price paid, in micro-lamports (0 if nothing was sent)
+    pub priority_fee_micro_lamports: u64,
     /// Execution time in milliseconds
     pub execution_time_ms: u64,
     /// Opportunity that was executed
     pub opportunity: ArbitrageOpportunity,
+    /// Simulation results when running in dry run mode
+    pub simulation: Option<SimulationReport>,
+}
+
+/// Balance of an account after a simulated transaction
+#[derive(Debug, Clone)]
+pub struct SimulatedBalance {
+    /// Account address
+    pub address: Pubkey,
+    /// Lamports held (None if the account would not exist)
+    pub lamports: Option<u64>,
+    /// Token amount, if this is a token account
+    pub token_amount: Option<u64>,
+}
+
+/// Outcome of simulating an opportunity without broadcasting it
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    /// Whether the transaction would succeed
+    pub success: bool,
+    /// Program error, if the transaction would fail
+    pub error: Option<String>,
+    /// Compute units consumed
+    pub units_consumed: Option<u64>,
+    /// Program logs
+    pub logs: Vec<String>,
+    /// Post-balances of the wallet and its base/quote token accounts
+    pub post_balances: Vec<SimulatedBalance>,
+}
+
+/// How deep a landed trade must be before its profit is booked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalityDepth {
+    /// Rooted by a supermajority of the cluster
+    Finalized,
+    /// Confirmed with at least this many blocks built on top
+    Confirmations(usize),
+}
+
+/// Holds landed trades until they're settled, so profit from a block later dropped in a fork is never booked
+#[derive(Debug, Clone, Copy)]
+pub struct FinalityGuardConfig {
+    /// Depth a trade must reach
+    pub depth: FinalityDepth,
+    /// How long to wait for the depth, in seconds, before booking the trade as provisional
+    pub timeout_sec: u64,
+}
+
+/// How a landed trade's wait for settlement ended
+#[derive(Debug, PartialEq, Eq)]
+enum Settlement {
+    /// Reached the guard's depth
+    Settled,
+    /// Gone from the chain, or failed on-chain
+    Dropped(String),
+    /// Still short of the depth, or its status unreadable, when the guard timed out
+    Unsettled(String),
+}
+
+impl FinalityGuardConfig {
+    /// Create default configuration
+    pub fn default() -> Self {
+        Self {
+            depth: FinalityDepth::Finalized,
+            timeout_sec: 60,
+        }
+    }
 }
 
 /// Arbitrage configuration
@@ -61,10 +409,26 @@ pub struct ArbitrageConfig {
     pub min_profit_percentage: f64,
     /// Maximum position size in quote token
     pub max_position_size: u64,
+    /// Minimum trade size in quote token (smaller opportunities are skipped)
+    pub min_trade_size: u64,
+    /// Minimum liquidity of each leg's pool in quote token (shallower pools are skipped)
+    pub min_liquidity: u64,
+    /// Largest fraction of a pool's liquidity a trade may take
+    pub price_impact_cap: f64,
+    /// Minimum profit in quote token after fees and slippage for a trade to be taken
+    pub min_profit_threshold: u64,
     /// Slippage tolerance percentage
     pub slippage_tolerance: f64,
-    /// Gas price multiplier
+    /// Shortfall below the minimum output, in basis points of the trade, at which a landed trade counts as failed
+    pub stop_loss_bps: u64,
+    /// Slippage tolerance added on each retry after a slippage failure
+    pub slippage_retry_step: f64,
+    /// Maximum number of retries after slippage failures
+    pub max_slippage_retries: u32,
+    /// Gas price multiplier, applied to the compute unit price
     pub gas_price_multiplier: f64,
+    /// Compute unit price and limit added to every transaction
+    pub priority_fee: PriorityFeeConfig,
     /// Whether to use flash loans
     pub use_flash_loans: bool,
     /// Maximum concurrent arbitrage operations
@@ -73,9 +437,48 @@ pub struct ArbitrageConfig {
     pub token_pairs: Vec<(Pubkey, Pubkey)>,
     /// Update interval in milliseconds
     pub update_interval_ms: u64,
+    /// Risk management configuration
+    pub risk_management: RiskManagementConfig,
+    /// Commitment levels for reads and trade confirmation
+    pub commitment_policy: CommitmentPolicy,
+    /// How long to wait for a sent transaction to confirm, in seconds
+    pub transaction_timeout_sec: u64,
+    /// Wait for landed trades to settle before counting them (profit is booked at the confirm commitment when unset)
+    pub finality_guard: Option<FinalityGuardConfig>,
+    /// How long stopping waits for in-flight trades to finish, in seconds
+    pub shutdown_timeout_sec: u64,
+    /// Dry run: simulate opportunities instead of broadcasting them
+    pub simulate: bool,
+    /// Whether trades are sent on-chain or filled against a virtual balance
+    pub execution_mode: ExecutionMode,
+    /// How long an opportunity stays claimed if its result never returns, in milliseconds
+    pub in_flight_ttl_ms: u64,
+    /// Prices within this many basis points count as the same opportunity
+    pub dedup_price_precision_bps: u64,
+    /// Failed or losing trades in a row before a pair is paused (0 never pauses)
+    pub pair_failure_threshold: u32,
+    /// How long a pair stays paused after a losing streak, in seconds
+    pub pair_cooldown_sec: u64,
+    /// Compare each leg against Jupiter's aggregated route for the exact trade size, taking it when it pays at least as much
+    pub prefer_aggregator: bool,
 }
 
+/// Quote rounds kept per pair for market condition analysis
+const MARKET_WINDOW_SAMPLES: usize = 60;
+
 impl ArbitrageConfig {
+    /// Market condition analyzer sized for this configuration
+    pub fn market_analyzer(&self) -> MarketConditionAnalyzer {
+        // Full liquidity score once pools hold ten times the largest position
+        MarketConditionAnalyzer::new(MARKET_WINDOW_SAMPLES, self.max_position_size.saturating_mul(10))
+    }
+    
+    /// Position scaling that never sizes beyond this configuration's maximum
+    pub fn position_scaling(&self, mut scaling_config: PositionScalingConfig) -> PositionScalingManager {
+        scaling_config.max_position_size = scaling_config.max_position_size.min(self.max_position_size);
+        PositionScalingManager::new(scaling_config)
+    }
+    
     /// Create default configuration
     pub fn default() -> Self {
         // Default token pairs (SOL/USDC)
@@ -85,44 +488,340 @@ impl ArbitrageConfig {
         Self {
             min_profit_percentage: 0.5, // 0.5%
             max_position_size: 1_000_000_000, // 1000 USDC (in smallest units)
+            min_trade_size: 1_000_000, // 1 USDC
+            min_liquidity: 10_000_000_000, // 10,000 USDC
+            price_impact_cap: 0.1, // 10% of the shallower pool
+            min_profit_threshold: 100_000, // 0.1 USDC
             slippage_tolerance: 0.5, // 0.5%
+            stop_loss_bps: 50, // 0.5%
+            slippage_retry_step: 0.1, // +0.1% per retry
+            max_slippage_retries: 2,
             gas_price_multiplier: 1.5,
+            priority_fee: PriorityFeeConfig::default(),
             use_flash_loans: true,
             max_concurrent_operations: 3,
             token_pairs: vec![(sol, usdc)],
             update_interval_ms: 1000,
+            risk_management: RiskManagementConfig::new(RiskLevel::Moderate),
+            commitment_policy: CommitmentPolicy::default(),
+            transaction_timeout_sec: 60,
+            finality_guard: None,
+            shutdown_timeout_sec: 90, // a full confirmation wait plus building and sending
+            simulate: false,
+            execution_mode: ExecutionMode::Live,
+            in_flight_ttl_ms: 30_000,
+            dedup_price_precision_bps: 10, // 0.1%
+            pair_failure_threshold: 3,
+            pair_cooldown_sec: 300, // 5 minutes
+            prefer_aggregator: true,
         }
     }
 }
 
-/// Arbitrage engine
-pub struct ArbitrageEngine {
+/// Buffered events per subscriber; events for a full subscriber are dropped
+const SUBSCRIBER_CHANNEL_CAPACITY: usize = 256;
+
+/// How often the distribution schedule is checked
+const DISTRIBUTION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Fan-out of events to subscribers that never blocks the publisher
+struct Subscribers<T> {
+    /// One sender per subscriber
+    senders: Mutex<Vec<SyncSender<T>>>,
+}
+
+impl<T> Default for Subscribers<T> {
+    fn default() -> Self {
+        Self {
+            senders: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<T: Clone> Subscribers<T> {
+    /// Register a new subscriber
+    fn subscribe(&self) -> Receiver<T> {
+        let (sender, receiver) = mpsc::sync_channel(SUBSCRIBER_CHANNEL_CAPACITY);
+        lock_or_recover(&self.senders).push(sender);
+        receiver
+    }
+    
+    /// Send an event to every subscriber without waiting, dropping subscribers that hung up
+    fn publish(&self, event: &T) {
+        lock_or_recover(&self.senders).retain(|sender| match sender.try_send(event.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                debug!("Subscriber is behind, dropping event");
+                true
+            },
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+}
+
+/// Publish an event to the engine's bot event subscribers
+fn publish(events: &broadcast::Sender<BotEvent>, event: BotEvent) {
+    // A send error only means there are no subscribers right now
+    let _ = events.send(event);
+}
+
+/// Engine state shared between the control methods, the monitoring thread and executing trades
+#[derive(Default)]
+struct EngineState {
+    /// Whether the engine is running
+    running: AtomicBool,
+    /// Active arbitrage operations
+    active_operations: AtomicUsize,
+    /// Total opportunities detected
+    total_opportunities: AtomicU64,
+    /// Total arbitrages executed
+    total_executed: AtomicU64,
+    /// Total successful arbitrages
+    total_successful: AtomicU64,
+    /// Total profit in quote token
+    total_profit: AtomicU64,
+    /// Subscribers to detected opportunities
+    opportunity_subscribers: Subscribers<ArbitrageOpportunity>,
+    /// Subscribers to execution results
+    result_subscribers: Subscribers<ArbitrageResult>,
+    /// Subscribers to scheduled profit distributions
+    distribution_subscribers: Subscribers<DistributionResult>,
+}
+
+/// Arbitrage engine statistics
+#[derive(Debug, Clone, Copy)]
+pub struct EngineStatistics {
+    /// Active arbitrage operations
+    pub active_operations: usize,
+    /// Total opportunities detected
+    pub total_opportunities: u64,
+    /// Total arbitrages executed
+    pub total_executed: u64,
+    /// Total successful arbitrages
+    pub total_successful: u64,
+    /// Total profit in quote token
+    pub total_profit: u64,
+    /// Minimum profit percentage currently applied, as tuned by fill quality if enabled
+    pub min_profit_percentage: f64,
+}
+
+/// Minimum profit percentage to apply: the tuner's when one is set, otherwise the configured one
+fn effective_min_profit_percentage(config: &ArbitrageConfig, profit_tuner: Option<&Mutex<MinProfitTuner>>) -> f64 {
+    profit_tuner
+        .map(|tuner| lock_or_recover(tuner).current())
+        .unwrap_or(config.min_profit_percentage)
+}
+
+/// Grow the pair's position after a profitable trade and shrink it after a failed or losing one
+fn record_position_result(position_scaling: &Mutex<PositionScalingManager>, arb_result: &ArbitrageResult) {
+    let opportunity = &arb_result.opportunity;
+    let size = opportunity.max_trade_size.max(1);
+    lock_or_recover(position_scaling).update_position_size(
+        &opportunity.base_token,
+        &opportunity.quote_token,
+        arb_result.success && arb_result.realized_pnl > 0,
+        arb_result.realized_pnl,
+        arb_result.realized_pnl as f64 / size as f64 * 100.0,
+        arb_result.execution_time_ms,
+    );
+}
+
+/// Builds, simulates and sends arbitrage transactions; shared with spawned trade tasks
+struct ArbitrageExecutor {
     /// RPC client for Solana
     rpc_client: RpcClient,
     /// DEX manager
     dex_manager: ThreadSafeDexManager,
     /// Flash loan manager
     flash_loan_manager: ThreadSafeFlashLoanManager,
+    /// Further flash loan providers; each loan goes to the cheapest one able to lend
+    flash_loan_providers: Mutex<Vec<ThreadSafeFlashLoanManager>>,
     /// Wallet manager
     wallet_manager: ThreadSafeWalletManager,
-    /// Profit manager
-    profit_manager: ThreadSafeProfitManager,
+    /// Capital limits against the trading wallet's balance
+    risk_manager: RiskManager,
     /// Arbitrage configuration
     config: ArbitrageConfig,
+    /// Token decimals fetched for USD valuation
+    token_decimals: Mutex<HashMap<Pubkey, u8>>,
+}
+
+/// Token pairs quoted at the same time in one monitoring pass
+const MAX_CONCURRENT_EVALUATIONS: usize = 8;
+
+/// Quotes, filters and sizes one token pair; cloned into each evaluation task
+#[derive(Clone)]
+struct PairEvaluator {
+    /// Transaction executor, for its DEX and flash loan managers
+    executor: Arc<ArbitrageExecutor>,
+    /// Runtime the DEX calls run on
+    runtime: tokio::runtime::Handle,
+    /// Per-pair volatility tracking
+    volatility_tracker: Arc<Mutex<VolatilityTracker>>,
+    /// Oracle sanity check for quoted prices (if configured)
+    oracle_guard: Option<Arc<OracleGuard>>,
+    /// Adaptive position sizing
+    position_scaling: Arc<Mutex<PositionScalingManager>>,
+    /// Volatility, liquidity and trend from fetched quotes
+    market_analyzer: Arc<Mutex<MarketConditionAnalyzer>>,
+    /// Nets fees and slippage out of gross profit
+    cost_model: Arc<NetProfitEstimator>,
+    /// USD prices for valuing transaction fees
+    price_oracle: Arc<dyn PriceOracle>,
+    /// Adaptive minimum profit (if enabled)
+    profit_tuner: Option<Arc<Mutex<MinProfitTuner>>>,
+}
+
+impl PairEvaluator {
+    /// Find a sized, costed opportunity for a pair worth executing
+    ///
+    /// Errors only when the pair can't be quoted, so the caller can throttle on rate limits.
+    fn evaluate(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<Option<ArbitrageOpportunity>, DexError> {
+        let config = &self.executor.config;
+        
+        // Route each leg to the best venue across all DEXs
+        let (buy_price, sell_price) = self.runtime.block_on(
+            self.executor.dex_manager.best_buy_and_sell(base_token, quote_token)
+        )?;
+        
+        // Feed every round of quotes to the market analysis, not just profitable ones
+        lock_or_recover(&self.market_analyzer).record_prices(&[buy_price.clone(), sell_price.clone()]);
+        
+        // Skip the pair entirely while its volatility is extreme; quiet rounds must be
+        // recorded too, or a halted pair never sees volatility subside
+        let halted = {
+            let mut tracker = lock_or_recover(&self.volatility_tracker);
+            let mid_price = (buy_price.price + sell_price.price) / 2.0;
+            tracker.record_price(base_token, quote_token, mid_price);
+            tracker.should_halt(base_token, quote_token, &config.risk_management)
+        };
+        
+        if halted {
+            return Ok(None);
+        }
+        
+        let profit_percentage = (sell_price.price - buy_price.price) / buy_price.price * 100.0;
+        if profit_percentage < effective_min_profit_percentage(config, self.profit_tuner.as_deref()) {
+            debug!("No arbitrage opportunity for {}/{}: spread {:.3}% below minimum", base_token, quote_token, profit_percentage);
+            return Ok(None);
+        }
+        
+        // Skip zero-liquidity or zero-price quotes
+        let opportunity = match ArbitrageOpportunity::from_prices(config, buy_price, sell_price, profit_percentage) {
+            Some(opportunity) => opportunity,
+            None => return Ok(None),
+        };
+        
+        // Everything logged for this opportunity from here on carries its trade id
+        let trade_span = opportunity.span();
+        let _trade_span = trade_span.enter();
+        
+        // Size from recent results and market conditions rather than the flat maximum
+        let condition = lock_or_recover(&self.market_analyzer).analyze(base_token, quote_token);
+        let scaled = opportunity.scaled(config, &mut lock_or_recover(&self.position_scaling), &condition);
+        let opportunity = match scaled {
+            Some(opportunity) => opportunity,
+            None => return Ok(None),
+        };
+        
+        // A multi-hop route can beat the direct pool once the real size is known
+        let opportunity = if config.prefer_aggregator {
+            self.runtime.block_on(self.executor.route_via_aggregator(&opportunity, &self.cost_model))
+        } else {
+            opportunity
+        };
+        
+        // Reject spreads that only exist because a pool is mispriced
+        if let Some(guard) = &self.oracle_guard {
+            if let Err(e) = guard.check_prices(
+                base_token,
+                quote_token,
+                &[opportunity.buy_price.price, opportunity.sell_price.price],
+            ) {
+                warn!("Skipping opportunity for {}/{}: {}", base_token, quote_token, e);
+                return Ok(None);
+            }
+        }
+        
+        // Only trade spreads that still pay once fees and slippage are netted out
+        let flash_loan_fee = if config.use_flash_loans {
+            match self.executor.flash_loan_manager.calculate_fee(opportunity.max_trade_size) {
+                Ok(fee) => fee,
+                Err(e) => {
+                    warn!("Skipping opportunity for {}/{}: failed to price flash loan: {}", base_token, quote_token, e);
+                    return Ok(None);
+                }
+            }
+        } else {
+            0
+        };
+        let costs = match self.cost_model.estimate(&opportunity, flash_loan_fee, self.price_oracle.as_ref()) {
+            Ok(costs) => costs,
+            Err(e) => {
+                warn!("Skipping opportunity for {}/{}: failed to estimate costs: {}", base_token, quote_token, e);
+                return Ok(None);
+            }
+        };
+        if costs.net_profit < config.min_profit_threshold as i64 {
+            debug!("Skipping opportunity for {}/{}: {} below minimum {}",
+                   base_token, quote_token, costs, config.min_profit_threshold);
+            return Ok(None);
+        }
+        
+        Ok(Some(ArbitrageOpportunity {
+            costs: Some(costs),
+            ..opportunity
+        }))
+    }
+}
+
+/// Builds a flash loan repay instruction from the index of its borrow instruction
+pub type RepayBuilder<'a> = Box<dyn FnOnce(u8) -> Result<Instruction, String> + 'a>;
+
+/// Arbitrage engine
+pub struct ArbitrageEngine {
+    /// Transaction executor
+    executor: Arc<ArbitrageExecutor>,
+    /// Live or paper execution, chosen from the configuration
+    backend: Arc<dyn ExecutionBackend>,
+    /// Profit manager
+    profit_manager: ThreadSafeProfitManager,
     /// Tokio runtime
     runtime: Runtime,
-    /// Whether the engine is running
-    running: bool,
-    /// Active arbitrage operations
-    active_operations: usize,
-    /// Total opportunities detected
-    total_opportunities: u64,
-    /// Total arbitrages executed
-    total_executed: u64,
-    /// Total successful arbitrages
-    total_successful: u64,
-    /// Total profit in quote token
-    total_profit: u64,
+    /// Running flag and counters
+    state: Arc<EngineState>,
+    /// Opportunities, trade results and distributions, in the bot's event format
+    events: broadcast::Sender<BotEvent>,
+    /// Monitoring thread handle
+    monitor_handle: Option<JoinHandle<()>>,
+    /// Scheduled profit distribution task (if a schedule is configured)
+    distribution_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Per-pair volatility tracking
+    volatility_tracker: Arc<Mutex<VolatilityTracker>>,
+    /// Oracle sanity check for quoted prices (if configured)
+    oracle_guard: Option<Arc<OracleGuard>>,
+    /// Daily loss, daily trade and concurrency limits
+    risk_guard: Arc<RiskGuard>,
+    /// Adaptive position sizing fed by trade results
+    position_scaling: Arc<Mutex<PositionScalingManager>>,
+    /// Volatility, liquidity and trend from the quotes the engine fetches
+    market_analyzer: Arc<Mutex<MarketConditionAnalyzer>>,
+    /// Persistent log of trade results (if configured)
+    journal: Option<Arc<TradeJournal>>,
+    /// USD prices for valuing profits
+    price_oracle: Arc<dyn PriceOracle>,
+    /// Opportunities being executed, so repeat scans don't fire a second trade
+    in_flight: Arc<InFlightGuard>,
+    /// Nets fees and slippage out of spreads before they're traded
+    cost_model: Arc<NetProfitEstimator>,
+    /// Pairs disabled by hand or cooling down after losses
+    pair_states: Arc<PairStateTracker>,
+    /// Mints allowed to be traded, checked before every evaluation
+    token_policy: Arc<TokenPolicy>,
+    /// Operator notifications for circuit breaker trips (if configured)
+    notifications: Option<NotificationDispatcher>,
+    /// Minimum profit tuned by recent fill quality (static when unset)
+    profit_tuner: Option<Arc<Mutex<MinProfitTuner>>>,
 }
 
 impl ArbitrageEngine {
@@ -135,157 +834,663 @@ impl ArbitrageEngine {
         profit_manager: ThreadSafeProfitManager,
         config: ArbitrageConfig,
     ) -> Result<Self, String> {
-        let rpc_client = RpcClient::new_with_commitment(
-            rpc_url.to_string(),
-            CommitmentConfig::confirmed(),
-        );
+        Self::with_rpc(
+            &FailoverRpcClient::new(rpc_url, &[]),
+            dex_manager,
+            flash_loan_manager,
+            wallet_manager,
+            profit_manager,
+            config,
+        )
+    }
+    
+    /// Create a new arbitrage engine whose reads fail over across `rpc`'s endpoints
+    pub fn with_rpc(
+        rpc: &FailoverRpcClient,
+        dex_manager: ThreadSafeDexManager,
+        flash_loan_manager: ThreadSafeFlashLoanManager,
+        wallet_manager: ThreadSafeWalletManager,
+        profit_manager: ThreadSafeProfitManager,
+        config: ArbitrageConfig,
+    ) -> Result<Self, String> {
+        let rpc_client = rpc.rpc_client(config.commitment_policy.read);
+        let token_policy = Arc::new(TokenPolicy::new(
+            config.risk_management.token_filter.clone(),
+            rpc.rpc_client(config.commitment_policy.read),
+        ));
+        
+        wallet_manager.set_commitment_policy(config.commitment_policy)
+            .map_err(|e| format!("Failed to set commitment policy: {}", e))?;
+        dex_manager.set_read_commitment(config.commitment_policy.read)
+            .map_err(|e| format!("Failed to set DEX read commitment: {}", e))?;
+        wallet_manager.set_priority_fee(config.priority_fee, config.gas_price_multiplier)
+            .map_err(|e| format!("Failed to set priority fee: {}", e))?;
         
         let runtime = Runtime::new()
             .map_err(|e| format!("Failed to create tokio runtime: {}", e))?;
         
-        Ok(Self {
+        // Measure the daily loss limit against the largest position until told otherwise
+        let risk_guard = Arc::new(RiskGuard::new(config.risk_management.clone(), config.max_position_size));
+        
+        // Scale positions for the configured risk level, never beyond the engine's maximum
+        let position_scaling = Arc::new(Mutex::new(
+            config.position_scaling(PositionScalingConfig::new(config.risk_management.risk_level)),
+        ));
+        let market_analyzer = Arc::new(Mutex::new(config.market_analyzer()));
+        
+        let executor = Arc::new(ArbitrageExecutor {
             rpc_client,
             dex_manager,
             flash_loan_manager,
+            flash_loan_providers: Mutex::new(Vec::new()),
             wallet_manager,
-            profit_manager,
+            risk_manager: RiskManager::new(config.risk_management.clone()),
             config,
+            token_decimals: Mutex::new(HashMap::new()),
+        });
+        
+        let price_oracle: Arc<dyn PriceOracle> = Arc::new(JupiterPriceOracle::new(runtime.handle().clone()));
+        let cost_model = Arc::new(NetProfitEstimator::new(
+            executor.config.priority_fee,
+            executor.config.gas_price_multiplier,
+        ));
+        let in_flight = Arc::new(InFlightGuard::new(
+            Duration::from_millis(executor.config.in_flight_ttl_ms),
+            executor.config.dedup_price_precision_bps,
+        ));
+        let pair_states = Arc::new(PairStateTracker::new(
+            executor.config.pair_failure_threshold,
+            Duration::from_secs(executor.config.pair_cooldown_sec),
+        ));
+        
+        let backend: Arc<dyn ExecutionBackend> = match &executor.config.execution_mode {
+            ExecutionMode::Live => Arc::new(LiveBackend::new(executor.clone(), runtime.handle().clone())),
+            ExecutionMode::Paper(paper_config) => {
+                info!("Paper trading with a virtual balance of {}", paper_config.initial_balance);
+                Arc::new(PaperBackend::new(paper_config.clone()))
+            },
+        };
+        
+        Ok(Self {
+            executor,
+            backend,
+            profit_manager,
             runtime,
-            running: false,
-            active_operations: 0,
-            total_opportunities: 0,
-            total_executed: 0,
-            total_successful: 0,
-            total_profit: 0,
+            state: Arc::new(EngineState::default()),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            monitor_handle: None,
+            distribution_handle: None,
+            volatility_tracker: Arc::new(Mutex::new(VolatilityTracker::new(60))),
+            oracle_guard: None,
+            journal: None,
+            price_oracle,
+            risk_guard,
+            position_scaling,
+            market_analyzer,
+            in_flight,
+            cost_model,
+            pair_states,
+            token_policy,
+            notifications: None,
+            profit_tuner: None,
         })
     }
     
+    /// Offer loans from another flash loan provider as well as the engine's own
+    ///
+    /// Each trade borrows from the cheapest provider whose reserve can cover it.
+    pub fn add_flash_loan_provider(&self, flash_loan_manager: ThreadSafeFlashLoanManager) {
+        lock_or_recover(&self.executor.flash_loan_providers).push(flash_loan_manager);
+    }
+    
+    /// Replace the cost model used to net fees and slippage out of spreads
+    pub fn set_cost_model(&mut self, cost_model: NetProfitEstimator) {
+        self.cost_model = Arc::new(cost_model);
+    }
+    
+    /// Send circuit breaker trips to operator notifications
+    ///
+    /// Trade results are published as bot events; forward those with `notify::forward_bot_events`.
+    pub fn set_notifications(&mut self, notifications: NotificationDispatcher) {
+        self.notifications = Some(notifications);
+    }
+    
+    /// Enable or disable trading a token pair; enabling also ends a cooldown after losses
+    pub fn set_pair_enabled(&self, base_token: &Pubkey, quote_token: &Pubkey, enabled: bool) -> Result<(), String> {
+        self.pair_states.set_enabled(base_token, quote_token, enabled)
+    }
+    
+    /// Trust a mint, even if it can be frozen, and take it off the denylist
+    pub fn add_to_allowlist(&self, mint: Pubkey) -> Result<(), String> {
+        self.token_policy.add_to_allowlist(mint)
+    }
+    
+    /// Stop trading every pair with a mint, taking it off the allowlist
+    pub fn add_to_denylist(&self, mint: Pubkey) -> Result<(), String> {
+        self.token_policy.add_to_denylist(mint)
+    }
+    
+    /// Current token allowlist, denylist and checks
+    pub fn token_filter(&self) -> Result<TokenFilter, String> {
+        self.token_policy.filter()
+    }
+    
+    /// Trading state of a token pair
+    pub fn pair_state(&self, base_token: &Pubkey, quote_token: &Pubkey) -> PairState {
+        self.pair_states.get(base_token, quote_token)
+    }
+    
+    /// Replace the position scaling configuration, resetting learned position sizes
+    pub fn set_position_scaling(&mut self, config: PositionScalingConfig) -> Result<(), String> {
+        let mut position_scaling = lock_or_recover(&self.position_scaling);
+        *position_scaling = PositionScalingManager::new(config);
+        Ok(())
+    }
+    
+    /// Current scaled position size for a token pair
+    pub fn position_size(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<u64, String> {
+        Ok(lock_or_recover(&self.position_scaling).get_position_size(base_token, quote_token))
+    }
+    
+    /// Reject opportunities whose prices deviate too far from an oracle
+    pub fn set_oracle_guard(&mut self, oracle_guard: OracleGuard) {
+        self.oracle_guard = Some(Arc::new(oracle_guard));
+    }
+    
+    /// Tune the minimum profit percentage from recent fill quality, starting from the configured one
+    pub fn set_profit_tuner(&mut self, config: ProfitTunerConfig) -> Result<(), String> {
+        config.validate()?;
+        let tuner = MinProfitTuner::new(config, self.executor.config.min_profit_percentage);
+        self.profit_tuner = Some(Arc::new(Mutex::new(tuner)));
+        Ok(())
+    }
+    
+    /// Record every trade result to a journal
+    pub fn set_trade_journal(&mut self, journal: TradeJournal) {
+        self.journal = Some(Arc::new(journal));
+    }
+    
+    /// Replace the USD price source used to value profits (Jupiter by default)
+    pub fn set_price_oracle(&mut self, price_oracle: Arc<dyn PriceOracle>) {
+        self.price_oracle = price_oracle;
+    }
+    
+    /// Trade journal, for querying and exporting past trades
+    pub fn trade_journal(&self) -> Option<Arc<TradeJournal>> {
+        self.journal.clone()
+    }
+    
+    /// Virtual balance when paper trading
+    pub fn virtual_balance(&self) -> Option<u64> {
+        self.backend.virtual_balance()
+    }
+    
+    /// Set the capital the daily loss limit is measured against
+    pub fn set_risk_capital(&self, capital: u64) {
+        self.risk_guard.set_capital(capital);
+    }
+    
+    /// Receive opportunities as they're detected, before they're executed
+    pub fn subscribe(&self) -> Receiver<ArbitrageOpportunity> {
+        self.state.opportunity_subscribers.subscribe()
+    }
+    
+    /// Receive the result of each execution attempt
+    pub fn subscribe_results(&self) -> Receiver<ArbitrageResult> {
+        self.state.result_subscribers.subscribe()
+    }
+    
+    /// Receive the result of each scheduled profit distribution
+    pub fn subscribe_distributions(&self) -> Receiver<DistributionResult> {
+        self.state.distribution_subscribers.subscribe()
+    }
+    
+    /// Receive the same activity summarized as bot events
+    ///
+    /// Subscribers that fall behind lose the oldest events rather than blocking the engine.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<BotEvent> {
+        self.events.subscribe()
+    }
+    
+    /// Publish events on an existing channel, such as the bot's, instead of the engine's own
+    ///
+    /// Takes effect the next time the engine starts.
+    pub fn set_event_sender(&mut self, events: broadcast::Sender<BotEvent>) {
+        self.events = events;
+    }
+    
+    /// Run profit distribution on the configured schedule until the engine stops
+    ///
+    /// Distribution waits for in-flight trades to finish and holds new ones
+    /// back, so it never moves capital a trade is about to use.
+    fn spawn_distribution_scheduler(&self) -> Option<tokio::task::JoinHandle<()>> {
+        match self.profit_manager.schedule() {
+            Ok(schedule) if schedule.is_enabled() => {},
+            Ok(_) => return None,
+            Err(e) => {
+                error!("Failed to read distribution schedule: {}", e);
+                return None;
+            }
+        }
+        
+        let state = self.state.clone();
+        let events = self.events.clone();
+        let profit_manager = self.profit_manager.clone();
+        let risk_guard = self.risk_guard.clone();
+        let executor = self.executor.clone();
+        
+        Some(self.runtime.spawn(async move {
+            let mut last_distribution = Instant::now();
+            
+            while state.running.load(Ordering::SeqCst) {
+                tokio::time::sleep(DISTRIBUTION_CHECK_INTERVAL).await;
+                
+                match profit_manager.distribution_due(last_distribution.elapsed()) {
+                    Ok(true) => {},
+                    Ok(false) => continue,
+                    Err(e) => {
+                        error!("Failed to check distribution schedule: {}", e);
+                        continue;
+                    }
+                }
+                
+                if !risk_guard.try_begin_distribution() {
+                    debug!("Profit distribution due, waiting for trades in flight");
+                    continue;
+                }
+                
+                // Transfers block on RPC, keep them off the async workers
+                let profit_manager = profit_manager.clone();
+                let executor = executor.clone();
+                let result = tokio::task::spawn_blocking(move || profit_manager.distribute_profits(&executor.wallet_manager)).await;
+                risk_guard.end_distribution();
+                last_distribution = Instant::now();
+                
+                match result {
+                    Ok(Ok(result)) => {
+                        info!("Scheduled profit distribution: reinvested={}, withdrawn={}, reserved={}",
+                              result.reinvested_amount, result.withdrawn_amount, result.reserved_amount);
+                        state.distribution_subscribers.publish(&result);
+                        publish(&events, BotEvent::ProfitDistributed {
+                            reinvested_amount: result.reinvested_amount,
+                            withdrawn_amount: result.withdrawn_amount,
+                            reserved_amount: result.reserved_amount,
+                        });
+                    },
+                    Ok(Err(e)) => error!("Scheduled profit distribution failed: {}", e),
+                    Err(e) => error!("Profit distribution task panicked: {}", e),
+                }
+            }
+            
+            debug!("Profit distribution scheduler exited");
+        }))
+    }
+    
+    /// Whether the engine is running
+    pub fn is_running(&self) -> bool {
+        self.state.running.load(Ordering::SeqCst)
+    }
+    
+    /// Get engine statistics
+    pub fn get_statistics(&self) -> EngineStatistics {
+        EngineStatistics {
+            active_operations: self.state.active_operations.load(Ordering::SeqCst),
+            total_opportunities: self.state.total_opportunities.load(Ordering::SeqCst),
+            total_executed: self.state.total_executed.load(Ordering::SeqCst),
+            total_successful: self.state.total_successful.load(Ordering::SeqCst),
+            total_profit: self.state.total_profit.load(Ordering::SeqCst),
+            min_profit_percentage: effective_min_profit_percentage(&self.executor.config, self.profit_tuner.as_deref()),
+        }
+    }
+    
+    /// Order an arbitrage's instructions for a single atomic transaction: borrow, buy, sell, repay
+    ///
+    /// Providers such as Solend check that the repayment lands in the same
+    /// transaction as the borrow, so a borrow without a repay (or the reverse)
+    /// is rejected rather than sent to revert on-chain. The repay is built here
+    /// from the borrow's final index, which providers like Kamino reference.
+    pub fn assemble_flash_loan_tx(
+        borrow: Option<Instruction>,
+        buy: Vec<Instruction>,
+        sell: Vec<Instruction>,
+        repay: Option<RepayBuilder<'_>>,
+    ) -> Result<Vec<Instruction>, String> {
+        if buy.is_empty() || sell.is_empty() {
+            return Err("Arbitrage needs both a buy and a sell leg".to_string());
+        }
+        
+        match (&borrow, &repay) {
+            (Some(_), None) => return Err("Flash loan borrow has no matching repayment".to_string()),
+            (None, Some(_)) => return Err("Flash loan repayment has no matching borrow".to_string()),
+            _ => {},
+        }
+        
+        // The borrow leads the transaction, right after the compute budget the wallet prepends when sending
+        let borrow_instruction_index = COMPUTE_BUDGET_INSTRUCTIONS as u8;
+        let repay = repay.map(|build| build(borrow_instruction_index)).transpose()?;
+        
+        let mut instructions = Vec::with_capacity(buy.len() + sell.len() + 2);
+        instructions.extend(borrow);
+        instructions.extend(buy);
+        instructions.extend(sell);
+        instructions.extend(repay);
+        
+        Ok(instructions)
+    }
+    
+    /// Simulate an opportunity without broadcasting it, so live trading can be gated on the result
+    pub fn simulate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<SimulationReport, String> {
+        self.runtime.block_on(self.executor.simulate_opportunity(opportunity))
+    }
+    
+    /// Configured token pairs, warning about those the token policy rejects
+    ///
+    /// Rejected pairs are still monitored, but skipped while the policy rejects them, so
+    /// allowlisting a mint while running brings its pairs back.
+    fn filtered_token_pairs(&self) -> Vec<(Pubkey, Pubkey)> {
+        for (base_token, quote_token) in &self.executor.config.token_pairs {
+            if let Err(e) = self.token_policy.check_pair(base_token, quote_token) {
+                warn!("Not trading {}/{}: {}", base_token, quote_token, e);
+            }
+        }
+        
+        self.executor.config.token_pairs.clone()
+    }
+    
     /// Start the arbitrage engine
     pub fn start(&mut self) -> Result<(), String> {
-        if self.running {
+        if self.state.running.swap(true, Ordering::SeqCst) {
             return Err("Arbitrage engine is already running".to_string());
         }
         
         info!("Starting arbitrage engine");
-        self.running = true;
         
         // Clone necessary components for the monitoring thread
-        let dex_manager = self.dex_manager.clone();
-        let flash_loan_manager = self.flash_loan_manager.clone();
-        let wallet_manager = self.wallet_manager.clone();
+        let executor = self.executor.clone();
+        let backend = self.backend.clone();
+        let state = self.state.clone();
         let profit_manager = self.profit_manager.clone();
-        let config = self.config.clone();
         let runtime = self.runtime.handle().clone();
+        let journal = self.journal.clone();
+        let price_oracle = self.price_oracle.clone();
+        let risk_guard = self.risk_guard.clone();
+        let position_scaling = self.position_scaling.clone();
+        let in_flight = self.in_flight.clone();
+        let pair_states = self.pair_states.clone();
+        let token_policy = self.token_policy.clone();
+        let evaluator = PairEvaluator {
+            executor: self.executor.clone(),
+            runtime: runtime.clone(),
+            volatility_tracker: self.volatility_tracker.clone(),
+            oracle_guard: self.oracle_guard.clone(),
+            position_scaling: self.position_scaling.clone(),
+            market_analyzer: self.market_analyzer.clone(),
+            cost_model: self.cost_model.clone(),
+            price_oracle: self.price_oracle.clone(),
+            profit_tuner: self.profit_tuner.clone(),
+        };
+        let evaluation_slots = Arc::new(Semaphore::new(MAX_CONCURRENT_EVALUATIONS));
+        let notifications = self.notifications.clone();
+        let events = self.events.clone();
+        let token_pairs = self.filtered_token_pairs();
+        
+        // Re-evaluate a pair as soon as one of its prices moves, rather than waiting for the next poll
+        let price_updates = match self.executor.dex_manager.subscribe_prices(&token_pairs) {
+            Ok(receiver) => Some(receiver),
+            Err(e) => {
+                warn!("Price streaming unavailable, polling only: {}", e);
+                None
+            }
+        };
         
         // Start monitoring thread
-        std::thread::spawn(move || {
+        let handle = std::thread::spawn(move || {
+            let config = &executor.config;
             let mut last_check = Instant::now();
+            let mut throttle = AdaptiveThrottle::new(config.update_interval_ms);
             
-            while self.running {
+            while state.running.load(Ordering::SeqCst) {
                 // Check if it's time to update
                 let now = Instant::now();
-                if now.duration_since(last_check) >= Duration::from_millis(config.update_interval_ms) {
+                let due = now.duration_since(last_check) >= Duration::from_millis(throttle.current_interval_ms());
+                if due {
                     last_check = now;
+                }
+                
+                // Pairs with a streamed price change since the last pass
+                let updated: HashSet<(Pubkey, Pubkey)> = price_updates.iter()
+                    .flat_map(|receiver| receiver.try_iter())
+                    .map(|price| (price.base_token, price.quote_token))
+                    .collect();
+                
+                // Nothing can be executed while every slot is taken, so don't spend quotes on it
+                let has_capacity = state.active_operations.load(Ordering::SeqCst) < config.max_concurrent_operations;
+                
+                if (due || !updated.is_empty()) && has_capacity {
+                    let due_pairs: Vec<(Pubkey, Pubkey)> = token_pairs.iter()
+                        .filter(|pair| due || updated.contains(*pair))
+                        // Skip pairs disabled by hand or cooling down after losses
+                        .filter(|(base_token, quote_token)| pair_states.is_tradable(base_token, quote_token))
+                        // Skip denylisted and freezable mints; metadata is cached after the first check
+                        .filter(|(base_token, quote_token)| token_policy.check_pair(base_token, quote_token).is_ok())
+                        .copied()
+                        .collect();
+                    
+                    // Fetch every due pair's pool accounts in a few batched requests instead of one per pool
+                    if let Err(e) = executor.dex_manager.prefetch_prices(&due_pairs) {
+                        debug!("Batched pool fetch failed, quoting pairs individually: {}", e);
+                    }
+                    
+                    // Evaluate every due pair at once, so the last pair isn't priced long after the first
+                    let evaluations: Vec<_> = due_pairs.iter()
+                        .map(|&(base_token, quote_token)| {
+                            // Waits for a free slot, so at most MAX_CONCURRENT_EVALUATIONS pairs are quoted at a time
+                            let permit = runtime.block_on(evaluation_slots.clone().acquire_owned()).ok();
+                            let evaluator = evaluator.clone();
+                            runtime.spawn_blocking(move || {
+                                let _permit = permit;
+                                evaluator.evaluate(&base_token, &quote_token)
+                            })
+                        })
+                        .collect();
                     
-                    // Check for arbitrage opportunities for each token pair
-                    for (base_token, quote_token) in &config.token_pairs {
-                        // Skip if we've reached max concurrent operations
-                        if self.active_operations >= config.max_concurrent_operations {
+                    let mut opportunities = Vec::new();
+                    for evaluation in evaluations {
+                        let result = runtime.block_on(evaluation)
+                            .unwrap_or_else(|e| Err(DexError::GeneralError(format!("Pair evaluation failed: {}", e))));
+                        
+                        // Back off when the RPC/DEX endpoints rate limit us
+                        throttle.record_result(&result);
+                        
+                        match result {
+                            Ok(Some(opportunity)) => {
+                                state.total_opportunities.fetch_add(1, Ordering::SeqCst);
+                                state.opportunity_subscribers.publish(&opportunity);
+                                publish(&events, BotEvent::OpportunityDetected {
+                                    base_token: opportunity.base_token,
+                                    quote_token: opportunity.quote_token,
+                                    profit_percentage: opportunity.profit_percentage,
+                                });
+                                opportunities.push(opportunity);
+                            },
+                            Ok(None) => {},
+                            Err(e) => debug!("No arbitrage opportunity found: {}", e),
+                        }
+                    }
+                    
+                    // When several spreads appear at once, capital goes to the most profitable first
+                    opportunities.sort_by_key(|opportunity| std::cmp::Reverse(opportunity.net_estimated_profit()));
+                    
+                    for opportunity in opportunities {
+                        let (base_token, quote_token) = (opportunity.base_token, opportunity.quote_token);
+                        let costs = opportunity.costs.unwrap_or_default();
+                        let trade_span = opportunity.span();
+                        
+                        // The rest are evaluated again next pass
+                        if state.active_operations.load(Ordering::SeqCst) >= config.max_concurrent_operations {
+                            break;
+                        }
+                        
+                        // The same spread is often seen again before its first trade confirms
+                        let ticket = match in_flight.try_claim(&opportunity) {
+                            Some(ticket) => ticket,
+                            None => continue,
+                        };
+                        
+                        // Enforce the circuit breakers before committing capital
+                        if let Err(e) = risk_guard.check_can_trade(opportunity.max_trade_size) {
+                            debug!("Not executing {}/{}: {}", base_token, quote_token, e);
+                            continue;
+                        }
+                        if let Err(e) = risk_guard.trade_started() {
+                            error!("Failed to record trade start: {}", e);
                             continue;
                         }
                         
-                        // Find arbitrage opportunity
-                        let opportunity_result = runtime.block_on(async {
-                            dex_manager.find_arbitrage_opportunity(
-                                base_token,
-                                quote_token,
-                                config.min_profit_percentage,
-                            ).await
-                        });
+                        // Execute arbitrage
+                        info!("Executing {}/{} at size {}: {}", base_token, quote_token, opportunity.max_trade_size, costs);
+                        state.active_operations.fetch_add(1, Ordering::SeqCst);
+                        let backend = backend.clone();
+                        let state = state.clone();
+                        let profit_manager = profit_manager.clone();
+                        let risk_guard = risk_guard.clone();
+                        let position_scaling = position_scaling.clone();
+                        let journal = journal.clone();
+                        let executor = executor.clone();
+                        let price_oracle = price_oracle.clone();
+                        let pair_states = pair_states.clone();
+                        let notifications = notifications.clone();
+                        let events = events.clone();
+                        let profit_tuner = evaluator.profit_tuner.clone();
                         
-                        match opportunity_result {
-                            Ok((buy_price, sell_price, profit_percentage)) => {
-                                self.total_opportunities += 1;
+                        // Backends block on RPC calls, so keep them off the async workers
+                        runtime.spawn_blocking(move || {
+                            // Execution runs on this thread, so DEX, flash loan and wallet logs join the span
+                            let _trade_span = trade_span.enter();
+                            let arb_result = backend.execute(&opportunity);
+                            
+                            // Settle the trade before anything counts it, so a trade dropped in a fork is only ever a failure
+                            let arb_result = match &executor.config.finality_guard {
+                                Some(guard) if arb_result.success => executor.settle(arb_result, guard),
+                                _ => arb_result,
+                            };
+                            
+                            // Grow positions after profitable trades and shrink them after failures
+                            if arb_result.simulation.is_none() {
+                                record_position_result(&position_scaling, &arb_result);
                                 
-                                // Calculate estimated profit and max trade size
-                                let max_liquidity = buy_price.liquidity.min(sell_price.liquidity);
-                                let max_trade_size = max_liquidity.min(config.max_position_size);
-                                let estimated_profit = ((max_trade_size as f64) * (profit_percentage / 100.0)) as u64;
+                                // Pause the pair after a run of failed or losing trades
+                                pair_states.record_result(
+                                    &opportunity.base_token,
+                                    &opportunity.quote_token,
+                                    arb_result.success && arb_result.realized_pnl >= 0,
+                                );
                                 
-                                let opportunity = ArbitrageOpportunity {
-                                    base_token: *base_token,
-                                    quote_token: *quote_token,
-                                    buy_price,
-                                    sell_price,
-                                    profit_percentage,
-                                    estimated_profit,
-                                    max_trade_size,
-                                    timestamp: std::time::SystemTime::now()
-                                        .duration_since(std::time::UNIX_EPOCH)
-                                        .unwrap_or_default()
-                                        .as_secs(),
-                                };
+                                // Demand wider spreads while fills fall short of their estimates
+                                if let Some(profit_tuner) = &profit_tuner {
+                                    lock_or_recover(profit_tuner).record(
+                                        arb_result.success,
+                                        arb_result.opportunity.net_estimated_profit(),
+                                        arb_result.realized_pnl,
+                                    );
+                                }
+                            }
+                            
+                            match risk_guard.trade_finished(arb_result.realized_pnl) {
+                                Ok(true) => {
+                                    if let Some(notifications) = &notifications {
+                                        notifications.dispatch(Notification::circuit_breaker_tripped(
+                                            "daily loss limit reached, trading halted until the next UTC day",
+                                        ));
+                                    }
+                                },
+                                Ok(false) => {},
+                                Err(e) => error!("Failed to record trade result: {}", e),
+                            }
+                            
+                            // Trade notifications are sent from these events by `notify::forward_bot_events`
+                            state.result_subscribers.publish(&arb_result);
+                            publish(&events, if arb_result.success {
+                                BotEvent::TradeExecuted {
+                                    signature: arb_result.transaction_signature.clone().unwrap_or_default(),
+                                    profit: arb_result.actual_profit,
+                                    execution_time_ms: arb_result.execution_time_ms,
+                                }
+                            } else {
+                                BotEvent::TradeFailed {
+                                    reason: arb_result.error_message.clone().unwrap_or_else(|| "unknown error".to_string()),
+                                    execution_time_ms: arb_result.execution_time_ms,
+                                }
+                            });
+                            
+                            if let Some(journal) = &journal {
+                                if let Err(e) = journal.record(&arb_result) {
+                                    error!("Failed to journal trade: {}", e);
+                                }
+                            }
+                            
+                            // One structured event per trade for grepping its outcome
+                            tracing::info!(
+                                target: "trade",
+                                trade_id = %opportunity.trade_id,
+                                outcome = if arb_result.success { "success" } else { "failure" },
+                                profit = arb_result.realized_pnl,
+                                latency_ms = arb_result.execution_time_ms,
+                                signature = arb_result.transaction_signature.as_deref().unwrap_or(""),
+                                "Trade finished"
+                            );
+                            
+                            if arb_result.success {
+                                info!("Arbitrage successful: profit={}, priority fee={} micro-lamports/CU, tx={}", 
+                                      arb_result.actual_profit,
+                                      arb_result.priority_fee_micro_lamports,
+                                      arb_result.transaction_signature.unwrap_or_default());
                                 
-                                // Execute arbitrage
-                                self.active_operations += 1;
-                                let engine_clone = self.clone();
+                                // Value the profit in USD, since the quote token isn't always a stablecoin
+                                let usd_cents = executor.usd_cents(price_oracle.as_ref(), &opportunity.quote_token, arb_result.actual_profit)
+                                    .unwrap_or_else(|e| {
+                                        warn!("Failed to value profit in USD, recording 0: {}", e);
+                                        0
+                                    });
                                 
-                                runtime.spawn(async move {
-                                    let result = engine_clone.execute_arbitrage(&opportunity).await;
-                                    
-                                    match result {
-                                        Ok(arb_result) => {
-                                            if arb_result.success {
-                                                info!("Arbitrage successful: profit={}, tx={}", 
-                                                      arb_result.actual_profit,
-                                                      arb_result.transaction_signature.unwrap_or_default());
-                                                
-                                                // Record profit
-                                                let _ = profit_manager.record_profit(
-                                                    opportunity.quote_token,
-                                                    arb_result.actual_profit,
-                                                    0, // SOL value (placeholder)
-                                                    arb_result.actual_profit, // USD value (assuming quote is a stablecoin)
-                                                );
-                                                
-                                                engine_clone.total_successful += 1;
-                                                engine_clone.total_profit += arb_result.actual_profit;
-                                            } else {
-                                                warn!("Arbitrage failed: {}", 
-                                                      arb_result.error_message.unwrap_or_default());
-                                                
-                                                // Record failed trade
-                                                let _ = profit_manager.record_failed_trade(
-                                                    opportunity.quote_token,
-                                                );
-                                            }
-                                        },
-                                        Err(e) => {
-                                            error!("Error executing arbitrage: {}", e);
-                                            
-                                            // Record failed trade
-                                            let _ = profit_manager.record_failed_trade(
-                                                opportunity.quote_token,
-                                            );
-                                        }
-                                    }
-                                    
-                                    engine_clone.active_operations -= 1;
-                                });
+                                // Record profit
+                                let _ = profit_manager.record_profit(
+                                    opportunity.quote_token,
+                                    arb_result.actual_profit,
+                                    0, // SOL value (placeholder)
+                                    usd_cents,
+                                );
                                 
-                                self.total_executed += 1;
-                            },
-                            Err(e) => {
-                                debug!("No arbitrage opportunity found: {}", e);
+                                state.total_successful.fetch_add(1, Ordering::SeqCst);
+                                state.total_profit.fetch_add(arb_result.actual_profit, Ordering::SeqCst);
+                            } else {
+                                warn!("Arbitrage failed: {}", 
+                                      arb_result.error_message.unwrap_or_default());
+                                
+                                // Record failed trade
+                                let _ = profit_manager.record_failed_trade(
+                                    opportunity.quote_token,
+                                );
                             }
-                        }
+                            
+                            state.active_operations.fetch_sub(1, Ordering::SeqCst);
+                            
+                            // The result is in, so the spread may be traded again
+                            drop(ticket);
+                        });
+                        
+                        state.total_executed.fetch_add(1, Ordering::SeqCst);
                     }
                 }
                 
                 // Sleep to avoid excessive CPU usage
                 std::thread::sleep(Duration::from_millis(10));
             }
+            
+            debug!("Arbitrage monitoring thread exited");
         });
+        self.monitor_handle = Some(handle);
+        self.distribution_handle = self.spawn_distribution_scheduler();
         
         info!("Arbitrage engine started successfully");
         Ok(())
@@ -293,22 +1498,142 @@ impl ArbitrageEngine {
     
     /// Stop the arbitrage engine
     pub fn stop(&mut self) -> Result<(), String> {
-        if !self.running {
+        if !self.state.running.swap(false, Ordering::SeqCst) {
             return Err("Arbitrage engine is not running".to_string());
         }
         
         info!("Stopping arbitrage engine");
-        self.running = false;
+        let deadline = Instant::now() + Duration::from_secs(self.executor.config.shutdown_timeout_sec);
         
-        // Wait for active operations to complete
-        while self.active_operations > 0 {
-            std::thread::sleep(Duration::from_millis(100));
+        // The monitoring thread checks the flag every loop iteration, so no new trades start after this
+        if let Some(handle) = self.monitor_handle.take() {
+            handle.join()
+                .map_err(|_| "Arbitrage monitoring thread panicked".to_string())?;
+        }
+        
+        // Trades already sent must be seen through to their result, not abandoned mid-flight
+        if !self.drain_operations(deadline) {
+            error!("Gave up waiting for {} in-flight trades after {}s, their results will not be recorded",
+                   self.state.active_operations.load(Ordering::SeqCst), self.executor.config.shutdown_timeout_sec);
+        }
+        
+        // Let a distribution in progress finish rather than cutting it off mid-transfer
+        if let Some(handle) = self.distribution_handle.take() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.runtime.block_on(tokio::time::timeout(remaining, handle)) {
+                Ok(Ok(())) => {},
+                Ok(Err(e)) => error!("Profit distribution scheduler panicked: {}", e),
+                Err(_) => error!("Gave up waiting for a profit distribution in progress"),
+            }
+        }
+        
+        if let Some(journal) = &self.journal {
+            journal.flush()?;
         }
         
         info!("Arbitrage engine stopped successfully");
         Ok(())
     }
     
+    /// Wait until no trades are in flight, returning false if `deadline` passes first
+    fn drain_operations(&self, deadline: Instant) -> bool {
+        let mut last_logged = None;
+        
+        loop {
+            let active = self.state.active_operations.load(Ordering::SeqCst);
+            if active == 0 {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            
+            if last_logged != Some(active) {
+                info!("Waiting for {} in-flight trades to finish", active);
+                last_logged = Some(active);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+// Stop on drop so in-flight trades are drained and the journal flushed
+impl Drop for ArbitrageEngine {
+    fn drop(&mut self) {
+        if self.state.running.load(Ordering::SeqCst) {
+            if let Err(e) = self.stop() {
+                error!("Failed to stop arbitrage engine cleanly: {}", e);
+            }
+        }
+    }
+}
+
+impl ArbitrageExecutor {
+    /// Decimals of a token mint, fetched once and cached
+    fn token_decimals(&self, mint: &Pubkey) -> Result<u8, String> {
+        if let Some(decimals) = lock_or_recover(&self.token_decimals).get(mint).copied() {
+            return Ok(decimals);
+        }
+        
+        let decimals = self.rpc_client.get_token_supply(mint)
+            .map_err(|e| format!("Failed to get decimals for {}: {}", mint, e))?
+            .decimals;
+        
+        lock_or_recover(&self.token_decimals).insert(*mint, decimals);
+        Ok(decimals)
+    }
+    
+    /// Value an amount of a token (in its smallest unit) in USD cents
+    fn usd_cents(&self, price_oracle: &dyn PriceOracle, mint: &Pubkey, amount: u64) -> Result<u64, String> {
+        let decimals = self.token_decimals(mint)?;
+        let price = price_oracle.get_usd_price(mint)
+            .map_err(|e| format!("Failed to price {}: {}", mint, e))?;
+        
+        Ok((amount as f64 / 10f64.powi(decimals as i32) * price * 100.0).round() as u64)
+    }
+    
+    /// Move each leg to Jupiter's aggregated route when it returns at least as much as the direct venue
+    ///
+    /// Both legs are quoted for the opportunity's exact size and compared net of each
+    /// venue's swap fee. Routed legs are priced so the cost model's Jupiter fee nets back
+    /// to the quoted output. A leg stays on its direct venue if Jupiter can't quote it.
+    async fn route_via_aggregator(&self, opportunity: &ArbitrageOpportunity, cost_model: &NetProfitEstimator) -> ArbitrageOpportunity {
+        let slippage_bps = (self.config.slippage_tolerance * 100.0) as u64;
+        let aggregator_fee_bps = cost_model.dex_fee_bps(DexType::Jupiter);
+        let size = opportunity.max_trade_size;
+        
+        // Buy leg: the trade size in quote token for as much base token as possible
+        let mut buy_price = opportunity.buy_price.clone();
+        if buy_price.dex != DexType::Jupiter {
+            let direct_out = net_of_fee(buy_price.quote_to_base_atoms(size), cost_model.leg_fee_bps(&buy_price));
+            match self.dex_manager.quote_aggregated_route(&opportunity.quote_token, &opportunity.base_token, size, slippage_bps).await {
+                Ok(route) if route.out_amount > 0 && route.out_amount >= direct_out => {
+                    debug!("Buying along Jupiter's route: {} out vs {} on {:?}", route.out_amount, direct_out, buy_price.dex);
+                    buy_price = aggregated_price(&buy_price, gross_of_fee(route.out_amount, aggregator_fee_bps), size);
+                },
+                Ok(_) => {},
+                Err(e) => debug!("No aggregated route for the buy leg: {}", e),
+            }
+        }
+        
+        // Sell leg: the bought base token for as much quote token as possible
+        let base_in = buy_price.quote_to_base_atoms(size);
+        let mut sell_price = opportunity.sell_price.clone();
+        if sell_price.dex != DexType::Jupiter && base_in > 0 {
+            let direct_out = net_of_fee(sell_price.base_to_quote_atoms(base_in), cost_model.leg_fee_bps(&sell_price));
+            match self.dex_manager.quote_aggregated_route(&opportunity.base_token, &opportunity.quote_token, base_in, slippage_bps).await {
+                Ok(route) if route.out_amount > 0 && route.out_amount >= direct_out => {
+                    debug!("Selling along Jupiter's route: {} out vs {} on {:?}", route.out_amount, direct_out, sell_price.dex);
+                    sell_price = aggregated_price(&sell_price, base_in, gross_of_fee(route.out_amount, aggregator_fee_bps));
+                },
+                Ok(_) => {},
+                Err(e) => debug!("No aggregated route for the sell leg: {}", e),
+            }
+        }
+        
+        opportunity.with_venues(buy_price, sell_price)
+    }
+    
     /// Execute arbitrage
     async fn execute_arbitrage(&self, opportunity: &ArbitrageOpportunity) -> Result<ArbitrageResult, String> {
         let start_time = Instant::now();
@@ -319,46 +1644,516 @@ impl ArbitrageEngine {
               opportunity.profit_percentage,
               opportunity.max_trade_size);
         
-        // Get trading wallet
-        let trading_wallets = self.wallet_manager.get_wallets_by_type(WalletType::Trading)
-            .map_err(|e| format!("Failed to get trading wallets: {}", e))?;
+        // Get the next trading wallet from the pool
+        let wallet = self.wallet_manager.get_next_trading_wallet()
+            .map_err(|e| format!("Failed to get trading wallet: {}", e))?;
+        
+        // Never commit more than the configured share of the wallet's capital
+        let trade_size = self.risk_manager.size_within_limits(&self.wallet_manager, &wallet, opportunity.max_trade_size)
+            .map_err(|e| format!("Risk check failed: {}", e))?;
+        let opportunity = opportunity.with_trade_size(trade_size);
         
-        if trading_wallets.is_empty() {
-            return Err("No trading wallet found".to_string());
+        self.risk_manager.open_position(trade_size)
+            .map_err(|e| format!("Failed to record open position: {}", e))?;
+        let result = self.execute_with_wallet(&opportunity, wallet, start_time).await;
+        if let Err(e) = self.risk_manager.close_position(trade_size) {
+            error!("Failed to release open position: {}", e);
+        }
+        
+        result
+    }
+    
+    /// Build, simulate and send a sized arbitrage from the given trading wallet
+    async fn execute_with_wallet(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        wallet: Pubkey,
+        start_time: Instant,
+    ) -> Result<ArbitrageResult, String> {
+        // Both legs need somewhere to receive tokens, even ones this wallet has never held
+        for mint in [&opportunity.base_token, &opportunity.quote_token] {
+            self.wallet_manager.ensure_associated_token_account(&wallet, mint)
+                .map_err(|e| format!("Failed to prepare token account: {}", e))?;
         }
         
-        let wallet = trading_wallets[0].pubkey;
+        // Build and simulate, loosening slippage a step at a time on slippage failures
+        let simulated = retry_on_slippage(&self.config, |slippage| async move {
+            let swaps = self.build_arbitrage_instructions(opportunity, &wallet, slippage).await?;
+            Ok(self.simulate_instructions(&swaps, &wallet).map(|()| swaps))
+        }).await?;
         
-        // Create arbitrage instructions
-        let instructions = if self.config.use_flash_loans {
-            // Flash loan approach
-            self.create_flash_loan_arbitrage_instructions(opportunity, &wallet).await?
-        } else {
-            // Direct approach using wallet funds
-            self.create_direct_arbitrage_instructions(opportunity, &wallet).await?
+        let (swaps, slippage) = match simulated {
+            Ok(signed) => signed,
+            Err(e) => {
+                return Ok(ArbitrageResult {
+                    success: false,
+                    actual_profit: 0,
+                    realized_pnl: 0,
+                    error_message: Some(format!("Simulation failed: {}", e)),
+                    transaction_signature: None,
+                    priority_fee_micro_lamports: 0,
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                    opportunity: opportunity.clone(),
+                    simulation: None,
+                });
+            },
         };
         
-        // Sign and send transaction
-        let signers = vec![&wallet];
+        // Dry run: report what the transaction would do and stop here
+        if self.config.simulate {
+            let report = self.simulate_report(&swaps, &wallet, opportunity)?;
+            info!("Dry run: success={}, compute units={:?}", report.success, report.units_consumed);
+            
+            return Ok(ArbitrageResult {
+                success: report.success,
+                actual_profit: 0,
+                realized_pnl: 0,
+                error_message: report.error.clone(),
+                transaction_signature: None,
+                priority_fee_micro_lamports: 0,
+                execution_time_ms: start_time.elapsed().as_millis() as u64,
+                opportunity: opportunity.clone(),
+                simulation: Some(report),
+            });
+        }
         
-        let signature = self.wallet_manager.sign_and_send_transaction(instructions, signers)
+        // Bid for inclusion against the recent fees on the pools we touch
+        let priority_fee = self.wallet_manager.priority_fee(&swaps.instructions)
+            .map_err(|e| format!("Failed to price transaction: {}", e))?;
+        
+        // Sign and send transaction; routes that reference lookup tables go out as v0.
+        // The trading wallet pays the fees unless the operational wallet is set to pay them
+        let signers = vec![&wallet];
+        let signature = self.wallet_manager.sign_and_send_priced(swaps.instructions, signers, swaps.lookup_tables, priority_fee, None)
             .map_err(|e| format!("Failed to sign and send transaction: {}", e))?;
         
+        // Wait for the trade to land, then measure what it actually made
+        let outcome = self.wait_for_confirmation(&signature)
+            .and_then(|()| self.realized_profit(&signature, &wallet, &opportunity.quote_token));
+        
         let execution_time = start_time.elapsed().as_millis() as u64;
         
-        // For now, we'll assume success if we get a signature
-        // In a real implementation, you would verify the transaction and calculate actual profit
+        match outcome {
+            Ok(profit) => {
+                if profit < 0 {
+                    warn!("Arbitrage {} landed at a loss of {}", signature, -profit);
+                }
+                
+                // A fill this far below the minimum we signed means we were sandwiched or slipped badly
+                if let Some(shortfall_bps) = self.stop_loss_shortfall(opportunity, slippage, profit) {
+                    warn!("Arbitrage {} hit the stop-loss: output {} bps below the minimum", signature, shortfall_bps);
+                    
+                    return Ok(ArbitrageResult {
+                        success: false,
+                        actual_profit: 0,
+                        realized_pnl: profit,
+                        error_message: Some(format!("Stop-loss: output {} bps below the minimum", shortfall_bps)),
+                        transaction_signature: Some(signature),
+                        priority_fee_micro_lamports: priority_fee,
+                        execution_time_ms: execution_time,
+                        opportunity: opportunity.clone(),
+                        simulation: None,
+                    });
+                }
+                
+                Ok(ArbitrageResult {
+                    success: true,
+                    actual_profit: profit.max(0) as u64,
+                    realized_pnl: profit,
+                    error_message: None,
+                    transaction_signature: Some(signature),
+                    priority_fee_micro_lamports: priority_fee,
+                    execution_time_ms: execution_time,
+                    opportunity: opportunity.clone(),
+                    simulation: None,
+                })
+            },
+            Err(e) => Ok(ArbitrageResult {
+                success: false,
+                actual_profit: 0,
+                realized_pnl: 0,
+                error_message: Some(e),
+                transaction_signature: Some(signature),
+                priority_fee_micro_lamports: priority_fee,
+                execution_time_ms: execution_time,
+                opportunity: opportunity.clone(),
+                simulation: None,
+            }),
+        }
+    }
+    
+    /// Shortfall of a landed trade's output below its minimum, in basis points, if it exceeds `stop_loss_bps`
+    fn stop_loss_shortfall(&self, opportunity: &ArbitrageOpportunity, slippage: f64, realized_pnl: i64) -> Option<i64> {
+        let amount_in = opportunity.max_trade_size;
+        let (_, min_quote_out) = opportunity.min_amounts_out(slippage);
+        
+        // The balance change is net of the loan repayment, so add the fee back to get the sell output
+        let flash_loan_fee = if self.config.use_flash_loans {
+            self.flash_loan_manager.calculate_fee(amount_in).unwrap_or(0)
+        } else {
+            0
+        };
+        let realized_output = amount_in as i64 + flash_loan_fee as i64 + realized_pnl;
+        
+        let shortfall_bps = (min_quote_out as i64 - realized_output) * 10_000 / amount_in.max(1) as i64;
+        if shortfall_bps > self.config.stop_loss_bps as i64 {
+            Some(shortfall_bps)
+        } else {
+            None
+        }
+    }
+    
+    /// Poll signature status until the transaction reaches the confirm commitment, fails, or times out
+    fn wait_for_confirmation(&self, signature: &str) -> Result<(), String> {
+        let parsed = Signature::from_str(signature)
+            .map_err(|e| format!("Invalid signature {}: {}", signature, e))?;
+        let commitment = self.config.commitment_policy.confirm;
+        let deadline = Instant::now() + Duration::from_secs(self.config.transaction_timeout_sec);
+        
+        loop {
+            let statuses = self.rpc_client.get_signature_statuses(&[parsed])
+                .map_err(|e| format!("Failed to get signature status: {}", e))?;
+            
+            if let Some(Some(status)) = statuses.value.into_iter().next() {
+                if let Some(err) = status.err {
+                    return Err(format!("Transaction failed on-chain: {}", err));
+                }
+                if status.satisfies_commitment(commitment) {
+                    return Ok(());
+                }
+            }
+            
+            if Instant::now() >= deadline {
+                return Err(format!("Transaction not confirmed within {}s", self.config.transaction_timeout_sec));
+            }
+            
+            std::thread::sleep(Duration::from_millis(500));
+        }
+    }
+    
+    /// Hold a landed trade until it reaches the guard's depth, failing it only if it was dropped
+    ///
+    /// A trade still on-chain when the guard times out keeps its result, flagged as provisional.
+    fn settle(&self, mut result: ArbitrageResult, guard: &FinalityGuardConfig) -> ArbitrageResult {
+        // Paper fills and dry runs have nothing on-chain to settle
+        let signature = match result.transaction_signature.clone() {
+            Some(signature) => signature,
+            None => return result,
+        };
+        
+        match self.wait_for_finality(&signature, guard) {
+            Settlement::Settled => debug!("Trade {} settled", signature),
+            Settlement::Dropped(e) => {
+                warn!("Trade {} was dropped, counting it as failed: {}", signature, e);
+                result.success = false;
+                result.actual_profit = 0;
+                result.realized_pnl = 0;
+                result.error_message = Some(format!("Not settled: {}", e));
+            },
+            Settlement::Unsettled(e) => {
+                warn!("Trade {} is provisional: {}", signature, e);
+                result.error_message = Some(format!("Provisional: {}", e));
+            },
+        }
+        result
+    }
+    
+    /// Poll signature status until the transaction reaches the guard's depth, disappears, or times out
+    ///
+    /// Failed status requests are retried until the deadline, since they say nothing about the trade.
+    fn wait_for_finality(&self, signature: &str, guard: &FinalityGuardConfig) -> Settlement {
+        let parsed = match Signature::from_str(signature) {
+            Ok(parsed) => parsed,
+            Err(e) => return Settlement::Unsettled(format!("Invalid signature {}: {}", signature, e)),
+        };
+        let deadline = Instant::now() + Duration::from_secs(guard.timeout_sec);
+        
+        loop {
+            // Search history too, so only a transaction the cluster no longer has counts as dropped
+            let rpc_error = match self.rpc_client.get_signature_statuses_with_history(&[parsed]) {
+                Ok(statuses) => {
+                    let status = match statuses.value.into_iter().next().flatten() {
+                        Some(status) => status,
+                        None => return Settlement::Dropped("Transaction dropped from the chain".to_string()),
+                    };
+                    if let Some(err) = status.err {
+                        return Settlement::Dropped(format!("Transaction failed on-chain: {}", err));
+                    }
+                    
+                    // Rooted transactions report no confirmation count
+                    let settled = match guard.depth {
+                        FinalityDepth::Finalized => status.satisfies_commitment(CommitmentConfig::finalized()),
+                        FinalityDepth::Confirmations(depth) => status.confirmations.map_or(true, |confirmations| confirmations >= depth),
+                    };
+                    if settled {
+                        return Settlement::Settled;
+                    }
+                    None
+                },
+                Err(e) => {
+                    debug!("Failed to get status of {}, retrying: {}", signature, e);
+                    Some(e)
+                },
+            };
+            
+            if Instant::now() >= deadline {
+                return Settlement::Unsettled(match rpc_error {
+                    Some(e) => format!("Failed to get signature status within {}s: {}", guard.timeout_sec, e),
+                    None => format!("Transaction not settled within {}s", guard.timeout_sec),
+                });
+            }
+            
+            std::thread::sleep(Duration::from_millis(500));
+        }
+    }
+    
+    /// Net change in the wallet's quote token balance caused by a confirmed transaction
+    fn realized_profit(&self, signature: &str, wallet: &Pubkey, quote_token: &Pubkey) -> Result<i64, String> {
+        let parsed = Signature::from_str(signature)
+            .map_err(|e| format!("Invalid signature {}: {}", signature, e))?;
+        
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(self.config.commitment_policy.confirm),
+            max_supported_transaction_version: Some(0),
+        };
+        let transaction = self.rpc_client.get_transaction_with_config(&parsed, config)
+            .map_err(|e| format!("Failed to fetch transaction {}: {}", signature, e))?;
+        
+        let meta = transaction.transaction.meta
+            .ok_or_else(|| format!("Transaction {} has no metadata", signature))?;
+        
+        let pre: Option<Vec<UiTransactionTokenBalance>> = meta.pre_token_balances.into();
+        let post: Option<Vec<UiTransactionTokenBalance>> = meta.post_token_balances.into();
+        
+        let wallet = wallet.to_string();
+        let mint = quote_token.to_string();
+        let total = |balances: Option<Vec<UiTransactionTokenBalance>>| -> i128 {
+            balances.unwrap_or_default().iter()
+                .filter(|balance| balance.mint == mint)
+                .filter(|balance| Option::<String>::from(balance.owner.clone()).as_deref() == Some(wallet.as_str()))
+                .filter_map(|balance| balance.ui_token_amount.amount.parse::<i128>().ok())
+                .sum()
+        };
+        
+        let delta = total(post) - total(pre);
+        i64::try_from(delta)
+            .map_err(|_| format!("Balance change out of range: {}", delta))
+    }
+    
+    /// Build the swap (and flash loan, if enabled) instructions for an opportunity at a given slippage
+    async fn build_arbitrage_instructions(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        wallet: &Pubkey,
+        slippage: f64,
+    ) -> Result<SwapInstructions, String> {
+        let amount_in = opportunity.max_trade_size;
+        let (min_base_out, min_quote_out) = opportunity.min_amounts_out(slippage);
+        if min_base_out == 0 || min_quote_out == 0 {
+            return Err(format!("Trade size {} is too small to set a minimum output", amount_in));
+        }
+        
+        // Buy the base token with the quote token on the cheaper DEX
+        let buy_params = SwapParams {
+            amount_in,
+            min_amount_out: min_base_out,
+            source_token: opportunity.quote_token,
+            destination_token: opportunity.base_token,
+            source_wallet: *wallet,
+            destination_wallet: *wallet,
+            slippage,
+        };
+        
+        // Sell it back on the more expensive DEX
+        let sell_params = SwapParams {
+            amount_in: min_base_out,
+            min_amount_out: min_quote_out,
+            source_token: opportunity.base_token,
+            destination_token: opportunity.quote_token,
+            source_wallet: *wallet,
+            destination_wallet: *wallet,
+            slippage,
+        };
+        
+        let buy_swap = self.dex_manager.create_swap_instructions_for(opportunity.buy_price.dex, &buy_params).await
+            .map_err(|e| format!("Failed to create buy instructions: {}", e))?;
+        let sell_swap = self.dex_manager.create_swap_instructions_for(opportunity.sell_price.dex, &sell_params).await
+            .map_err(|e| format!("Failed to create sell instructions: {}", e))?;
+        
+        // Keep the legs apart so they can be ordered around the loan; merge their lookup tables
+        let buy_instructions = buy_swap.instructions;
+        let sell_instructions = sell_swap.instructions;
+        let mut swaps = SwapInstructions { instructions: Vec::new(), lookup_tables: buy_swap.lookup_tables };
+        swaps.extend(SwapInstructions { instructions: Vec::new(), lookup_tables: sell_swap.lookup_tables });
+        
+        if !self.config.use_flash_loans {
+            swaps.instructions = ArbitrageEngine::assemble_flash_loan_tx(None, buy_instructions, sell_instructions, None)?;
+            return Ok(swaps);
+        }
+        
+        // Borrow from the cheapest provider whose reserve covers the loan
+        let flash_loan_manager = self.flash_loan_provider(&opportunity.quote_token, amount_in)?;
+        
+        // Never take a loan whose fee eats the whole expected profit
+        let flash_loan_fee = flash_loan_manager.calculate_fee(amount_in)
+            .map_err(|e| format!("Failed to calculate flash loan fee: {}", e))?;
+        if opportunity.estimated_profit < flash_loan_fee {
+            return Err(format!(
+                "Estimated profit {} is less than the flash loan fee {}",
+                opportunity.estimated_profit, flash_loan_fee
+            ));
+        }
+        
+        // Wrap the swaps in a borrow and repay of the quote token
+        let flash_loan_program_id = flash_loan_manager.get_provider_program_id()
+            .map_err(|e| format!("Failed to get flash loan program ID: {}", e))?;
+        
+        let borrow_instruction = flash_loan_manager.create_flash_loan_instruction(
+            amount_in,
+            &opportunity.quote_token,
+            wallet,
+            wallet,
+            &flash_loan_program_id,
+        ).map_err(|e| format!("Failed to create flash loan instruction: {}", e))?;
+        
+        // Principal plus fee, and it must be the final instruction
+        let repay_instruction: RepayBuilder<'_> = Box::new(|borrow_instruction_index| {
+            flash_loan_manager.create_repayment_instruction(
+                amount_in,
+                &opportunity.quote_token,
+                wallet,
+                &flash_loan_program_id,
+                borrow_instruction_index,
+            ).map_err(|e| format!("Failed to create repayment instruction: {}", e))
+        });
+        
+        swaps.instructions = ArbitrageEngine::assemble_flash_loan_tx(
+            Some(borrow_instruction),
+            buy_instructions,
+            sell_instructions,
+            Some(repay_instruction),
+        )?;
         
-        Ok(ArbitrageResult {
-            success: true,
-            actual_profit: opportunity.estimated_profit, // Placeholder
-            error_message: None,
-            transaction_signature: Some(signature),
-            execution_time_ms: execution_time,
-            opportunity: opportunity.clone(),
+        Ok(swaps)
+    }
+    
+    /// Cheapest flash loan provider able to lend `amount` of `token_mint`
+    fn flash_loan_provider(&self, token_mint: &Pubkey, amount: u64) -> Result<ThreadSafeFlashLoanManager, String> {
+        let mut candidates = vec![self.flash_loan_manager.clone()];
+        candidates.extend(lock_or_recover(&self.flash_loan_providers).iter().cloned());
+        
+        ThreadSafeFlashLoanManager::select_cheapest(&candidates, token_mint, amount)
+            .ok_or_else(|| format!("No flash loan provider can lend {} of {}", amount, token_mint))
+    }
+    
+    /// Build an opportunity at the configured slippage and simulate it for the next trading wallet
+    async fn simulate_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<SimulationReport, String> {
+        let wallet = self.wallet_manager.get_next_trading_wallet()
+            .map_err(|e| format!("Failed to get trading wallet: {}", e))?;
+        
+        let slippage = self.config.slippage_tolerance.min(self.config.risk_management.max_slippage);
+        let swaps = self.build_arbitrage_instructions(opportunity, &wallet, slippage).await?;
+        
+        self.simulate_report(&swaps, &wallet, opportunity)
+    }
+    
+    /// Simulate instructions and report the outcome along with the wallet's post-balances
+    fn simulate_report(
+        &self,
+        swaps: &SwapInstructions,
+        wallet: &Pubkey,
+        opportunity: &ArbitrageOpportunity,
+    ) -> Result<SimulationReport, String> {
+        let addresses = vec![
+            *wallet,
+            get_associated_token_address(wallet, &opportunity.base_token),
+            get_associated_token_address(wallet, &opportunity.quote_token),
+        ];
+        let accounts = RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: addresses.iter().map(|address| address.to_string()).collect(),
+        };
+        
+        let result = self.simulate(swaps, wallet, Some(accounts))?;
+        
+        let post_accounts = result.accounts.unwrap_or_default();
+        let post_balances = addresses.iter().enumerate()
+            .map(|(i, address)| {
+                let account = post_accounts.get(i)
+                    .and_then(|account| account.as_ref())
+                    .and_then(|account| account.decode::<Account>());
+                
+                SimulatedBalance {
+                    address: *address,
+                    lamports: account.as_ref().map(|account| account.lamports),
+                    token_amount: account.as_ref()
+                        .filter(|account| account.owner == spl_token::id())
+                        .and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+                        .map(|token_account| token_account.amount),
+                }
+            })
+            .collect();
+        
+        Ok(SimulationReport {
+            success: result.err.is_none(),
+            error: result.err.map(|err| err.to_string()),
+            units_consumed: result.units_consumed,
+            logs: result.logs.unwrap_or_default(),
+            post_balances,
         })
     }
     
+    /// Simulate instructions as a transaction paid by `payer`, returning the failure on error
+    fn simulate_instructions(&self, swaps: &SwapInstructions, payer: &Pubkey) -> Result<(), String> {
+        let result = self.simulate(swaps, payer, None)?;
+        
+        match result.err {
+            None => Ok(()),
+            Some(err) => {
+                // Include the program logs so slippage errors can be recognized
+                let logs = result.logs.unwrap_or_default().join("\n");
+                Err(format!("{}\n{}", err, logs))
+            },
+        }
+    }
+    
+    /// Simulate instructions as a transaction paid by `payer`, optionally returning account states
+    fn simulate(
+        &self,
+        swaps: &SwapInstructions,
+        payer: &Pubkey,
+        accounts: Option<RpcSimulateTransactionAccountsConfig>,
+    ) -> Result<RpcSimulateTransactionResult, String> {
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(CommitmentConfig::processed()),
+            accounts,
+            ..RpcSimulateTransactionConfig::default()
+        };
+        
+        // Simulate what will be sent, so instruction positions match those the flash loan checks
+        let instructions = self.wallet_manager.with_compute_budget(swaps.instructions.clone())
+            .map_err(|e| format!("Failed to add compute budget: {}", e))?;
+        
+        let response = if swaps.lookup_tables.is_empty() {
+            let transaction = Transaction::new_with_payer(&instructions, Some(payer));
+            self.rpc_client.simulate_transaction_with_config(&transaction, config)
+        } else {
+            // Signatures aren't verified, so placeholders are fine
+            let message = v0::Message::try_compile(payer, &instructions, &swaps.lookup_tables, Hash::default())
+                .map_err(|e| format!("Failed to compile v0 message: {}", e))?;
+            let transaction = VersionedTransaction {
+                signatures: vec![Signature::default(); message.header.num_required_signatures as usize],
+                message: VersionedMessage::V0(message),
+            };
+            self.rpc_client.simulate_transaction_with_config(&transaction, config)
+        }.map_err(|e| format!("Simulation request failed: {}", e))?;
+        
+        Ok(response.value)
+    }
+    
     /// Create flash loan arbitrage instructions
     async fn create_flash_loan_arbitrage_instructions(
         &self,
@@ -0,0 +1,121 @@
+// Per-Pair Trading State for Solana Flash Loan Arbitrage Bot
+// Disables a token pair for a cooldown after repeated losses, or on request
+
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use log::{info, warn};
+
+use crate::lock_or_recover;
+
+/// Trading state of one token pair
+#[derive(Debug, Clone, Copy)]
+pub struct PairState {
+    /// Whether the pair may be traded at all (manual switch)
+    pub enabled: bool,
+    /// Failed or losing trades in a row
+    pub consecutive_failures: u32,
+    /// When the pair may be traded again after a losing streak
+    pub cooldown_until: Option<Instant>,
+}
+
+impl Default for PairState {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            consecutive_failures: 0,
+            cooldown_until: None,
+        }
+    }
+}
+
+/// Tracks which token pairs may be traded
+///
+/// A pair that fails `failure_threshold` trades in a row is put in cooldown and
+/// skipped until the cooldown passes. Pairs disabled by hand stay disabled until
+/// enabled again.
+pub struct PairStateTracker {
+    /// (base, quote) -> state
+    pairs: Mutex<HashMap<(Pubkey, Pubkey), PairState>>,
+    /// Failures in a row that start a cooldown (0 disables cooldowns)
+    failure_threshold: u32,
+    /// How long a cooldown lasts
+    cooldown: Duration,
+}
+
+impl PairStateTracker {
+    /// Create a new tracker
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            pairs: Mutex::new(HashMap::new()),
+            failure_threshold,
+            cooldown,
+        }
+    }
+    
+    /// Whether a pair may be traded now, ending its cooldown if it has passed
+    pub fn is_tradable(&self, base_token: &Pubkey, quote_token: &Pubkey) -> bool {
+        let mut pairs = lock_or_recover(&self.pairs);
+        
+        let state = match pairs.get_mut(&(*base_token, *quote_token)) {
+            Some(state) => state,
+            None => return true,
+        };
+        
+        if let Some(until) = state.cooldown_until {
+            if Instant::now() < until {
+                return false;
+            }
+            info!("Cooldown for {}/{} over, re-enabling", base_token, quote_token);
+            state.cooldown_until = None;
+            state.consecutive_failures = 0;
+        }
+        
+        state.enabled
+    }
+    
+    /// Record a trade result, starting a cooldown after too many failures in a row
+    pub fn record_result(&self, base_token: &Pubkey, quote_token: &Pubkey, success: bool) {
+        let mut pairs = lock_or_recover(&self.pairs);
+        
+        let state = pairs.entry((*base_token, *quote_token)).or_default();
+        if success {
+            state.consecutive_failures = 0;
+            return;
+        }
+        
+        state.consecutive_failures += 1;
+        if self.failure_threshold > 0
+            && state.consecutive_failures >= self.failure_threshold
+            && state.cooldown_until.is_none()
+        {
+            warn!("{}/{} failed {} trades in a row, pausing it for {}s",
+                  base_token, quote_token, state.consecutive_failures, self.cooldown.as_secs());
+            state.cooldown_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+    
+    /// Enable or disable a pair by hand; enabling also ends any cooldown
+    pub fn set_enabled(&self, base_token: &Pubkey, quote_token: &Pubkey, enabled: bool) -> Result<(), String> {
+        let mut pairs = lock_or_recover(&self.pairs);
+        
+        let state = pairs.entry((*base_token, *quote_token)).or_default();
+        state.enabled = enabled;
+        if enabled {
+            state.consecutive_failures = 0;
+            state.cooldown_until = None;
+        }
+        
+        info!("{} {}/{}", if enabled { "Enabled" } else { "Disabled" }, base_token, quote_token);
+        Ok(())
+    }
+    
+    /// Current state of a pair
+    pub fn get(&self, base_token: &Pubkey, quote_token: &Pubkey) -> PairState {
+        lock_or_recover(&self.pairs)
+            .get(&(*base_token, *quote_token))
+            .copied()
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,116 @@
+// Adaptive Minimum Profit for Solana Flash Loan Arbitrage Bot
+// Raises the minimum spread while fills disappoint and lowers it while they come in clean
+
+use std::collections::VecDeque;
+use log::info;
+
+/// Trades recorded before the minimum starts moving
+const MIN_SAMPLES: usize = 5;
+
+/// Adaptive minimum profit configuration
+#[derive(Debug, Clone, Copy)]
+pub struct ProfitTunerConfig {
+    /// Lowest the minimum profit percentage may go
+    pub floor_percentage: f64,
+    /// Highest the minimum profit percentage may go
+    pub ceiling_percentage: f64,
+    /// Recent trades fill quality is judged over
+    pub window: usize,
+    /// Share of the estimated profit a trade must realize on average for fills to count as clean
+    pub clean_fill_ratio: f64,
+    /// How far the minimum moves per adjustment, in percentage points
+    pub step_percentage: f64,
+}
+
+impl ProfitTunerConfig {
+    /// Create default configuration
+    pub fn default() -> Self {
+        Self {
+            floor_percentage: 0.2, // 0.2%
+            ceiling_percentage: 2.0, // 2%
+            window: 20,
+            clean_fill_ratio: 0.8, // 80% of the estimate
+            step_percentage: 0.05, // 0.05% per trade
+        }
+    }
+    
+    /// Validate the configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if self.floor_percentage < 0.0 || self.floor_percentage > self.ceiling_percentage {
+            return Err(format!("Profit tuner floor {}% must be between 0 and the ceiling {}%",
+                               self.floor_percentage, self.ceiling_percentage));
+        }
+        if self.window < MIN_SAMPLES {
+            return Err(format!("Profit tuner window must hold at least {} trades", MIN_SAMPLES));
+        }
+        Ok(())
+    }
+}
+
+/// Tunes the minimum profit percentage from how recent trades realized their estimates
+///
+/// Failed trades realize nothing. While the window's average realized share of the
+/// estimate is below `clean_fill_ratio` the minimum rises a step per trade; while
+/// every trade in the window fills and the average clears it, the minimum falls.
+pub struct MinProfitTuner {
+    /// Bounds and step sizes
+    config: ProfitTunerConfig,
+    /// Realized share of the estimated profit of recent trades, oldest first
+    fill_ratios: VecDeque<f64>,
+    /// Whether each recent trade failed, oldest first
+    failures: VecDeque<bool>,
+    /// Effective minimum profit percentage
+    current: f64,
+}
+
+impl MinProfitTuner {
+    /// Create a tuner starting from the configured static minimum
+    pub fn new(config: ProfitTunerConfig, initial_percentage: f64) -> Self {
+        Self {
+            config,
+            fill_ratios: VecDeque::with_capacity(config.window),
+            failures: VecDeque::with_capacity(config.window),
+            current: initial_percentage.clamp(config.floor_percentage, config.ceiling_percentage),
+        }
+    }
+    
+    /// Effective minimum profit percentage
+    pub fn current(&self) -> f64 {
+        self.current
+    }
+    
+    /// Record a finished trade's estimated (net) and realized profit, and adjust the minimum
+    pub fn record(&mut self, success: bool, estimated_profit: i64, realized_pnl: i64) {
+        let ratio = if success && estimated_profit > 0 {
+            realized_pnl as f64 / estimated_profit as f64
+        } else {
+            0.0
+        };
+        
+        self.fill_ratios.push_back(ratio);
+        self.failures.push_back(!success);
+        while self.fill_ratios.len() > self.config.window {
+            self.fill_ratios.pop_front();
+            self.failures.pop_front();
+        }
+        
+        if self.fill_ratios.len() < MIN_SAMPLES {
+            return;
+        }
+        
+        let average = self.fill_ratios.iter().sum::<f64>() / self.fill_ratios.len() as f64;
+        let previous = self.current;
+        
+        if average < self.config.clean_fill_ratio {
+            self.current += self.config.step_percentage;
+        } else if !self.failures.contains(&true) {
+            self.current -= self.config.step_percentage;
+        }
+        self.current = self.current.clamp(self.config.floor_percentage, self.config.ceiling_percentage);
+        
+        if self.current != previous {
+            info!("Minimum profit {:.3}% -> {:.3}% (fills realizing {:.0}% of estimates)",
+                  previous, self.current, average * 100.0);
+        }
+    }
+}
@@ -0,0 +1,993 @@
+// Tests for the arbitrage module
+// Exercise detection and sizing without RPC or network access
+
+use super::*;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use solana_client::client_error::{ClientErrorKind, Result as ClientResult};
+use solana_client::rpc_client::RpcClientConfig;
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use std::collections::VecDeque;
+
+/// USDC mint, the quote token of the default pair
+fn usdc() -> Pubkey {
+    Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap()
+}
+
+/// SOL mint, the base token of the default pair
+fn sol() -> Pubkey {
+    Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap()
+}
+
+/// SOL/USDC quote on `dex` with `liquidity` SOL atoms
+fn quote(dex: DexType, price: f64, liquidity: u64) -> PriceInfo {
+    PriceInfo {
+        base_token: sol(),
+        quote_token: usdc(),
+        price,
+        liquidity,
+        dex,
+        timestamp: 0,
+        base_decimals: 9,
+        quote_decimals: 6,
+        pool: None,
+        fee_bps: None,
+    }
+}
+
+/// 1,000,000 SOL: deep enough that no default limit binds
+const DEEP: u64 = 1_000_000 * 1_000_000_000;
+
+#[test]
+fn zero_liquidity_quote_is_never_an_opportunity() {
+    let config = ArbitrageConfig::default();
+    
+    assert!(ArbitrageOpportunity::from_prices(&config, quote(DexType::Raydium, 100.0, 0), quote(DexType::Orca, 101.0, DEEP), 1.0).is_none());
+    assert!(ArbitrageOpportunity::from_prices(&config, quote(DexType::Raydium, 100.0, DEEP), quote(DexType::Orca, 101.0, 0), 1.0).is_none());
+}
+
+#[test]
+fn zero_price_quote_is_never_an_opportunity() {
+    let config = ArbitrageConfig::default();
+    
+    assert!(ArbitrageOpportunity::from_prices(&config, quote(DexType::Raydium, 0.0, DEEP), quote(DexType::Orca, 101.0, DEEP), 1.0).is_none());
+    assert!(ArbitrageOpportunity::from_prices(&config, quote(DexType::Raydium, 100.0, DEEP), quote(DexType::Orca, 0.0, DEEP), 1.0).is_none());
+    assert!(ArbitrageOpportunity::from_prices(&config, quote(DexType::Raydium, -1.0, DEEP), quote(DexType::Orca, 101.0, DEEP), 1.0).is_none());
+}
+
+#[test]
+fn thin_pool_is_rejected_and_deep_pool_accepted() {
+    let config = ArbitrageConfig::default();
+    
+    // 50 SOL at $100 is $5,000 of liquidity, under the $10,000 minimum, on either leg
+    let thin = 50 * 1_000_000_000;
+    assert!(ArbitrageOpportunity::from_prices(&config, quote(DexType::Raydium, 100.0, thin), quote(DexType::Orca, 101.0, DEEP), 1.0).is_none());
+    assert!(ArbitrageOpportunity::from_prices(&config, quote(DexType::Raydium, 100.0, DEEP), quote(DexType::Orca, 101.0, thin), 1.0).is_none());
+    
+    // The same spread over deep pools trades at the full position size
+    let opportunity = ArbitrageOpportunity::from_prices(&config, quote(DexType::Raydium, 100.0, DEEP), quote(DexType::Orca, 101.0, DEEP), 1.0)
+        .expect("deep pools are tradable");
+    assert_eq!(opportunity.max_trade_size, config.max_position_size);
+    
+    // Lowering the minimum lets the thin pool through, sized to the impact cap
+    let mut config = ArbitrageConfig::default();
+    config.min_liquidity = 1_000 * 1_000_000;
+    let opportunity = ArbitrageOpportunity::from_prices(&config, quote(DexType::Raydium, 100.0, thin), quote(DexType::Orca, 101.0, DEEP), 1.0)
+        .expect("above the lowered minimum");
+    let thin_liquidity = quote(DexType::Raydium, 100.0, thin).liquidity_in_quote();
+    assert_eq!(opportunity.max_trade_size, (thin_liquidity as f64 * config.price_impact_cap) as u64);
+    assert!(opportunity.max_trade_size < config.max_position_size);
+}
+
+#[test]
+fn oversized_trade_is_clamped_to_the_impact_cap() {
+    let config = ArbitrageConfig::default();
+    
+    // 200 SOL at $100 is $20,000 of liquidity, above the $10,000 minimum
+    let pool_sol = 200 * 1_000_000_000;
+    let opportunity = ArbitrageOpportunity::from_prices(
+        &config,
+        quote(DexType::Raydium, 100.0, pool_sol),
+        quote(DexType::Orca, 101.0, DEEP),
+        1.0,
+    ).expect("clamped, not rejected");
+    
+    // 10% of $20,000 is $2,000, above the $1,000 position cap, so the cap binds
+    assert_eq!(opportunity.max_trade_size, config.max_position_size);
+    
+    let mut config = ArbitrageConfig::default();
+    config.max_position_size = 100_000_000_000; // $100,000
+    let opportunity = ArbitrageOpportunity::from_prices(
+        &config,
+        quote(DexType::Raydium, 100.0, pool_sol),
+        quote(DexType::Orca, 101.0, DEEP),
+        1.0,
+    ).expect("clamped, not rejected");
+    
+    // Now the impact cap binds: 10% of $20,000
+    assert_eq!(opportunity.max_trade_size, 2_000_000_000);
+    assert_eq!(opportunity.estimated_profit, 20_000_000);
+}
+
+/// Config allowing two slippage retries from 0.5% up to a 1.0% risk limit
+fn retrying_config() -> ArbitrageConfig {
+    let mut config = ArbitrageConfig::default();
+    config.slippage_tolerance = 0.5;
+    config.slippage_retry_step = 0.25;
+    config.max_slippage_retries = 2;
+    config.risk_management.max_slippage = 1.0;
+    config
+}
+
+#[test]
+fn slippage_failure_retries_at_a_looser_tolerance() {
+    let config = retrying_config();
+    let mut tried = Vec::new();
+    
+    let result = Runtime::new().unwrap().block_on(retry_on_slippage(&config, |slippage| {
+        tried.push(slippage);
+        let outcome = if tried.len() == 1 {
+            Err("Error processing Instruction 2: custom program error: 0x1771".to_string())
+        } else {
+            Ok(slippage)
+        };
+        async move { Ok(outcome) }
+    }));
+    
+    assert_eq!(result, Ok(Ok((0.75, 0.75))));
+    assert_eq!(tried, vec![0.5, 0.75]);
+}
+
+#[test]
+fn slippage_retries_stop_at_the_risk_limit() {
+    let mut config = retrying_config();
+    config.max_slippage_retries = 10;
+    let mut tried = Vec::new();
+    
+    let result: Result<Result<((), f64), String>, String> = Runtime::new().unwrap().block_on(retry_on_slippage(&config, |slippage| {
+        tried.push(slippage);
+        async { Ok(Err("SlippageToleranceExceeded".to_string())) }
+    }));
+    
+    assert_eq!(result, Ok(Err("SlippageToleranceExceeded".to_string())));
+    assert_eq!(tried, vec![0.5, 0.75, 1.0]);
+}
+
+#[test]
+fn other_simulation_failures_are_not_retried() {
+    let config = retrying_config();
+    let mut attempts = 0;
+    
+    let result: Result<Result<((), f64), String>, String> = Runtime::new().unwrap().block_on(retry_on_slippage(&config, |_| {
+        attempts += 1;
+        async { Ok(Err("insufficient funds".to_string())) }
+    }));
+    
+    assert_eq!(result, Ok(Err("insufficient funds".to_string())));
+    assert_eq!(attempts, 1);
+}
+
+/// SOL/USDC quote on `dex` seen at feed time `timestamp`
+fn feed_quote(dex: DexType, price: f64, timestamp: u64) -> PriceInfo {
+    PriceInfo {
+        timestamp,
+        ..quote(dex, price, DEEP)
+    }
+}
+
+#[test]
+fn scripted_backtest_counts_opportunities_and_pnl() {
+    let mut config = ArbitrageConfig::default();
+    config.risk_management.halt_on_extreme_volatility = false;
+    
+    // Fixed $250 trades: no adaptive sizing and no growth or shrinkage after a trade
+    let mut scaling = PositionScalingConfig::new(RiskLevel::Moderate);
+    scaling.use_adaptive_sizing = false;
+    scaling.use_profit_based_scaling = false;
+    scaling.growth_factor = 1.0;
+    scaling.reduction_factor = 1.0;
+    let size = scaling.base_position_size;
+    
+    let fill = backtest::FillModel::new(30, 0);
+    let feed = vec![
+        feed_quote(DexType::Raydium, 100.0, 1),
+        // 1% spread: detected, filled once the next quote arrives
+        feed_quote(DexType::Orca, 101.0, 1),
+        // Orca falls back before the fill, leaving a 0.2% spread below the minimum
+        feed_quote(DexType::Orca, 100.2, 2),
+        // Raydium drops: a 1.2% spread, filled at the final quotes
+        feed_quote(DexType::Raydium, 99.0, 3),
+    ];
+    
+    let summary = backtest::Backtester::new(config, scaling, fill).run(&feed);
+    
+    assert_eq!(summary.quotes_processed, 4);
+    assert_eq!(summary.opportunities_found, 2);
+    assert_eq!(summary.simulated_trades, 2);
+    
+    // The first trade fills at 100.0 -> 100.2, which doesn't cover the 0.3% fee
+    let first = fill.profit(size, 100.0, 100.2);
+    let second = fill.profit(size, 99.0, 100.2);
+    assert!(first < 0);
+    assert!(second > 0);
+    assert_eq!(summary.profitable_trades, 1);
+    assert_eq!(summary.net_pnl, first + second);
+    assert_eq!(summary.max_drawdown, -first);
+}
+
+/// Unreachable RPC endpoint: requests fail at once instead of hanging
+const UNREACHABLE_RPC: &str = "http://127.0.0.1:1";
+
+/// Engine over unreachable endpoints and a throwaway wallet directory
+fn test_engine(name: &str, config: ArbitrageConfig) -> ArbitrageEngine {
+    test_engine_with_dexes(name, config, crate::dex::DexManager::new(UNREACHABLE_RPC))
+}
+
+/// Engine like `test_engine`, quoting through the given DEX connectors
+fn test_engine_with_dexes(name: &str, config: ArbitrageConfig, dex_manager: crate::dex::DexManager) -> ArbitrageEngine {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let storage = std::env::temp_dir().join(format!("sab-engine-{}-{}-{}", name, std::process::id(), nanos));
+    
+    ArbitrageEngine::new(
+        UNREACHABLE_RPC,
+        dex_manager.into(),
+        ThreadSafeFlashLoanManager::new(UNREACHABLE_RPC, FlashLoanConfig::new_solend(config.max_position_size)),
+        ThreadSafeWalletManager::with_rpc_client(RpcClient::new_mock("succeeds".to_string()), &storage.to_string_lossy()),
+        ThreadSafeProfitManager::new(crate::profit_management::ProfitDistributionConfig::default(Pubkey::new_unique())),
+        config,
+    ).expect("engine over unreachable endpoints")
+}
+
+#[test]
+fn stop_returns_within_the_update_interval() {
+    let mut config = ArbitrageConfig::default();
+    config.token_pairs = Vec::new();
+    config.update_interval_ms = 5_000;
+    let mut engine = test_engine("stop", config);
+    
+    engine.start().unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    
+    let started = Instant::now();
+    engine.stop().unwrap();
+    
+    // The monitor thread notices the flag between passes, well before the next scheduled update
+    assert!(started.elapsed() < Duration::from_millis(500), "stop took {:?}", started.elapsed());
+    assert!(engine.stop().is_err());
+}
+
+/// Engine over empty pairs with a trade marked in flight until `trade_time` passes
+fn engine_with_trade_in_flight(name: &str, shutdown_timeout_sec: u64, trade_time: Duration) -> (ArbitrageEngine, Arc<AtomicBool>) {
+    let mut config = ArbitrageConfig::default();
+    config.token_pairs = Vec::new();
+    config.update_interval_ms = 50;
+    config.shutdown_timeout_sec = shutdown_timeout_sec;
+    let mut engine = test_engine(name, config);
+    engine.start().unwrap();
+    
+    // Counted the way the monitor loop counts a trade it hands to a backend
+    let state = engine.state.clone();
+    let finished = Arc::new(AtomicBool::new(false));
+    let trade_finished = finished.clone();
+    state.active_operations.fetch_add(1, Ordering::SeqCst);
+    std::thread::spawn(move || {
+        std::thread::sleep(trade_time);
+        trade_finished.store(true, Ordering::SeqCst);
+        state.active_operations.fetch_sub(1, Ordering::SeqCst);
+    });
+    
+    (engine, finished)
+}
+
+#[test]
+fn lock_poisoned_by_a_panicked_trade_is_recovered() {
+    let mut engine = test_engine("poisoned", ArbitrageConfig::default());
+    let (base, quote) = (sol(), usdc());
+    let size = engine.position_size(&base, &quote).unwrap();
+    
+    // A trade task panics while updating position sizes
+    let position_scaling = engine.position_scaling.clone();
+    let panicked = std::thread::spawn(move || {
+        let _scaling = position_scaling.lock().unwrap();
+        panic!("trade task panicked holding the lock");
+    }).join();
+    assert!(panicked.is_err());
+    assert!(engine.position_scaling.is_poisoned());
+    
+    // Later calls still get through, and the poison is cleared rather than hit every time
+    assert_eq!(engine.position_size(&base, &quote).unwrap(), size);
+    assert!(!engine.position_scaling.is_poisoned());
+    engine.set_position_scaling(PositionScalingConfig::new(RiskLevel::Moderate)).unwrap();
+}
+
+#[test]
+fn stop_waits_for_an_in_flight_trade_to_finish() {
+    let (mut engine, finished) = engine_with_trade_in_flight("drain", 10, Duration::from_millis(500));
+    
+    engine.stop().unwrap();
+    
+    // Stop only returned once the trade had seen its result through
+    assert!(finished.load(Ordering::SeqCst));
+    assert_eq!(engine.state.active_operations.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn stop_gives_up_on_a_trade_past_the_shutdown_timeout() {
+    let (mut engine, finished) = engine_with_trade_in_flight("drain-timeout", 0, Duration::from_secs(5));
+    
+    let started = Instant::now();
+    engine.stop().unwrap();
+    
+    assert!(started.elapsed() < Duration::from_secs(1), "stop took {:?}", started.elapsed());
+    assert!(!finished.load(Ordering::SeqCst));
+    assert_eq!(engine.state.active_operations.load(Ordering::SeqCst), 1);
+}
+
+/// Instruction to a fresh program, told apart by its one data byte
+fn tagged(tag: u8) -> Instruction {
+    Instruction::new_with_bytes(Pubkey::new_unique(), &[tag], Vec::new())
+}
+
+#[test]
+fn flash_loan_tx_is_borrow_buy_sell_repay() {
+    let repay: RepayBuilder = Box::new(|borrow_index| Ok(Instruction::new_with_bytes(Pubkey::new_unique(), &[4, borrow_index], Vec::new())));
+    
+    let instructions = ArbitrageEngine::assemble_flash_loan_tx(
+        Some(tagged(0)),
+        vec![tagged(1), tagged(2)],
+        vec![tagged(3)],
+        Some(repay),
+    ).unwrap();
+    
+    let tags: Vec<u8> = instructions.iter().map(|instruction| instruction.data[0]).collect();
+    assert_eq!(tags, vec![0, 1, 2, 3, 4]);
+    
+    // The repay was built from where the borrow lands once the compute budget is prepended
+    assert_eq!(instructions[4].data[1], COMPUTE_BUDGET_INSTRUCTIONS as u8);
+    
+    let storage = std::env::temp_dir().join(format!("sab-engine-budget-{}", std::process::id()));
+    let wallet_manager = ThreadSafeWalletManager::with_rpc_client(RpcClient::new_mock("succeeds".to_string()), &storage.to_string_lossy());
+    let sent = wallet_manager.with_compute_budget(instructions).unwrap();
+    let borrow_index = sent.last().unwrap().data[1] as usize;
+    assert_eq!(sent[borrow_index].data[0], 0);
+}
+
+#[test]
+fn flash_loan_tx_rejects_unpaired_loans() {
+    let missing_repay = ArbitrageEngine::assemble_flash_loan_tx(Some(tagged(0)), vec![tagged(1)], vec![tagged(2)], None);
+    assert_eq!(missing_repay, Err("Flash loan borrow has no matching repayment".to_string()));
+    
+    let repay: RepayBuilder = Box::new(|_| Ok(tagged(3)));
+    let missing_borrow = ArbitrageEngine::assemble_flash_loan_tx(None, vec![tagged(1)], vec![tagged(2)], Some(repay));
+    assert_eq!(missing_borrow, Err("Flash loan repayment has no matching borrow".to_string()));
+    
+    // Without a loan the swaps are sent on their own
+    let unleveraged = ArbitrageEngine::assemble_flash_loan_tx(None, vec![tagged(1)], vec![tagged(2)], None).unwrap();
+    assert_eq!(unleveraged.len(), 2);
+}
+
+/// Result of trading `opportunity` with the given outcome
+fn trade_result(opportunity: &ArbitrageOpportunity, success: bool, realized_pnl: i64) -> ArbitrageResult {
+    ArbitrageResult {
+        success,
+        actual_profit: realized_pnl.max(0) as u64,
+        realized_pnl,
+        error_message: None,
+        transaction_signature: None,
+        priority_fee_micro_lamports: 0,
+        execution_time_ms: 0,
+        opportunity: opportunity.clone(),
+        simulation: None,
+    }
+}
+
+#[test]
+fn position_size_grows_on_wins_and_shrinks_on_losses() {
+    let config = ArbitrageConfig::default();
+    let mut scaling_config = PositionScalingConfig::new(RiskLevel::Moderate);
+    scaling_config.use_profit_based_scaling = false;
+    let (base, growth, reduction, daily_cap) = (
+        scaling_config.base_position_size,
+        scaling_config.growth_factor,
+        scaling_config.reduction_factor,
+        (scaling_config.base_position_size as f64 * scaling_config.max_daily_growth) as u64,
+    );
+    let position_scaling = Mutex::new(config.position_scaling(scaling_config));
+    let size = || position_scaling.lock().unwrap().get_position_size(&sol(), &usdc());
+    
+    let opportunity = ArbitrageOpportunity::from_prices(
+        &config,
+        quote(DexType::Raydium, 100.0, DEEP),
+        quote(DexType::Orca, 101.0, DEEP),
+        1.0,
+    ).unwrap();
+    assert_eq!(size(), base);
+    
+    // Win, win, then a failed trade, then one that landed at a loss
+    let mut expected = base;
+    for (success, pnl, factor) in [(true, 1_000, growth), (true, 1_000, growth), (false, 0, reduction), (true, -500, reduction)] {
+        record_position_result(&position_scaling, &trade_result(&opportunity, success, pnl));
+        expected = (expected as f64 * factor) as u64;
+        assert_eq!(size(), expected);
+    }
+    
+    // A winning streak stops at the daily growth limit
+    for _ in 0..20 {
+        record_position_result(&position_scaling, &trade_result(&opportunity, true, 1_000));
+    }
+    assert_eq!(size(), daily_cap);
+}
+
+#[test]
+fn same_opportunity_twice_within_the_ttl_executes_once() {
+    let config = ArbitrageConfig::default();
+    let ttl = Duration::from_millis(200);
+    let in_flight = Arc::new(InFlightGuard::new(ttl, config.dedup_price_precision_bps));
+    let opportunity = ArbitrageOpportunity::from_prices(
+        &config,
+        quote(DexType::Raydium, 100.0, DEEP),
+        quote(DexType::Orca, 101.0, DEEP),
+        1.0,
+    ).unwrap();
+    
+    // Two scans see the same spread before the first trade reports back
+    let mut executing = Vec::new();
+    for seen in [&opportunity, &opportunity] {
+        if let Some(ticket) = in_flight.try_claim(seen) {
+            executing.push(ticket);
+        }
+    }
+    assert_eq!(executing.len(), 1);
+    assert_eq!(in_flight.len(), 1);
+    
+    // Once the result is back the spread can be traded again
+    drop(executing);
+    assert!(in_flight.is_empty());
+    let stuck = in_flight.try_claim(&opportunity).expect("released claim");
+    
+    // A trade that never reports back only blocks the spread until the TTL
+    assert!(in_flight.try_claim(&opportunity).is_none());
+    std::thread::sleep(ttl);
+    let retried = in_flight.try_claim(&opportunity).expect("expired claim");
+    
+    // The stale ticket doesn't release the newer claim
+    drop(stuck);
+    assert_eq!(in_flight.len(), 1);
+    drop(retried);
+    assert!(in_flight.is_empty());
+}
+
+/// Whether `actual` is within one atom of `expected`, allowing for float rounding
+fn within_an_atom(actual: u64, expected: u64) -> bool {
+    actual.abs_diff(expected) <= 1
+}
+
+#[test]
+fn sol_usdc_quotes_are_sized_across_their_decimals() {
+    let mut config = ArbitrageConfig::default();
+    config.max_position_size = 100_000 * 1_000_000;
+    
+    // 200 SOL (9 decimals) at $100 is $20,000 of USDC (6 decimals)
+    let pool_sol = 200 * 1_000_000_000;
+    let buy = quote(DexType::Raydium, 100.0, pool_sol);
+    assert!(within_an_atom(buy.liquidity_in_quote(), 20_000 * 1_000_000));
+    
+    let opportunity = ArbitrageOpportunity::from_prices(&config, buy, quote(DexType::Orca, 101.0, pool_sol), 1.0).unwrap();
+    assert!(within_an_atom(opportunity.max_trade_size, 2_000 * 1_000_000));
+    
+    // $2,000 buys 20 SOL, which sells for $2,020
+    let (min_base_out, min_quote_out) = opportunity.min_amounts_out(0.0);
+    assert!(within_an_atom(min_base_out, 20 * 1_000_000_000));
+    assert!(within_an_atom(min_quote_out, 2_020 * 1_000_000));
+}
+
+#[test]
+fn equal_decimal_quotes_are_sized_in_the_same_atoms() {
+    let mut config = ArbitrageConfig::default();
+    config.max_position_size = 100_000 * 1_000_000;
+    let usdt = Pubkey::new_unique();
+    let stable = |dex, price| PriceInfo {
+        base_token: usdt,
+        base_decimals: 6,
+        ..quote(dex, price, 20_000 * 1_000_000)
+    };
+    
+    // With matching decimals atoms convert at the quoted price itself
+    assert_eq!(stable(DexType::Raydium, 1.0).raw_price(), 1.0);
+    assert_eq!(stable(DexType::Raydium, 1.0).liquidity_in_quote(), 20_000 * 1_000_000);
+    
+    let opportunity = ArbitrageOpportunity::from_prices(&config, stable(DexType::Raydium, 1.0), stable(DexType::Orca, 1.01), 1.0).unwrap();
+    assert_eq!(opportunity.max_trade_size, 2_000 * 1_000_000);
+    
+    let (min_base_out, min_quote_out) = opportunity.min_amounts_out(0.0);
+    assert_eq!(min_base_out, 2_000 * 1_000_000);
+    assert!(within_an_atom(min_quote_out, 2_020 * 1_000_000));
+}
+
+#[test]
+fn quotes_disagreeing_on_decimals_are_not_compared() {
+    let config = ArbitrageConfig::default();
+    let misreported = PriceInfo {
+        quote_decimals: 9,
+        ..quote(DexType::Orca, 101.0, DEEP)
+    };
+    
+    assert!(ArbitrageOpportunity::from_prices(&config, quote(DexType::Raydium, 100.0, DEEP), misreported, 1.0).is_none());
+}
+
+#[test]
+fn pair_is_re_enabled_once_its_cooldown_passes() {
+    let cooldown = Duration::from_millis(100);
+    let pairs = PairStateTracker::new(2, cooldown);
+    let (base, quote) = (sol(), usdc());
+    
+    // One failure short of the threshold the pair still trades, and a success resets the streak
+    pairs.record_result(&base, &quote, false);
+    assert!(pairs.is_tradable(&base, &quote));
+    pairs.record_result(&base, &quote, true);
+    pairs.record_result(&base, &quote, false);
+    assert!(pairs.is_tradable(&base, &quote));
+    
+    // Two in a row start the cooldown
+    pairs.record_result(&base, &quote, false);
+    assert!(!pairs.is_tradable(&base, &quote));
+    assert!(pairs.get(&base, &quote).cooldown_until.is_some());
+    
+    // Other pairs are unaffected
+    assert!(pairs.is_tradable(&Pubkey::new_unique(), &quote));
+    
+    // Once it passes the pair trades again with a fresh streak
+    std::thread::sleep(cooldown);
+    assert!(pairs.is_tradable(&base, &quote));
+    let state = pairs.get(&base, &quote);
+    assert!(state.cooldown_until.is_none());
+    assert_eq!(state.consecutive_failures, 0);
+    
+    // A single failure after re-enabling doesn't pause it again
+    pairs.record_result(&base, &quote, false);
+    assert!(pairs.is_tradable(&base, &quote));
+}
+
+#[test]
+fn disabled_pair_stays_disabled_after_its_cooldown() {
+    let cooldown = Duration::from_millis(100);
+    let pairs = PairStateTracker::new(1, cooldown);
+    let (base, quote) = (sol(), usdc());
+    
+    pairs.record_result(&base, &quote, false);
+    pairs.set_enabled(&base, &quote, false).unwrap();
+    std::thread::sleep(cooldown);
+    assert!(!pairs.is_tradable(&base, &quote));
+    
+    // Enabling by hand takes effect at once
+    pairs.set_enabled(&base, &quote, true).unwrap();
+    assert!(pairs.is_tradable(&base, &quote));
+}
+
+/// Jupiter quote API answering each route with a fixed output by input mint, and 400 for any other
+///
+/// Returns the API's base URL.
+fn mock_jupiter(route_outputs: Vec<(Pubkey, u64)>) -> String {
+    use std::io::{BufRead, BufReader, Write};
+    
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            
+            // GET /quote?inputMint=...&outputMint=...&amount=... HTTP/1.1
+            let mut request_line = String::new();
+            let _ = reader.read_line(&mut request_line);
+            let param = |name: &str| request_line.split(&['?', '&', ' '][..])
+                .find_map(|pair| pair.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')))
+                .map(str::to_string);
+            let mut line = String::new();
+            while reader.read_line(&mut line).map_or(false, |read| read > 0) && line != "\r\n" {
+                line.clear();
+            }
+            
+            let out_amount = param("inputMint")
+                .and_then(|mint| route_outputs.iter().find(|(input, _)| input.to_string() == mint))
+                .map(|(_, out_amount)| *out_amount);
+            let (status, body) = match (out_amount, param("amount")) {
+                (Some(out_amount), Some(in_amount)) => ("200 OK", format!(
+                    r#"{{"inAmount":"{}","outAmount":"{}","otherAmountThreshold":"{}","priceImpactPct":"0"}}"#,
+                    in_amount, out_amount, out_amount,
+                )),
+                _ => ("400 Bad Request", r#"{"error":"Could not find any route"}"#.to_string()),
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status, body.len(), body,
+            );
+        }
+    });
+    
+    url
+}
+
+/// Engine whose aggregated routes come from `mock_jupiter(route_outputs)`
+fn aggregator_engine(name: &str, route_outputs: Vec<(Pubkey, u64)>) -> ArbitrageEngine {
+    let mut dex_manager = crate::dex::DexManager::new(UNREACHABLE_RPC);
+    let mut jupiter = DexConfig::new_jupiter();
+    jupiter.api_url = mock_jupiter(route_outputs);
+    dex_manager.add_connector(jupiter);
+    
+    test_engine_with_dexes(name, ArbitrageConfig::default(), dex_manager)
+}
+
+#[test]
+fn aggregator_route_is_taken_only_where_it_pays_more() {
+    let config = ArbitrageConfig::default();
+    let opportunity = ArbitrageOpportunity::from_prices(
+        &config,
+        quote(DexType::Raydium, 100.0, DEEP),
+        quote(DexType::Orca, 101.0, DEEP),
+        1.0,
+    ).unwrap();
+    
+    // $1,000 buys 10 SOL on Raydium less its 0.25% fee, and 10 SOL sells for $1,010 on Orca less 0.3%
+    let route = |engine: &ArbitrageEngine| engine.runtime.block_on(engine.executor.route_via_aggregator(&opportunity, &engine.cost_model));
+    
+    // Jupiter's route buys a full 10 SOL but sells for only $1,000
+    let engine = aggregator_engine("aggregator-buy", vec![(usdc(), 10_000_000_000), (sol(), 1_000_000_000)]);
+    let routed = route(&engine);
+    assert_eq!(routed.buy_price.dex, DexType::Jupiter);
+    assert!((routed.buy_price.price - 100.0).abs() < 1e-6, "{}", routed.buy_price.price);
+    assert_eq!(routed.buy_price.pool, None);
+    assert_eq!(routed.sell_price.dex, DexType::Orca);
+    assert_eq!(routed.sell_price.price, 101.0);
+    
+    // Jupiter's route buys only 9.9 SOL but sells 10 for $1,010
+    let engine = aggregator_engine("aggregator-sell", vec![(usdc(), 9_900_000_000), (sol(), 1_010_000_000)]);
+    let routed = route(&engine);
+    assert_eq!(routed.buy_price.dex, DexType::Raydium);
+    assert_eq!(routed.sell_price.dex, DexType::Jupiter);
+    assert!((routed.sell_price.price - 101.0).abs() < 1e-6, "{}", routed.sell_price.price);
+    assert!(routed.profit_percentage > 0.0);
+    
+    // Without a route both legs stay on their direct venues
+    let engine = aggregator_engine("aggregator-none", Vec::new());
+    let routed = route(&engine);
+    assert_eq!((routed.buy_price.dex, routed.sell_price.dex), (DexType::Raydium, DexType::Orca));
+    assert_eq!(routed.profit_percentage, opportunity.profit_percentage);
+}
+
+/// RPC sender answering signature status requests from a script
+struct ScriptedStatuses {
+    /// Statuses (null for an unknown signature), or errors, for the next requests in order
+    statuses: Mutex<VecDeque<Result<Value, String>>>,
+}
+
+#[async_trait]
+impl RpcSender for ScriptedStatuses {
+    async fn send(&self, request: RpcRequest, _params: Value) -> ClientResult<Value> {
+        assert_eq!(request, RpcRequest::GetSignatureStatuses);
+        match self.statuses.lock().unwrap().pop_front().expect("a scripted status") {
+            Ok(status) => Ok(json!({"context": {"slot": 1}, "value": [status]})),
+            Err(e) => Err(ClientErrorKind::Custom(e).into()),
+        }
+    }
+    
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+    
+    fn url(&self) -> String {
+        "scripted-statuses".to_string()
+    }
+}
+
+/// Status of a successful transaction at `confirmation_status`
+fn landed_status(confirmation_status: &str, confirmations: Option<usize>) -> Result<Value, String> {
+    Ok(json!({
+        "slot": 1,
+        "confirmations": confirmations,
+        "status": {"Ok": null},
+        "err": null,
+        "confirmationStatus": confirmation_status,
+    }))
+}
+
+/// Executor whose signature status requests are answered by `statuses`
+fn executor_with_statuses(statuses: Vec<Result<Value, String>>) -> ArbitrageExecutor {
+    let sender = ScriptedStatuses { statuses: Mutex::new(statuses.into()) };
+    let config = ArbitrageConfig::default();
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let storage = std::env::temp_dir().join(format!("sab-executor-{}-{}", std::process::id(), nanos));
+    
+    ArbitrageExecutor {
+        rpc_client: RpcClient::new_sender(sender, RpcClientConfig::with_commitment(CommitmentConfig::confirmed())),
+        dex_manager: crate::dex::DexManager::new(UNREACHABLE_RPC).into(),
+        flash_loan_manager: ThreadSafeFlashLoanManager::new(UNREACHABLE_RPC, FlashLoanConfig::new_solend(config.max_position_size)),
+        flash_loan_providers: Mutex::new(Vec::new()),
+        wallet_manager: ThreadSafeWalletManager::with_rpc_client(RpcClient::new_mock("succeeds".to_string()), &storage.to_string_lossy()),
+        risk_manager: RiskManager::new(config.risk_management.clone()),
+        config,
+        token_decimals: Mutex::new(HashMap::new()),
+    }
+}
+
+#[test]
+fn finality_guard_only_fails_trades_that_were_dropped() {
+    let config = ArbitrageConfig::default();
+    let opportunity = ArbitrageOpportunity::from_prices(
+        &config,
+        quote(DexType::Raydium, 100.0, DEEP),
+        quote(DexType::Orca, 101.0, DEEP),
+        1.0,
+    ).unwrap();
+    let landed = ArbitrageResult {
+        transaction_signature: Some(Signature::new_unique().to_string()),
+        ..trade_result(&opportunity, true, 500)
+    };
+    let guard = FinalityGuardConfig { depth: FinalityDepth::Finalized, timeout_sec: 5 };
+    
+    // A flaky RPC is retried until the trade finalizes
+    let executor = executor_with_statuses(vec![
+        Err("connection reset".to_string()),
+        landed_status("confirmed", Some(3)),
+        landed_status("finalized", None),
+    ]);
+    let settled = executor.settle(landed.clone(), &guard);
+    assert!(settled.success);
+    assert_eq!((settled.actual_profit, settled.realized_pnl), (500, 500));
+    assert_eq!(settled.error_message, None);
+    
+    // Gone from the chain, or failed on it, the trade is reversed
+    for status in [Ok(Value::Null), Ok(json!({"slot": 1, "confirmations": 3, "status": {"Err": "AccountInUse"}, "err": "AccountInUse", "confirmationStatus": "confirmed"}))] {
+        let dropped = executor_with_statuses(vec![status]).settle(landed.clone(), &guard);
+        assert!(!dropped.success);
+        assert_eq!((dropped.actual_profit, dropped.realized_pnl), (0, 0));
+        assert!(dropped.error_message.unwrap().starts_with("Not settled"));
+    }
+    
+    // Short of finality, or unreadable, when the guard gives up, it keeps its result as provisional
+    let impatient = FinalityGuardConfig { depth: FinalityDepth::Finalized, timeout_sec: 0 };
+    for status in [landed_status("confirmed", Some(3)), Err("connection refused".to_string())] {
+        let provisional = executor_with_statuses(vec![status]).settle(landed.clone(), &impatient);
+        assert!(provisional.success);
+        assert_eq!((provisional.actual_profit, provisional.realized_pnl), (500, 500));
+        assert!(provisional.error_message.unwrap().starts_with("Provisional"));
+    }
+}
+
+/// Oracle with no feeds, for SOL pairs whose costs never need one
+struct NoOracle;
+
+impl PriceOracle for NoOracle {
+    fn get_usd_price(&self, token_mint: &Pubkey) -> Result<f64, crate::oracle::OracleError> {
+        Err(crate::oracle::OracleError::MissingFeed(token_mint.to_string()))
+    }
+}
+
+#[test]
+fn same_spread_nets_more_through_lower_fee_venues() {
+    let config = ArbitrageConfig::default();
+    let estimator = NetProfitEstimator::new(PriorityFeeConfig::default(), 1.0);
+    let spread = |buy: PriceInfo| ArbitrageOpportunity::from_prices(&config, buy, quote(DexType::Raydium, 101.0, DEEP), 1.0).unwrap();
+    
+    // Buying on Phoenix (5 bps) instead of Orca (30 bps), selling on Raydium (25 bps) either way
+    let cheap_spread = spread(quote(DexType::Phoenix, 100.0, DEEP));
+    let cheap = estimator.estimate(&cheap_spread, 0, &NoOracle).unwrap();
+    let dear = estimator.estimate(&spread(quote(DexType::Orca, 100.0, DEEP)), 0, &NoOracle).unwrap();
+    assert_eq!((cheap.buy_fee_bps, cheap.sell_fee_bps), (5, 25));
+    assert_eq!((dear.buy_fee_bps, dear.sell_fee_bps), (30, 25));
+    
+    // Only the fees differ
+    let size = cheap_spread.max_trade_size;
+    let fee_gap = size * 30 / 10_000 - size * 5 / 10_000;
+    assert!(fee_gap > 0);
+    assert_eq!(cheap.gross_profit, dear.gross_profit);
+    assert_eq!((cheap.transaction_fee, cheap.slippage), (dear.transaction_fee, dear.slippage));
+    assert_eq!(dear.dex_fees - cheap.dex_fees, fee_gap);
+    assert_eq!(cheap.net_profit - dear.net_profit, fee_gap as i64);
+    
+    // An Orca pool reporting a lower fee tier is charged its own fee, not Orca's typical one
+    let mut low_tier = quote(DexType::Orca, 100.0, DEEP);
+    low_tier.fee_bps = Some(1);
+    let tiered = estimator.estimate(&spread(low_tier), 0, &NoOracle).unwrap();
+    assert_eq!(tiered.buy_fee_bps, 1);
+    assert!(tiered.net_profit > cheap.net_profit);
+}
+
+#[test]
+fn typed_subscribers_get_whole_events_without_blocking_the_publisher() {
+    let engine = test_engine("typed-subscribers", ArbitrageConfig::default());
+    let opportunities = engine.subscribe();
+    let results = engine.subscribe_results();
+    drop(engine.subscribe_results());
+    
+    let opportunity = ArbitrageOpportunity::from_prices(
+        &ArbitrageConfig::default(),
+        quote(DexType::Orca, 100.0, DEEP),
+        quote(DexType::Raydium, 101.0, DEEP),
+        1.0,
+    ).unwrap();
+    engine.state.opportunity_subscribers.publish(&opportunity);
+    let seen = opportunities.try_recv().unwrap();
+    assert_eq!(seen.trade_id, opportunity.trade_id);
+    assert_eq!((seen.buy_price.dex, seen.sell_price.dex), (DexType::Orca, DexType::Raydium));
+    assert_eq!(seen.max_trade_size, opportunity.max_trade_size);
+    
+    // A subscriber that never reads loses the overflow instead of stalling the trading loop
+    let result = ArbitrageResult {
+        success: false,
+        actual_profit: 0,
+        realized_pnl: -250,
+        error_message: Some("Stop-loss".to_string()),
+        transaction_signature: Some("sig".to_string()),
+        priority_fee_micro_lamports: 1_000,
+        execution_time_ms: 40,
+        opportunity,
+        simulation: None,
+    };
+    for _ in 0..SUBSCRIBER_CHANNEL_CAPACITY + 10 {
+        engine.state.result_subscribers.publish(&result);
+    }
+    assert_eq!(results.try_iter().count(), SUBSCRIBER_CHANNEL_CAPACITY);
+    
+    // The dropped subscriber was pruned, and the live one still gets pnl and error details
+    assert_eq!(engine.state.result_subscribers.senders.lock().unwrap().len(), 1);
+    engine.state.result_subscribers.publish(&result);
+    let seen = results.try_recv().unwrap();
+    assert_eq!(seen.realized_pnl, -250);
+    assert_eq!(seen.error_message.as_deref(), Some("Stop-loss"));
+}
+
+/// Flash loan manager for `config` whose reserve for `token_mint` holds `reserve` atoms
+fn funded_flash_loans(mut config: FlashLoanConfig, token_mint: Pubkey, reserve: u64) -> ThreadSafeFlashLoanManager {
+    config.add_liquidity_account(token_mint, Pubkey::new_unique());
+    let mut mocks = HashMap::new();
+    mocks.insert(RpcRequest::GetTokenAccountBalance, json!({
+        "context": { "slot": 1 },
+        "value": { "amount": reserve.to_string(), "decimals": 6, "uiAmount": null, "uiAmountString": reserve.to_string() },
+    }));
+    ThreadSafeFlashLoanManager::with_rpc_client(RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks), config)
+}
+
+#[test]
+fn loans_go_to_the_cheapest_provider_that_can_lend() {
+    let engine = test_engine("flash-loan-providers", ArbitrageConfig::default());
+    let mint = Pubkey::new_unique();
+    
+    // The engine's own provider has no liquidity account for the mint
+    assert!(engine.executor.flash_loan_provider(&mint, 1_000_000).is_err());
+    
+    // Flash Protocol (0.2%) is cheaper than Flash Loan Mastery (0.25%)
+    engine.add_flash_loan_provider(funded_flash_loans(FlashLoanConfig::new_flash_loan_mastery(u64::MAX), mint, 5_000_000));
+    engine.add_flash_loan_provider(funded_flash_loans(FlashLoanConfig::new_flash_protocol(u64::MAX), mint, 5_000_000));
+    let selected = engine.executor.flash_loan_provider(&mint, 1_000_000).unwrap();
+    assert_eq!(selected.calculate_fee(1_000_000).unwrap(), 2_000);
+    
+    // Too shallow a reserve rules the cheaper one out
+    let engine = test_engine("flash-loan-shallow", ArbitrageConfig::default());
+    engine.add_flash_loan_provider(funded_flash_loans(FlashLoanConfig::new_flash_loan_mastery(u64::MAX), mint, 5_000_000));
+    engine.add_flash_loan_provider(funded_flash_loans(FlashLoanConfig::new_flash_protocol(u64::MAX), mint, 500_000));
+    let selected = engine.executor.flash_loan_provider(&mint, 1_000_000).unwrap();
+    assert_eq!(selected.calculate_fee(1_000_000).unwrap(), 2_500);
+}
+
+/// Fresh journal path under the temp directory
+fn journal_path(name: &str) -> std::path::PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    std::env::temp_dir().join(format!("sab-journal-{}-{}-{}", name, std::process::id(), nanos)).join("trades.jsonl")
+}
+
+/// Winning SOL/USDC trade signed as `signature`
+fn journaled_trade(signature: &str, realized_pnl: i64) -> ArbitrageResult {
+    let config = ArbitrageConfig::default();
+    let opportunity = ArbitrageOpportunity::from_prices(
+        &config,
+        quote(DexType::Raydium, 100.0, DEEP),
+        quote(DexType::Orca, 101.0, DEEP),
+        1.0,
+    ).unwrap();
+    let mut result = trade_result(&opportunity, true, realized_pnl);
+    result.transaction_signature = Some(signature.to_string());
+    result
+}
+
+#[test]
+fn journal_reloads_after_restart_and_skips_a_torn_last_line() {
+    use std::io::Write;
+    
+    let path = journal_path("restart");
+    let journal = TradeJournal::open(&path, Duration::from_secs(60)).unwrap();
+    journal.record(&journaled_trade("first", 1_000)).unwrap();
+    journal.record(&journaled_trade("second", -200)).unwrap();
+    drop(journal);
+    
+    // A crash halfway through the next write
+    let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+    write!(file, "{{\"timestamp\":17000").unwrap();
+    drop(file);
+    
+    let reopened = TradeJournal::open(&path, Duration::from_secs(60)).unwrap();
+    let records = reopened.query_by_range(0, u64::MAX).unwrap();
+    let loaded: Vec<(Option<&str>, i64)> = records.iter()
+        .map(|record| (record.transaction_signature.as_deref(), record.realized_pnl))
+        .collect();
+    assert_eq!(loaded, vec![(Some("first"), 1_000), (Some("second"), -200)]);
+}
+
+#[test]
+fn buffered_trades_are_written_without_another_record() {
+    let path = journal_path("timer");
+    let journal = TradeJournal::open(&path, Duration::from_millis(20)).unwrap();
+    journal.record(&journaled_trade("quiet", 500)).unwrap();
+    
+    // Nothing else is recorded or flushed, yet the trade reaches the file
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let written = || std::fs::read_to_string(&path).map_or(0, |data| data.lines().count());
+    while written() == 0 {
+        assert!(Instant::now() < deadline, "buffered trade never written");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(written(), 1);
+}
+
+#[test]
+fn range_query_includes_both_bounds() {
+    let path = journal_path("range");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let lines: Vec<String> = [100, 200, 300].iter()
+        .map(|&timestamp| {
+            let mut record = journal::TradeRecord::from_result(&journaled_trade(&timestamp.to_string(), 0));
+            record.timestamp = timestamp;
+            serde_json::to_string(&record).unwrap()
+        })
+        .collect();
+    std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+    
+    let journal = TradeJournal::open(&path, Duration::from_secs(60)).unwrap();
+    let timestamps = |from, to| -> Vec<u64> {
+        journal.query_by_range(from, to).unwrap().iter().map(|record| record.timestamp).collect()
+    };
+    assert_eq!(timestamps(100, 200), vec![100, 200]);
+    assert_eq!(timestamps(300, 300), vec![300]);
+    assert_eq!(timestamps(201, 299), Vec::<u64>::new());
+    assert_eq!(timestamps(0, u64::MAX), vec![100, 200, 300]);
+}
+
+#[test]
+fn csv_export_has_a_header_and_a_row_per_trade() {
+    let path = journal_path("csv");
+    let journal = TradeJournal::open(&path, Duration::from_secs(60)).unwrap();
+    journal.record(&journaled_trade("landed", 1_000)).unwrap();
+    let mut failed = journaled_trade("", 0);
+    failed.success = false;
+    failed.transaction_signature = None;
+    journal.record(&failed).unwrap();
+    
+    let csv_path = path.with_extension("csv");
+    journal.export_csv(&csv_path).unwrap();
+    let csv = std::fs::read_to_string(&csv_path).unwrap();
+    let rows: Vec<Vec<&str>> = csv.lines().map(|line| line.split(',').collect()).collect();
+    
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0], vec![
+        "timestamp", "base_token", "quote_token", "buy_venue", "sell_venue", "size", "realized_pnl",
+        "priority_fee_micro_lamports", "slippage", "transaction_signature", "success",
+    ]);
+    let sol = sol().to_string();
+    let usdc = usdc().to_string();
+    assert_eq!(rows[1][1..5], [sol.as_str(), usdc.as_str(), "Raydium", "Orca"]);
+    assert_eq!(rows[1][6], "1000");
+    assert_eq!(rows[1][9..], ["landed", "true"]);
+    assert_eq!(rows[2][9..], ["", "false"]);
+}
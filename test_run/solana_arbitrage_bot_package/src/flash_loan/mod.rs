@@ -13,6 +13,8 @@ use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use log::{info, warn, error, debug};
 
+use crate::lock_or_recover;
+
 /// Error type for flash loan operations
 #[derive(Debug)]
 pub enum FlashLoanError {
@@ -312,15 +314,13 @@ impl ThreadSafeFlashLoanManager {
     
     /// Get the program ID for the configured provider (thread-safe)
     pub fn get_provider_program_id(&self) -> Result<Pubkey, FlashLoanError> {
-        let manager = self.inner.lock()
-            .map_err(|e| FlashLoanError::GeneralError(format!("Lock error: {}", e)))?;
+        let manager = lock_or_recover(&self.inner);
         Ok(manager.get_provider_program_id())
     }
     
     /// Calculate the fee for a flash loan (thread-safe)
     pub fn calculate_fee(&self, amount: u64) -> Result<u64, FlashLoanError> {
-        let manager = self.inner.lock()
-            .map_err(|e| FlashLoanError::GeneralError(format!("Lock error: {}", e)))?;
+        let manager = lock_or_recover(&self.inner);
         Ok(manager.calculate_fee(amount))
     }
     
@@ -333,8 +333,7 @@ impl ThreadSafeFlashLoanManager {
         receiver: &Pubkey,
         callback_program_id: &Pubkey,
     ) -> Result<Instruction, FlashLoanError> {
-        let manager = self.inner.lock()
-            .map_err(|e| FlashLoanError::GeneralError(format!("Lock error: {}", e)))?;
+        let manager = lock_or_recover(&self.inner);
         manager.create_flash_loan_instruction(amount, token_mint, borrower, receiver, callback_program_id)
     }
 }
@@ -9,6 +9,8 @@ use solana_sdk::{
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
+use crate::lock_or_recover;
+
 /// Configuration for profit distribution
 pub struct ProfitDistributionConfig {
     /// Percentage of profits to reinvest (0-100)
@@ -282,33 +284,33 @@ impl ThreadSafeProfitManager {
     
     /// Record profit (thread-safe)
     pub fn record_profit(&self, token_mint: Pubkey, amount: u64, sol_value: u64, usd_value: u64) -> Result<(), String> {
-        let mut manager = self.inner.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let mut manager = lock_or_recover(&self.inner);
         manager.record_profit(token_mint, amount, sol_value, usd_value);
         Ok(())
     }
     
     /// Record failed trade (thread-safe)
     pub fn record_failed_trade(&self, token_mint: Pubkey) -> Result<(), String> {
-        let mut manager = self.inner.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let mut manager = lock_or_recover(&self.inner);
         manager.record_failed_trade(token_mint);
         Ok(())
     }
     
     /// Distribute profits (thread-safe)
     pub fn distribute_profits(&self, wallet_manager: &WalletManager) -> Result<DistributionResult, String> {
-        let mut manager = self.inner.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let mut manager = lock_or_recover(&self.inner);
         manager.distribute_profits(wallet_manager)
     }
     
     /// Get profit statistics (thread-safe)
     pub fn get_statistics(&self) -> Result<ProfitStatistics, String> {
-        let manager = self.inner.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let manager = lock_or_recover(&self.inner);
         Ok(manager.get_statistics())
     }
     
     /// Update distribution configuration (thread-safe)
     pub fn update_config(&self, config: ProfitDistributionConfig) -> Result<(), String> {
-        let mut manager = self.inner.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let mut manager = lock_or_recover(&self.inner);
         manager.update_config(config);
         Ok(())
     }
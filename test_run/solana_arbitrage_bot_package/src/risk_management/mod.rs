@@ -3,12 +3,21 @@
 
 use solana_sdk::{
     pubkey::Pubkey,
+    program_pack::Pack,
 };
-use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+use solana_client::rpc_client::RpcClient;
+use spl_token::state::Mint;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use log::{info, warn, error, debug};
 
+use crate::dex::PriceInfo;
+use crate::lock_or_recover;
+use crate::wallet_integration::ThreadSafeWalletManager;
+
 /// Risk level for position sizing
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RiskLevel {
@@ -40,6 +49,8 @@ pub struct PositionScalingConfig {
     pub use_adaptive_sizing: bool,
     /// Whether to use profit-based scaling
     pub use_profit_based_scaling: bool,
+    /// Per-pair (min, max) position sizes overriding the global limits
+    pub per_pair_overrides: HashMap<(Pubkey, Pubkey), (u64, u64)>,
 }
 
 impl PositionScalingConfig {
@@ -55,6 +66,7 @@ impl PositionScalingConfig {
                 risk_level,
                 use_adaptive_sizing: true,
                 use_profit_based_scaling: true,
+                per_pair_overrides: HashMap::new(),
             },
             RiskLevel::Moderate => Self {
                 base_position_size: 250_000_000, // 250 USDC
@@ -65,6 +77,7 @@ impl PositionScalingConfig {
                 risk_level,
                 use_adaptive_sizing: true,
                 use_profit_based_scaling: true,
+                per_pair_overrides: HashMap::new(),
             },
             RiskLevel::Aggressive => Self {
                 base_position_size: 500_000_000, // 500 USDC
@@ -75,6 +88,7 @@ impl PositionScalingConfig {
                 risk_level,
                 use_adaptive_sizing: true,
                 use_profit_based_scaling: true,
+                per_pair_overrides: HashMap::new(),
             },
             RiskLevel::Custom => Self {
                 base_position_size: 250_000_000, // 250 USDC
@@ -85,12 +99,27 @@ impl PositionScalingConfig {
                 risk_level,
                 use_adaptive_sizing: true,
                 use_profit_based_scaling: true,
+                per_pair_overrides: HashMap::new(),
             },
         }
     }
+    
+    /// Get the (min, max) position size for a token pair
+    pub fn position_limits(&self, token_pair: &(Pubkey, Pubkey)) -> (u64, u64) {
+        self.per_pair_overrides.get(token_pair)
+            .copied()
+            .unwrap_or((self.base_position_size / 2, self.max_position_size))
+    }
+    
+    /// Starting position size for a token pair, clamped to its limits
+    pub fn starting_position_size(&self, token_pair: &(Pubkey, Pubkey)) -> u64 {
+        let (min_size, max_size) = self.position_limits(token_pair);
+        self.base_position_size.max(min_size).min(max_size)
+    }
 }
 
 /// Risk management configuration
+#[derive(Clone)]
 pub struct RiskManagementConfig {
     /// Maximum percentage of capital to use per trade
     pub max_capital_per_trade: f64,
@@ -108,6 +137,12 @@ pub struct RiskManagementConfig {
     pub max_daily_loss: f64,
     /// Whether to use circuit breakers
     pub use_circuit_breakers: bool,
+    /// Whether to stop trading a pair entirely while its volatility is extreme
+    pub halt_on_extreme_volatility: bool,
+    /// Volatility a halted pair must fall to before trading resumes
+    pub volatility_resume_level: VolatilityLevel,
+    /// Mints allowed or blocked from trading
+    pub token_filter: TokenFilter,
     /// Risk level
     pub risk_level: RiskLevel,
 }
@@ -125,6 +160,9 @@ impl RiskManagementConfig {
                 max_trades_per_day: 20,          // 20 trades per day
                 max_daily_loss: 0.05,            // 5% maximum daily loss
                 use_circuit_breakers: true,
+                halt_on_extreme_volatility: true,
+                volatility_resume_level: VolatilityLevel::Medium,
+                token_filter: TokenFilter::default(),
                 risk_level,
             },
             RiskLevel::Moderate => Self {
@@ -136,6 +174,9 @@ impl RiskManagementConfig {
                 max_trades_per_day: 50,          // 50 trades per day
                 max_daily_loss: 0.1,             // 10% maximum daily loss
                 use_circuit_breakers: true,
+                halt_on_extreme_volatility: true,
+                volatility_resume_level: VolatilityLevel::Medium,
+                token_filter: TokenFilter::default(),
                 risk_level,
             },
             RiskLevel::Aggressive => Self {
@@ -147,6 +188,9 @@ impl RiskManagementConfig {
                 max_trades_per_day: 100,         // 100 trades per day
                 max_daily_loss: 0.15,            // 15% maximum daily loss
                 use_circuit_breakers: true,
+                halt_on_extreme_volatility: true,
+                volatility_resume_level: VolatilityLevel::Medium,
+                token_filter: TokenFilter::default(),
                 risk_level,
             },
             RiskLevel::Custom => Self {
@@ -158,14 +202,303 @@ impl RiskManagementConfig {
                 max_trades_per_day: 50,          // 50 trades per day
                 max_daily_loss: 0.1,             // 10% maximum daily loss
                 use_circuit_breakers: true,
+                halt_on_extreme_volatility: true,
+                volatility_resume_level: VolatilityLevel::Medium,
+                token_filter: TokenFilter::default(),
                 risk_level,
             },
         }
     }
 }
 
+/// Seconds in a UTC day
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Days since the Unix epoch, so day boundaries fall at UTC midnight
+fn current_utc_day() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() / SECONDS_PER_DAY
+}
+
+/// Risk limit violations
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskError {
+    /// Daily loss limit reached or would be breached
+    DailyLossLimit(String),
+    /// Daily trade count reached
+    DailyTradeLimit(String),
+    /// Too many trades in flight
+    ConcurrencyLimit(String),
+    /// Trade would exceed the per-trade or total capital limits
+    CapitalLimit(String),
+    /// Wallet balance is empty or couldn't be read
+    BalanceError(String),
+    /// General error
+    GeneralError(String),
+}
+
+impl std::fmt::Display for RiskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RiskError::DailyLossLimit(msg) => write!(f, "Daily loss limit: {}", msg),
+            RiskError::DailyTradeLimit(msg) => write!(f, "Daily trade limit: {}", msg),
+            RiskError::ConcurrencyLimit(msg) => write!(f, "Concurrency limit: {}", msg),
+            RiskError::CapitalLimit(msg) => write!(f, "Capital limit: {}", msg),
+            RiskError::BalanceError(msg) => write!(f, "Balance error: {}", msg),
+            RiskError::GeneralError(msg) => write!(f, "Error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RiskError {}
+
+/// Realized results for the current UTC day
+#[derive(Debug, Default)]
+struct DailyRiskState {
+    /// UTC day these counters belong to
+    day: u64,
+    /// Realized profit and loss today (negative for a loss)
+    realized_pnl: i64,
+    /// Trades started today
+    trades: usize,
+    /// Whether the loss circuit breaker has tripped today
+    halted: bool,
+}
+
+/// Circuit breakers for the daily loss, daily trade and concurrency limits
+///
+/// The daily loss limit is `max_daily_loss` of the reference capital. Once it
+/// trips, trading stays halted until the next UTC day.
+pub struct RiskGuard {
+    /// Risk management configuration
+    config: RiskManagementConfig,
+    /// Capital the daily loss limit is measured against
+    capital: AtomicU64,
+    /// Today's realized results
+    daily: Mutex<DailyRiskState>,
+    /// Trades currently in flight
+    active_trades: AtomicUsize,
+    /// Profits are being distributed, so no trade may start
+    distributing: AtomicBool,
+}
+
+impl RiskGuard {
+    /// Create a new risk guard
+    pub fn new(config: RiskManagementConfig, capital: u64) -> Self {
+        Self {
+            config,
+            capital: AtomicU64::new(capital),
+            daily: Mutex::new(DailyRiskState {
+                day: current_utc_day(),
+                ..Default::default()
+            }),
+            active_trades: AtomicUsize::new(0),
+            distributing: AtomicBool::new(false),
+        }
+    }
+    
+    /// Update the capital the daily loss limit is measured against
+    pub fn set_capital(&self, capital: u64) {
+        self.capital.store(capital, Ordering::SeqCst);
+    }
+    
+    /// Largest realized loss allowed in a day
+    pub fn daily_loss_limit(&self) -> i64 {
+        (self.capital.load(Ordering::SeqCst) as f64 * self.config.max_daily_loss) as i64
+    }
+    
+    /// Lock today's counters, starting fresh if a UTC day boundary has passed
+    fn today(&self) -> Result<MutexGuard<'_, DailyRiskState>, RiskError> {
+        let mut daily = lock_or_recover(&self.daily);
+        
+        let today = current_utc_day();
+        if daily.day != today {
+            if daily.halted {
+                info!("New UTC day, circuit breaker reset");
+            }
+            *daily = DailyRiskState {
+                day: today,
+                ..Default::default()
+            };
+        }
+        
+        Ok(daily)
+    }
+    
+    /// Check whether a trade of `proposed_size` may start
+    pub fn check_can_trade(&self, proposed_size: u64) -> Result<(), RiskError> {
+        let active = self.active_trades.load(Ordering::SeqCst);
+        if active >= self.config.max_concurrent_trades {
+            return Err(RiskError::ConcurrencyLimit(format!(
+                "{} trades active, limit is {}", active, self.config.max_concurrent_trades
+            )));
+        }
+        
+        if !self.config.use_circuit_breakers {
+            return Ok(());
+        }
+        
+        let daily = self.today()?;
+        
+        if daily.halted {
+            return Err(RiskError::DailyLossLimit(format!(
+                "trading halted until the next UTC day after a loss of {}", -daily.realized_pnl
+            )));
+        }
+        
+        if daily.trades >= self.config.max_trades_per_day {
+            return Err(RiskError::DailyTradeLimit(format!(
+                "{} trades today, limit is {}", daily.trades, self.config.max_trades_per_day
+            )));
+        }
+        
+        // An atomic arbitrage can lose at most its slippage allowance
+        let worst_case_loss = (proposed_size as f64 * self.config.max_slippage / 100.0) as i64;
+        let remaining = self.daily_loss_limit() + daily.realized_pnl;
+        if worst_case_loss > remaining {
+            return Err(RiskError::DailyLossLimit(format!(
+                "worst-case loss of {} exceeds the remaining daily budget of {}", worst_case_loss, remaining.max(0)
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// Record that a trade passed `check_can_trade` and is starting
+    pub fn trade_started(&self) -> Result<(), RiskError> {
+        // Claim the slot before checking, so a distribution starting concurrently sees it
+        self.active_trades.fetch_add(1, Ordering::SeqCst);
+        if self.distributing.load(Ordering::SeqCst) {
+            self.active_trades.fetch_sub(1, Ordering::SeqCst);
+            return Err(RiskError::ConcurrencyLimit("profit distribution in progress".to_string()));
+        }
+        
+        self.today()?.trades += 1;
+        Ok(())
+    }
+    
+    /// Block new trades for a profit distribution, if none are in flight
+    ///
+    /// Returns false (and blocks nothing) while a trade is active. Call
+    /// `end_distribution` once the distribution is done.
+    pub fn try_begin_distribution(&self) -> bool {
+        if self.distributing.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        
+        if self.active_trades.load(Ordering::SeqCst) > 0 {
+            self.distributing.store(false, Ordering::SeqCst);
+            return false;
+        }
+        
+        true
+    }
+    
+    /// Allow trades again after a profit distribution
+    pub fn end_distribution(&self) {
+        self.distributing.store(false, Ordering::SeqCst);
+    }
+    
+    /// Record a finished trade's realized profit or loss, tripping the breaker on the daily loss limit
+    ///
+    /// Returns whether this result tripped the daily loss circuit breaker.
+    pub fn trade_finished(&self, realized_pnl: i64) -> Result<bool, RiskError> {
+        let _ = self.active_trades.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| active.checked_sub(1));
+        
+        let mut daily = self.today()?;
+        daily.realized_pnl += realized_pnl;
+        
+        let limit = self.daily_loss_limit();
+        if self.config.use_circuit_breakers && !daily.halted && -daily.realized_pnl >= limit {
+            daily.halted = true;
+            warn!("Circuit breaker tripped: daily loss of {} reached the limit of {}, halting until the next UTC day",
+                  -daily.realized_pnl, limit);
+            return Ok(true);
+        }
+        
+        Ok(false)
+    }
+}
+
+/// Caps trade sizes to the configured fractions of the trading wallet's live balance
+pub struct RiskManager {
+    /// Risk management configuration
+    config: RiskManagementConfig,
+    /// Capital committed to trades still in flight
+    open_exposure: Mutex<u64>,
+}
+
+impl RiskManager {
+    /// Create a new risk manager
+    pub fn new(config: RiskManagementConfig) -> Self {
+        Self {
+            config,
+            open_exposure: Mutex::new(0),
+        }
+    }
+    
+    /// Clamp a proposed trade to `max_capital_per_trade` and the room left under `max_capital_exposure`
+    ///
+    /// Rejects the trade when the balance is zero or can't be fetched, rather
+    /// than sizing it blind.
+    pub fn size_within_limits(
+        &self,
+        wallet_manager: &ThreadSafeWalletManager,
+        wallet: &Pubkey,
+        proposed_size: u64,
+    ) -> Result<u64, RiskError> {
+        let balance = wallet_manager.get_balance(wallet)
+            .map_err(|e| RiskError::BalanceError(format!("Failed to get balance of {}: {}", wallet, e)))?;
+        
+        if balance == 0 {
+            return Err(RiskError::BalanceError(format!("Trading wallet {} has no balance", wallet)));
+        }
+        
+        let per_trade_limit = (balance as f64 * self.config.max_capital_per_trade) as u64;
+        let exposure_limit = (balance as f64 * self.config.max_capital_exposure) as u64;
+        
+        let open_exposure = *lock_or_recover(&self.open_exposure);
+        let remaining = exposure_limit.saturating_sub(open_exposure);
+        
+        let size = proposed_size.min(per_trade_limit).min(remaining);
+        if size == 0 {
+            return Err(RiskError::CapitalLimit(format!(
+                "{} of {} already committed, exposure limit is {}", open_exposure, balance, exposure_limit
+            )));
+        }
+        
+        if size < proposed_size {
+            debug!("Clamped trade size from {} to {} ({:.1}% of capital)",
+                   proposed_size, size, size as f64 / balance as f64 * 100.0);
+        }
+        
+        Ok(size)
+    }
+    
+    /// Count a trade's size toward open exposure
+    pub fn open_position(&self, size: u64) -> Result<(), RiskError> {
+        let mut open_exposure = lock_or_recover(&self.open_exposure);
+        *open_exposure += size;
+        Ok(())
+    }
+    
+    /// Release a finished trade's size from open exposure
+    pub fn close_position(&self, size: u64) -> Result<(), RiskError> {
+        let mut open_exposure = lock_or_recover(&self.open_exposure);
+        *open_exposure = open_exposure.saturating_sub(size);
+        Ok(())
+    }
+    
+    /// Capital committed to trades still in flight
+    pub fn open_exposure(&self) -> Result<u64, RiskError> {
+        Ok(*lock_or_recover(&self.open_exposure))
+    }
+}
+
 /// Market volatility level
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum VolatilityLevel {
     /// Low volatility
     Low,
@@ -177,6 +510,196 @@ pub enum VolatilityLevel {
     Extreme,
 }
 
+/// Stablecoins trusted by default even though their issuers can freeze accounts (USDC, USDT)
+const TRUSTED_FREEZABLE_MINTS: [&str; 2] = [
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+];
+
+/// How long a mint's metadata is trusted while it still has a freeze authority
+const FREEZABLE_MINT_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Allowlist/denylist of token mints
+///
+/// Guards against trading honeypot tokens that can be bought but not sold.
+#[derive(Debug, Clone)]
+pub struct TokenFilter {
+    /// Mints explicitly allowed
+    pub allowlist: HashSet<Pubkey>,
+    /// Mints never traded
+    pub denylist: HashSet<Pubkey>,
+    /// Only trade allowlisted mints
+    pub allowlist_only: bool,
+    /// Reject non-allowlisted mints that have a freeze authority
+    pub require_no_freeze_authority: bool,
+}
+
+impl Default for TokenFilter {
+    /// Reject mints that can be frozen, apart from the major stablecoins
+    fn default() -> Self {
+        Self {
+            allowlist: TRUSTED_FREEZABLE_MINTS.iter()
+                .filter_map(|mint| Pubkey::from_str(mint).ok())
+                .collect(),
+            denylist: HashSet::new(),
+            allowlist_only: false,
+            require_no_freeze_authority: true,
+        }
+    }
+}
+
+impl TokenFilter {
+    /// Create a filter from allowlist and denylist mints on top of the default checks
+    pub fn new(allowlist: Vec<Pubkey>, denylist: Vec<Pubkey>, allowlist_only: bool) -> Self {
+        let mut filter = Self::default();
+        filter.allowlist.extend(allowlist);
+        filter.denylist.extend(denylist);
+        filter.allowlist_only = allowlist_only;
+        filter
+    }
+    
+    /// Check a mint against the allowlist and denylist
+    pub fn check_mint(&self, mint: &Pubkey) -> Result<(), String> {
+        if self.denylist.contains(mint) {
+            return Err(format!("Mint {} is denylisted", mint));
+        }
+        
+        if self.allowlist_only && !self.allowlist.contains(mint) {
+            return Err(format!("Mint {} is not allowlisted", mint));
+        }
+        
+        Ok(())
+    }
+    
+    /// Check both mints of a token pair
+    pub fn check_pair(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<(), String> {
+        self.check_mint(base_token)?;
+        self.check_mint(quote_token)
+    }
+    
+    /// Whether a mint that passed `check_mint` still needs its freeze authority checked
+    ///
+    /// Allowlisted mints are trusted even if they can be frozen (e.g. USDC).
+    fn needs_freeze_check(&self, mint: &Pubkey) -> bool {
+        self.require_no_freeze_authority && !self.allowlist.contains(mint)
+    }
+}
+
+/// Mint account fields the token policy checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MintMetadata {
+    /// Decimals of the mint
+    pub decimals: u8,
+    /// Authority that can freeze the mint's token accounts
+    pub freeze_authority: Option<Pubkey>,
+}
+
+impl MintMetadata {
+    /// Decode mint account data (Token-2022 mints share the base layout and append extensions)
+    pub fn from_account_data(mint: &Pubkey, data: &[u8]) -> Result<Self, String> {
+        let mint_state = data.get(..Mint::LEN)
+            .ok_or_else(|| format!("Account {} is not a mint", mint))
+            .and_then(|base| Mint::unpack(base).map_err(|e| format!("Failed to decode mint {}: {}", mint, e)))?;
+        
+        Ok(Self {
+            decimals: mint_state.decimals,
+            freeze_authority: mint_state.freeze_authority.into(),
+        })
+    }
+}
+
+/// Token filter checked before every trade, with mint metadata cached
+///
+/// Denylisted mints are always rejected and allowlisted mints always pass. Other mints
+/// are rejected while `allowlist_only` is set, or when `require_no_freeze_authority` is
+/// set and the mint has a freeze authority. A freeze authority can't be added once
+/// revoked, so a mint without one is fetched only once; a mint that has one is fetched
+/// again every `FREEZABLE_MINT_TTL` in case it's been revoked.
+pub struct TokenPolicy {
+    /// Allowlist, denylist and checks, editable while trading
+    filter: Mutex<TokenFilter>,
+    /// RPC client mint accounts are fetched with
+    rpc_client: RpcClient,
+    /// Mint metadata fetched so far, and when it was fetched
+    mints: Mutex<HashMap<Pubkey, (MintMetadata, Instant)>>,
+    /// How long metadata of a mint with a freeze authority is served before it's fetched again
+    freezable_mint_ttl: Duration,
+}
+
+impl TokenPolicy {
+    /// Create a policy enforcing a token filter
+    pub fn new(filter: TokenFilter, rpc_client: RpcClient) -> Self {
+        Self {
+            filter: Mutex::new(filter),
+            rpc_client,
+            mints: Mutex::new(HashMap::new()),
+            freezable_mint_ttl: FREEZABLE_MINT_TTL,
+        }
+    }
+    
+    /// Check a mint before it's traded
+    pub fn check_mint(&self, mint: &Pubkey) -> Result<(), String> {
+        let needs_freeze_check = {
+            let filter = lock_or_recover(&self.filter);
+            filter.check_mint(mint)?;
+            filter.needs_freeze_check(mint)
+        };
+        
+        if needs_freeze_check {
+            if let Some(authority) = self.mint_metadata(mint)?.freeze_authority {
+                return Err(format!("Mint {} can be frozen by {}", mint, authority));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Check both mints of a token pair before it's traded
+    pub fn check_pair(&self, base_token: &Pubkey, quote_token: &Pubkey) -> Result<(), String> {
+        self.check_mint(base_token)?;
+        self.check_mint(quote_token)
+    }
+    
+    /// Metadata of a mint, fetched on first use and again once a freeze authority's entry ages out
+    pub fn mint_metadata(&self, mint: &Pubkey) -> Result<MintMetadata, String> {
+        if let Some((metadata, fetched_at)) = lock_or_recover(&self.mints).get(mint) {
+            if metadata.freeze_authority.is_none() || fetched_at.elapsed() < self.freezable_mint_ttl {
+                return Ok(*metadata);
+            }
+        }
+        
+        let data = self.rpc_client.get_account_data(mint)
+            .map_err(|e| format!("Failed to fetch mint {}: {}", mint, e))?;
+        let metadata = MintMetadata::from_account_data(mint, &data)?;
+        
+        lock_or_recover(&self.mints).insert(*mint, (metadata, Instant::now()));
+        Ok(metadata)
+    }
+    
+    /// Trust a mint, taking it off the denylist
+    pub fn add_to_allowlist(&self, mint: Pubkey) -> Result<(), String> {
+        let mut filter = lock_or_recover(&self.filter);
+        filter.denylist.remove(&mint);
+        filter.allowlist.insert(mint);
+        info!("Mint {} allowlisted", mint);
+        Ok(())
+    }
+    
+    /// Never trade a mint again, taking it off the allowlist
+    pub fn add_to_denylist(&self, mint: Pubkey) -> Result<(), String> {
+        let mut filter = lock_or_recover(&self.filter);
+        filter.allowlist.remove(&mint);
+        filter.denylist.insert(mint);
+        info!("Mint {} denylisted", mint);
+        Ok(())
+    }
+    
+    /// Current allowlist, denylist and checks
+    pub fn filter(&self) -> Result<TokenFilter, String> {
+        Ok(lock_or_recover(&self.filter).clone())
+    }
+}
+
 /// Market condition assessment
 pub struct MarketCondition {
     /// Volatility level
@@ -189,6 +712,219 @@ pub struct MarketCondition {
     pub timestamp: u64,
 }
 
+/// Standard deviation of returns (in percent) at which volatility becomes Medium, High and Extreme
+const VOLATILITY_THRESHOLDS: (f64, f64, f64) = (0.5, 1.5, 3.0);
+
+/// Classify volatility from the standard deviation of percentage returns between samples
+fn volatility_level(prices: &[f64], thresholds: (f64, f64, f64)) -> VolatilityLevel {
+    if prices.len() < 2 {
+        return VolatilityLevel::Low;
+    }
+    
+    // Percentage returns between consecutive samples
+    let returns: Vec<f64> = prices.windows(2)
+        .map(|w| (w[1] - w[0]) / w[0] * 100.0)
+        .collect();
+    
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+    let std_dev = variance.sqrt();
+    
+    let (medium, high, extreme) = thresholds;
+    if std_dev >= extreme {
+        VolatilityLevel::Extreme
+    } else if std_dev >= high {
+        VolatilityLevel::High
+    } else if std_dev >= medium {
+        VolatilityLevel::Medium
+    } else {
+        VolatilityLevel::Low
+    }
+}
+
+/// Aggregated quote for a token pair at one point in time
+struct MarketSample {
+    /// Mean price across the DEXs quoted
+    price: f64,
+    /// Total liquidity across the DEXs quoted, in quote token atoms
+    liquidity: u64,
+}
+
+/// Derives market conditions for adaptive sizing from recent quotes
+///
+/// Volatility comes from the standard deviation of returns, liquidity from the
+/// average depth observed across DEXs, and trend from a short/long moving
+/// average crossover.
+pub struct MarketConditionAnalyzer {
+    /// Recent samples by token pair, oldest first
+    samples: HashMap<(Pubkey, Pubkey), VecDeque<MarketSample>>,
+    /// Number of samples kept per pair (the long moving average window)
+    window_size: usize,
+    /// Number of most recent samples in the short moving average
+    short_window: usize,
+    /// Average liquidity (in quote token atoms) that earns a full liquidity score
+    reference_liquidity: u64,
+    /// Moving average divergence (in percent) that maps to a full-strength trend
+    full_trend_percentage: f64,
+}
+
+impl MarketConditionAnalyzer {
+    /// Create a new analyzer
+    pub fn new(window_size: usize, reference_liquidity: u64) -> Self {
+        let window_size = window_size.max(2);
+        Self {
+            samples: HashMap::new(),
+            window_size,
+            short_window: (window_size / 4).max(1),
+            reference_liquidity: reference_liquidity.max(1),
+            full_trend_percentage: 1.0,
+        }
+    }
+    
+    /// Record one round of quotes, aggregating the DEXs quoted for each pair
+    pub fn record_prices(&mut self, prices: &[PriceInfo]) {
+        let mut by_pair: HashMap<(Pubkey, Pubkey), (f64, u64, usize)> = HashMap::new();
+        for price in prices.iter().filter(|p| p.price > 0.0) {
+            let entry = by_pair.entry((price.base_token, price.quote_token)).or_insert((0.0, 0, 0));
+            entry.0 += price.price;
+            entry.1 = entry.1.saturating_add(price.liquidity_in_quote());
+            entry.2 += 1;
+        }
+        
+        for (token_pair, (price_sum, liquidity, count)) in by_pair {
+            let samples = self.samples.entry(token_pair).or_default();
+            samples.push_back(MarketSample {
+                price: price_sum / count as f64,
+                liquidity,
+            });
+            
+            while samples.len() > self.window_size {
+                samples.pop_front();
+            }
+        }
+    }
+    
+    /// Assess current market conditions for a token pair
+    pub fn analyze(&self, base_token: &Pubkey, quote_token: &Pubkey) -> MarketCondition {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        
+        let samples = match self.samples.get(&(*base_token, *quote_token)) {
+            Some(samples) if !samples.is_empty() => samples,
+            _ => {
+                return MarketCondition {
+                    volatility: VolatilityLevel::Low,
+                    liquidity_score: 0,
+                    trend_direction: 0,
+                    timestamp,
+                };
+            }
+        };
+        
+        let prices: Vec<f64> = samples.iter().map(|s| s.price).collect();
+        
+        let avg_liquidity = samples.iter().map(|s| s.liquidity as f64).sum::<f64>() / samples.len() as f64;
+        let liquidity_score = (avg_liquidity / self.reference_liquidity as f64 * 100.0).min(100.0) as u8;
+        
+        // Short average above the long average is an uptrend
+        let mean = |window: &[f64]| window.iter().sum::<f64>() / window.len() as f64;
+        let long_average = mean(&prices);
+        let short_average = mean(&prices[prices.len().saturating_sub(self.short_window)..]);
+        let divergence = (short_average - long_average) / long_average * 100.0;
+        let trend_direction = (divergence / self.full_trend_percentage * 100.0).clamp(-100.0, 100.0) as i8;
+        
+        MarketCondition {
+            volatility: volatility_level(&prices, VOLATILITY_THRESHOLDS),
+            liquidity_score,
+            trend_direction,
+            timestamp,
+        }
+    }
+}
+
+/// Tracks recent price volatility per token pair
+pub struct VolatilityTracker {
+    /// Recent prices by token pair
+    prices: HashMap<(Pubkey, Pubkey), Vec<f64>>,
+    /// Number of samples kept per pair
+    window_size: usize,
+    /// Standard deviation of returns (in percent) at which volatility becomes Medium, High and Extreme
+    thresholds: (f64, f64, f64),
+    /// Pairs currently halted because of extreme volatility
+    halted_pairs: HashMap<(Pubkey, Pubkey), u64>,
+}
+
+impl VolatilityTracker {
+    /// Create a new volatility tracker
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            prices: HashMap::new(),
+            window_size: window_size.max(2),
+            thresholds: VOLATILITY_THRESHOLDS,
+            halted_pairs: HashMap::new(),
+        }
+    }
+    
+    /// Record a price sample for a token pair
+    pub fn record_price(&mut self, base_token: &Pubkey, quote_token: &Pubkey, price: f64) {
+        if price <= 0.0 {
+            return;
+        }
+        
+        let samples = self.prices.entry((*base_token, *quote_token)).or_insert_with(Vec::new);
+        samples.push(price);
+        
+        if samples.len() > self.window_size {
+            let excess = samples.len() - self.window_size;
+            samples.drain(0..excess);
+        }
+    }
+    
+    /// Get the current volatility level for a token pair
+    pub fn volatility(&self, base_token: &Pubkey, quote_token: &Pubkey) -> VolatilityLevel {
+        match self.prices.get(&(*base_token, *quote_token)) {
+            Some(samples) => volatility_level(samples, self.thresholds),
+            None => VolatilityLevel::Low,
+        }
+    }
+    
+    /// Check whether trading a pair should be halted because of volatility
+    ///
+    /// A pair is halted once its volatility is Extreme and stays halted until
+    /// volatility falls to `config.volatility_resume_level` or lower.
+    pub fn should_halt(&mut self, base_token: &Pubkey, quote_token: &Pubkey, config: &RiskManagementConfig) -> bool {
+        if !config.halt_on_extreme_volatility {
+            return false;
+        }
+        
+        let token_pair = (*base_token, *quote_token);
+        let level = self.volatility(base_token, quote_token);
+        
+        if self.halted_pairs.contains_key(&token_pair) {
+            if level <= config.volatility_resume_level {
+                self.halted_pairs.remove(&token_pair);
+                info!("Volatility for {}/{} subsided to {:?}, resuming trading", base_token, quote_token, level);
+                return false;
+            }
+            return true;
+        }
+        
+        if level == VolatilityLevel::Extreme {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            self.halted_pairs.insert(token_pair, now);
+            warn!("Extreme volatility for {}/{}, halting trading on this pair", base_token, quote_token);
+            return true;
+        }
+        
+        false
+    }
+}
+
 /// Trade performance record
 pub struct TradePerformance {
     /// Token pair
@@ -201,10 +937,31 @@ pub struct TradePerformance {
     pub profit_percentage: f64,
     /// Execution time in milliseconds
     pub execution_time_ms: u64,
+    /// Whether the trade succeeded
+    pub success: bool,
     /// Timestamp
     pub timestamp: u64,
 }
 
+/// Aggregate performance over the retained trade history
+#[derive(Debug, Clone)]
+pub struct PerformanceStatistics {
+    /// Number of trades in the history
+    pub total_trades: usize,
+    /// Number of successful trades
+    pub successful_trades: usize,
+    /// Success rate as a percentage
+    pub success_rate: f64,
+    /// Total profit (negative for a net loss)
+    pub total_profit: i64,
+    /// Average profit per trade
+    pub avg_profit: i64,
+    /// Average profit percentage per trade
+    pub avg_profit_percentage: f64,
+    /// Average execution time in milliseconds
+    pub avg_execution_time_ms: u64,
+}
+
 /// Position scaling manager
 pub struct PositionScalingManager {
     /// Position scaling configuration
@@ -234,7 +991,8 @@ impl PositionScalingManager {
         
         // Only initialize if not already present
         if !self.current_position_sizes.contains_key(&token_pair) {
-            self.current_position_sizes.insert(token_pair, self.config.base_position_size);
+            let starting_size = self.config.starting_position_size(&token_pair);
+            self.current_position_sizes.insert(token_pair, starting_size);
             
             // Record daily starting size
             let now = SystemTime::now()
@@ -242,7 +1000,7 @@ impl PositionScalingManager {
                 .unwrap_or_default()
                 .as_secs();
             
-            self.daily_starting_sizes.insert(token_pair, (self.config.base_position_size, now));
+            self.daily_starting_sizes.insert(token_pair, (starting_size, now));
         }
     }
     
@@ -256,8 +1014,10 @@ impl PositionScalingManager {
         // Check if we need to reset daily starting size
         self.check_daily_reset(&token_pair);
         
-        // Return current position size
-        *self.current_position_sizes.get(&token_pair).unwrap_or(&self.config.base_position_size)
+        // Return current position size, honoring any pair-specific limits
+        let (min_size, max_size) = self.config.position_limits(&token_pair);
+        let size = *self.current_position_sizes.get(&token_pair).unwrap_or(&self.config.base_position_size);
+        size.max(min_size).min(max_size)
     }
     
     /// Check if we need to reset daily starting size
@@ -325,6 +1085,7 @@ impl PositionScalingManager {
             profit_amount,
             profit_percentage,
             execution_time_ms,
+            success,
             timestamp: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap_or_default()
@@ -339,11 +1100,13 @@ impl PositionScalingManager {
     
     /// Apply position size limits
     fn apply_position_limits(&self, token_pair: (Pubkey, Pubkey), size: u64) -> u64 {
-        // Apply base minimum
-        let size = size.max(self.config.base_position_size / 2);
+        let (min_size, max_size) = self.config.position_limits(&token_pair);
+        
+        // Apply minimum
+        let size = size.max(min_size);
         
         // Apply maximum
-        let size = size.min(self.config.max_position_size);
+        let size = size.min(max_size);
         
         // Apply daily growth limit
         if let Some((daily_start, _)) = self.daily_starting_sizes.get(&token_pair) {
@@ -402,4 +1165,308 @@ impl PositionScalingManager {
         let mut avg_execution_time = 0;
         
         if !self.trade_history.is_empty() {
-            total_trades = self.trade_history.len();<response clipped><NOTE>To save on context only part of this file has been shown to you. You should retry this tool after you have searched inside the file with `grep -n` in order to find the line numbers of what you are looking for.</NOTE>
\ No newline at end of file
+            total_trades = self.trade_history.len();
+            
+            let mut total_profit_percentage = 0.0;
+            let mut total_execution_time = 0;
+            
+            for trade in &self.trade_history {
+                if trade.success {
+                    successful_trades += 1;
+                }
+                total_profit += trade.profit_amount;
+                total_profit_percentage += trade.profit_percentage;
+                total_execution_time += trade.execution_time_ms;
+            }
+            
+            avg_profit_percentage = total_profit_percentage / total_trades as f64;
+            avg_execution_time = total_execution_time / total_trades as u64;
+        }
+        
+        let success_rate = if total_trades == 0 {
+            0.0
+        } else {
+            (successful_trades as f64 / total_trades as f64) * 100.0
+        };
+        
+        let avg_profit = if total_trades == 0 {
+            0
+        } else {
+            total_profit / total_trades as i64
+        };
+        
+        PerformanceStatistics {
+            total_trades,
+            successful_trades,
+            success_rate,
+            total_profit,
+            avg_profit,
+            avg_profit_percentage,
+            avg_execution_time_ms: avg_execution_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+    use solana_client::client_error::Result as ClientResult;
+    use solana_client::rpc_client::RpcClientConfig;
+    use solana_client::rpc_request::RpcRequest;
+    use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+    use solana_sdk::commitment_config::CommitmentConfig;
+    
+    /// USDC mint, trusted by default
+    fn usdc() -> Pubkey {
+        Pubkey::from_str(TRUSTED_FREEZABLE_MINTS[0]).unwrap()
+    }
+    
+    /// `getAccountInfo` response for a mint with `freeze_authority`
+    fn mint_account(freeze_authority: Option<Pubkey>) -> Value {
+        let mint = Mint {
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: freeze_authority.into(),
+            ..Mint::default()
+        };
+        let mut data = vec![0u8; Mint::LEN];
+        Mint::pack(mint, &mut data).unwrap();
+        
+        json!({
+            "context": { "slot": 1 },
+            "value": {
+                "data": [BASE64.encode(&data), "base64"],
+                "executable": false,
+                "lamports": 1_461_600,
+                "owner": spl_token::id().to_string(),
+                "rentEpoch": 0,
+                "space": data.len(),
+            }
+        })
+    }
+    
+    /// Mock RPC client whose next `getAccountInfo` returns a mint with `freeze_authority`
+    fn rpc_with_mint(freeze_authority: Option<Pubkey>) -> RpcClient {
+        let mut mocks = HashMap::new();
+        mocks.insert(RpcRequest::GetAccountInfo, mint_account(freeze_authority));
+        RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+    }
+    
+    /// RPC sender answering each `getAccountInfo` with the next mint in a script
+    struct ScriptedMints {
+        /// Freeze authority of the mint each fetch returns, in order
+        freeze_authorities: Mutex<VecDeque<Option<Pubkey>>>,
+        /// Mint fetches answered so far
+        fetches: Arc<AtomicUsize>,
+    }
+    
+    #[async_trait]
+    impl RpcSender for ScriptedMints {
+        async fn send(&self, request: RpcRequest, _params: Value) -> ClientResult<Value> {
+            assert_eq!(request, RpcRequest::GetAccountInfo);
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            let freeze_authority = self.freeze_authorities.lock().unwrap().pop_front().expect("a scripted mint");
+            Ok(mint_account(freeze_authority))
+        }
+        
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+        
+        fn url(&self) -> String {
+            "scripted-mints".to_string()
+        }
+    }
+    
+    #[test]
+    fn new_filter_keeps_the_default_checks() {
+        let filter = TokenFilter::new(Vec::new(), Vec::new(), false);
+        let default = TokenFilter::default();
+        
+        assert_eq!(filter.require_no_freeze_authority, default.require_no_freeze_authority);
+        assert_eq!(filter.allowlist, default.allowlist);
+    }
+    
+    #[test]
+    fn allowlist_only_rejects_everything_else() {
+        let listed = Pubkey::new_unique();
+        let filter = TokenFilter::new(vec![listed], Vec::new(), true);
+        
+        assert!(filter.check_mint(&listed).is_ok());
+        assert!(filter.check_mint(&usdc()).is_ok());
+        assert!(filter.check_mint(&Pubkey::new_unique()).is_err());
+        assert!(filter.check_pair(&Pubkey::new_unique(), &listed).is_err());
+    }
+    
+    #[test]
+    fn denylist_wins_over_the_allowlist() {
+        let mint = Pubkey::new_unique();
+        let filter = TokenFilter::new(vec![mint], vec![mint], false);
+        assert!(filter.check_mint(&mint).is_err());
+        
+        // Moving a mint between lists at runtime takes it off the other one
+        let policy = TokenPolicy::new(TokenFilter::new(Vec::new(), vec![mint], false), RpcClient::new_mock("succeeds".to_string()));
+        assert!(policy.check_mint(&mint).is_err());
+        policy.add_to_allowlist(mint).unwrap();
+        assert!(policy.check_mint(&mint).is_ok());
+        policy.add_to_denylist(mint).unwrap();
+        assert!(policy.check_mint(&mint).is_err());
+    }
+    
+    #[test]
+    fn freezable_mint_is_rejected_unless_allowlisted() {
+        let mint = Pubkey::new_unique();
+        
+        let policy = TokenPolicy::new(TokenFilter::default(), rpc_with_mint(Some(Pubkey::new_unique())));
+        let error = policy.check_mint(&mint).unwrap_err();
+        assert!(error.contains("can be frozen"));
+        
+        // Trusted mints skip the fetch entirely
+        assert!(policy.check_mint(&usdc()).is_ok());
+        policy.add_to_allowlist(mint).unwrap();
+        assert!(policy.check_mint(&mint).is_ok());
+        
+        let policy = TokenPolicy::new(TokenFilter::default(), rpc_with_mint(None));
+        assert!(policy.check_mint(&mint).is_ok());
+        assert_eq!(policy.mint_metadata(&mint).unwrap().decimals, 6);
+    }
+    
+    #[test]
+    fn freezable_mint_is_rechecked_once_its_entry_ages() {
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let sender = ScriptedMints {
+            freeze_authorities: Mutex::new(VecDeque::from([Some(Pubkey::new_unique()), None])),
+            fetches: fetches.clone(),
+        };
+        let rpc_client = RpcClient::new_sender(sender, RpcClientConfig::with_commitment(CommitmentConfig::confirmed()));
+        let mut policy = TokenPolicy::new(TokenFilter::default(), rpc_client);
+        policy.freezable_mint_ttl = Duration::from_millis(50);
+        let mint = Pubkey::new_unique();
+        
+        // Rejected from the cache while its entry is fresh
+        assert!(policy.check_mint(&mint).is_err());
+        assert!(policy.check_mint(&mint).is_err());
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        
+        // The authority has since been revoked, which the next fetch picks up
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(policy.check_mint(&mint).is_ok());
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        
+        // Without a freeze authority the mint is never fetched again
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(policy.check_mint(&mint).is_ok());
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+    
+    #[test]
+    fn extreme_volatility_halts_then_resumes() {
+        let config = RiskManagementConfig::new(RiskLevel::Moderate);
+        let base = Pubkey::new_unique();
+        let quote = Pubkey::new_unique();
+        let mut tracker = VolatilityTracker::new(5);
+        
+        // Calm market trades
+        for _ in 0..5 {
+            tracker.record_price(&base, &quote, 100.0);
+        }
+        assert!(!tracker.should_halt(&base, &quote, &config));
+        
+        // 10% swings every round push the pair to Extreme
+        for price in [110.0, 100.0, 110.0, 100.0] {
+            tracker.record_price(&base, &quote, price);
+        }
+        assert_eq!(tracker.volatility(&base, &quote), VolatilityLevel::Extreme);
+        assert!(tracker.should_halt(&base, &quote, &config));
+        
+        // Still halted while the swings are in the window
+        tracker.record_price(&base, &quote, 100.0);
+        assert!(tracker.should_halt(&base, &quote, &config));
+        
+        // Rounds with no tradable spread still feed the tracker until volatility subsides
+        for _ in 0..5 {
+            tracker.record_price(&base, &quote, 100.0);
+        }
+        assert!(tracker.volatility(&base, &quote) <= config.volatility_resume_level);
+        assert!(!tracker.should_halt(&base, &quote, &config));
+        assert!(!tracker.should_halt(&base, &quote, &config));
+    }
+    
+    #[test]
+    fn pair_override_and_global_limits_bound_position_sizes() {
+        let (sol, bonk, usdc) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut config = PositionScalingConfig::new(RiskLevel::Moderate);
+        config.use_profit_based_scaling = false;
+        config.max_daily_growth = 100.0;
+        config.per_pair_overrides.insert((bonk, usdc), (10_000_000, 50_000_000));
+        let mut manager = PositionScalingManager::new(config);
+        
+        // The overridden pair starts at its own cap, the other at the base size
+        assert_eq!(manager.get_position_size(&bonk, &usdc), 50_000_000);
+        assert_eq!(manager.get_position_size(&sol, &usdc), 250_000_000);
+        
+        // Wins grow each pair only up to its own maximum
+        for _ in 0..30 {
+            manager.update_position_size(&bonk, &usdc, true, 1, 0.01, 100);
+            manager.update_position_size(&sol, &usdc, true, 1, 0.01, 100);
+        }
+        assert_eq!(manager.get_position_size(&bonk, &usdc), 50_000_000);
+        assert_eq!(manager.get_position_size(&sol, &usdc), 1_000_000_000);
+        
+        // Losses shrink each pair only down to its own minimum
+        for _ in 0..60 {
+            manager.update_position_size(&bonk, &usdc, false, -1, -0.01, 100);
+            manager.update_position_size(&sol, &usdc, false, -1, -0.01, 100);
+        }
+        assert_eq!(manager.get_position_size(&bonk, &usdc), 10_000_000);
+        assert_eq!(manager.get_position_size(&sol, &usdc), 125_000_000);
+    }
+    
+    #[test]
+    fn performance_stats_aggregate_wins_and_losses() {
+        let (base, quote) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut manager = PositionScalingManager::new(PositionScalingConfig::new(RiskLevel::Moderate));
+        assert_eq!(manager.get_performance_stats().total_trades, 0);
+        assert_eq!(manager.get_performance_stats().success_rate, 0.0);
+        
+        // Three wins and a loss that outweighs one of them
+        manager.update_position_size(&base, &quote, true, 3_000, 1.5, 100);
+        manager.update_position_size(&base, &quote, true, 1_000, 0.5, 200);
+        manager.update_position_size(&base, &quote, false, -2_000, -1.0, 300);
+        manager.update_position_size(&base, &quote, true, 2_000, 1.0, 400);
+        
+        let stats = manager.get_performance_stats();
+        assert_eq!(stats.total_trades, 4);
+        assert_eq!(stats.successful_trades, 3);
+        assert_eq!(stats.success_rate, 75.0);
+        assert_eq!(stats.total_profit, 4_000);
+        assert_eq!(stats.avg_profit, 1_000);
+        assert_eq!(stats.avg_profit_percentage, 0.5);
+        assert_eq!(stats.avg_execution_time_ms, 250);
+        
+        // A net loss stays negative
+        manager.update_position_size(&base, &quote, false, -9_000, -4.0, 500);
+        let stats = manager.get_performance_stats();
+        assert_eq!(stats.total_profit, -5_000);
+        assert_eq!(stats.avg_profit, -1_000);
+        assert_eq!(stats.success_rate, 60.0);
+    }
+    
+    #[test]
+    fn halting_can_be_disabled() {
+        let mut config = RiskManagementConfig::new(RiskLevel::Moderate);
+        config.halt_on_extreme_volatility = false;
+        let base = Pubkey::new_unique();
+        let quote = Pubkey::new_unique();
+        let mut tracker = VolatilityTracker::new(5);
+        
+        for price in [100.0, 110.0, 100.0, 110.0, 100.0] {
+            tracker.record_price(&base, &quote, price);
+        }
+        assert_eq!(tracker.volatility(&base, &quote), VolatilityLevel::Extreme);
+        assert!(!tracker.should_halt(&base, &quote, &config));
+    }
+}